@@ -0,0 +1,72 @@
+// Runs against a local mock of the OpenAI API; build the module first with `npm run build`.
+import assert from 'node:assert/strict'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { after, before, test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const { AskAi, Conversation } = require(process.env.ASK_AI_NODE_MODULE ?? '../index.js')
+
+let server
+const requests = []
+
+before(async () => {
+  server = createServer((req, res) => {
+    let body = ''
+    req.on('data', (chunk) => (body += chunk))
+    req.on('end', () => {
+      const payload = JSON.parse(body)
+      requests.push(payload)
+      if (payload.stream) {
+        res.writeHead(200, { 'content-type': 'text/event-stream' })
+        for (const content of ['Hello ', 'from a stream!']) {
+          res.write(`data: ${JSON.stringify({ choices: [{ delta: { content } }] })}\n\n`)
+        }
+        res.end('data: [DONE]\n\n')
+      } else {
+        res.writeHead(200, { 'content-type': 'application/json' })
+        res.end(JSON.stringify({ choices: [{ message: { content: 'Hello' } }] }))
+      }
+    })
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  process.env.OPENAI_API_KEY = 'test_key'
+  process.env.OPENAI_API_URL = `http://127.0.0.1:${server.address().port}/v1/chat/completions`
+})
+
+after(() => server.close())
+
+const client = () => new AskAi({ llm: 'openai', model: 'gpt-4o-mini' })
+
+test('ask resolves to the answer', async () => {
+  const answer = await client().ask({
+    newPrompt: 'Hi',
+    systemPrompt: 'Be brief.',
+    messages: [{ content: 'Earlier', output: 'Answer' }],
+  })
+  assert.equal(answer, 'Hello')
+  const roles = requests.at(-1).messages.map((message) => message.role)
+  assert.deepEqual(roles, ['system', 'user', 'assistant', 'user'])
+})
+
+test('stream calls back with each fragment', async () => {
+  const deltas = []
+  const answer = await client().stream({ newPrompt: 'Hi' }, (delta) => deltas.push(delta))
+  assert.equal(answer, 'Hello from a stream!')
+  await new Promise((resolve) => setImmediate(resolve))
+  assert.deepEqual(deltas, ['Hello ', 'from a stream!'])
+})
+
+test('conversations record exchanges', async () => {
+  const conversation = new Conversation('Be brief.')
+  assert.equal(await conversation.ask(client(), 'Hi'), 'Hello')
+  assert.deepEqual(await conversation.messages(), [{ content: 'Hi', output: 'Hello' }])
+  const restored = Conversation.fromJson(await conversation.toJson())
+  assert.deepEqual(await restored.messages(), [{ content: 'Hi', output: 'Hello' }])
+})
+
+test('errors reject', async () => {
+  const custom = AskAi.fromJson('{ "llm": "mistral", "model": "mistral-small" }')
+  await assert.rejects(custom.ask({ newPrompt: 'Hi' }), /mistral/)
+  assert.throws(() => AskAi.fromJson('{ "model": 42 }'), /Invalid AiConfig/)
+})
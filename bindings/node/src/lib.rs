@@ -0,0 +1,196 @@
+//! Node.js bindings for `ask_ai`, built with napi-rs, for Electron apps and Node backends.
+//!
+//! Build with `npm run build` from this directory, which also generates the TypeScript
+//! declarations.
+//!
+//! ```js
+//! const { AskAi, Conversation } = require('ask-ai')
+//!
+//! const client = new AskAi({ llm: 'openai', model: 'gpt-4o-mini', maxToken: 256 })
+//! console.log(await client.ask({ newPrompt: 'What is Rust?' }))
+//!
+//! await client.stream({ newPrompt: 'Tell me more.' }, (delta) => process.stdout.write(delta))
+//! ```
+
+use ask_ai::config::{self, AiConfig, AiPrompt};
+use ask_ai::conversation;
+use ask_ai::error::AppError;
+use futures_util::StreamExt;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn js_err(error: AppError) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+/// Provider, model and the most common options; use `AskAi.fromJson` for the others.
+#[napi(object)]
+pub struct AiConfigOptions {
+    /// `openai`, `anthropic` or `ollama`.
+    pub llm: String,
+    pub model: String,
+    pub max_token: Option<u32>,
+    /// Falls back to the provider's environment variable, e.g. `OPENAI_API_KEY`.
+    pub api_key: Option<String>,
+}
+
+/// A completed exchange of the conversation so far.
+#[napi(object)]
+pub struct Exchange {
+    pub content: String,
+    pub output: String,
+}
+
+/// A new prompt, with an optional system prompt and the conversation so far.
+#[napi(object)]
+pub struct Question {
+    pub new_prompt: String,
+    pub system_prompt: Option<String>,
+    pub messages: Option<Vec<Exchange>>,
+}
+
+impl From<Question> for config::Question {
+    fn from(question: Question) -> Self {
+        let messages: Vec<AiPrompt> = question
+            .messages
+            .unwrap_or_default()
+            .into_iter()
+            .map(|msg| AiPrompt {
+                content: msg.content,
+                output: msg.output,
+            })
+            .collect();
+        config::Question {
+            system_prompt: question.system_prompt,
+            messages: (!messages.is_empty()).then_some(messages),
+            new_prompt: question.new_prompt,
+            images: vec![],
+            audio: vec![],
+        }
+    }
+}
+
+/// A configured client.
+#[napi]
+pub struct AskAi {
+    ai_config: Arc<AiConfig>,
+}
+
+#[napi]
+impl AskAi {
+    #[napi(constructor)]
+    pub fn new(options: AiConfigOptions) -> Result<Self> {
+        Ok(Self {
+            ai_config: Arc::new(AiConfig {
+                llm: options.llm.parse().map_err(js_err)?,
+                model: options.model,
+                max_token: options.max_token,
+                api_key: options.api_key.map(Into::into),
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// A client with every option, from the JSON form of the Rust `AiConfig`.
+    #[napi(factory)]
+    pub fn from_json(json: String) -> Result<Self> {
+        let ai_config = serde_json::from_str(&json)
+            .map_err(|e| Error::from_reason(format!("Invalid AiConfig: {}", e)))?;
+        Ok(Self {
+            ai_config: Arc::new(ai_config),
+        })
+    }
+
+    /// Asks a question and resolves to the full answer.
+    #[napi]
+    pub async fn ask(&self, question: Question) -> Result<String> {
+        ask_ai::ask_question(&self.ai_config, question.into())
+            .await
+            .map_err(js_err)
+    }
+
+    /// Asks a question, calling `onDelta` with each fragment of the answer as it is
+    /// generated, and resolves to the full answer.
+    #[napi(ts_args_type = "question: Question, onDelta: (delta: string) => void")]
+    pub async fn stream(
+        &self,
+        question: Question,
+        on_delta: ThreadsafeFunction<String, ErrorStrategy::Fatal>,
+    ) -> Result<String> {
+        let mut stream = ask_ai::ask_question_stream(&self.ai_config, question.into())
+            .await
+            .map_err(js_err)?;
+        let mut answer = String::new();
+        while let Some(delta) = stream.next().await {
+            let delta = delta.map_err(js_err)?;
+            answer.push_str(&delta);
+            on_delta.call(delta, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+        Ok(answer)
+    }
+}
+
+/// A multi-turn conversation that records each exchange.
+#[napi]
+pub struct Conversation {
+    inner: Arc<Mutex<conversation::Conversation>>,
+}
+
+#[napi]
+impl Conversation {
+    #[napi(constructor)]
+    pub fn new(system_prompt: Option<String>) -> Self {
+        Self::from(conversation::Conversation::new(system_prompt))
+    }
+
+    #[napi(factory)]
+    pub fn from_json(json: String) -> Result<Self> {
+        serde_json::from_str::<conversation::Conversation>(&json)
+            .map(Self::from)
+            .map_err(|e| Error::from_reason(format!("Invalid Conversation: {}", e)))
+    }
+
+    #[napi]
+    pub async fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&*self.inner.lock().await)
+            .map_err(|e| Error::from_reason(e.to_string()))
+    }
+
+    /// Asks `newPrompt` in the context of this conversation and records the exchange.
+    #[napi]
+    pub async fn ask(&self, client: &AskAi, new_prompt: String) -> Result<String> {
+        let ai_config = Arc::clone(&client.ai_config);
+        self.inner
+            .lock()
+            .await
+            .ask(&ai_config, &new_prompt)
+            .await
+            .map_err(js_err)
+    }
+
+    /// The completed exchanges, oldest first.
+    #[napi]
+    pub async fn messages(&self) -> Vec<Exchange> {
+        self.inner
+            .lock()
+            .await
+            .messages
+            .iter()
+            .map(|msg| Exchange {
+                content: msg.content.clone(),
+                output: msg.output.clone(),
+            })
+            .collect()
+    }
+}
+
+impl From<conversation::Conversation> for Conversation {
+    fn from(conversation: conversation::Conversation) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(conversation)),
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Python bindings for `ask_ai`: one configured client shared by Rust and Python code.
+//!
+//! Calls block the calling Python thread, without holding the GIL, on a runtime owned by the
+//! module. Build with `maturin develop` from this directory.
+//!
+//! ```python
+//! from ask_ai import AiConfig, Question, ask_question, ask_question_stream
+//!
+//! config = AiConfig("openai", "gpt-4o-mini", max_token=256)
+//! print(ask_question(config, Question("What is Rust?")))
+//!
+//! for delta in ask_question_stream(config, Question("Tell me more.")):
+//!     print(delta, end="", flush=True)
+//! ```
+
+use ask_ai::config::{self, AiPrompt};
+use ask_ai::conversation;
+use ask_ai::error::AppError;
+use ask_ai::stream::AnswerStream;
+use futures_util::StreamExt;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+
+create_exception!(ask_ai, AskAiError, PyException);
+
+fn py_err(error: AppError) -> PyErr {
+    AskAiError::new_err(error.to_string())
+}
+
+/// Runs `future` to completion on the module's runtime, with the GIL released.
+fn block_on<F>(py: Python<'_>, future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    let runtime = RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to start the ask_ai runtime")
+    });
+    py.detach(|| runtime.block_on(future))
+}
+
+/// Provider, model and options used to ask questions.
+#[pyclass(module = "ask_ai", from_py_object)]
+#[derive(Clone)]
+struct AiConfig(config::AiConfig);
+
+#[pymethods]
+impl AiConfig {
+    #[new]
+    #[pyo3(signature = (llm, model, max_token = None, api_key = None))]
+    fn new(
+        llm: &str,
+        model: &str,
+        max_token: Option<u32>,
+        api_key: Option<String>,
+    ) -> PyResult<Self> {
+        Ok(Self(config::AiConfig {
+            llm: llm.parse().map_err(py_err)?,
+            model: model.to_string(),
+            max_token,
+            api_key: api_key.map(Into::into),
+            ..Default::default()
+        }))
+    }
+
+    /// A configuration with every option, in the JSON form of the Rust `AiConfig`.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        serde_json::from_str(json)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(format!("Invalid AiConfig: {}", e)))
+    }
+
+    /// A configuration with the provider's default model and answer limit.
+    #[staticmethod]
+    fn default_for(llm: &str) -> PyResult<Self> {
+        Ok(Self(config::AiConfig::default_for(
+            llm.parse().map_err(py_err)?,
+        )))
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.0).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn llm(&self) -> String {
+        self.0.llm.to_string()
+    }
+
+    #[getter]
+    fn model(&self) -> String {
+        self.0.model.clone()
+    }
+
+    #[getter]
+    fn max_token(&self) -> Option<u32> {
+        self.0.max_token
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AiConfig(llm={:?}, model={:?})",
+            self.0.llm.to_string(),
+            self.0.model
+        )
+    }
+}
+
+/// A new prompt with an optional system prompt and earlier `(prompt, answer)` exchanges.
+#[pyclass(module = "ask_ai", from_py_object)]
+#[derive(Clone)]
+struct Question {
+    #[pyo3(get, set)]
+    new_prompt: String,
+    #[pyo3(get, set)]
+    system_prompt: Option<String>,
+    #[pyo3(get, set)]
+    messages: Vec<(String, String)>,
+}
+
+#[pymethods]
+impl Question {
+    #[new]
+    #[pyo3(signature = (new_prompt, system_prompt = None, messages = vec![]))]
+    fn new(
+        new_prompt: String,
+        system_prompt: Option<String>,
+        messages: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            new_prompt,
+            system_prompt,
+            messages,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Question(new_prompt={:?})", self.new_prompt)
+    }
+}
+
+impl From<Question> for config::Question {
+    fn from(question: Question) -> Self {
+        let messages: Vec<AiPrompt> = question
+            .messages
+            .into_iter()
+            .map(|(content, output)| AiPrompt { content, output })
+            .collect();
+        config::Question {
+            system_prompt: question.system_prompt,
+            messages: (!messages.is_empty()).then_some(messages),
+            new_prompt: question.new_prompt,
+            images: vec![],
+            audio: vec![],
+        }
+    }
+}
+
+/// A multi-turn conversation that records each exchange.
+#[pyclass(module = "ask_ai")]
+struct Conversation(conversation::Conversation);
+
+#[pymethods]
+impl Conversation {
+    #[new]
+    #[pyo3(signature = (system_prompt = None))]
+    fn new(system_prompt: Option<String>) -> Self {
+        Self(conversation::Conversation::new(system_prompt))
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        serde_json::from_str(json)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(format!("Invalid Conversation: {}", e)))
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.0).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Appends a completed exchange.
+    fn push(&mut self, content: &str, output: &str) {
+        self.0.push(content, output);
+    }
+
+    /// The `Question` that continues this conversation with `new_prompt`.
+    fn question(&self, new_prompt: &str) -> Question {
+        let question = self.0.question(new_prompt);
+        Question {
+            new_prompt: question.new_prompt,
+            system_prompt: question.system_prompt,
+            messages: question
+                .messages
+                .unwrap_or_default()
+                .into_iter()
+                .map(|msg| (msg.content, msg.output))
+                .collect(),
+        }
+    }
+
+    /// Asks `new_prompt` in the context of this conversation and records the exchange.
+    fn ask(&mut self, py: Python<'_>, ai_config: &AiConfig, new_prompt: &str) -> PyResult<String> {
+        block_on(py, self.0.ask(&ai_config.0, new_prompt)).map_err(py_err)
+    }
+
+    #[getter]
+    fn system_prompt(&self) -> Option<String> {
+        self.0.system_prompt.clone()
+    }
+
+    #[getter]
+    fn messages(&self) -> Vec<(String, String)> {
+        self.0
+            .messages
+            .iter()
+            .map(|msg| (msg.content.clone(), msg.output.clone()))
+            .collect()
+    }
+}
+
+/// An iterator over the fragments of a streamed answer.
+#[pyclass(module = "ask_ai")]
+struct AnswerIterator(Mutex<AnswerStream>);
+
+#[pymethods]
+impl AnswerIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<String>> {
+        let mut stream = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        block_on(py, stream.next()).transpose().map_err(py_err)
+    }
+}
+
+/// Asks a question and returns the full answer.
+#[pyfunction]
+fn ask_question(py: Python<'_>, ai_config: &AiConfig, question: Question) -> PyResult<String> {
+    block_on(py, ask_ai::ask_question(&ai_config.0, question.into())).map_err(py_err)
+}
+
+/// Asks a question and returns an iterator over the answer as it is generated.
+#[pyfunction]
+fn ask_question_stream(
+    py: Python<'_>,
+    ai_config: &AiConfig,
+    question: Question,
+) -> PyResult<AnswerIterator> {
+    let stream = block_on(
+        py,
+        ask_ai::ask_question_stream(&ai_config.0, question.into()),
+    )
+    .map_err(py_err)?;
+    Ok(AnswerIterator(Mutex::new(stream)))
+}
+
+#[pymodule]
+#[pyo3(name = "ask_ai")]
+fn ask_ai_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("AskAiError", m.py().get_type::<AskAiError>())?;
+    m.add_class::<AiConfig>()?;
+    m.add_class::<Question>()?;
+    m.add_class::<Conversation>()?;
+    m.add_class::<AnswerIterator>()?;
+    m.add_function(wrap_pyfunction!(ask_question, m)?)?;
+    m.add_function(wrap_pyfunction!(ask_question_stream, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+"""Runs against a local mock of the OpenAI API; build the module first with `maturin develop`."""
+
+import json
+import os
+import threading
+import unittest
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+from ask_ai import (
+    AiConfig,
+    AskAiError,
+    Conversation,
+    Question,
+    ask_question,
+    ask_question_stream,
+)
+
+
+class MockOpenAI(BaseHTTPRequestHandler):
+    def do_POST(self):
+        payload = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+        self.server.requests.append(payload)
+        self.send_response(200)
+        if payload.get("stream"):
+            self.send_header("Content-Type", "text/event-stream")
+            self.end_headers()
+            for delta in ["Hello ", "from a stream!"]:
+                chunk = {"choices": [{"delta": {"content": delta}}]}
+                self.wfile.write(f"data: {json.dumps(chunk)}\n\n".encode())
+            self.wfile.write(b"data: [DONE]\n\n")
+        else:
+            self.send_header("Content-Type", "application/json")
+            self.end_headers()
+            answer = {"choices": [{"message": {"content": "Hello"}}]}
+            self.wfile.write(json.dumps(answer).encode())
+
+    def log_message(self, *args):
+        pass
+
+
+class AskAiTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.server = HTTPServer(("127.0.0.1", 0), MockOpenAI)
+        cls.server.requests = []
+        threading.Thread(target=cls.server.serve_forever, daemon=True).start()
+        os.environ["OPENAI_API_KEY"] = "test_key"
+        os.environ["OPENAI_API_URL"] = f"http://127.0.0.1:{cls.server.server_port}/v1/chat/completions"
+        cls.config = AiConfig("openai", "gpt-4o-mini")
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.server.shutdown()
+
+    def test_ask_question(self):
+        question = Question("Hi", system_prompt="Be brief.", messages=[("Earlier", "Answer")])
+        self.assertEqual(ask_question(self.config, question), "Hello")
+        roles = [message["role"] for message in self.server.requests[-1]["messages"]]
+        self.assertEqual(roles, ["system", "user", "assistant", "user"])
+
+    def test_ask_question_stream(self):
+        deltas = list(ask_question_stream(self.config, Question("Hi")))
+        self.assertEqual("".join(deltas), "Hello from a stream!")
+
+    def test_conversation_records_exchanges(self):
+        conversation = Conversation("Be brief.")
+        self.assertEqual(conversation.ask(self.config, "Hi"), "Hello")
+        self.assertEqual(conversation.messages, [("Hi", "Hello")])
+        restored = Conversation.from_json(conversation.to_json())
+        self.assertEqual(restored.messages, conversation.messages)
+
+    def test_config_round_trips_through_json(self):
+        config = AiConfig.from_json('{ "llm": "anthropic", "model": "claude-sonnet-4-5", "max_token": 64 }')
+        self.assertEqual((config.llm, config.model, config.max_token), ("anthropic", "claude-sonnet-4-5", 64))
+        self.assertEqual(AiConfig.from_json(config.to_json()).model, "claude-sonnet-4-5")
+
+    def test_errors_raise_ask_ai_error(self):
+        with self.assertRaises(AskAiError):
+            ask_question(AiConfig("together", "llama-3.1-8b"), Question("Hi"))
+
+
+if __name__ == "__main__":
+    unittest.main()
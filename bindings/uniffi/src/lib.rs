@@ -0,0 +1,194 @@
+//! Swift and Kotlin bindings for `ask_ai`, generated with uniffi, so mobile apps reuse the
+//! crate's provider logic and cost tracking instead of reimplementing them per platform.
+//!
+//! Build the library for the target platform, then generate the bindings from it:
+//!
+//! ```sh
+//! cargo build -p ask_ai_uniffi --release
+//! cargo run -p ask_ai_uniffi --bin uniffi-bindgen -- generate \
+//!     --library target/release/libask_ai_uniffi.so --language kotlin --out-dir out
+//! ```
+
+use ask_ai::config::{self, AiConfig, AiPrompt};
+use ask_ai::cost::{CostGroup, Pricing, UsageLedger};
+use ask_ai::error::AppError;
+use std::fmt;
+use std::sync::Arc;
+
+uniffi::setup_scaffolding!();
+
+/// Why a call failed, with the message of the underlying `AppError`.
+#[derive(Debug, uniffi::Error)]
+pub enum AskAiError {
+    /// The configuration could not be read.
+    InvalidConfig { message: String },
+    /// The question was not answered.
+    Failed { message: String },
+}
+
+impl fmt::Display for AskAiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AskAiError::InvalidConfig { message } | AskAiError::Failed { message } => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl From<AppError> for AskAiError {
+    fn from(error: AppError) -> Self {
+        AskAiError::Failed {
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A completed exchange of the conversation so far.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Exchange {
+    pub content: String,
+    pub output: String,
+}
+
+/// A new prompt, with an optional system prompt and the conversation so far.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Question {
+    pub new_prompt: String,
+    #[uniffi(default = None)]
+    pub system_prompt: Option<String>,
+    #[uniffi(default = [])]
+    pub messages: Vec<Exchange>,
+}
+
+impl From<Question> for config::Question {
+    fn from(question: Question) -> Self {
+        let messages: Vec<AiPrompt> = question
+            .messages
+            .into_iter()
+            .map(|msg| AiPrompt {
+                content: msg.content,
+                output: msg.output,
+            })
+            .collect();
+        config::Question {
+            system_prompt: question.system_prompt,
+            messages: (!messages.is_empty()).then_some(messages),
+            new_prompt: question.new_prompt,
+            images: vec![],
+            audio: vec![],
+        }
+    }
+}
+
+/// USD prices per million tokens of one model, for cost tracking.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ModelPrice {
+    pub model: String,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+/// Spend of one model, from `AskAi::costs`.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ModelCost {
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub answer_tokens: u64,
+    pub cost: f64,
+}
+
+/// A configured client that records the usage and cost of every answered question.
+#[derive(uniffi::Object)]
+pub struct AskAi {
+    ai_config: AiConfig,
+    ledger: UsageLedger,
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl AskAi {
+    /// A client for `llm` ("openai", "anthropic" or "ollama") and `model`. Without an
+    /// `api_key`, the provider's environment variable is used.
+    #[uniffi::constructor(default(max_token = None, api_key = None, prices = []))]
+    pub fn new(
+        llm: String,
+        model: String,
+        max_token: Option<u32>,
+        api_key: Option<String>,
+        prices: Vec<ModelPrice>,
+    ) -> Result<Arc<Self>, AskAiError> {
+        let llm = llm
+            .parse()
+            .map_err(|e: AppError| AskAiError::InvalidConfig {
+                message: e.to_string(),
+            })?;
+        Ok(Self::with_config(
+            AiConfig {
+                llm,
+                model,
+                max_token,
+                api_key: api_key.map(Into::into),
+                ..Default::default()
+            },
+            prices,
+        ))
+    }
+
+    /// A client with every option, from the JSON form of `AiConfig`.
+    #[uniffi::constructor(default(prices = []))]
+    pub fn from_json(json: String, prices: Vec<ModelPrice>) -> Result<Arc<Self>, AskAiError> {
+        let ai_config = serde_json::from_str(&json).map_err(|e| AskAiError::InvalidConfig {
+            message: format!("Invalid AiConfig: {}", e),
+        })?;
+        Ok(Self::with_config(ai_config, prices))
+    }
+
+    /// Asks a question and records its usage under `tags` when it is answered.
+    pub async fn ask(&self, question: Question, tags: Vec<String>) -> Result<String, AskAiError> {
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        Ok(self
+            .ledger
+            .ask_question(&self.ai_config, question.into(), &tags)
+            .await?)
+    }
+
+    /// Usage and cost per model of the questions answered so far.
+    pub fn costs(&self) -> Vec<ModelCost> {
+        self.ledger
+            .report(&[CostGroup::Model])
+            .lines
+            .into_iter()
+            .map(|line| ModelCost {
+                model: line.model.unwrap_or_default(),
+                requests: line.requests,
+                prompt_tokens: line.prompt_tokens,
+                answer_tokens: line.answer_tokens,
+                cost: line.cost,
+            })
+            .collect()
+    }
+
+    /// The cost report as CSV, one line per model and UTC day.
+    pub fn cost_report_csv(&self) -> String {
+        self.ledger
+            .report(&[CostGroup::Day, CostGroup::Model])
+            .to_csv()
+    }
+}
+
+impl AskAi {
+    fn with_config(ai_config: AiConfig, prices: Vec<ModelPrice>) -> Arc<Self> {
+        let pricing = prices.into_iter().fold(Pricing::new(), |pricing, price| {
+            pricing.price(
+                &price.model,
+                price.input_per_million,
+                price.output_per_million,
+            )
+        });
+        Arc::new(Self {
+            ai_config,
+            ledger: UsageLedger::new(pricing),
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use ask_ai_uniffi::{AskAi, AskAiError, Exchange, ModelPrice, Question};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn question() -> Question {
+    Question {
+        new_prompt: "Is it fast?".to_string(),
+        system_prompt: None,
+        messages: vec![Exchange {
+            content: "What is Rust?".to_string(),
+            output: "A systems language.".to_string(),
+        }],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn answers_are_recorded_with_their_cost() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("A systems language.");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Very." } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let client = AskAi::new(
+        "openai".to_string(),
+        "gpt-4o-mini".to_string(),
+        None,
+        None,
+        vec![ModelPrice {
+            model: "gpt-4o-mini".to_string(),
+            input_per_million: 1_000_000.0,
+            output_per_million: 0.0,
+        }],
+    )
+    .unwrap();
+
+    let answer = client
+        .ask(question(), vec!["mobile".to_string()])
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Very.");
+    mock.assert();
+
+    let costs = client.costs();
+    assert_eq!(costs.len(), 1);
+    assert_eq!(costs[0].model, "gpt-4o-mini");
+    assert_eq!(costs[0].requests, 1);
+    assert!(costs[0].cost > 0.0);
+    assert!(client.cost_report_csv().starts_with("model,day,tag,"));
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[tokio::test]
+async fn invalid_configs_are_reported() {
+    let result = AskAi::from_json(r#"{ "model": 42 }"#.to_string(), vec![]);
+    assert!(matches!(result, Err(AskAiError::InvalidConfig { .. })));
+
+    let client = AskAi::new(
+        "together".to_string(),
+        "llama-3.1-8b".to_string(),
+        None,
+        None,
+        vec![],
+    )
+    .unwrap();
+    match client.ask(question(), vec![]).await {
+        Err(AskAiError::Failed { message }) => assert!(message.contains("together")),
+        other => panic!("Expected AskAiError::Failed, got {:?}", other),
+    }
+    assert!(client.costs().is_empty());
+}
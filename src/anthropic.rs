@@ -0,0 +1,402 @@
+use crate::ask_ai::{
+    anthropic_endpoint_request, anthropic_request, build_anthropic_payload, send_request,
+};
+use crate::config::{AiConfig, Framework, Question};
+use crate::deadline;
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Counts the input tokens of `question` for the Claude model named by `ai_config.model`,
+/// with Anthropic's `/v1/messages/count_tokens` endpoint.
+///
+/// The count covers the system prompt, history, new prompt and images, as `ask_question`
+/// would build them, so a conversation can be checked against the model's context window
+/// before it is sent. Counting is free, but rate limited. Other providers fail with
+/// `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::anthropic::count_tokens;
+/// use ask_ai::capabilities::capabilities;
+///
+/// let input_tokens = count_tokens(&ai_config, &question).await?;
+/// let max_context = capabilities(ai_config.llm.clone(), &ai_config.model).max_context;
+/// if max_context.is_some_and(|max| input_tokens + ai_config.max_token.unwrap_or(0) > max) {
+///     // Summarize or drop the oldest exchanges first
+/// }
+/// ```
+pub async fn count_tokens(ai_config: &AiConfig, question: &Question) -> Result<u32> {
+    if ai_config.llm != Framework::Anthropic {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no token counting API", ai_config.llm),
+        });
+    }
+
+    // The endpoint takes the messages request without its generation options
+    let messages = build_anthropic_payload(question, ai_config);
+    let payload = serde_json::json!({
+        "model": messages["model"],
+        "messages": messages["messages"],
+        "system": messages["system"]
+    });
+    let builder = anthropic_endpoint_request(ai_config, "messages/count_tokens")?;
+    let resp = send_request(builder.json(&payload), ai_config).await?;
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    response["input_tokens"]
+        .as_u64()
+        .map(|tokens| tokens as u32)
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract input_tokens from response".to_string(),
+        })
+}
+
+/// A plain-text document Claude answers from, citing its passages.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Document {
+    /// Shown to Claude and returned with its citations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// A passage of a document, or of a web page found by web search, supporting part of an
+/// answer.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Citation {
+    /// The passage, quoted from the document or page.
+    pub cited_text: String,
+    /// Index of the document in the list given to `ask_with_citations`.
+    #[serde(default)]
+    pub document_index: Option<usize>,
+    #[serde(default)]
+    pub document_title: Option<String>,
+    /// Where the passage starts in the document's text, in characters.
+    #[serde(default)]
+    pub start_char_index: Option<usize>,
+    /// Where the passage ends in the document's text, in characters (exclusive).
+    #[serde(default)]
+    pub end_char_index: Option<usize>,
+    /// The cited web page, for web search results.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The cited web page's title, for web search results.
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// A page returned by a web search Claude ran.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WebSearchResult {
+    pub url: String,
+    pub title: String,
+    /// How old the page is, as reported by the search (e.g. `April 30, 2025`).
+    #[serde(default)]
+    pub page_age: Option<String>,
+}
+
+/// A part of an answer, with the passages it cites (none for connecting text).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CitedText {
+    pub text: String,
+    #[serde(default)]
+    pub citations: Vec<Citation>,
+}
+
+/// An answer grounded in documents or web pages, with its citations.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CitedAnswer {
+    /// The answer in parts, in order.
+    pub parts: Vec<CitedText>,
+    /// The pages found by the web searches Claude ran, if web search was enabled.
+    #[serde(default)]
+    pub search_results: Vec<WebSearchResult>,
+}
+
+impl CitedAnswer {
+    /// The whole answer text.
+    pub fn text(&self) -> String {
+        self.parts.iter().map(|part| part.text.as_str()).collect()
+    }
+
+    /// Every citation of the answer, in order.
+    pub fn citations(&self) -> impl Iterator<Item = &Citation> {
+        self.parts.iter().flat_map(|part| &part.citations)
+    }
+}
+
+/// Asks `question` with `documents` attached, returning the answer with the passages of the
+/// documents each of its parts relies on.
+///
+/// Uses Anthropic's citations: the documents are sent as document blocks, before the new
+/// prompt, with citations enabled. Cited text does not count towards output tokens. With
+/// `AnthropicOptions::web_search` set, the pages Claude found are returned and cited too, and
+/// `documents` may be empty. Other providers fail with `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::anthropic::{ask_with_citations, Document};
+///
+/// let documents = vec![Document {
+///     title: Some("Employee handbook".to_string()),
+///     text: std::fs::read_to_string("handbook.txt")?,
+/// }];
+/// let answer = ask_with_citations(&ai_config, question, &documents).await?;
+/// println!("{}", answer.text());
+/// for citation in answer.citations() {
+///     println!("> {}", citation.cited_text);
+/// }
+/// ```
+pub async fn ask_with_citations(
+    ai_config: &AiConfig,
+    question: Question,
+    documents: &[Document],
+) -> Result<CitedAnswer> {
+    if ai_config.llm != Framework::Anthropic {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no citations API", ai_config.llm),
+        });
+    }
+    deadline::within(ai_config, cited_answer(ai_config, question, documents)).await
+}
+
+async fn cited_answer(
+    ai_config: &AiConfig,
+    question: Question,
+    documents: &[Document],
+) -> Result<CitedAnswer> {
+    let mut payload = build_anthropic_payload(&question, ai_config);
+    let blocks = documents.iter().map(|document| {
+        let mut block = serde_json::json!({
+            "type": "document",
+            "source": { "type": "text", "media_type": "text/plain", "data": document.text },
+            "citations": { "enabled": true }
+        });
+        if let Some(title) = &document.title {
+            block["title"] = serde_json::json!(title);
+        }
+        block
+    });
+    // The new prompt is the last user message, followed only by a prefill
+    if let Some(content) = payload["messages"]
+        .as_array_mut()
+        .and_then(|messages| messages.iter_mut().rev().find(|m| m["role"] == "user"))
+        .and_then(|message| message["content"].as_array_mut())
+    {
+        content.splice(0..0, blocks);
+    }
+
+    let resp = send_request(anthropic_request(ai_config)?.json(&payload), ai_config).await?;
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    let content = response["content"]
+        .as_array()
+        .map(|content| content.as_slice())
+        .unwrap_or_default();
+    let parts: Vec<CitedText> = content
+        .iter()
+        .filter(|block| block["type"] == "text")
+        .map(|block| {
+            // Uncited parts may carry `"citations": null`
+            let citations = match block["citations"].as_array() {
+                Some(citations) => serde_json::from_value(Value::from(citations.clone()))?,
+                None => vec![],
+            };
+            Ok(CitedText {
+                text: block["text"].as_str().unwrap_or_default().to_string(),
+                citations,
+            })
+        })
+        .collect::<std::result::Result<_, serde_json::Error>>()
+        .map_err(|e| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Failed to parse citations: {}", e),
+        })?;
+    if parts.is_empty() {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract content from Anthropic response".to_string(),
+        });
+    }
+
+    // A failed search carries an error object rather than a list of results
+    let search_results = content
+        .iter()
+        .filter(|block| block["type"] == "web_search_tool_result")
+        .filter_map(|block| block["content"].as_array())
+        .flatten()
+        .filter_map(|result| serde_json::from_value(result.clone()).ok())
+        .collect();
+    Ok(CitedAnswer {
+        parts,
+        search_results,
+    })
+}
+
+/// A block of a Claude reply's content.
+///
+/// Beta features and server tools add block types; those not listed here are kept as
+/// `Other`, with their JSON.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    /// Claude's reasoning, with extended thinking. The signature must be sent back unchanged
+    /// when the block is.
+    Thinking {
+        thinking: String,
+        #[serde(default)]
+        signature: String,
+    },
+    /// Reasoning flagged by safety systems, encrypted.
+    RedactedThinking {
+        data: String,
+    },
+    /// A call to a tool run by the caller, e.g. an action of the computer use tool.
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    /// A call to a tool Anthropic runs, e.g. web search.
+    ServerToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    #[serde(untagged)]
+    Other(Value),
+}
+
+/// A Claude reply, block by block.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnthropicReply {
+    pub content: Vec<ContentBlock>,
+    /// Why Claude stopped, e.g. `end_turn`, `max_tokens` or `tool_use`.
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+}
+
+impl AnthropicReply {
+    /// The text blocks, joined.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The calls to tools run by the caller, as `(id, name, input)`.
+    pub fn tool_uses(&self) -> impl Iterator<Item = (&str, &str, &Value)> {
+        self.content.iter().filter_map(|block| match block {
+            ContentBlock::ToolUse { id, name, input } => Some((id.as_str(), name.as_str(), input)),
+            _ => None,
+        })
+    }
+
+    /// The reply as an assistant message, to send back with the tool results.
+    pub fn message(&self) -> Value {
+        serde_json::json!({ "role": "assistant", "content": self.content })
+    }
+}
+
+/// A user message answering tool calls, with `(tool_use_id, content)` pairs. Content is a
+/// string or a list of content blocks (e.g. a screenshot, for computer use).
+pub fn tool_results<'a>(results: impl IntoIterator<Item = (&'a str, Value)>) -> Value {
+    let content: Vec<Value> = results
+        .into_iter()
+        .map(|(tool_use_id, content)| {
+            serde_json::json!({ "type": "tool_result", "tool_use_id": tool_use_id, "content": content })
+        })
+        .collect();
+    serde_json::json!({ "role": "user", "content": content })
+}
+
+/// Asks `question` and returns Claude's reply block by block, for beta features whose
+/// output is more than text, such as computer use.
+///
+/// Beta features are enabled with `AnthropicOptions::betas`, sent in the `anthropic-beta`
+/// header, and their tools with `AnthropicOptions::tools`. `continuation` is appended after
+/// the new prompt: the previous replies (`AnthropicReply::message`) and the results of their
+/// tool calls (`tool_results`), so the caller drives the tool loop; the prefill is only sent
+/// without one. Request hooks do not apply.
+/// Other providers fail with `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::anthropic::{ask_with_blocks, tool_results};
+/// use ask_ai::config::AnthropicOptions;
+///
+/// let ai_config = AiConfig {
+///     anthropic: Some(AnthropicOptions {
+///         betas: vec!["computer-use-2025-01-24".to_string()],
+///         tools: vec![serde_json::json!({
+///             "type": "computer_20250124",
+///             "name": "computer",
+///             "display_width_px": 1280,
+///             "display_height_px": 800
+///         })],
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// let mut continuation = vec![];
+/// loop {
+///     let reply = ask_with_blocks(&ai_config, question.clone(), &continuation).await?;
+///     let results: Vec<_> = reply
+///         .tool_uses()
+///         .map(|(id, _, action)| (id, perform(action)))
+///         .collect();
+///     if results.is_empty() {
+///         break println!("{}", reply.text());
+///     }
+///     continuation.push(reply.message());
+///     continuation.push(tool_results(results));
+/// }
+/// ```
+pub async fn ask_with_blocks(
+    ai_config: &AiConfig,
+    question: Question,
+    continuation: &[Value],
+) -> Result<AnthropicReply> {
+    if ai_config.llm != Framework::Anthropic {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no content blocks API", ai_config.llm),
+        });
+    }
+    deadline::within(ai_config, async {
+        let mut payload = build_anthropic_payload(&question, ai_config);
+        if let Some(messages) = payload["messages"].as_array_mut() {
+            // The prefill only makes sense as the last message
+            if !continuation.is_empty() && ai_config.anthropic_prefill().is_some() {
+                messages.pop();
+            }
+            messages.extend(continuation.iter().cloned());
+        }
+        let resp = send_request(anthropic_request(ai_config)?.json(&payload), ai_config).await?;
+        resp.json().await.map_err(|e| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Failed to parse JSON response: {}", e),
+        })
+    })
+    .await
+}
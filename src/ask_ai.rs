@@ -1,12 +1,30 @@
-use crate::config::{AiConfig, Framework, Question};
-use crate::error::{AppError, Result};
-use ollama_rs::{
-    generation::chat::{request::ChatMessageRequest, ChatMessage, MessageRole},
-    Ollama,
+use crate::bedrock::build_bedrock_payload;
+use crate::capabilities::{capabilities, degrade, Capability, Degradation};
+use crate::config::{
+    AiConfig, AudioInput, EmptyPromptPolicy, Framework, Question, WebSearch, DEFAULT_MAX_TOKEN,
 };
-use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use crate::deadline;
+use crate::error::{AppError, Result};
+use crate::http::http_client;
+use crate::lmstudio::lmstudio_url;
+use crate::moderation;
+use crate::ollama::ollama_url;
+use crate::privacy::Redactions;
+use crate::provider::{provider, Completion};
+use crate::replicate::build_replicate_payload;
+use crate::secret::{scrub_secrets, SecretString};
+use crate::transcript::ImageSource;
+use ollama_rs::generation::chat::{request::ChatMessageRequest, ChatMessage, MessageRole};
+use ollama_rs::generation::images::Image;
+use ollama_rs::generation::options::GenerationOptions;
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Method, RequestBuilder, Response};
 use serde_json::Value;
 use std::env;
+use std::net::IpAddr;
+
+/// Anthropic API version sent unless `AnthropicOptions::version` overrides it.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
 
 ///### `get_openai_response`
 ///
@@ -17,7 +35,7 @@ use std::env;
 ///#### Signature:
 ///
 ///```rust,ignore
-///async fn get_openai_response(question: Question, ai_config: &AiConfig) -> Result<String>
+///async fn get_openai_response(question: Question, ai_config: &AiConfig) -> Result<(String, bool)>
 ///```
 ///
 ///---
@@ -35,26 +53,172 @@ use std::env;
 ///#### Example Usage:
 ///
 ///This function is not meant to be directly used by end-users. Instead, it gets invoked through the `ask_question` function when the `llm` field of `AiConfig` is set to `Framework::OpenAI`.
-async fn get_openai_response(question: Question, ai_config: &AiConfig) -> Result<String> {
-    let api_key = env::var("OPENAI_API_KEY").map_err(|e| AppError::ApiError {
-        model_name: ai_config.llm.to_string(),
-        failure_str: format!("Missing or invalid OPENAI_API_KEY: {}", e),
+pub(crate) async fn get_openai_response(
+    question: Question,
+    ai_config: &AiConfig,
+) -> Result<(String, bool)> {
+    let payload = build_payload(&question, ai_config);
+    let resp = send_request(openai_request(ai_config)?.json(&payload), ai_config).await?;
+
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
     })?;
 
-    // Messages array as before
-    let mut messages = vec![];
-    if let Some(sys_prompt) = &question.system_prompt {
-        messages.push(serde_json::json!({
-            "role": "system",
-            "content": sys_prompt
-        }));
-    } else {
-        messages.push(serde_json::json!({
-            "role": "system",
-            "content": ""
-        }));
+    let answer = response["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract content from OpenAI response".to_string(),
+        })?
+        .to_string();
+
+    let truncated = response["choices"][0]["finish_reason"] == "length";
+    Ok((answer, truncated))
+}
+
+/// Builds the OpenAI chat completions payload `ask_question` sends for `question`.
+///
+/// A pure function, so payload construction can be inspected and unit-tested without a server.
+/// Reasoning models (o-series, gpt-5) get `max_token` as `max_completion_tokens`, and no
+/// sampling parameters; `reasoning_effort` is sent when set.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::ask_ai::build_openai_payload;
+///
+/// let payload = build_openai_payload(&question, &ai_config);
+/// assert_eq!(payload["messages"][0]["role"], "system");
+/// ```
+pub fn build_openai_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    let mut payload = serde_json::json!({
+        "model": ai_config.model,
+        "messages": openai_messages(question, ai_config)
+    });
+    if let Some(seed) = ai_config.seed {
+        payload["seed"] = seed.into();
+    }
+    if let Some(user_id) = ai_config.client.as_ref().and_then(|c| c.user_id.as_ref()) {
+        payload["user"] = user_id.as_str().into();
+    }
+    // Reasoning models reject `max_tokens`; their limit covers reasoning tokens too
+    if capabilities(ai_config.llm.clone(), &ai_config.model).reasoning {
+        if let Some(max_tokens) = ai_config.max_token {
+            payload["max_completion_tokens"] = max_tokens.into();
+        }
+    }
+    if let Some(effort) = ai_config.reasoning_effort {
+        payload["reasoning_effort"] = serde_json::json!(effort);
+    }
+    if let Some(params) = &ai_config.params {
+        params.apply(Framework::OpenAI, &ai_config.model, &mut payload);
+    }
+    if let Some(constraint) = &ai_config.constraint {
+        constraint.apply(Framework::OpenAI, &mut payload);
+    }
+    if let Some(format) = &ai_config.response_format {
+        format.apply(Framework::OpenAI, &mut payload);
+    }
+    if let Some(vllm) = &ai_config.vllm {
+        vllm.apply(&mut payload);
+    }
+    payload
+}
+
+/// Builds the Mistral chat completions payload for `question`.
+///
+/// The messages are those of the OpenAI payload. Unlike it, `max_token` is sent (as
+/// `max_tokens`), and the seed goes under Mistral's `random_seed`; the end-user id is left
+/// out, since Mistral rejects unknown fields.
+pub fn build_mistral_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    let mut payload = serde_json::json!({
+        "model": ai_config.model,
+        "messages": openai_messages(question, ai_config)
+    });
+    if let Some(max_tokens) = ai_config.max_token {
+        payload["max_tokens"] = max_tokens.into();
+    }
+    if let Some(seed) = ai_config.seed {
+        payload["random_seed"] = seed.into();
+    }
+    if let Some(params) = &ai_config.params {
+        params.apply(Framework::Mistral, &ai_config.model, &mut payload);
+    }
+    if let Some(format) = &ai_config.response_format {
+        format.apply(Framework::Mistral, &mut payload);
+    }
+    payload
+}
+
+/// Builds the Groq chat completions payload for `question`.
+///
+/// The OpenAI payload, with `max_token` sent as `max_tokens` when set.
+pub fn build_groq_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    compatible_payload(question, ai_config, Framework::Groq)
+}
+
+/// Builds the OpenRouter chat completions payload for `question`.
+///
+/// The OpenAI payload, with `max_token` sent as `max_tokens` when set. OpenRouter forwards
+/// `top_k` to the models that take it.
+pub fn build_openrouter_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    compatible_payload(question, ai_config, Framework::OpenRouter)
+}
+
+/// Builds the Hugging Face chat completions payload for `question`.
+///
+/// The OpenAI payload, with `max_token` sent as `max_tokens` when set. Inference Endpoints
+/// serve a single model and ignore `model`.
+pub fn build_huggingface_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    compatible_payload(question, ai_config, Framework::HuggingFace)
+}
+
+/// Builds the LM Studio chat completions payload for `question`.
+///
+/// The OpenAI payload, with `max_token` sent as `max_tokens` when set. LM Studio also takes
+/// `top_k`.
+pub fn build_lmstudio_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    compatible_payload(question, ai_config, Framework::LmStudio)
+}
+
+/// The payload for Ollama's OpenAI-compatible endpoint, which takes OpenAI's parameter names.
+/// Used for tool calls, which the native chat API does not return in OpenAI's shape.
+pub(crate) fn build_ollama_openai_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    compatible_payload(question, ai_config, Framework::OpenAI)
+}
+
+/// The OpenAI payload plus `max_tokens`, with sampling parameters mapped for `framework`.
+fn compatible_payload(question: &Question, ai_config: &AiConfig, framework: Framework) -> Value {
+    let mut payload = serde_json::json!({
+        "model": ai_config.model,
+        "messages": openai_messages(question, ai_config)
+    });
+    if let Some(max_tokens) = ai_config.max_token {
+        payload["max_tokens"] = max_tokens.into();
+    }
+    if let Some(seed) = ai_config.seed {
+        payload["seed"] = seed.into();
+    }
+    if let Some(user_id) = ai_config.client.as_ref().and_then(|c| c.user_id.as_ref()) {
+        payload["user"] = user_id.as_str().into();
+    }
+    if let Some(params) = &ai_config.params {
+        params.apply(framework.clone(), &ai_config.model, &mut payload);
+    }
+    if let Some(format) = &ai_config.response_format {
+        format.apply(framework, &mut payload);
     }
-    if let Some(prev_messages) = question.messages {
+    payload
+}
+
+/// The OpenAI chat messages for `question`: system prompt, history, then the new prompt.
+fn openai_messages(question: &Question, ai_config: &AiConfig) -> Vec<Value> {
+    let mut messages = vec![serde_json::json!({
+        "role": "system",
+        "content": ai_config.system_prompt(question, "")
+    })];
+    if let Some(prev_messages) = &question.messages {
         for msg in prev_messages.iter() {
             if !msg.content.is_empty() {
                 messages.push(serde_json::json!({
@@ -70,60 +234,112 @@ async fn get_openai_response(question: Question, ai_config: &AiConfig) -> Result
             }
         }
     }
-    let usr_input = if question.new_prompt.is_empty() {
-        ".".to_string()
+    let usr_input = ai_config.new_prompt(question);
+    let content = if question.images.is_empty() && question.audio.is_empty() {
+        Value::from(usr_input)
     } else {
-        question.new_prompt
+        let mut parts = vec![serde_json::json!({ "type": "text", "text": usr_input })];
+        parts.extend(question.images.iter().map(ImageSource::openai_part));
+        parts.extend(question.audio.iter().map(AudioInput::openai_part));
+        Value::Array(parts)
     };
     messages.push(serde_json::json!({
         "role": "user",
-        "content": usr_input
+        "content": content
     }));
+    messages
+}
 
-    let payload = serde_json::json!({
-        "model": ai_config.model,
-        "messages": messages
-    });
-
-    // Use env-var for endpoint (to allow httpmock substitution)
-    let api_url = env::var("OPENAI_API_URL")
-        .unwrap_or_else(|_| "https://api.openai.com/v1/chat/completions".to_string());
+/// Prepares an authenticated POST to the OpenAI chat completions endpoint, or to that of
+/// LM Studio, Mistral, Groq, OpenRouter or Hugging Face, which speak the same protocol, or to
+/// Ollama's OpenAI-compatible `/v1` endpoint. `AiConfig::base_url` overrides the endpoint.
+pub(crate) fn openai_request(ai_config: &AiConfig) -> Result<RequestBuilder> {
+    openai_endpoint_request(ai_config, "chat/completions")
+}
 
-    let resp = reqwest::Client::new()
-        .post(&api_url)
-        .header(CONTENT_TYPE, "application/json")
-        .header(AUTHORIZATION, format!("Bearer {}", api_key))
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| AppError::ApiError {
-            model_name: ai_config.llm.to_string(),
-            failure_str: format!("Request error: {}", e),
-        })?;
+/// Like `openai_request`, for the endpoint at `path` under the provider's API root (e.g.
+/// `embeddings`). A `*_API_URL` override not ending in `chat/completions` is used as is.
+pub(crate) fn openai_endpoint_request(ai_config: &AiConfig, path: &str) -> Result<RequestBuilder> {
+    Ok(openai_api_request(ai_config, Method::POST, path)?.header(CONTENT_TYPE, "application/json"))
+}
 
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let err_body = resp.text().await.unwrap_or_default();
-        return Err(AppError::ApiError {
-            model_name: ai_config.llm.to_string(),
-            failure_str: format!("Status {}: {}", status, err_body),
-        });
+/// Like `openai_endpoint_request`, with any method and no content type (e.g. for `GET`
+/// requests and multipart uploads).
+pub(crate) fn openai_api_request(
+    ai_config: &AiConfig,
+    method: Method,
+    path: &str,
+) -> Result<RequestBuilder> {
+    let (api_url, api_key) = openai_endpoint(ai_config, path)?;
+    let builder = http_client(ai_config)?.request(method, &api_url);
+    match api_key {
+        Some(api_key) => Ok(builder.header(
+            AUTHORIZATION,
+            sensitive_header(&format!("Bearer {}", api_key.expose_secret()))?,
+        )),
+        None => Ok(builder),
     }
+}
 
-    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
-        model_name: ai_config.model.to_string(),
-        failure_str: format!("Failed to parse JSON response: {}", e),
-    })?;
-
-    let answer = response["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or_else(|| AppError::ModelError {
-            model_name: ai_config.model.to_string(),
-            failure_str: "Failed to extract content from OpenAI response".to_string(),
-        })?
-        .to_string();
-
-    Ok(answer)
+/// The URL of the OpenAI-protocol endpoint `path` for `ai_config`, and the API key to send.
+pub(crate) fn openai_endpoint(
+    ai_config: &AiConfig,
+    path: &str,
+) -> Result<(String, Option<SecretString>)> {
+    let (key_var, url_var, default_url) = match ai_config.llm {
+        Framework::Mistral => (
+            "MISTRAL_API_KEY",
+            "MISTRAL_API_URL",
+            "https://api.mistral.ai/v1/chat/completions",
+        ),
+        Framework::Groq => (
+            "GROQ_API_KEY",
+            "GROQ_API_URL",
+            "https://api.groq.com/openai/v1/chat/completions",
+        ),
+        Framework::OpenRouter => (
+            "OPENROUTER_API_KEY",
+            "OPENROUTER_API_URL",
+            "https://openrouter.ai/api/v1/chat/completions",
+        ),
+        Framework::HuggingFace => (
+            "HF_TOKEN",
+            "HUGGINGFACE_API_URL",
+            "https://router.huggingface.co/v1/chat/completions",
+        ),
+        _ => (
+            "OPENAI_API_KEY",
+            "OPENAI_API_URL",
+            "https://api.openai.com/v1/chat/completions",
+        ),
+    };
+    // A compatible server only gets an explicitly configured key, and often needs none
+    let (api_url, api_key) = match &ai_config.base_url {
+        // `base_url` is the Ollama server root, as for the native API
+        _ if ai_config.llm == Framework::Ollama => (
+            format!("{}v1/{}", ollama_url(ai_config)?, path),
+            ai_config.api_key.clone(),
+        ),
+        Some(base_url) => (
+            format!("{}/{}", base_url.trim_end_matches('/'), path),
+            ai_config.api_key.clone(),
+        ),
+        None if ai_config.llm == Framework::LmStudio => (
+            format!("{}/{}", lmstudio_url(ai_config), path),
+            ai_config.api_key.clone(),
+        ),
+        // Use env-var for endpoint (to allow httpmock substitution)
+        None => {
+            let chat_url = env::var(url_var).unwrap_or_else(|_| default_url.to_string());
+            let api_url = match chat_url.strip_suffix("chat/completions") {
+                Some(api_root) => format!("{}{}", api_root, path),
+                None => chat_url,
+            };
+            (api_url, Some(api_key(ai_config, key_var)?))
+        }
+    };
+    ensure_local(ai_config, &api_url)?;
+    Ok((api_url, api_key))
 }
 
 ///### `get_anthropic_response`
@@ -135,7 +351,7 @@ async fn get_openai_response(question: Question, ai_config: &AiConfig) -> Result
 ///#### Signature:
 ///
 ///```rust,ignore
-///async fn get_anthropic_response(question: Question, ai_config: &AiConfig) -> Result<String>
+///async fn get_anthropic_response(question: Question, ai_config: &AiConfig) -> Result<(String, bool)>
 ///```
 ///
 ///---
@@ -153,15 +369,47 @@ async fn get_openai_response(question: Question, ai_config: &AiConfig) -> Result
 ///
 ///This function is also internal and should not be called directly. Use invocation through `ask_question`.
 ///
-async fn get_anthropic_response(question: Question, ai_config: &AiConfig) -> Result<String> {
-    let api_key = env::var("ANTHROPIC_API_KEY").map_err(|e| AppError::ApiError {
-        model_name: ai_config.llm.to_string(),
-        failure_str: format!("Missing or invalid ANTHROPIC_API_KEY: {}", e),
+pub(crate) async fn get_anthropic_response(
+    question: Question,
+    ai_config: &AiConfig,
+) -> Result<(String, bool)> {
+    let payload = build_anthropic_payload(&question, ai_config);
+    let resp = send_request(anthropic_request(ai_config)?.json(&payload), ai_config).await?;
+
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
     })?;
 
-    // Build messages array
+    // Server tools such as web search split the answer into several text blocks
+    let text: String = response["content"]
+        .as_array()
+        .map(|content| {
+            content
+                .iter()
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+        })
+        .filter(|texts| !texts.is_empty())
+        .map(|texts| texts.concat())
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract content from Anthropic response".to_string(),
+        })?;
+    let answer = format!(
+        "{}{}",
+        ai_config.anthropic_prefill().unwrap_or_default(),
+        text
+    );
+
+    let truncated = response["stop_reason"] == "max_tokens";
+    Ok((answer, truncated))
+}
+
+/// Builds the Anthropic messages payload `ask_question` sends for `question`.
+pub fn build_anthropic_payload(question: &Question, ai_config: &AiConfig) -> Value {
     let mut messages = vec![];
-    if let Some(prev_messages) = question.messages {
+    if let Some(prev_messages) = &question.messages {
         for msg in prev_messages.iter() {
             if !msg.content.is_empty() {
                 messages.push(serde_json::json!({
@@ -177,67 +425,219 @@ async fn get_anthropic_response(question: Question, ai_config: &AiConfig) -> Res
             }
         }
     }
-    let usr_input = if question.new_prompt.is_empty() {
-        ".".to_string()
-    } else {
-        question.new_prompt
-    };
+    let usr_input = ai_config.new_prompt(question);
+    let mut content: Vec<Value> = question
+        .images
+        .iter()
+        .map(ImageSource::anthropic_block)
+        .collect();
+    content.push(serde_json::json!({"type": "text", "text": usr_input}));
     messages.push(serde_json::json!({
         "role": "user",
-        "content": [{"type": "text", "text": usr_input}]
+        "content": content
     }));
+    if let Some(prefill) = ai_config.anthropic_prefill() {
+        messages.push(serde_json::json!({
+            "role": "assistant",
+            "content": [{"type": "text", "text": prefill}]
+        }));
+    }
 
-    let system_prompt = question.system_prompt.unwrap_or_else(|| {
-        "You are a helpful assistant. Answer the question concisely.".to_string()
-    });
-    let max_tokens = ai_config.max_token.unwrap_or(1024);
+    let system_prompt = ai_config.system_prompt(
+        question,
+        "You are a helpful assistant. Answer the question concisely.",
+    );
+    let max_tokens = ai_config.max_token.unwrap_or(DEFAULT_MAX_TOKEN);
 
-    let payload = serde_json::json!({
+    let mut payload = serde_json::json!({
         "model": ai_config.model,
         "max_tokens": max_tokens,
         "messages": messages,
         "system": system_prompt
     });
+    if let Some(user_id) = ai_config.client.as_ref().and_then(|c| c.user_id.as_ref()) {
+        payload["metadata"] = serde_json::json!({ "user_id": user_id });
+    }
+    if let Some(options) = &ai_config.anthropic {
+        let tools: Vec<Value> = options
+            .web_search
+            .iter()
+            .map(WebSearch::tool)
+            .chain(options.tools.iter().cloned())
+            .collect();
+        if !tools.is_empty() {
+            payload["tools"] = Value::from(tools);
+        }
+    }
+    if let Some(params) = &ai_config.params {
+        params.apply(Framework::Anthropic, &ai_config.model, &mut payload);
+    }
+    payload
+}
+
+/// Prepares an authenticated POST to the Anthropic messages endpoint.
+pub(crate) fn anthropic_request(ai_config: &AiConfig) -> Result<RequestBuilder> {
+    anthropic_endpoint_request(ai_config, "messages")
+}
 
-    let api_url = env::var("ANTHROPIC_API_URL")
+/// Like `anthropic_request`, for the endpoint at `path` under the API root (e.g.
+/// `messages/count_tokens`). An `ANTHROPIC_API_URL` not ending in `messages` is used as is.
+pub(crate) fn anthropic_endpoint_request(
+    ai_config: &AiConfig,
+    path: &str,
+) -> Result<RequestBuilder> {
+    let api_key = api_key(ai_config, "ANTHROPIC_API_KEY")?;
+
+    let messages_url = env::var("ANTHROPIC_API_URL")
         .unwrap_or_else(|_| "https://api.anthropic.com/v1/messages".to_string());
+    let api_url = match messages_url.strip_suffix("messages") {
+        Some(api_root) => format!("{}{}", api_root, path),
+        None => messages_url,
+    };
+    ensure_local(ai_config, &api_url)?;
+    let options = ai_config.anthropic.clone().unwrap_or_default();
 
-    let resp = reqwest::Client::new()
+    let builder = http_client(ai_config)?
         .post(&api_url)
-        .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
-        .header(CONTENT_TYPE, "application/json")
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| AppError::ApiError {
-            model_name: ai_config.llm.to_string(),
-            failure_str: format!("Request error: {}", e),
-        })?;
+        .header("x-api-key", sensitive_header(api_key.expose_secret())?)
+        .header(
+            "anthropic-version",
+            options.version.as_deref().unwrap_or(ANTHROPIC_VERSION),
+        )
+        .header(CONTENT_TYPE, "application/json");
+    if options.betas.is_empty() {
+        return Ok(builder);
+    }
+    Ok(builder.header("anthropic-beta", options.betas.join(",")))
+}
+
+/// Resolves the provider API key: `AiConfig::api_key` when set, else the `env_var` variable.
+pub(crate) fn api_key(ai_config: &AiConfig, env_var: &str) -> Result<SecretString> {
+    match &ai_config.api_key {
+        Some(api_key) => Ok(api_key.clone()),
+        None => env::var(env_var)
+            .map(SecretString::from)
+            .map_err(|e| AppError::ApiError {
+                model_name: ai_config.llm.to_string(),
+                failure_str: format!("Missing or invalid {}: {}", env_var, e),
+            }),
+    }
+}
+
+/// Rejects `url` when `AiConfig::local_only` is set and it is not a local endpoint.
+pub(crate) fn ensure_local(ai_config: &AiConfig, url: &str) -> Result<()> {
+    // A Unix domain socket never leaves the machine, whatever host the URL names
+    let unix_socket = ai_config
+        .http
+        .as_ref()
+        .is_some_and(|http| http.unix_socket.is_some());
+    if !ai_config.local_only || unix_socket || is_local_endpoint(url) {
+        return Ok(());
+    }
+
+    Err(AppError::RemoteEndpointBlocked {
+        model_name: ai_config.llm.to_string(),
+        endpoint: url.to_string(),
+    })
+}
+
+/// Whether `url` points at this machine or a private network address.
+fn is_local_endpoint(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+
+    match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => {
+            // Loopback, unique local (fc00::/7) and link-local (fe80::/10)
+            ip.is_loopback()
+                || ip.segments()[0] & 0xfe00 == 0xfc00
+                || ip.segments()[0] & 0xffc0 == 0xfe80
+        }
+        Err(_) => host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local"),
+    }
+}
+
+/// Builds a credential header value that `Debug` output (and logging middleware) masks.
+pub(crate) fn sensitive_header(value: &str) -> Result<HeaderValue> {
+    let mut header = HeaderValue::from_str(value).map_err(|_| {
+        AppError::UnexpectedError("API key contains invalid header characters".to_string())
+    })?;
+    header.set_sensitive(true);
+    Ok(header)
+}
+
+/// Sends a prepared request, turning transport failures and non-success statuses into
+/// `AppError::ApiError`.
+pub(crate) async fn send_request(
+    builder: RequestBuilder,
+    ai_config: &AiConfig,
+) -> Result<Response> {
+    let request_error = |e: reqwest::Error| AppError::ApiError {
+        model_name: ai_config.llm.to_string(),
+        failure_str: scrub_secrets(&format!("Request error: {}", e), ai_config),
+    };
+    if let Some(faults) = &ai_config.faults {
+        faults.inject(ai_config).await?;
+    }
+    let (client, request) = builder.build_split();
+    let mut request = request.map_err(request_error)?;
+    add_client_headers(&mut request, ai_config)?;
+    if let Some(signing) = &ai_config.signing {
+        signing.sign(&mut request)?;
+    }
+
+    let resp = client.execute(request).await.map_err(request_error)?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let err_body = resp.text().await.unwrap_or_default();
         return Err(AppError::ApiError {
             model_name: ai_config.llm.to_string(),
-            failure_str: format!("Status {}: {}", status, err_body),
+            failure_str: scrub_secrets(&format!("Status {}: {}", status, err_body), ai_config),
         });
     }
 
-    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
-        model_name: ai_config.model.to_string(),
-        failure_str: format!("Failed to parse JSON response: {}", e),
-    })?;
+    Ok(resp)
+}
 
-    let answer = response["content"][0]["text"]
-        .as_str()
-        .ok_or_else(|| AppError::ModelError {
-            model_name: ai_config.model.to_string(),
-            failure_str: "Failed to extract content from Anthropic response".to_string(),
-        })?
-        .to_string();
+/// Sets the User-Agent and the configured application headers on `request`.
+///
+/// OpenRouter attributes requests to an application by `HTTP-Referer` and `X-Title`, so
+/// those are always sent to it, naming this crate unless configured.
+fn add_client_headers(request: &mut reqwest::Request, ai_config: &AiConfig) -> Result<()> {
+    let mut client = ai_config.client.clone().unwrap_or_default();
+    let user_agent = client
+        .user_agent
+        .unwrap_or_else(|| concat!("ask_ai/", env!("CARGO_PKG_VERSION")).to_string());
+    if ai_config.llm == Framework::OpenRouter {
+        client.app_url = client
+            .app_url
+            .or_else(|| Some(env!("CARGO_PKG_REPOSITORY").to_string()));
+        client.app_title = client.app_title.or_else(|| Some("ask_ai".to_string()));
+    }
+
+    let headers = [
+        ("User-Agent".to_string(), Some(user_agent)),
+        ("HTTP-Referer".to_string(), client.app_url),
+        ("X-Title".to_string(), client.app_title),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some((name, value?)))
+    .chain(client.headers);
 
-    Ok(answer)
+    for (name, value) in headers {
+        // The value is left out of the error, it may be a credential
+        let invalid = || AppError::UnexpectedError(format!("Invalid client header `{}`", name));
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+        let header_value = HeaderValue::from_str(&value).map_err(|_| invalid())?;
+        request.headers_mut().insert(header_name, header_value);
+    }
+    Ok(())
 }
 
 ///### `get_ollama_response`
@@ -249,7 +649,7 @@ async fn get_anthropic_response(question: Question, ai_config: &AiConfig) -> Res
 ///#### Signature:
 ///
 ///```rust,ignore
-///async fn get_ollama_response(question: Question, ai_config: &AiConfig) -> Result<String>
+///async fn get_ollama_response(question: Question, ai_config: &AiConfig) -> Result<(String, bool)>
 ///```
 ///
 ///---
@@ -266,32 +666,123 @@ async fn get_anthropic_response(question: Question, ai_config: &AiConfig) -> Res
 ///#### Example Usage:
 ///
 ///This function is internal and used exclusively through `ask_question`.
-async fn get_ollama_response(question: Question, ai_config: &AiConfig) -> Result<String> {
-    let mut ollama = Ollama::default();
+pub(crate) async fn get_ollama_response(
+    question: Question,
+    ai_config: &AiConfig,
+) -> Result<(String, bool)> {
+    let builder = ollama_http_request(question, ai_config, false)?;
+    let resp = send_request(builder, ai_config).await?;
 
-    // Creating the chain
-    let mut msgs = vec![];
-
-    if question.system_prompt.is_some() {
-        msgs.push(ChatMessage {
-            role: MessageRole::System,
-            content: question.system_prompt.unwrap(),
-            tool_calls: vec![],
-            images: None,
-        });
-    } else {
-        let default_sys_prompt =
-            String::from("You are helpful assistant. Answer the question consicely.");
-        msgs.push(ChatMessage {
-            role: MessageRole::System,
-            content: default_sys_prompt,
-            tool_calls: vec![],
-            images: None,
-        });
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    let answer = response["message"]["content"]
+        .as_str()
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract content from Ollama response".to_string(),
+        })?
+        .to_string();
+
+    let truncated = response["done_reason"] == "length";
+    Ok((answer, truncated))
+}
+
+/// Builds the Ollama `/api/chat` payload for `question` (non-streaming).
+pub fn build_ollama_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    let req = ollama_chat_request(ai_config, ollama_messages(question, ai_config));
+    // A request made of strings and numbers always serializes
+    let mut payload = serde_json::to_value(req).unwrap_or_default();
+    payload["stream"] = Value::Bool(false);
+    if let Some(params) = &ai_config.params {
+        params.apply(Framework::Ollama, &ai_config.model, &mut payload);
+    }
+    if let Some(options) = &ai_config.ollama {
+        options.apply(&mut payload);
+    }
+    if let Some(constraint) = &ai_config.constraint {
+        constraint.apply(Framework::Ollama, &mut payload);
     }
+    if let Some(format) = &ai_config.response_format {
+        format.apply(Framework::Ollama, &mut payload);
+    }
+    payload
+}
 
-    if question.messages.is_some() {
-        for msg in question.messages.unwrap().iter() {
+/// Builds the payload for the configured provider.
+pub(crate) fn build_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    match ai_config.llm {
+        Framework::OpenAI => build_openai_payload(question, ai_config),
+        Framework::Anthropic => build_anthropic_payload(question, ai_config),
+        Framework::Ollama => build_ollama_payload(question, ai_config),
+        Framework::LmStudio => build_lmstudio_payload(question, ai_config),
+        Framework::Bedrock => build_bedrock_payload(question, ai_config),
+        Framework::Mistral => build_mistral_payload(question, ai_config),
+        Framework::Groq => build_groq_payload(question, ai_config),
+        Framework::OpenRouter => build_openrouter_payload(question, ai_config),
+        Framework::HuggingFace => build_huggingface_payload(question, ai_config),
+        Framework::Replicate => build_replicate_payload(question, ai_config),
+        // Only measured, never sent, so the most common shape will do
+        Framework::Custom(_) => build_openai_payload(question, ai_config),
+    }
+}
+
+/// The error for sending a question to a `Framework::Custom` provider without a registered
+/// client.
+pub(crate) fn unsupported_provider(ai_config: &AiConfig) -> AppError {
+    AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("No client registered for provider `{}`", ai_config.llm),
+    }
+}
+
+/// Builds the Ollama chat request for `messages`, applying the configured seed.
+pub(crate) fn ollama_chat_request(
+    ai_config: &AiConfig,
+    messages: Vec<ChatMessage>,
+) -> ChatMessageRequest {
+    let req = ChatMessageRequest::new(ai_config.model.to_owned(), messages);
+    match ai_config.seed {
+        Some(seed) => req.options(GenerationOptions::default().seed(seed)),
+        None => req,
+    }
+}
+
+/// Prepares a POST to Ollama's `/api/chat` endpoint, for callers that handle the HTTP
+/// response themselves (streaming, raw responses).
+pub(crate) fn ollama_http_request(
+    question: Question,
+    ai_config: &AiConfig,
+    stream: bool,
+) -> Result<RequestBuilder> {
+    let api_url = format!("{}api/chat", ollama_url(ai_config)?);
+    ensure_local(ai_config, &api_url)?;
+    let mut payload = build_ollama_payload(&question, ai_config);
+    payload["stream"] = Value::Bool(stream);
+
+    Ok(http_client(ai_config)?
+        .post(api_url)
+        .header(CONTENT_TYPE, "application/json")
+        .json(&payload))
+}
+
+/// Builds the Ollama chat messages for `question`.
+pub(crate) fn ollama_messages(question: &Question, ai_config: &AiConfig) -> Vec<ChatMessage> {
+    // Creating the chain
+    let mut msgs = vec![ChatMessage {
+        role: MessageRole::System,
+        content: ai_config.system_prompt(
+            question,
+            "You are helpful assistant. Answer the question consicely.",
+        ),
+        tool_calls: vec![],
+        images: None,
+    }];
+
+    if let Some(prev_messages) = &question.messages {
+        for msg in prev_messages.iter() {
             if !msg.content.is_empty() {
                 msgs.push(ChatMessage {
                     role: MessageRole::User,
@@ -312,42 +803,236 @@ async fn get_ollama_response(question: Question, ai_config: &AiConfig) -> Result
         }
     }
 
-    if question.new_prompt.is_empty() {
-        msgs.push(ChatMessage {
-            role: MessageRole::User,
-            content: String::from("."),
-            tool_calls: vec![],
-            images: None,
-        });
-    } else {
-        msgs.push(ChatMessage {
-            role: MessageRole::User,
-            content: question.new_prompt.to_owned(),
-            tool_calls: vec![],
-            images: None,
-        });
+    // URL images are rejected by `prepare`, as Ollama only takes image data
+    let images: Vec<Image> = question
+        .images
+        .iter()
+        .filter_map(|image| match image {
+            ImageSource::Base64 { data, .. } => Some(Image::from_base64(data)),
+            ImageSource::Url { .. } => None,
+        })
+        .collect();
+    msgs.push(ChatMessage {
+        role: MessageRole::User,
+        content: ai_config.new_prompt(question),
+        tool_calls: vec![],
+        images: (!images.is_empty()).then_some(images),
+    });
+
+    msgs
+}
+
+pub async fn ask_question(ai_config: &AiConfig, question: Question) -> Result<String> {
+    deadline::within(ai_config, async {
+        match &ai_config.cache {
+            Some(cache) => cache.ask(ai_config, question).await,
+            None => uncached(ai_config, question).await,
+        }
+    })
+    .await
+}
+
+/// Answers without consulting `AiConfig::cache`.
+pub(crate) async fn uncached(ai_config: &AiConfig, question: Question) -> Result<String> {
+    match (&ai_config.replay, &ai_config.hedge) {
+        (Some(replay), _) => replay.ask(ai_config, question).await,
+        (None, Some(hedge)) => hedge.ask(ai_config, question).await,
+        (None, None) => dispatch(ai_config, question).await,
     }
+}
+
+/// Asks a question and returns the provider's full JSON response instead of only the answer.
+///
+/// Privacy mode applies as usual (placeholders are restored in every string of the response);
+/// replay cassettes are not consulted. Pair it with `normalize::Normalizer` to snapshot-test
+/// responses. Custom providers answer with `provider::Provider::ask_raw`.
+///
+/// Parameters from `AiConfig::params` the provider could not take as given are listed under
+/// `ask_ai.param_warnings` in the response.
+/// How a question that overflowed the context window was answered, if it did, is under
+/// `ask_ai.context_overflow` (see `overflow::ContextOverflow`), and what the provider's content
+/// filter decided under `AiConfig::safety` is under `ask_ai.safety` (see `safety::SafetyVerdict`).
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::{ask_ai::ask_question_raw, normalize::Normalizer};
+///
+/// let response = ask_question_raw(&ai_config, question).await?;
+/// insta::assert_json_snapshot!(Normalizer::default().normalize(&response));
+/// ```
+pub async fn ask_question_raw(ai_config: &AiConfig, question: Question) -> Result<Value> {
+    deadline::within(ai_config, raw(ai_config, question)).await
+}
+
+async fn raw(ai_config: &AiConfig, question: Question) -> Result<Value> {
+    let (mut response, action) = match &ai_config.on_context_overflow {
+        Some(overflow) => {
+            let fallback = overflow.fallback_for(ai_config);
+            overflow
+                .send(ai_config, fallback.as_deref(), question, raw_response)
+                .await?
+        }
+        None => (raw_response(ai_config, question).await?, None),
+    };
+
+    if let Some(action) = action {
+        response["ask_ai"]["context_overflow"] = serde_json::json!(action);
+    }
+    let warnings = ai_config
+        .params
+        .as_ref()
+        .map(|params| {
+            params
+                .translate_for_model(ai_config.llm.clone(), &ai_config.model)
+                .warnings
+        })
+        .unwrap_or_default();
+    if !warnings.is_empty() {
+        response["ask_ai"]["param_warnings"] = serde_json::json!(warnings);
+    }
+    let verdict = ai_config
+        .safety
+        .as_ref()
+        .and_then(|safety| safety.verdict(&ai_config.llm, &response));
+    if let Some(verdict) = verdict {
+        response["ask_ai"]["safety"] = serde_json::json!(verdict);
+    }
+    Ok(response)
+}
 
-    // Construct the chat completion request with the system and user messages
-    let req = ChatMessageRequest::new(ai_config.model.to_owned(), msgs.to_owned());
+async fn raw_response(ai_config: &AiConfig, question: Question) -> Result<Value> {
+    let (question, redactions) = prepare(ai_config, question).await?;
 
-    let result = ollama
-        .send_chat_messages_with_history(&mut msgs, req)
+    let response = provider(ai_config)?.ask_raw(ai_config, question).await?;
+    Ok(redactions.restore_json(response))
+}
+
+/// Sends `builder` and parses the provider's JSON response.
+pub(crate) async fn send_raw(builder: RequestBuilder, ai_config: &AiConfig) -> Result<Value> {
+    send_request(builder, ai_config)
+        .await?
+        .json()
         .await
         .map_err(|e| AppError::ModelError {
-            model_name: ai_config.model.to_owned(),
-            failure_str: e.to_string(),
-        })?;
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Failed to parse JSON response: {}", e),
+        })
+}
 
-    let answer = result.message.content;
+/// Sends a question to the configured provider, applying `AiConfig::on_unsupported` to
+/// images, privacy mode, `AiConfig::on_context_overflow` and `AiConfig::auto_continue`.
+pub(crate) async fn dispatch(ai_config: &AiConfig, mut question: Question) -> Result<String> {
+    if !question.images.is_empty() {
+        match degrade(ai_config, Capability::Vision)? {
+            Degradation::Supported => {}
+            Degradation::Strip => question.images.clear(),
+            Degradation::Fallback(fallback) => return Box::pin(dispatch(fallback, question)).await,
+        }
+    }
+    match &ai_config.on_context_overflow {
+        Some(overflow) => {
+            let fallback = overflow.fallback_for(ai_config);
+            overflow
+                .send(ai_config, fallback.as_deref(), question, send_question)
+                .await
+                .map(|(answer, _)| answer)
+        }
+        None => send_question(ai_config, question).await,
+    }
+}
 
-    Ok(answer)
+async fn send_question(ai_config: &AiConfig, question: Question) -> Result<String> {
+    let (answer, truncated) = complete(ai_config, question.clone()).await?;
+    match &ai_config.auto_continue {
+        Some(auto_continue) if truncated => auto_continue.finish(ai_config, question, answer).await,
+        _ => Ok(answer),
+    }
 }
 
-pub async fn ask_question(ai_config: &AiConfig, question: Question) -> Result<String> {
-    match ai_config.llm {
-        Framework::OpenAI => get_openai_response(question, ai_config).await,
-        Framework::Anthropic => get_anthropic_response(question, ai_config).await,
-        Framework::Ollama => get_ollama_response(question, ai_config).await,
+/// Sends a question to the configured provider, applying privacy mode. Also returns whether
+/// the answer was cut off by the token limit.
+pub(crate) async fn complete(ai_config: &AiConfig, question: Question) -> Result<(String, bool)> {
+    let (question, redactions) = prepare(ai_config, question).await?;
+
+    let Completion { answer, truncated } = provider(ai_config)?.ask(ai_config, question).await?;
+
+    Ok((redactions.restore(&answer), truncated))
+}
+
+/// Rejects empty prompts, attachments, output constraints and safety settings the provider
+/// cannot take, then
+/// applies the configured locale defaults, privacy mode, compression and payload limits, if
+/// any, before a question is sent.
+pub(crate) async fn prepare(
+    ai_config: &AiConfig,
+    question: Question,
+) -> Result<(Question, Redactions)> {
+    check_empty_prompt(ai_config, &question)?;
+    check_attachments(ai_config, &question)?;
+    if let Some(constraint) = &ai_config.constraint {
+        constraint.check(ai_config)?;
+    }
+    if let Some(safety) = &ai_config.safety {
+        safety.check(ai_config)?;
+    }
+    let question = match &ai_config.locale {
+        Some(locale) => locale.apply(question),
+        None => question,
+    };
+    let (question, redactions) = match &ai_config.privacy {
+        Some(privacy) => privacy.redact(question)?,
+        None => (question, Redactions::default()),
+    };
+    // Screened after redaction, so that moderation never sees the sensitive values
+    if ai_config.moderate_prompts {
+        moderation::screen(ai_config, &question).await?;
+    }
+    let question = match &ai_config.compression {
+        Some(compression) => compression.compress(ai_config, question).await?,
+        None => question,
+    };
+    match &ai_config.limits {
+        Some(limits) => Ok((limits.enforce(ai_config, question)?, redactions)),
+        None => Ok((question, redactions)),
+    }
+}
+
+/// Fails when the question has attachments the provider cannot take: images for Bedrock and
+/// Replicate, image URLs for Ollama, and audio for providers without OpenAI's protocol.
+fn check_attachments(ai_config: &AiConfig, question: &Question) -> Result<()> {
+    let has_urls = question
+        .images
+        .iter()
+        .any(|image| matches!(image, ImageSource::Url { .. }));
+    let failure_str = match ai_config.llm {
+        Framework::Anthropic | Framework::Ollama | Framework::Bedrock | Framework::Replicate
+            if !question.audio.is_empty() =>
+        {
+            format!("{} does not take audio", ai_config.llm)
+        }
+        Framework::Bedrock | Framework::Replicate if !question.images.is_empty() => {
+            format!("{} does not take images", ai_config.llm)
+        }
+        Framework::Ollama if has_urls => "Ollama takes images as base64 data, not URLs".to_string(),
+        _ => return Ok(()),
+    };
+    Err(AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str,
+    })
+}
+
+/// Fails with `AppError::EmptyPrompt` when the prompt is empty and the config rejects those.
+pub(crate) fn check_empty_prompt(ai_config: &AiConfig, question: &Question) -> Result<()> {
+    let reject_empty = ai_config
+        .prompts
+        .as_ref()
+        .is_some_and(|prompts| prompts.empty_prompt == EmptyPromptPolicy::Reject);
+    if reject_empty && question.new_prompt.is_empty() {
+        return Err(AppError::EmptyPrompt {
+            model_name: ai_config.model.to_string(),
+        });
     }
+    Ok(())
 }
@@ -0,0 +1,330 @@
+use crate::ask_ai::{openai_api_request, send_request};
+use crate::config::{AiConfig, Framework};
+use crate::deadline;
+use crate::error::{AppError, Result};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// How long `wait_for_run` waits between two looks at a run.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// An assistant: a model with instructions, run against threads.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Assistant {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub model: String,
+    #[serde(default)]
+    pub instructions: Option<String>,
+}
+
+/// A conversation stored by OpenAI, to which messages are added and assistants run.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Thread {
+    pub id: String,
+    pub created_at: u64,
+}
+
+/// A message of a thread, with its text content.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ThreadMessage {
+    pub id: String,
+    /// `user` or `assistant`.
+    pub role: String,
+    /// The message's text parts, joined.
+    pub text: String,
+    /// The run that wrote the message, for assistant messages.
+    #[serde(default)]
+    pub run_id: Option<String>,
+}
+
+/// Where a run is in its lifecycle.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Queued,
+    InProgress,
+    /// The assistant called a function tool and waits for its output.
+    RequiresAction,
+    Cancelling,
+    Cancelled,
+    Failed,
+    Completed,
+    Incomplete,
+    Expired,
+}
+
+impl RunStatus {
+    /// Whether the run stopped moving on its own: done, failed, or waiting for tool output.
+    pub fn is_settled(self) -> bool {
+        !matches!(
+            self,
+            RunStatus::Queued | RunStatus::InProgress | RunStatus::Cancelling
+        )
+    }
+}
+
+/// Why a run failed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RunError {
+    pub code: String,
+    pub message: String,
+}
+
+/// An assistant running on a thread.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Run {
+    pub id: String,
+    pub thread_id: String,
+    pub assistant_id: String,
+    pub status: RunStatus,
+    #[serde(default)]
+    pub last_error: Option<RunError>,
+}
+
+/// Creates an assistant on the model named by `ai_config.model`.
+///
+/// OpenAI's Assistants API keeps threads server-side, so a conversation lives on across calls
+/// and processes without resending its history, unlike the stateless `ask_question`. Requests
+/// go to the `assistants` and `threads` endpoints next to chat completions, so
+/// `AiConfig::base_url` and `OPENAI_API_URL` apply. OpenAI has deprecated this API in favour
+/// of its Responses API; it remains useful with servers that still implement it.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::assistants::{ask_thread, create_assistant, create_thread};
+///
+/// let assistant = create_assistant(&ai_config, Some("Tutor"), "You teach Rust.").await?;
+/// let thread = create_thread(&ai_config).await?;
+/// // Store `assistant.id` and `thread.id` to pick the conversation up later
+/// let answer = ask_thread(&ai_config, &thread.id, &assistant.id, "What is a lifetime?").await?;
+/// let follow_up = ask_thread(&ai_config, &thread.id, &assistant.id, "An example?").await?;
+/// ```
+pub async fn create_assistant(
+    ai_config: &AiConfig,
+    name: Option<&str>,
+    instructions: &str,
+) -> Result<Assistant> {
+    let payload = serde_json::json!({
+        "model": ai_config.model,
+        "name": name,
+        "instructions": instructions
+    });
+    call(ai_config, Method::POST, "assistants", Some(payload)).await
+}
+
+/// Deletes the assistant `assistant_id`.
+pub async fn delete_assistant(ai_config: &AiConfig, assistant_id: &str) -> Result<()> {
+    call::<Value>(
+        ai_config,
+        Method::DELETE,
+        &format!("assistants/{}", assistant_id),
+        None,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Creates an empty thread.
+pub async fn create_thread(ai_config: &AiConfig) -> Result<Thread> {
+    call(
+        ai_config,
+        Method::POST,
+        "threads",
+        Some(serde_json::json!({})),
+    )
+    .await
+}
+
+/// Deletes the thread `thread_id` with its messages.
+pub async fn delete_thread(ai_config: &AiConfig, thread_id: &str) -> Result<()> {
+    call::<Value>(
+        ai_config,
+        Method::DELETE,
+        &format!("threads/{}", thread_id),
+        None,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Adds a user message to the thread `thread_id`.
+pub async fn add_message(
+    ai_config: &AiConfig,
+    thread_id: &str,
+    content: &str,
+) -> Result<ThreadMessage> {
+    let payload = serde_json::json!({ "role": "user", "content": content });
+    let message: Value = call(
+        ai_config,
+        Method::POST,
+        &format!("threads/{}/messages", thread_id),
+        Some(payload),
+    )
+    .await?;
+    thread_message(ai_config, &message)
+}
+
+/// The messages of the thread `thread_id`, oldest first (at most the last 100).
+pub async fn list_messages(ai_config: &AiConfig, thread_id: &str) -> Result<Vec<ThreadMessage>> {
+    messages(
+        ai_config,
+        &format!("threads/{}/messages?order=asc&limit=100", thread_id),
+    )
+    .await
+}
+
+/// Starts the assistant `assistant_id` on the thread `thread_id`.
+pub async fn create_run(ai_config: &AiConfig, thread_id: &str, assistant_id: &str) -> Result<Run> {
+    let payload = serde_json::json!({ "assistant_id": assistant_id });
+    call(
+        ai_config,
+        Method::POST,
+        &format!("threads/{}/runs", thread_id),
+        Some(payload),
+    )
+    .await
+}
+
+/// The run `run_id` of the thread `thread_id`, as it is now.
+pub async fn get_run(ai_config: &AiConfig, thread_id: &str, run_id: &str) -> Result<Run> {
+    call(
+        ai_config,
+        Method::GET,
+        &format!("threads/{}/runs/{}", thread_id, run_id),
+        None,
+    )
+    .await
+}
+
+/// Polls the run `run_id` until it is settled (see `RunStatus::is_settled`), and returns it.
+///
+/// Bounded by `AiConfig::deadline_ms` when set.
+pub async fn wait_for_run(ai_config: &AiConfig, thread_id: &str, run_id: &str) -> Result<Run> {
+    deadline::within(ai_config, async {
+        loop {
+            let run = get_run(ai_config, thread_id, run_id).await?;
+            if run.status.is_settled() {
+                return Ok(run);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    })
+    .await
+}
+
+/// Adds `prompt` to the thread `thread_id`, runs the assistant `assistant_id` on it, and
+/// returns the assistant's answer.
+///
+/// Runs that end in any state but `completed` fail with `AppError::ModelError`, including runs
+/// waiting for function tool output, as those are not handled here.
+pub async fn ask_thread(
+    ai_config: &AiConfig,
+    thread_id: &str,
+    assistant_id: &str,
+    prompt: &str,
+) -> Result<String> {
+    add_message(ai_config, thread_id, prompt).await?;
+    let run = create_run(ai_config, thread_id, assistant_id).await?;
+    let run = wait_for_run(ai_config, thread_id, &run.id).await?;
+    if run.status != RunStatus::Completed {
+        let reason = match &run.last_error {
+            Some(error) => format!("{}: {}", error.code, error.message),
+            None => format!("{:?}", run.status),
+        };
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Run {} did not complete: {}", run.id, reason),
+        });
+    }
+
+    let answer: Vec<String> = messages(
+        ai_config,
+        &format!("threads/{}/messages?order=asc&run_id={}", thread_id, run.id),
+    )
+    .await?
+    .into_iter()
+    .filter(|message| message.role == "assistant")
+    .map(|message| message.text)
+    .collect();
+    if answer.is_empty() {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Run {} wrote no message", run.id),
+        });
+    }
+    Ok(answer.join("\n\n"))
+}
+
+async fn messages(ai_config: &AiConfig, path: &str) -> Result<Vec<ThreadMessage>> {
+    let list: Value = call(ai_config, Method::GET, path, None).await?;
+    list["data"]
+        .as_array()
+        .map(|data| data.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|message| thread_message(ai_config, message))
+        .collect()
+}
+
+fn thread_message(ai_config: &AiConfig, message: &Value) -> Result<ThreadMessage> {
+    let text = message["content"]
+        .as_array()
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|part| part["text"]["value"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+    match (message["id"].as_str(), message["role"].as_str()) {
+        (Some(id), Some(role)) => Ok(ThreadMessage {
+            id: id.to_string(),
+            role: role.to_string(),
+            text,
+            run_id: message["run_id"].as_str().map(str::to_string),
+        }),
+        _ => Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract message from response".to_string(),
+        }),
+    }
+}
+
+/// Sends a request to the Assistants endpoint `path` and parses the response.
+async fn call<T: DeserializeOwned>(
+    ai_config: &AiConfig,
+    method: Method,
+    path: &str,
+    payload: Option<Value>,
+) -> Result<T> {
+    let mut builder = request(ai_config, method, path)?;
+    if let Some(payload) = payload {
+        builder = builder.json(&payload);
+    }
+    let resp = send_request(builder, ai_config).await?;
+    resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })
+}
+
+/// Prepares an authenticated request to the Assistants endpoint `path`.
+fn request(ai_config: &AiConfig, method: Method, path: &str) -> Result<RequestBuilder> {
+    if ai_config.llm != Framework::OpenAI {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no Assistants API", ai_config.llm),
+        });
+    }
+    Ok(openai_api_request(ai_config, method, path)?
+        .header(CONTENT_TYPE, "application/json")
+        .header("OpenAI-Beta", "assistants=v2"))
+}
@@ -0,0 +1,283 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Framework, Question};
+use crate::error::{AppError, Result};
+use crate::tenant::{estimate_tokens, prompt_tokens};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One entry of the audit log: who asked what, which model answered and how.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AuditEvent {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Who asked, e.g. a tenant or user id.
+    pub actor: String,
+    pub framework: Framework,
+    pub model: String,
+    /// The new prompt of the question.
+    pub prompt: String,
+    /// Redactions applied to the question before it was sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<String>,
+    pub decision: AuditDecision,
+}
+
+/// What happened to an audited question.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum AuditDecision {
+    /// The model answered. Token counts are estimated from text length.
+    Answered {
+        prompt_tokens: u64,
+        answer_tokens: u64,
+    },
+    /// A policy (e.g. a quota, local-only mode or moderation) rejected the question before it
+    /// was sent.
+    Blocked { reason: String },
+    /// The question was sent but the provider call failed.
+    Failed { error: String },
+}
+
+/// Destination for audit events. Sinks only ever append.
+pub trait AuditSink: Send + Sync {
+    /// Appends `event` to the log.
+    fn record(&self, event: &AuditEvent) -> Result<()>;
+}
+
+/// Appends events as JSON lines to a file.
+pub struct FileAuditSink {
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    /// Opens (and creates if needed) the log file at `path` for appending.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                AppError::UnexpectedError(format!("Failed to open {}: {}", path.display(), e))
+            })?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, event: &AuditEvent) -> Result<()> {
+        let mut line = serde_json::to_vec(event).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to serialize audit event: {}", e))
+        })?;
+        line.push(b'\n');
+
+        // One write per event, so concurrent writers never interleave lines
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(&line)
+            .and_then(|_| file.flush())
+            .map_err(|e| AppError::UnexpectedError(format!("Failed to write audit log: {}", e)))
+    }
+}
+
+/// Appends events to an `audit_log` table in a SQLite database (requires the `sqlite` feature).
+#[cfg(feature = "sqlite")]
+pub struct SqliteAuditSink {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteAuditSink {
+    /// Opens (and creates if needed) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        use crate::store::sqlite_error;
+
+        let conn = rusqlite::Connection::open(path).map_err(sqlite_error)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                actor TEXT NOT NULL,
+                event TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(sqlite_error)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl AuditSink for SqliteAuditSink {
+    fn record(&self, event: &AuditEvent) -> Result<()> {
+        let json = serde_json::to_string(event).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to serialize audit event: {}", e))
+        })?;
+
+        self.conn
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .execute(
+                "INSERT INTO audit_log (timestamp, actor, event) VALUES (?1, ?2, ?3)",
+                rusqlite::params![event.timestamp as i64, event.actor, json],
+            )
+            .map_err(crate::store::sqlite_error)?;
+        Ok(())
+    }
+}
+
+/// Hands every event to a closure, e.g. to forward it to an external logging system.
+pub struct CallbackAuditSink<F>(F);
+
+impl<F> CallbackAuditSink<F>
+where
+    F: Fn(&AuditEvent) + Send + Sync,
+{
+    pub fn new(callback: F) -> Self {
+        Self(callback)
+    }
+}
+
+impl<F> AuditSink for CallbackAuditSink<F>
+where
+    F: Fn(&AuditEvent) + Send + Sync,
+{
+    fn record(&self, event: &AuditEvent) -> Result<()> {
+        (self.0)(event);
+        Ok(())
+    }
+}
+
+/// A cheaply cloneable handle to an audit sink.
+///
+/// Recording is fail-closed: if an event cannot be written, the audited call returns the
+/// sink's error instead of the answer.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::audit::{AuditLog, FileAuditSink};
+///
+/// let audit = AuditLog::new(FileAuditSink::open("audit.jsonl")?);
+/// let answer = audit.ask_question("alice", &ai_config, question).await?;
+///
+/// // Or audit every question asked through a tenant registry
+/// let registry = TenantRegistry::new().with_audit(audit);
+/// ```
+#[derive(Clone)]
+pub struct AuditLog(Arc<dyn AuditSink>);
+
+impl AuditLog {
+    pub fn new(sink: impl AuditSink + 'static) -> Self {
+        Self(Arc::new(sink))
+    }
+
+    /// Appends `event` to the underlying sink.
+    pub fn record(&self, event: &AuditEvent) -> Result<()> {
+        self.0.record(event)
+    }
+
+    /// Asks a question on behalf of `actor` and records the outcome.
+    pub async fn ask_question(
+        &self,
+        actor: &str,
+        ai_config: &AiConfig,
+        question: Question,
+    ) -> Result<String> {
+        let audited = AuditedQuestion::new(ai_config, &question);
+        let result = ask_question(ai_config, question).await;
+
+        self.record_outcome(actor, ai_config, &audited, &result)?;
+        result
+    }
+
+    /// Records the result of a question. Errors of questions refused by a policy (quotas,
+    /// local-only mode, payload limits, moderation, capability and empty-prompt checks) count
+    /// as blocks.
+    pub(crate) fn record_outcome(
+        &self,
+        actor: &str,
+        ai_config: &AiConfig,
+        audited: &AuditedQuestion,
+        result: &Result<String>,
+    ) -> Result<()> {
+        let decision = match result {
+            Ok(answer) => AuditDecision::Answered {
+                prompt_tokens: audited.prompt_tokens,
+                answer_tokens: estimate_tokens(answer),
+            },
+            Err(
+                e @ (AppError::QuotaExceeded { .. }
+                | AppError::RemoteEndpointBlocked { .. }
+                | AppError::PayloadTooLarge { .. }
+                | AppError::ContentFlagged { .. }
+                | AppError::UnsupportedCapability { .. }
+                | AppError::EmptyPrompt { .. }),
+            ) => AuditDecision::Blocked {
+                reason: e.to_string(),
+            },
+            Err(e) => AuditDecision::Failed {
+                error: e.to_string(),
+            },
+        };
+
+        self.record(&AuditEvent {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            actor: actor.to_string(),
+            framework: ai_config.llm.clone(),
+            model: ai_config.model.clone(),
+            prompt: audited.prompt.clone(),
+            redactions: audited.redactions.clone(),
+            decision,
+        })
+    }
+}
+
+/// The parts of a question that go into its audit event, captured before it is sent.
+pub(crate) struct AuditedQuestion {
+    pub(crate) prompt: String,
+    pub(crate) redactions: Vec<String>,
+    pub(crate) prompt_tokens: u64,
+}
+
+impl AuditedQuestion {
+    /// In privacy mode the prompt is recorded as the provider saw it, redacted.
+    pub(crate) fn new(ai_config: &AiConfig, question: &Question) -> Self {
+        let redacted = ai_config
+            .privacy
+            .as_ref()
+            .and_then(|privacy| privacy.redact(question.clone()).ok());
+
+        match redacted {
+            Some((redacted, redactions)) => Self {
+                prompt: redacted.new_prompt.clone(),
+                redactions: redactions.labels().to_vec(),
+                prompt_tokens: prompt_tokens(&redacted),
+            },
+            None => Self {
+                prompt: question.new_prompt.clone(),
+                redactions: Vec::new(),
+                prompt_tokens: prompt_tokens(question),
+            },
+        }
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AuditLog").finish_non_exhaustive()
+    }
+}
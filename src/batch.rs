@@ -0,0 +1,344 @@
+use crate::ask_ai::{
+    build_openai_payload, openai_api_request, openai_endpoint_request, send_request,
+};
+use crate::config::{AiConfig, Framework, Question};
+use crate::deadline;
+use crate::error::{AppError, Result};
+use reqwest::multipart::{Form, Part};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// How long `wait_for_batch` waits between two looks at a batch.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// The most batches the API lists per page.
+const LIST_PAGE_SIZE: u32 = 100;
+
+/// Where a batch is in its lifecycle.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Validating,
+    Failed,
+    InProgress,
+    Finalizing,
+    Completed,
+    Expired,
+    Cancelling,
+    Cancelled,
+}
+
+impl BatchStatus {
+    /// Whether the batch is over: its output, if any, can be downloaded.
+    pub fn is_done(self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Failed
+                | BatchStatus::Completed
+                | BatchStatus::Expired
+                | BatchStatus::Cancelled
+        )
+    }
+}
+
+/// How many requests of a batch are done.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestCounts {
+    pub total: u64,
+    pub completed: u64,
+    pub failed: u64,
+}
+
+/// A batch of chat completions, run by OpenAI within 24 hours.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Batch {
+    pub id: String,
+    pub status: BatchStatus,
+    pub input_file_id: String,
+    /// The answers, once some requests completed.
+    #[serde(default)]
+    pub output_file_id: Option<String>,
+    /// The failed requests, if any.
+    #[serde(default)]
+    pub error_file_id: Option<String>,
+    #[serde(default)]
+    pub request_counts: RequestCounts,
+}
+
+/// The outcome of one question of a batch.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BatchAnswer {
+    /// The id the question was given in `batch_file`.
+    pub custom_id: String,
+    /// The answer, or why the request failed.
+    pub answer: std::result::Result<String, String>,
+}
+
+/// Builds the batch input file for `questions`, given as `(custom_id, question)` pairs: one
+/// chat completions request per line, with the payload `ask_question` would send.
+///
+/// Custom ids must be unique within a batch; they tie the answers to the questions.
+pub fn batch_file(ai_config: &AiConfig, questions: &[(&str, Question)]) -> String {
+    questions
+        .iter()
+        .map(|(custom_id, question)| {
+            serde_json::json!({
+                "custom_id": custom_id,
+                "method": "POST",
+                "url": "/v1/chat/completions",
+                "body": build_openai_payload(question, ai_config)
+            })
+            .to_string()
+                + "\n"
+        })
+        .collect()
+}
+
+/// Uploads the batch file for `questions` and creates a batch of them.
+///
+/// OpenAI's Batch API answers within 24 hours at half the price of chat completions, for large
+/// workloads nobody waits on (evaluations, classification, data extraction). Requests
+/// go to the `files` and `batches` endpoints next to chat completions, so `AiConfig::base_url`
+/// and `OPENAI_API_URL` apply. Questions are sent as built: the cache, privacy mode, limits and
+/// other request hooks do not apply to batches.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::batch::{batch_results, submit_batch, wait_for_batch};
+///
+/// let batch = submit_batch(&ai_config, &[("review-1", question1), ("review-2", question2)]).await?;
+/// // Store `batch.id`, and come back later
+/// let batch = wait_for_batch(&ai_config, &batch.id).await?;
+/// for result in batch_results(&ai_config, &batch).await? {
+///     println!("{}: {:?}", result.custom_id, result.answer);
+/// }
+/// ```
+pub async fn submit_batch(ai_config: &AiConfig, questions: &[(&str, Question)]) -> Result<Batch> {
+    check_framework(ai_config)?;
+    let form = Form::new().text("purpose", "batch").part(
+        "file",
+        Part::bytes(batch_file(ai_config, questions).into_bytes()).file_name("batch.jsonl"),
+    );
+    let file: Value = parse(
+        ai_config,
+        openai_api_request(ai_config, Method::POST, "files")?.multipart(form),
+    )
+    .await?;
+    let file_id = file["id"].as_str().ok_or_else(|| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: "Failed to extract file id from response".to_string(),
+    })?;
+
+    let payload = serde_json::json!({
+        "input_file_id": file_id,
+        "endpoint": "/v1/chat/completions",
+        "completion_window": "24h"
+    });
+    parse(
+        ai_config,
+        openai_endpoint_request(ai_config, "batches")?.json(&payload),
+    )
+    .await
+}
+
+/// The batch `batch_id`, as it is now.
+pub async fn get_batch(ai_config: &AiConfig, batch_id: &str) -> Result<Batch> {
+    check_framework(ai_config)?;
+    parse(
+        ai_config,
+        openai_api_request(ai_config, Method::GET, &format!("batches/{}", batch_id))?,
+    )
+    .await
+}
+
+/// Cancels the batch `batch_id`; the requests already done are kept.
+pub async fn cancel_batch(ai_config: &AiConfig, batch_id: &str) -> Result<Batch> {
+    check_framework(ai_config)?;
+    parse(
+        ai_config,
+        openai_api_request(
+            ai_config,
+            Method::POST,
+            &format!("batches/{}/cancel", batch_id),
+        )?,
+    )
+    .await
+}
+
+/// Polls the batch `batch_id` every 30 seconds until it is done, and returns it.
+///
+/// Bounded by `AiConfig::deadline_ms` when set.
+pub async fn wait_for_batch(ai_config: &AiConfig, batch_id: &str) -> Result<Batch> {
+    watch_batch(ai_config, batch_id, |_| {}).await
+}
+
+/// Like `wait_for_batch`, calling `on_progress` with the batch on the first poll and whenever
+/// its status or request counts change.
+///
+/// Batches run on OpenAI's side, so a process that restarted can re-attach to one by its id,
+/// stored when it was submitted or found with `list_batches`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::batch::watch_batch;
+///
+/// let batch = watch_batch(&ai_config, &batch_id, |batch| {
+///     let counts = batch.request_counts;
+///     println!("{}/{} done, {} failed", counts.completed, counts.total, counts.failed);
+/// })
+/// .await?;
+/// ```
+pub async fn watch_batch(
+    ai_config: &AiConfig,
+    batch_id: &str,
+    mut on_progress: impl FnMut(&Batch),
+) -> Result<Batch> {
+    deadline::within(ai_config, async {
+        let mut last: Option<(BatchStatus, RequestCounts)> = None;
+        loop {
+            let batch = get_batch(ai_config, batch_id).await?;
+            if last != Some((batch.status, batch.request_counts)) {
+                last = Some((batch.status, batch.request_counts));
+                on_progress(&batch);
+            }
+            if batch.status.is_done() {
+                return Ok(batch);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    })
+    .await
+}
+
+/// The batches of the account, most recent first, e.g. to re-attach to one whose id was lost.
+///
+/// Follows the list's pages until the last one, so every batch is returned.
+pub async fn list_batches(ai_config: &AiConfig) -> Result<Vec<Batch>> {
+    check_framework(ai_config)?;
+    let mut batches: Vec<Batch> = vec![];
+    let mut after: Option<String> = None;
+    loop {
+        let mut query = vec![("limit", LIST_PAGE_SIZE.to_string())];
+        if let Some(after) = &after {
+            query.push(("after", after.clone()));
+        }
+        let builder = openai_api_request(ai_config, Method::GET, "batches")?.query(&query);
+        let page: Value = parse(ai_config, builder).await?;
+        let data: Vec<Batch> =
+            serde_json::from_value(page["data"].clone()).map_err(|e| AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: format!("Failed to parse batches: {}", e),
+            })?;
+        after = match page["last_id"].as_str() {
+            Some(last_id) => Some(last_id.to_string()),
+            None => data.last().map(|batch| batch.id.clone()),
+        };
+        batches.extend(data);
+        if page["has_more"] != true || after.is_none() {
+            return Ok(batches);
+        }
+    }
+}
+
+/// Downloads and parses the answers of `batch`, from its output and error files.
+///
+/// Answers come in no particular order; match them to questions by `custom_id`. A batch that
+/// was cancelled or expired keeps the answers of the requests done before it stopped: they are
+/// returned as well, and `unanswered` lists the questions left to submit again.
+pub async fn batch_results(ai_config: &AiConfig, batch: &Batch) -> Result<Vec<BatchAnswer>> {
+    check_framework(ai_config)?;
+    let mut answers = vec![];
+    for file_id in [&batch.output_file_id, &batch.error_file_id]
+        .into_iter()
+        .flatten()
+    {
+        let builder = openai_api_request(
+            ai_config,
+            Method::GET,
+            &format!("files/{}/content", file_id),
+        )?;
+        let content = send_request(builder, ai_config)
+            .await?
+            .text()
+            .await
+            .map_err(|e| AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: format!("Failed to read response: {}", e),
+            })?;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            answers.push(batch_answer(ai_config, line)?);
+        }
+    }
+    Ok(answers)
+}
+
+/// Parses a line of a batch output or error file.
+fn batch_answer(ai_config: &AiConfig, line: &str) -> Result<BatchAnswer> {
+    let parse_error = |failure_str: String| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str,
+    };
+    let result: Value = serde_json::from_str(line)
+        .map_err(|e| parse_error(format!("Failed to parse batch result: {}", e)))?;
+    let custom_id = result["custom_id"]
+        .as_str()
+        .ok_or_else(|| parse_error("Batch result without custom_id".to_string()))?
+        .to_string();
+
+    let response = &result["response"];
+    let body = &response["body"];
+    let answer = if let Some(message) = result["error"]["message"].as_str() {
+        Err(message.to_string())
+    } else if response["status_code"] != 200 {
+        Err(body["error"]["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Status {}", response["status_code"])))
+    } else {
+        body["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "No content in response".to_string())
+    };
+    Ok(BatchAnswer { custom_id, answer })
+}
+
+async fn parse<T: DeserializeOwned>(
+    ai_config: &AiConfig,
+    builder: reqwest::RequestBuilder,
+) -> Result<T> {
+    let resp = send_request(builder, ai_config).await?;
+    resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })
+}
+
+fn check_framework(ai_config: &AiConfig) -> Result<()> {
+    if ai_config.llm != Framework::OpenAI {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no Batch API", ai_config.llm),
+        });
+    }
+    Ok(())
+}
+
+/// The questions of `questions` with no answer in `answers`, e.g. those a cancelled or
+/// expired batch never got to, ready for `submit_batch`.
+pub fn unanswered<'a>(
+    questions: &[(&'a str, Question)],
+    answers: &[BatchAnswer],
+) -> Vec<(&'a str, Question)> {
+    let answered: HashSet<&str> = answers.iter().map(|a| a.custom_id.as_str()).collect();
+    questions
+        .iter()
+        .filter(|(custom_id, _)| !answered.contains(custom_id))
+        .cloned()
+        .collect()
+}
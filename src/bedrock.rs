@@ -0,0 +1,283 @@
+use crate::ask_ai::{ensure_local, send_request, sensitive_header};
+use crate::config::{AiConfig, Framework, Question, DEFAULT_MAX_TOKEN};
+use crate::cost::utc_day;
+use crate::error::{AppError, Result};
+use crate::http::http_client;
+use crate::secret::SecretString;
+use hmac::{Hmac, Mac};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{RequestBuilder, Url};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Region used when neither `AWS_REGION` nor `AWS_DEFAULT_REGION` is set.
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// AWS credentials for signing Bedrock requests with Signature Version 4.
+///
+/// Read from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and, for temporary credentials,
+/// `AWS_SESSION_TOKEN`. A Bedrock API key (`AiConfig::api_key` or `AWS_BEARER_TOKEN_BEDROCK`)
+/// takes precedence and is sent as a bearer token instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: SecretString,
+    pub session_token: Option<SecretString>,
+}
+
+impl AwsCredentials {
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| {
+            env::var(name).map_err(|e| AppError::ApiError {
+                model_name: Framework::Bedrock.to_string(),
+                failure_str: format!("Missing or invalid {}: {}", name, e),
+            })
+        };
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?.into(),
+            session_token: env::var("AWS_SESSION_TOKEN").ok().map(SecretString::from),
+        })
+    }
+
+    /// The SigV4 `Authorization` header for a Bedrock POST of a JSON `body` to `url` in
+    /// `region`, at `timestamp` (seconds since the Unix epoch, sent as `X-Amz-Date`).
+    ///
+    /// The signed headers are `content-type`, `host`, `x-amz-date` and, with a session
+    /// token, `x-amz-security-token`.
+    pub fn authorization(
+        &self,
+        region: &str,
+        url: &str,
+        body: &[u8],
+        timestamp: u64,
+    ) -> Result<String> {
+        let url = Url::parse(url)
+            .map_err(|e| AppError::UnexpectedError(format!("Invalid Bedrock URL: {}", e)))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let amz_date = amz_date(timestamp);
+        let date = &amz_date[..8];
+
+        let mut headers = vec![
+            ("content-type", "application/json".to_string()),
+            ("host", host),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.expose_secret().to_string()));
+        }
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+
+        // Every service but S3 encodes the already encoded path once more
+        let canonical_uri = url
+            .path()
+            .split('/')
+            .map(uri_encode)
+            .collect::<Vec<_>>()
+            .join("/");
+        let canonical_request = format!(
+            "POST\n{}\n{}\n{}\n{}\n{}",
+            canonical_uri,
+            url.query().unwrap_or_default(),
+            canonical_headers,
+            signed_headers,
+            hex(&Sha256::digest(body))
+        );
+
+        let scope = format!("{}/{}/bedrock/aws4_request", date, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let secret = format!("AWS4{}", self.secret_access_key.expose_secret());
+        let key = [date, region, "bedrock", "aws4_request"]
+            .iter()
+            .fold(secret.into_bytes(), |key, part| hmac(&key, part.as_bytes()));
+        let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+        Ok(format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        ))
+    }
+}
+
+/// Builds the Bedrock Converse payload for `question`.
+///
+/// The model is part of the URL, not the payload; sampling parameters go under
+/// `inferenceConfig`.
+pub fn build_bedrock_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    let text = |text: &str| serde_json::json!([{ "text": text }]);
+
+    let mut messages = vec![];
+    for msg in question.messages.iter().flatten() {
+        if !msg.content.is_empty() {
+            messages.push(serde_json::json!({ "role": "user", "content": text(&msg.content) }));
+        }
+        if !msg.output.is_empty() {
+            messages.push(serde_json::json!({ "role": "assistant", "content": text(&msg.output) }));
+        }
+    }
+    messages.push(serde_json::json!({
+        "role": "user",
+        "content": text(&ai_config.new_prompt(question))
+    }));
+
+    let system_prompt = ai_config.system_prompt(
+        question,
+        "You are a helpful assistant. Answer the question concisely.",
+    );
+    let mut payload = serde_json::json!({
+        "messages": messages,
+        "system": text(&system_prompt),
+        "inferenceConfig": {
+            "maxTokens": ai_config.max_token.unwrap_or(DEFAULT_MAX_TOKEN)
+        }
+    });
+    if let Some(params) = &ai_config.params {
+        params.apply(Framework::Bedrock, &ai_config.model, &mut payload);
+    }
+    if let Some(safety) = &ai_config.safety {
+        safety.apply(Framework::Bedrock, &mut payload);
+    }
+    payload
+}
+
+/// Prepares an authenticated POST of `payload` to the Converse endpoint of the configured
+/// model.
+///
+/// The endpoint is `https://bedrock-runtime.<region>.amazonaws.com` unless the
+/// `BEDROCK_API_URL` environment variable overrides it.
+pub(crate) fn bedrock_request(ai_config: &AiConfig, payload: &Value) -> Result<RequestBuilder> {
+    let region = env::var("AWS_REGION")
+        .or_else(|_| env::var("AWS_DEFAULT_REGION"))
+        .unwrap_or_else(|_| DEFAULT_REGION.to_string());
+    let base_url = env::var("BEDROCK_API_URL")
+        .unwrap_or_else(|_| format!("https://bedrock-runtime.{}.amazonaws.com", region));
+    let api_url = format!(
+        "{}/model/{}/converse",
+        base_url.trim_end_matches('/'),
+        uri_encode(&ai_config.model)
+    );
+    ensure_local(ai_config, &api_url)?;
+
+    // A JSON value always serializes
+    let body = serde_json::to_vec(payload).unwrap_or_default();
+    let builder = http_client(ai_config)?
+        .post(&api_url)
+        .header(CONTENT_TYPE, "application/json");
+
+    let bearer = ai_config
+        .api_key
+        .clone()
+        .or_else(|| env::var("AWS_BEARER_TOKEN_BEDROCK").ok().map(Into::into));
+    if let Some(token) = bearer {
+        return Ok(builder
+            .header(
+                AUTHORIZATION,
+                sensitive_header(&format!("Bearer {}", token.expose_secret()))?,
+            )
+            .body(body));
+    }
+
+    let credentials = AwsCredentials::from_env()?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let authorization = credentials.authorization(&region, &api_url, &body, timestamp)?;
+    let mut builder = builder
+        .header("x-amz-date", amz_date(timestamp))
+        .header(AUTHORIZATION, sensitive_header(&authorization)?);
+    if let Some(token) = &credentials.session_token {
+        builder = builder.header(
+            "x-amz-security-token",
+            sensitive_header(token.expose_secret())?,
+        );
+    }
+    Ok(builder.body(body))
+}
+
+/// Sends `question` to Bedrock's Converse API. Also returns whether the answer was cut off by
+/// the token limit.
+pub(crate) async fn get_bedrock_response(
+    question: Question,
+    ai_config: &AiConfig,
+) -> Result<(String, bool)> {
+    let payload = build_bedrock_payload(&question, ai_config);
+    let resp = send_request(bedrock_request(ai_config, &payload)?, ai_config).await?;
+
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+    bedrock_answer(&response, ai_config)
+}
+
+/// The answer text of a Converse response, and whether it hit the token limit.
+fn bedrock_answer(response: &Value, ai_config: &AiConfig) -> Result<(String, bool)> {
+    let blocks = response["output"]["message"]["content"]
+        .as_array()
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract content from Bedrock response".to_string(),
+        })?;
+    let answer = blocks
+        .iter()
+        .filter_map(|block| block["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("");
+
+    let truncated = response["stopReason"] == "max_tokens";
+    Ok((answer, truncated))
+}
+
+/// `YYYYMMDDTHHMMSSZ`, UTC.
+fn amz_date(timestamp: u64) -> String {
+    let seconds = timestamp % 86_400;
+    format!(
+        "{}T{:02}{:02}{:02}Z",
+        utc_day(timestamp).replace('-', ""),
+        seconds / 3_600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Percent-encodes everything but unreserved characters, as SigV4 requires.
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
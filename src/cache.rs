@@ -0,0 +1,541 @@
+use crate::ask_ai::uncached;
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A cached answer and how long it may be served.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub answer: String,
+    /// When the answer was cached, in milliseconds since the Unix epoch.
+    pub stored_at: u64,
+    /// Until when the answer is served as is, in milliseconds since the Unix epoch; `None`
+    /// never expires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fresh_until: Option<u64>,
+    /// Until when the expired answer is still served while a new one is fetched in the
+    /// background.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_until: Option<u64>,
+}
+
+impl CacheEntry {
+    /// An entry cached now, fresh for `ttl` (forever when `None`) and then served stale for
+    /// `stale_while_revalidate`.
+    pub fn new(
+        answer: impl Into<String>,
+        ttl: Option<Duration>,
+        stale_while_revalidate: Option<Duration>,
+    ) -> Self {
+        let stored_at = now_millis();
+        let fresh_until = ttl.map(|ttl| stored_at + ttl.as_millis() as u64);
+        let stale_until = fresh_until
+            .zip(stale_while_revalidate)
+            .map(|(fresh_until, stale)| fresh_until + stale.as_millis() as u64);
+
+        Self {
+            answer: answer.into(),
+            stored_at,
+            fresh_until,
+            stale_until,
+        }
+    }
+
+    /// When the entry can no longer be served and may be dropped by the store.
+    pub fn expires_at(&self) -> Option<u64> {
+        self.stale_until.or(self.fresh_until)
+    }
+
+    fn freshness(&self, now: u64) -> Freshness {
+        match (self.fresh_until, self.expires_at()) {
+            (Some(fresh_until), _) if now < fresh_until => Freshness::Fresh,
+            (Some(_), Some(expires_at)) if now < expires_at => Freshness::Stale,
+            (Some(_), _) => Freshness::Expired,
+            (None, _) => Freshness::Fresh,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Freshness {
+    Fresh,
+    Stale,
+    Expired,
+}
+
+/// Storage for cache entries, keyed by `Cache::key`.
+///
+/// Methods return boxed futures so a cache can be used as `dyn ResponseCache`; implementations
+/// wrap their body in `Box::pin(async move { ... })`. Stores may return expired entries, the
+/// `Cache` handle skips them.
+pub trait ResponseCache: Send + Sync {
+    /// Returns the entry cached under `key`, if any.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<CacheEntry>>>;
+    /// Caches `entry` under `key`, replacing any previous one.
+    fn put<'a>(&'a self, key: &'a str, entry: &'a CacheEntry) -> BoxFuture<'a, Result<()>>;
+    /// Removes the entry cached under `key`, if any.
+    fn invalidate<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Keeps answers in process memory.
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<CacheEntry>>> {
+        Box::pin(async move { Ok(self.entries().get(key).cloned()) })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, entry: &'a CacheEntry) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let now = now_millis();
+            let mut entries = self.entries();
+            // Expired entries are only dropped on writes, which bounds their number
+            entries.retain(|_, entry| entry.freshness(now) != Freshness::Expired);
+            entries.insert(key.to_string(), entry.clone());
+            Ok(())
+        })
+    }
+
+    fn invalidate<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.entries().remove(key);
+            Ok(())
+        })
+    }
+}
+
+/// Keeps each answer in its own JSON file under a directory, so answers survive restarts.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Uses `dir` for cached answers, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Keys are hashed again so any key makes a safe file name
+        self.dir.join(format!("{}.json", sha256_hex(key)))
+    }
+}
+
+impl ResponseCache for DiskCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<CacheEntry>>> {
+        Box::pin(async move {
+            let path = self.path(key);
+            let data = match fs::read(&path) {
+                Ok(data) => data,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(e) => {
+                    return Err(AppError::UnexpectedError(format!(
+                        "Failed to read {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            };
+            serde_json::from_slice(&data).map(Some).map_err(|e| {
+                AppError::UnexpectedError(format!("Failed to parse {}: {}", path.display(), e))
+            })
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, entry: &'a CacheEntry) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let path = self.path(key);
+            let json = serde_json::to_vec(entry).map_err(|e| {
+                AppError::UnexpectedError(format!("Failed to serialize cache entry: {}", e))
+            })?;
+            fs::write(&path, json).map_err(|e| {
+                AppError::UnexpectedError(format!("Failed to write {}: {}", path.display(), e))
+            })
+        })
+    }
+
+    fn invalidate<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let path = self.path(key);
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(AppError::UnexpectedError(
+                    format!("Failed to remove {}: {}", path.display(), e),
+                )),
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_cache::RedisCache;
+
+#[cfg(feature = "redis")]
+mod redis_cache {
+    use super::{now_millis, CacheEntry, ResponseCache};
+    use crate::error::{AppError, Result};
+    use futures_util::future::BoxFuture;
+    use redis::aio::MultiplexedConnection;
+    use std::fmt;
+
+    /// Keeps answers in Redis, shared by every process using the same server and key prefix.
+    ///
+    /// Entries with a TTL expire in Redis once they can no longer be served.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// use ask_ai::cache::{Cache, RedisCache};
+    ///
+    /// let cache = RedisCache::open("redis://cache.internal:6379", "ask_ai:answers").await?;
+    /// let ai_config = AiConfig { cache: Some(Cache::new(cache)), ..ai_config };
+    /// ```
+    #[derive(Clone)]
+    pub struct RedisCache {
+        connection: MultiplexedConnection,
+        prefix: String,
+    }
+
+    impl fmt::Debug for RedisCache {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RedisCache")
+                .field("prefix", &self.prefix)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RedisCache {
+        /// Connects to the Redis server at `url`; answers are stored as `<prefix>:<key>`.
+        pub async fn open(url: &str, prefix: &str) -> Result<Self> {
+            let client = redis::Client::open(url).map_err(redis_error)?;
+            let connection = client
+                .get_multiplexed_async_connection()
+                .await
+                .map_err(redis_error)?;
+
+            Ok(Self {
+                connection,
+                prefix: prefix.to_string(),
+            })
+        }
+
+        async fn query<T: redis::FromRedisValue>(&self, command: &redis::Cmd) -> Result<T> {
+            // Multiplexed connections are cheap to clone and share one socket
+            let mut connection = self.connection.clone();
+            command
+                .query_async(&mut connection)
+                .await
+                .map_err(redis_error)
+        }
+    }
+
+    impl ResponseCache for RedisCache {
+        fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<CacheEntry>>> {
+            Box::pin(async move {
+                let key = format!("{}:{}", self.prefix, key);
+                let json: Option<String> = self.query(redis::cmd("GET").arg(key)).await?;
+                json.map(|json| serde_json::from_str(&json))
+                    .transpose()
+                    .map_err(|e| {
+                        AppError::UnexpectedError(format!("Failed to parse cache entry: {}", e))
+                    })
+            })
+        }
+
+        fn put<'a>(&'a self, key: &'a str, entry: &'a CacheEntry) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                let key = format!("{}:{}", self.prefix, key);
+                let json = serde_json::to_string(entry).map_err(|e| {
+                    AppError::UnexpectedError(format!("Failed to serialize cache entry: {}", e))
+                })?;
+
+                let mut command = redis::cmd("SET");
+                command.arg(key).arg(json);
+                if let Some(expires_at) = entry.expires_at() {
+                    command
+                        .arg("PX")
+                        .arg(expires_at.saturating_sub(now_millis()).max(1));
+                }
+                self.query(&command).await
+            })
+        }
+
+        fn invalidate<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                let key = format!("{}:{}", self.prefix, key);
+                self.query::<i64>(redis::cmd("DEL").arg(key))
+                    .await
+                    .map(|_| ())
+            })
+        }
+    }
+
+    fn redis_error(e: redis::RedisError) -> AppError {
+        AppError::UnexpectedError(format!("Redis cache error: {}", e))
+    }
+}
+
+/// Which parts of a request make up its cache key.
+///
+/// The provider, model, token limit, seed and conversation are always part of the key, with
+/// everything else that reaches the request: attachments, base URL, output format and
+/// constraint, safety settings, and Anthropic and vLLM options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKeyPolicy {
+    /// Include the system prompt, with `AiConfig::prompts` and `AiConfig::locale`. Turn off
+    /// when it changes per request (e.g. holds the date) without changing the answer.
+    pub system_prompt: bool,
+    /// Include `AiConfig::params` (temperature, stop sequences...) and `AiConfig::ollama`.
+    pub params: bool,
+    /// A label for the model's current version (e.g. a snapshot date or fine-tune id); bumping
+    /// it retires every answer cached under the previous one.
+    pub model_revision: Option<String>,
+}
+
+impl Default for CacheKeyPolicy {
+    fn default() -> Self {
+        Self {
+            system_prompt: true,
+            params: true,
+            model_revision: None,
+        }
+    }
+}
+
+/// How answers are keyed and how long they are served.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CachePolicy {
+    pub key: CacheKeyPolicy,
+    /// How long an answer is served as is; `None` keeps it until invalidated.
+    pub ttl: Option<Duration>,
+    /// How long after `ttl` an expired answer is still served, while a new one is fetched in
+    /// the background.
+    pub stale_while_revalidate: Option<Duration>,
+}
+
+/// A cheaply cloneable handle to a response cache, set on `AiConfig::cache`.
+///
+/// Every question asked through `ask_question` (and so `Conversation`, `TenantRegistry`...)
+/// is answered from the cache when an identical one was answered before; otherwise the answer
+/// is cached once it arrives. Cache errors fail the question, while a failed background
+/// refresh leaves the stale answer in place. Streams and raw responses are not cached.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::cache::{Cache, CacheKeyPolicy, CachePolicy, DiskCache};
+/// use std::time::Duration;
+///
+/// let cache = Cache::new(DiskCache::new(".cache/answers")?).with_policy(CachePolicy {
+///     key: CacheKeyPolicy { model_revision: Some("2024-08-06".to_string()), ..Default::default() },
+///     ttl: Some(Duration::from_secs(3600)),
+///     stale_while_revalidate: Some(Duration::from_secs(300)),
+/// });
+/// let ai_config = AiConfig { cache: Some(cache.clone()), ..ai_config };
+/// let answer = ask_question(&ai_config, question.clone()).await?;
+///
+/// // Drop a bad answer so the next identical question goes to the provider
+/// cache.invalidate(&cache.key(&ai_config, &question)).await?;
+/// ```
+#[derive(Clone)]
+pub struct Cache {
+    store: Arc<dyn ResponseCache>,
+    policy: CachePolicy,
+    /// Keys with a background refresh in flight, so a stale entry is only refreshed once
+    refreshing: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Cache {
+    pub fn new(cache: impl ResponseCache + 'static) -> Self {
+        Self {
+            store: Arc::new(cache),
+            policy: CachePolicy::default(),
+            refreshing: Arc::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: CachePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &CachePolicy {
+        &self.policy
+    }
+
+    /// The key `question` is cached under with `ai_config`, following the key policy.
+    pub fn key(&self, ai_config: &AiConfig, question: &Question) -> String {
+        cache_key(ai_config, question, &self.policy.key)
+    }
+
+    /// Returns the answer cached under `key` while it can be served, fresh or stale.
+    pub async fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .store
+            .get(key)
+            .await?
+            .filter(|entry| entry.freshness(now_millis()) != Freshness::Expired)
+            .map(|entry| entry.answer))
+    }
+
+    /// Caches `answer` under `key` with the policy's TTLs.
+    pub async fn put(&self, key: &str, answer: &str) -> Result<()> {
+        let entry = CacheEntry::new(answer, self.policy.ttl, self.policy.stale_while_revalidate);
+        self.store.put(key, &entry).await
+    }
+
+    /// Caches `entry` as it is, e.g. with its own TTLs.
+    pub async fn put_entry(&self, key: &str, entry: &CacheEntry) -> Result<()> {
+        self.store.put(key, entry).await
+    }
+
+    pub async fn invalidate(&self, key: &str) -> Result<()> {
+        self.store.invalidate(key).await
+    }
+
+    /// Answers `question` from the cache, or asks the provider and caches the answer.
+    pub(crate) async fn ask(&self, ai_config: &AiConfig, question: Question) -> Result<String> {
+        let key = self.key(ai_config, &question);
+        if let Some(entry) = self.store.get(&key).await? {
+            match entry.freshness(now_millis()) {
+                Freshness::Fresh => return Ok(entry.answer),
+                Freshness::Stale => {
+                    self.refresh(key, ai_config.clone(), question);
+                    return Ok(entry.answer);
+                }
+                Freshness::Expired => {}
+            }
+        }
+
+        let answer = uncached(ai_config, question).await?;
+        self.put(&key, &answer).await?;
+        Ok(answer)
+    }
+
+    /// Fetches a new answer for a stale entry in the background.
+    fn refresh(&self, key: String, ai_config: AiConfig, question: Question) {
+        if !self.refreshing().insert(key.clone()) {
+            return;
+        }
+
+        let cache = self.clone();
+        tokio::spawn(async move {
+            if let Ok(answer) = uncached(&ai_config, question).await {
+                let _ = cache.put(&key, &answer).await;
+            }
+            cache.refreshing().remove(&key);
+        });
+    }
+
+    fn refreshing(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.refreshing.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The key `question` is cached under with `ai_config`: a SHA-256 of the canonical JSON of the
+/// parts `policy` includes.
+pub fn cache_key(ai_config: &AiConfig, question: &Question, policy: &CacheKeyPolicy) -> String {
+    let mut fingerprint = serde_json::json!({
+        "framework": ai_config.llm,
+        "model": ai_config.model,
+        "max_token": ai_config.max_token,
+        "seed": ai_config.seed,
+        "messages": question.messages,
+        "new_prompt": question.new_prompt,
+    });
+    if let Some(effort) = ai_config.reasoning_effort {
+        fingerprint["reasoning_effort"] = serde_json::json!(effort);
+    }
+    // Included only when set, so that the keys of plain requests stay as they were
+    let payload_parts = serde_json::json!({
+        "images": question.images,
+        "audio": question.audio,
+        "base_url": ai_config.base_url,
+        "anthropic": ai_config.anthropic,
+        "vllm": ai_config.vllm,
+        "constraint": ai_config.constraint,
+        "response_format": ai_config.response_format,
+        "safety": ai_config.safety,
+    });
+    insert_set(&mut fingerprint, payload_parts);
+    if policy.system_prompt {
+        fingerprint["system_prompt"] = serde_json::json!(question.system_prompt);
+        insert_set(
+            &mut fingerprint,
+            serde_json::json!({ "prompts": ai_config.prompts, "locale": ai_config.locale }),
+        );
+    }
+    if policy.params {
+        fingerprint["params"] = serde_json::json!(ai_config.params);
+        if let Some(ollama) = &ai_config.ollama {
+            fingerprint["ollama"] = serde_json::json!(ollama);
+        }
+    }
+    if let Some(revision) = &policy.model_revision {
+        fingerprint["model_revision"] = serde_json::json!(revision);
+    }
+    sha256_hex(&fingerprint.to_string())
+}
+
+/// Adds the members of `parts` that are neither null nor empty arrays to `fingerprint`.
+fn insert_set(fingerprint: &mut Value, parts: Value) {
+    let Value::Object(parts) = parts else {
+        return;
+    };
+    for (name, value) in parts {
+        let empty = value.is_null() || value.as_array().is_some_and(Vec::is_empty);
+        if !empty {
+            fingerprint[name] = value;
+        }
+    }
+}
+
+fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
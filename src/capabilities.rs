@@ -0,0 +1,244 @@
+use crate::config::AiConfig;
+use crate::config::Framework::{self, Anthropic, Groq, Mistral, Ollama, OpenAI};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a model can do, for feature-gating UI and request construction.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Function / tool calling.
+    pub supports_tools: bool,
+    /// Image inputs.
+    pub supports_vision: bool,
+    pub supports_streaming: bool,
+    /// Answers constrained to a JSON Schema.
+    pub supports_json_schema: bool,
+    /// Context window in tokens, prompt and answer together, when known.
+    pub max_context: Option<u32>,
+    /// A reasoning model (OpenAI o-series, gpt-5), which takes `max_completion_tokens` and
+    /// `reasoning_effort` but no sampling parameters.
+    #[serde(default)]
+    pub reasoning: bool,
+}
+
+/// A feature a request can depend on.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    Tools,
+    Vision,
+    Streaming,
+    JsonSchema,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::Tools => write!(f, "tools"),
+            Capability::Vision => write!(f, "image inputs"),
+            Capability::Streaming => write!(f, "streaming"),
+            Capability::JsonSchema => write!(f, "JSON Schema output"),
+        }
+    }
+}
+
+impl Capabilities {
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Tools => self.supports_tools,
+            Capability::Vision => self.supports_vision,
+            Capability::Streaming => self.supports_streaming,
+            Capability::JsonSchema => self.supports_json_schema,
+        }
+    }
+}
+
+/// What to do when a request needs a capability the configured model lacks.
+///
+/// Set on `AiConfig::on_unsupported`; without it requests are sent as they are and the
+/// provider decides. Capabilities come from `capabilities`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::capabilities::UnsupportedPolicy;
+///
+/// // Tool calls from a model without tools go to gpt-4o-mini instead
+/// let ai_config = AiConfig {
+///     on_unsupported: Some(UnsupportedPolicy::Fallback(Box::new(AiConfig {
+///         llm: Framework::OpenAI,
+///         model: "gpt-4o-mini".to_string(),
+///         ..Default::default()
+///     }))),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsupportedPolicy {
+    /// Fail with `AppError::UnsupportedCapability`.
+    #[default]
+    Error,
+    /// Leave out the unsupported part: no tools, no images, or the answer streamed as a single
+    /// delta.
+    Strip,
+    /// Send the whole request to this configuration instead.
+    Fallback(Box<AiConfig>),
+}
+
+/// How a request needing a capability goes ahead, see `degrade`.
+pub(crate) enum Degradation<'a> {
+    Supported,
+    Strip,
+    Fallback(&'a AiConfig),
+}
+
+/// Applies `AiConfig::on_unsupported` to a request needing `capability`.
+pub(crate) fn degrade(ai_config: &AiConfig, capability: Capability) -> Result<Degradation<'_>> {
+    let Some(policy) = &ai_config.on_unsupported else {
+        return Ok(Degradation::Supported);
+    };
+    if capabilities(ai_config.llm.clone(), &ai_config.model).supports(capability) {
+        return Ok(Degradation::Supported);
+    }
+
+    match policy {
+        UnsupportedPolicy::Error => Err(AppError::UnsupportedCapability {
+            model_name: ai_config.model.to_string(),
+            capability,
+        }),
+        UnsupportedPolicy::Strip => Ok(Degradation::Strip),
+        UnsupportedPolicy::Fallback(fallback) => Ok(Degradation::Fallback(fallback)),
+    }
+}
+
+const fn caps(tools: bool, vision: bool, json_schema: bool, max_context: u32) -> Capabilities {
+    Capabilities {
+        supports_tools: tools,
+        supports_vision: vision,
+        supports_streaming: true,
+        supports_json_schema: json_schema,
+        max_context: if max_context == 0 {
+            None
+        } else {
+            Some(max_context)
+        },
+        reasoning: false,
+    }
+}
+
+const fn reasoning(capabilities: Capabilities) -> Capabilities {
+    Capabilities {
+        reasoning: true,
+        ..capabilities
+    }
+}
+
+/// Known model families by name prefix; the first match wins, so specific prefixes go first.
+/// A context of 0 means unknown.
+const MODELS: &[(Framework, &str, Capabilities)] = &[
+    (OpenAI, "gpt-5-chat", caps(true, true, true, 128_000)),
+    (OpenAI, "gpt-5", reasoning(caps(true, true, true, 400_000))),
+    (OpenAI, "gpt-4.1", caps(true, true, true, 1_047_576)),
+    (OpenAI, "gpt-4o", caps(true, true, true, 128_000)),
+    (OpenAI, "gpt-4-turbo", caps(true, true, false, 128_000)),
+    (OpenAI, "gpt-4", caps(true, false, false, 8_192)),
+    (OpenAI, "gpt-3.5-turbo", caps(true, false, false, 16_385)),
+    (
+        OpenAI,
+        "o1-mini",
+        reasoning(caps(false, false, false, 128_000)),
+    ),
+    (
+        OpenAI,
+        "o1-preview",
+        reasoning(caps(false, false, false, 128_000)),
+    ),
+    (OpenAI, "o1", reasoning(caps(true, true, true, 200_000))),
+    (OpenAI, "o3", reasoning(caps(true, true, true, 200_000))),
+    (OpenAI, "o4", reasoning(caps(true, true, true, 200_000))),
+    (Anthropic, "claude-2.0", caps(false, false, false, 100_000)),
+    (Anthropic, "claude-2", caps(false, false, false, 200_000)),
+    (
+        Anthropic,
+        "claude-instant",
+        caps(false, false, false, 100_000),
+    ),
+    (
+        Anthropic,
+        "claude-3-5-haiku",
+        caps(true, false, false, 200_000),
+    ),
+    (Anthropic, "claude-", caps(true, true, false, 200_000)),
+    (Mistral, "mistral-large", caps(true, false, false, 128_000)),
+    (Mistral, "mistral-medium", caps(true, true, false, 128_000)),
+    (Mistral, "mistral-small", caps(true, true, false, 128_000)),
+    (Mistral, "pixtral", caps(true, true, false, 128_000)),
+    (Mistral, "codestral", caps(true, false, false, 256_000)),
+    (Mistral, "ministral", caps(true, false, false, 128_000)),
+    (Groq, "llama-3.1", caps(true, false, false, 131_072)),
+    (Groq, "llama-3.3", caps(true, false, false, 131_072)),
+    (Groq, "meta-llama/llama-4", caps(true, true, false, 131_072)),
+    (Groq, "mixtral", caps(true, false, false, 32_768)),
+    (Groq, "gemma2", caps(true, false, false, 8_192)),
+    // Ollama constrains output with `format` for every model; context depends on the setup
+    (Ollama, "llama3.2-vision", caps(false, true, true, 0)),
+    (Ollama, "llama3.1", caps(true, false, true, 0)),
+    (Ollama, "llama3.2", caps(true, false, true, 0)),
+    (Ollama, "llama3.3", caps(true, false, true, 0)),
+    (Ollama, "llama4", caps(true, true, true, 0)),
+    (Ollama, "qwen2.5vl", caps(false, true, true, 0)),
+    (Ollama, "qwen2.5", caps(true, false, true, 0)),
+    (Ollama, "qwen3", caps(true, false, true, 0)),
+    (Ollama, "mistral", caps(true, false, true, 0)),
+    (Ollama, "mixtral", caps(true, false, true, 0)),
+    (Ollama, "command-r", caps(true, false, true, 0)),
+    (Ollama, "gemma3", caps(false, true, true, 0)),
+    (Ollama, "llava", caps(false, true, true, 0)),
+    (Ollama, "bakllava", caps(false, true, true, 0)),
+    (Ollama, "minicpm-v", caps(false, true, true, 0)),
+];
+
+/// Returns what `model` supports on `framework`.
+///
+/// Based on a built-in table of model families; unknown models are assumed to only stream
+/// plain text (plus JSON Schema output on Ollama), with an unknown context window. OpenRouter
+/// models of OpenAI, Anthropic and Mistral (`openai/gpt-4o`, ...) are looked up as their
+/// vendor's.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::capabilities::capabilities;
+///
+/// let caps = capabilities(ai_config.llm.clone(), &ai_config.model);
+/// if caps.supports_vision {
+///     show_image_upload_button();
+/// }
+/// ```
+pub fn capabilities(framework: Framework, model: &str) -> Capabilities {
+    let model = model.to_lowercase();
+    if framework == Framework::OpenRouter {
+        let vendor = match model.split_once('/') {
+            Some(("openai", name)) => Some((OpenAI, name)),
+            Some(("anthropic", name)) => Some((Anthropic, name)),
+            Some(("mistralai", name)) => Some((Mistral, name)),
+            _ => None,
+        };
+        if let Some((vendor, name)) = vendor {
+            return capabilities(vendor, name);
+        }
+    }
+    let known = MODELS
+        .iter()
+        .find(|(llm, prefix, _)| *llm == framework && model.starts_with(prefix));
+
+    match known {
+        Some((_, _, capabilities)) => *capabilities,
+        None => Capabilities {
+            supports_json_schema: framework == Framework::Ollama,
+            ..caps(false, false, false, 0)
+        },
+    }
+}
@@ -0,0 +1,215 @@
+use crate::ask_ai::dispatch;
+use crate::config::{AiConfig, Question};
+use crate::error::Result;
+use crate::tenant::{estimate_tokens, prompt_tokens};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Phrases that add length but no information. Single-word intensifiers ("very", "really")
+/// are left alone: dropping them changes meaning ("not very good").
+const FILLER: [&str; 10] = [
+    "as you know",
+    "as a matter of fact",
+    "at the end of the day",
+    "for what it's worth",
+    "it goes without saying that",
+    "it is important to note that",
+    "it should be noted that",
+    "needless to say",
+    "please note that",
+    "to be honest",
+];
+
+/// Passages shorter than this are not worth a round trip to the compression model.
+const MIN_MODEL_PASSAGE_TOKENS: u64 = 64;
+
+const COMPRESSOR_PROMPT: &str = "Compress the user's text to as few words as possible. Keep \
+    every fact, name, number, identifier, placeholder in square brackets and code snippet \
+    verbatim. Reply with the compressed text only.";
+
+/// Shrinks long context before it is sent, aiming for a token budget.
+///
+/// Passes run in order, each only while the question is over `target_tokens`: blank lines,
+/// trailing whitespace and repeated paragraphs are removed, then filler phrases (both outside
+/// fenced code blocks), then (if a `model` is set) long passages are rewritten by that model,
+/// oldest history first and the new prompt last. The system prompt is never changed. Compression is best effort: a question may
+/// still be over budget afterwards, pair it with `limits::PayloadLimits` for a hard bound.
+///
+/// The model is held to the local-only mode of the config it compresses for.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::compress::Compression;
+///
+/// let ai_config = AiConfig {
+///     compression: Some(Compression {
+///         model: Some(Box::new(AiConfig {
+///             llm: Framework::Ollama,
+///             model: "llama3.2:1b".to_string(),
+///             ..Default::default()
+///         })),
+///         ..Compression::new(8_000)
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Compression {
+    /// Estimated prompt tokens to aim for.
+    pub target_tokens: u64,
+    /// Drop blank lines, trailing whitespace and repeated paragraphs.
+    #[serde(default = "enabled")]
+    pub dedupe: bool,
+    /// Drop filler phrases.
+    #[serde(default = "enabled")]
+    pub drop_filler: bool,
+    /// Optional (small, cheap) model that rewrites long passages in fewer words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<Box<AiConfig>>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Compression {
+    /// Dedupes and drops filler towards `target_tokens`, without a compression model.
+    pub fn new(target_tokens: u64) -> Self {
+        Self {
+            target_tokens,
+            dedupe: true,
+            drop_filler: true,
+            model: None,
+        }
+    }
+
+    /// Returns `question`, asked with `ai_config`, compressed towards the token budget.
+    pub(crate) async fn compress(
+        &self,
+        ai_config: &AiConfig,
+        mut question: Question,
+    ) -> Result<Question> {
+        if self.dedupe && self.over_budget(&question) {
+            let mut seen = HashSet::new();
+            for text in passages(&mut question) {
+                *text = map_prose(text, |prose| dedupe(prose, &mut seen));
+            }
+        }
+        if self.drop_filler && self.over_budget(&question) {
+            for text in passages(&mut question) {
+                *text = map_prose(text, drop_filler);
+            }
+        }
+        if let Some(model) = &self.model {
+            // The passages are the caller's prompt text, so its local-only mode applies
+            let model = model.on_behalf_of(ai_config);
+            let mut index = 0;
+            while self.over_budget(&question) {
+                let Some(text) = passages(&mut question).nth(index) else {
+                    break;
+                };
+                index += 1;
+                if estimate_tokens(text) < MIN_MODEL_PASSAGE_TOKENS {
+                    continue;
+                }
+                let request = Question {
+                    system_prompt: Some(COMPRESSOR_PROMPT.to_string()),
+                    messages: None,
+                    new_prompt: text.clone(),
+                    images: vec![],
+                    audio: vec![],
+                };
+                // The compression model may itself be configured to compress, hence the box
+                let compressed = Box::pin(dispatch(&model, request)).await?;
+                if estimate_tokens(&compressed) < estimate_tokens(text) {
+                    *text = compressed;
+                }
+            }
+        }
+        Ok(question)
+    }
+
+    fn over_budget(&self, question: &Question) -> bool {
+        prompt_tokens(question) > self.target_tokens
+    }
+}
+
+/// The compressible texts of `question`: history oldest first, then the new prompt.
+fn passages(question: &mut Question) -> impl Iterator<Item = &mut String> {
+    question
+        .messages
+        .iter_mut()
+        .flatten()
+        .flat_map(|prompt| [&mut prompt.content, &mut prompt.output])
+        .chain(std::iter::once(&mut question.new_prompt))
+}
+
+/// Applies `compress` to the prose of `text`, leaving fenced code blocks as they are.
+fn map_prose(text: &str, mut compress: impl FnMut(&str) -> String) -> String {
+    let mut segments = Vec::new();
+    let mut prose = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        let fence = line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~");
+        match &mut code {
+            Some(block) => {
+                block.push(line);
+                if fence {
+                    segments.push(block.join("\n"));
+                    code = None;
+                }
+            }
+            None if fence => {
+                segments.push(compress(&prose.join("\n")));
+                prose.clear();
+                code = Some(vec![line]);
+            }
+            None => prose.push(line),
+        }
+    }
+    // An unclosed fence runs to the end of the text
+    match code {
+        Some(block) => segments.push(block.join("\n")),
+        None => segments.push(compress(&prose.join("\n"))),
+    }
+    segments.retain(|segment| !segment.is_empty());
+    segments.join("\n")
+}
+
+/// Drops trailing whitespace, blank lines and paragraphs already in `seen`.
+fn dedupe(text: &str, seen: &mut HashSet<String>) -> String {
+    let mut paragraphs = Vec::new();
+    for paragraph in text.split("\n\n") {
+        let paragraph = paragraph
+            .lines()
+            // Leading whitespace is kept, it is meaningful in code
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if paragraph.is_empty() {
+            continue;
+        }
+        // Short paragraphs ("Yes.", "Thanks") repeat legitimately
+        if paragraph.len() < 40 || seen.insert(paragraph.to_lowercase()) {
+            paragraphs.push(paragraph);
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+fn drop_filler(text: &str) -> String {
+    static FILLER_REGEX: OnceLock<Regex> = OnceLock::new();
+    let regex = FILLER_REGEX.get_or_init(|| {
+        let phrases = FILLER.iter().map(|phrase| regex::escape(phrase));
+        Regex::new(&format!(
+            r"(?i)\b(?:{})\b,? ?",
+            phrases.collect::<Vec<_>>().join("|")
+        ))
+        .expect("filler phrases are escaped")
+    });
+    regex.replace_all(text, "").into_owned()
+}
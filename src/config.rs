@@ -1,10 +1,37 @@
+use crate::cache::Cache;
+use crate::capabilities::UnsupportedPolicy;
+use crate::compress::Compression;
+use crate::continuation::AutoContinue;
+use crate::error::AppError;
+use crate::faults::FaultInjection;
+use crate::grammar::{OutputConstraint, ResponseFormat};
+use crate::hedge::Hedge;
+use crate::http::HttpOptions;
+use crate::limits::PayloadLimits;
+use crate::locale::LocaleConfig;
+use crate::overflow::ContextOverflow;
+use crate::params::GenerationParams;
+use crate::privacy::PrivacyConfig;
+use crate::replay::Replay;
+use crate::safety::SafetySettings;
+use crate::secret::SecretString;
+use crate::signing::RequestSigning;
+use crate::stream::StreamResume;
+use crate::transcript::ImageSource;
+use base64::prelude::{Engine, BASE64_STANDARD};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::str::FromStr;
 
 /// Enum representing different Large Language Model (LLM) providers.
 ///
 /// This enum is used to specify which LLM framework to use when interacting with AI models.
-/// It supports three providers: OpenAI, Anthropic, and Ollama.
+/// It supports ten providers: OpenAI, Anthropic, Ollama, LM Studio, AWS Bedrock, Mistral, Groq,
+/// OpenRouter, Hugging Face and Replicate. Any other name parses to
+/// `Framework::Custom`, and serializes back to the same name.
 ///
 /// ### Example Usage:
 ///
@@ -13,16 +40,62 @@ use std::fmt;
 ///
 /// let framework = Framework::OpenAI; // Use OpenAI as the LLM provider
 /// assert_eq!(framework.to_string(), "openai");
+///
+/// let framework: Framework = "Anthropic".parse()?;
+/// assert_eq!(framework, Framework::Anthropic);
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
 pub enum Framework {
     /// Represents the OpenAI framework (e.g., GPT models).
+    #[default]
     OpenAI,
     /// Represents the Anthropic framework (e.g., Claude models).
     Anthropic,
     /// Represents the Ollama framework (e.g., locally hosted models).
     Ollama,
+    /// LM Studio's local server (e.g., any model loaded in the LM Studio app). With no model
+    /// configured, the first one loaded is used.
+    LmStudio,
+    /// AWS Bedrock's Converse API (e.g., Claude or Llama models hosted on AWS).
+    Bedrock,
+    /// Mistral AI's hosted API (e.g., Mistral Large, Codestral).
+    Mistral,
+    /// Groq's hosted API (e.g., low-latency Llama or Mixtral inference).
+    Groq,
+    /// OpenRouter's gateway to many vendors' models through one key (e.g., `openai/gpt-4o`).
+    OpenRouter,
+    /// Hugging Face's serverless Inference API or a dedicated Inference Endpoint (e.g., hosted
+    /// open models such as Llama or Qwen).
+    HuggingFace,
+    /// Replicate's hosted models, as `owner/name` or `owner/name:version` (e.g.,
+    /// `meta/meta-llama-3-8b-instruct`).
+    Replicate,
+    /// A provider without built-in support, by name. Sending a question to it fails unless a
+    /// client is registered under that name (see `provider::register_provider`), but it can
+    /// still be parsed, stored and matched on.
+    Custom(String),
+}
+
+impl Framework {
+    /// A current general-purpose model of this provider, used by `AiConfig::default_for`.
+    /// Empty for `Framework::Custom`, and for `Framework::LmStudio`, which then uses whatever
+    /// model is loaded.
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            Framework::OpenAI => "gpt-4.1-mini",
+            Framework::Anthropic => "claude-sonnet-4-5",
+            Framework::Ollama => "llama3.2",
+            Framework::LmStudio => "",
+            Framework::Bedrock => "anthropic.claude-3-5-sonnet-20240620-v1:0",
+            Framework::Mistral => "mistral-small-latest",
+            Framework::Groq => "llama-3.1-8b-instant",
+            Framework::OpenRouter => "openai/gpt-4o-mini",
+            Framework::HuggingFace => "meta-llama/Llama-3.1-8B-Instruct",
+            Framework::Replicate => "meta/meta-llama-3-8b-instruct",
+            Framework::Custom(_) => "",
+        }
+    }
 }
 
 impl fmt::Display for Framework {
@@ -31,10 +104,58 @@ impl fmt::Display for Framework {
             Framework::OpenAI => write!(f, "openai"),
             Framework::Anthropic => write!(f, "anthropic"),
             Framework::Ollama => write!(f, "ollama"),
+            Framework::LmStudio => write!(f, "lmstudio"),
+            Framework::Bedrock => write!(f, "bedrock"),
+            Framework::Mistral => write!(f, "mistral"),
+            Framework::Groq => write!(f, "groq"),
+            Framework::OpenRouter => write!(f, "openrouter"),
+            Framework::HuggingFace => write!(f, "huggingface"),
+            Framework::Replicate => write!(f, "replicate"),
+            Framework::Custom(name) => write!(f, "{}", name),
         }
     }
 }
 
+impl FromStr for Framework {
+    type Err = AppError;
+
+    /// Parses a provider name, ignoring case and surrounding whitespace. Unknown names become
+    /// `Framework::Custom`; only an empty name fails.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        match name.to_lowercase().as_str() {
+            "" => Err(AppError::UnexpectedError(
+                "Provider name is empty".to_string(),
+            )),
+            "openai" => Ok(Framework::OpenAI),
+            "anthropic" => Ok(Framework::Anthropic),
+            "ollama" => Ok(Framework::Ollama),
+            "lmstudio" => Ok(Framework::LmStudio),
+            "bedrock" => Ok(Framework::Bedrock),
+            "mistral" => Ok(Framework::Mistral),
+            "groq" => Ok(Framework::Groq),
+            "openrouter" => Ok(Framework::OpenRouter),
+            "huggingface" => Ok(Framework::HuggingFace),
+            "replicate" => Ok(Framework::Replicate),
+            _ => Ok(Framework::Custom(name.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for Framework {
+    type Error = AppError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<Framework> for String {
+    fn from(framework: Framework) -> Self {
+        framework.to_string()
+    }
+}
+
 /// Configuration for interacting with an AI model.
 ///
 /// This struct defines the necessary configuration for querying an AI model, including the
@@ -49,9 +170,10 @@ impl fmt::Display for Framework {
 ///     llm: Framework::OpenAI,           // Specify the framework provider
 ///     model: "gpt-4".to_string(),       // Specify the model to use
 ///     max_token: Some(1000),            // Optional: Limit the response to 1000 tokens
+///     ..Default::default()              // Optional settings, e.g. an explicit `api_key`
 /// };
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct AiConfig {
     /// The LLM framework provider to use (e.g., OpenAI, Anthropic, Ollama).
     pub llm: Framework,
@@ -60,6 +182,534 @@ pub struct AiConfig {
     /// Optional maximum token limit for the AI's response. If `None`, the default limit
     /// provided by the LLM API will be used.
     pub max_token: Option<u32>,
+    /// Optional API key for the provider. If `None`, the key is read from the provider's
+    /// environment variable (e.g. `OPENAI_API_KEY`). Redacted in `Debug` output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<SecretString>,
+    /// Optional base URL of an OpenAI-compatible server (LM Studio, vLLM, llama.cpp server,
+    /// LocalAI), e.g. `http://localhost:1234/v1`. Questions for OpenAI-protocol providers are
+    /// then sent to `<base_url>/chat/completions`, with `api_key` if set; the provider's key
+    /// environment variable is never sent there. For Ollama, the root of the Ollama server,
+    /// e.g. `http://gpu-box:11434`, in place of `OLLAMA_API_URL`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Optional privacy mode: sensitive values are replaced before the question is sent and
+    /// restored in the answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<PrivacyConfig>,
+    /// When set, questions are only sent to endpoints on this machine or the local network
+    /// (e.g. Ollama); anything else fails with `AppError::RemoteEndpointBlocked`.
+    #[serde(default)]
+    pub local_only: bool,
+    /// Optional sampling seed, for reproducible answers where the provider supports it
+    /// (OpenAI, Ollama).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i32>,
+    /// Optional reasoning effort of OpenAI reasoning models (o-series, gpt-5). Lower effort
+    /// answers faster and with fewer reasoning tokens, which count towards `max_token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// Optional record/replay cassette; see `replay::Replay`. Not serialized.
+    #[serde(skip)]
+    pub replay: Option<Replay>,
+    /// Optional answer cache; see `cache::Cache`. Not serialized.
+    #[serde(skip)]
+    pub cache: Option<Cache>,
+    /// Optional HMAC signing of every outgoing request, for authenticated internal gateways.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<RequestSigning>,
+    /// Optional application identity sent with every request (User-Agent, OpenRouter app
+    /// headers, end-user id).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientMetadata>,
+    /// Optional connection tuning (pool size, idle timeout, HTTP version, TCP keep-alive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpOptions>,
+    /// Optional maximum request size (body bytes, estimated prompt tokens), checked before
+    /// sending.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<PayloadLimits>,
+    /// Optional compression of long context towards a token budget, applied before `limits`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<Compression>,
+    /// Optional language-aware defaults: answer in the prompt's language, per-language system
+    /// prompts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<LocaleConfig>,
+    /// Optional Anthropic API version and beta feature flags.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anthropic: Option<AnthropicOptions>,
+    /// Optional vLLM extensions (guided decoding, `best_of`), for a vLLM server behind
+    /// `Framework::OpenAI`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vllm: Option<VllmOptions>,
+    /// Optional Ollama model options (context size, mirostat, GPU layers...) and keep-alive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ollama: Option<OllamaOptions>,
+    /// Optional sampling parameters (temperature, top-p, stop sequences...), translated to
+    /// each provider's fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<GenerationParams>,
+    /// Optional policy for requests needing a capability the model lacks (error, strip the
+    /// unsupported part, or reroute to a fallback model).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_unsupported: Option<UnsupportedPolicy>,
+    /// Optional injected failures (429, 500, timeouts) and latency, for chaos-testing retry and
+    /// fallback logic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faults: Option<FaultInjection>,
+    /// Optional default system prompt and handling of empty prompts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptDefaults>,
+    /// Optional hedged requests: a second request after a latency threshold, first answer wins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hedge: Option<Hedge>,
+    /// Optional time budget in milliseconds for a whole call, including fallbacks, hedges,
+    /// tool rounds and streaming. Running out fails the call with `AppError::DeadlineExceeded`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_ms: Option<u64>,
+    /// Optional recovery from context-length errors: a larger-context fallback model, or
+    /// dropping the oldest exchanges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_context_overflow: Option<ContextOverflow>,
+    /// Optional follow-up requests that complete answers cut off by the token limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_continue: Option<AutoContinue>,
+    /// Optional resumption of answer streams whose connection drops mid-answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_resume: Option<StreamResume>,
+    /// Optional grammar, regex or JSON Schema the answer must follow, enforced by local
+    /// model servers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<OutputConstraint>,
+    /// Optional JSON output mode: the provider's own where it has one, otherwise a request
+    /// written into the system prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Optional provider-side content filtering, such as a Bedrock guardrail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety: Option<SafetySettings>,
+    /// When set, each new prompt is screened by OpenAI's moderation endpoint first, and a
+    /// flagged one fails with `AppError::ContentFlagged` instead of being sent.
+    #[serde(default)]
+    pub moderate_prompts: bool,
+}
+
+/// Identifies the calling application (and optionally its end user) to providers and gateways.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::ClientMetadata;
+///
+/// let ai_config = AiConfig {
+///     client: Some(ClientMetadata {
+///         user_agent: Some("acme-support/2.1".to_string()),
+///         app_url: Some("https://support.acme.com".to_string()),
+///         app_title: Some("Acme Support".to_string()),
+///         user_id: Some("user-1234".to_string()),
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ClientMetadata {
+    /// `User-Agent` header. Defaults to `ask_ai/<version>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Application URL, sent as OpenRouter's `HTTP-Referer` header. Defaults to the crate's
+    /// repository for `Framework::OpenRouter`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_url: Option<String>,
+    /// Application name, sent as OpenRouter's `X-Title` header. Defaults to `ask_ai` for
+    /// `Framework::OpenRouter`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_title: Option<String>,
+    /// Opaque end-user id for provider abuse monitoring: Anthropic's `metadata.user_id` and
+    /// OpenAI's `user`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Additional headers sent with every request.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// How much a reasoning model reasons before answering, see `AiConfig::reasoning_effort`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    /// gpt-5 models only.
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+/// Anthropic-specific request headers and options.
+///
+/// With `prefill`, Claude's reply starts with the given text (sent as a final assistant
+/// message), e.g. `{` to force a JSON object. The returned answer, and the first fragment of a
+/// streamed one, include the prefill, so the output reads as a whole.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::AnthropicOptions;
+///
+/// let ai_config = AiConfig {
+///     anthropic: Some(AnthropicOptions {
+///         betas: vec!["context-1m-2025-08-07".to_string()],
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct AnthropicOptions {
+    /// `anthropic-version` header. Defaults to `ask_ai::ask_ai::ANTHROPIC_VERSION`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Beta feature flags, sent comma separated in the `anthropic-beta` header.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub betas: Vec<String>,
+    /// Start of the assistant's reply. Trailing whitespace is removed, as the API rejects it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefill: Option<String>,
+    /// Lets Claude search the web with Anthropic's server-side `web_search` tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_search: Option<WebSearch>,
+    /// Anthropic-defined tools sent as is, such as the beta computer use tool
+    /// (`{"type": "computer_20250124", "name": "computer", ...}`), next to the beta flag they
+    /// need in `betas`. Their calls are returned by `anthropic::ask_with_blocks`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Value>,
+}
+
+/// Anthropic's server-side web search tool. Claude decides when to search, Anthropic runs the
+/// searches, and the answer cites the pages it used (see `anthropic::ask_with_citations`).
+/// Searches are billed per use, on top of tokens.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::{AnthropicOptions, WebSearch};
+///
+/// let ai_config = AiConfig {
+///     anthropic: Some(AnthropicOptions {
+///         web_search: Some(WebSearch {
+///             max_uses: Some(3),
+///             allowed_domains: vec!["docs.rs".to_string(), "rust-lang.org".to_string()],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct WebSearch {
+    /// At most this many searches per request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u32>,
+    /// Only search these domains. Cannot be combined with `blocked_domains`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_domains: Vec<String>,
+    /// Never search these domains.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_domains: Vec<String>,
+}
+
+impl WebSearch {
+    /// The tool definition sent in the messages request.
+    pub(crate) fn tool(&self) -> Value {
+        let mut tool = serde_json::json!({ "type": "web_search_20250305", "name": "web_search" });
+        if let Some(max_uses) = self.max_uses {
+            tool["max_uses"] = max_uses.into();
+        }
+        if !self.allowed_domains.is_empty() {
+            tool["allowed_domains"] = serde_json::json!(self.allowed_domains);
+        }
+        if !self.blocked_domains.is_empty() {
+            tool["blocked_domains"] = serde_json::json!(self.blocked_domains);
+        }
+        tool
+    }
+}
+
+/// vLLM's extensions to the OpenAI chat completions API.
+///
+/// Sent with `Framework::OpenAI` requests, for a vLLM server reached through
+/// `AiConfig::base_url`; hosted OpenAI rejects them. Guided decoding makes the answer follow a
+/// JSON Schema, a regex or one of a list of choices, one kind at a time.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::VllmOptions;
+///
+/// let ai_config = AiConfig {
+///     base_url: Some("http://localhost:8000/v1".to_string()),
+///     vllm: Some(VllmOptions {
+///         guided_choice: vec!["positive".to_string(), "negative".to_string()],
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct VllmOptions {
+    /// JSON Schema the answer must follow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guided_json: Option<Value>,
+    /// Regular expression the answer must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guided_regex: Option<String>,
+    /// Answers the model must pick from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guided_choice: Vec<String>,
+    /// Number of answers generated, of which the most likely one is returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_of: Option<u32>,
+}
+
+impl VllmOptions {
+    /// The kinds of guided decoding set, by option name.
+    pub(crate) fn guides(&self) -> Vec<&'static str> {
+        let mut guides = vec![];
+        if self.guided_json.is_some() {
+            guides.push("vllm.guided_json");
+        }
+        if self.guided_regex.is_some() {
+            guides.push("vllm.guided_regex");
+        }
+        if !self.guided_choice.is_empty() {
+            guides.push("vllm.guided_choice");
+        }
+        guides
+    }
+
+    /// Adds the options to a chat completions `payload`.
+    pub(crate) fn apply(&self, payload: &mut Value) {
+        if let Some(schema) = &self.guided_json {
+            payload["guided_json"] = schema.clone();
+        }
+        if let Some(regex) = &self.guided_regex {
+            payload["guided_regex"] = regex.as_str().into();
+        }
+        if !self.guided_choice.is_empty() {
+            payload["guided_choice"] = self.guided_choice.clone().into();
+        }
+        if let Some(best_of) = self.best_of {
+            payload["best_of"] = best_of.into();
+        }
+    }
+}
+
+/// Ollama's model options, sent under `options` of chat and generate requests.
+///
+/// They override the model's Modelfile parameters, and the sampling parameters of
+/// `AiConfig::params`. Options not listed here go in `other`, by their Ollama name.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::OllamaOptions;
+///
+/// let ai_config = AiConfig {
+///     ollama: Some(OllamaOptions {
+///         num_ctx: Some(32_768),
+///         mirostat: Some(2),
+///         num_gpu: Some(0),
+///         keep_alive: Some("30m".to_string()),
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct OllamaOptions {
+    /// Context window in tokens. Ollama's default is small (2048 to 4096 tokens), and longer
+    /// prompts are silently cut.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    /// Maximum tokens to generate; -1 for no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f32>,
+    /// Mirostat sampling: 0 off, 1 Mirostat, 2 Mirostat 2.0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirostat: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirostat_eta: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirostat_tau: Option<f32>,
+    /// How strongly repetitions are penalized, e.g. 1.1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    /// How far back repetitions are looked for, in tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_last_n: Option<i32>,
+    /// Layers offloaded to the GPU; 0 runs on the CPU only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_gpu: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_thread: Option<u32>,
+    /// How long the model stays loaded after the request, e.g. `10m`, or `0` to unload it.
+    /// Sent next to `options`, not in it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    /// Any other option, e.g. `num_batch` or `use_mmap`.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl OllamaOptions {
+    /// Adds the options to a chat or generate `payload`, over those already there.
+    pub(crate) fn apply(&self, payload: &mut Value) {
+        let Ok(Value::Object(mut options)) = serde_json::to_value(self) else {
+            return;
+        };
+        if let Some(keep_alive) = options.remove("keep_alive") {
+            payload["keep_alive"] = keep_alive;
+        }
+        if options.is_empty() {
+            return;
+        }
+        match payload["options"].as_object_mut() {
+            Some(existing) => existing.extend(options),
+            None => payload["options"] = Value::Object(options),
+        }
+    }
+}
+
+/// Overrides for what is sent when a question leaves something out.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::{EmptyPromptPolicy, PromptDefaults};
+///
+/// let ai_config = AiConfig {
+///     prompts: Some(PromptDefaults {
+///         system_prompt: Some("You are Acme's support assistant.".to_string()),
+///         empty_prompt: EmptyPromptPolicy::Reject,
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PromptDefaults {
+    /// System prompt for questions without one. Without it OpenAI gets an empty system
+    /// prompt, and Anthropic and Ollama a short "helpful assistant" one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// What to do when the new prompt is empty.
+    #[serde(default)]
+    pub empty_prompt: EmptyPromptPolicy,
+}
+
+/// How an empty new prompt is handled.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyPromptPolicy {
+    /// Send this text instead, since providers reject empty messages. Defaults to `.`.
+    Substitute(String),
+    /// Fail with `AppError::EmptyPrompt` before anything is sent.
+    Reject,
+}
+
+impl Default for EmptyPromptPolicy {
+    fn default() -> Self {
+        EmptyPromptPolicy::Substitute(".".to_string())
+    }
+}
+
+/// Answer token limit of `AiConfig::default_for`, the same Anthropic gets when none is set.
+pub const DEFAULT_MAX_TOKEN: u32 = 1024;
+
+impl AiConfig {
+    /// A ready-to-use configuration for `framework`: its default model and an answer limit of
+    /// `DEFAULT_MAX_TOKEN`, everything else unset.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// use ask_ai::config::{AiConfig, Framework};
+    ///
+    /// let ai_config = AiConfig::default_for(Framework::Anthropic);
+    /// let answer = ask_question(&ai_config, question).await?;
+    /// ```
+    pub fn default_for(framework: Framework) -> Self {
+        Self {
+            model: framework.default_model().to_string(),
+            llm: framework,
+            max_token: Some(DEFAULT_MAX_TOKEN),
+            ..Default::default()
+        }
+    }
+
+    /// The system prompt sent for `question`: its own, the configured default, or `builtin`,
+    /// followed by the JSON instructions of a `response_format` the provider cannot enforce.
+    pub(crate) fn system_prompt(&self, question: &Question, builtin: &str) -> String {
+        let system_prompt = question
+            .system_prompt
+            .clone()
+            .or_else(|| self.prompts.as_ref()?.system_prompt.clone())
+            .unwrap_or_else(|| builtin.to_string());
+        match &self.response_format {
+            Some(format) if !ResponseFormat::is_native(&self.llm) => {
+                format!("{}\n\n{}", system_prompt, format.instruction())
+            }
+            _ => system_prompt,
+        }
+    }
+
+    /// The new prompt sent for `question`, with an empty one substituted.
+    pub(crate) fn new_prompt(&self, question: &Question) -> String {
+        if !question.new_prompt.is_empty() {
+            return question.new_prompt.clone();
+        }
+        match self.prompts.as_ref().map(|prompts| &prompts.empty_prompt) {
+            Some(EmptyPromptPolicy::Substitute(text)) => text.clone(),
+            // `prepare` fails on empty prompts before anything is built
+            Some(EmptyPromptPolicy::Reject) | None => ".".to_string(),
+        }
+    }
+
+    /// This config, for questions sent on `caller`'s behalf (a compression model, a hedge
+    /// target, a context-overflow fallback): bound by the caller's local-only mode, and with
+    /// the caller's client metadata and connection options unless it sets its own.
+    pub(crate) fn on_behalf_of(&self, caller: &AiConfig) -> Cow<'_, AiConfig> {
+        let local_only = caller.local_only && !self.local_only;
+        let client = self.client.is_none() && caller.client.is_some();
+        let http = self.http.is_none() && caller.http.is_some();
+        if !(local_only || client || http) {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(AiConfig {
+            local_only: self.local_only || caller.local_only,
+            client: self.client.clone().or_else(|| caller.client.clone()),
+            http: self.http.clone().or_else(|| caller.http.clone()),
+            ..self.clone()
+        })
+    }
+
+    /// The Anthropic prefill, without its trailing whitespace, if one is set.
+    pub(crate) fn anthropic_prefill(&self) -> Option<&str> {
+        self.anthropic
+            .as_ref()
+            .and_then(|options| options.prefill.as_deref())
+            .map(str::trim_end)
+            .filter(|prefill| !prefill.is_empty())
+    }
 }
 
 /// Represents a single prompt and its corresponding AI response.
@@ -77,7 +727,7 @@ pub struct AiConfig {
 ///     output: "Rust is a systems programming language...".to_string(), // AI's response
 /// };
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AiPrompt {
     /// The user's input or question.
     pub content: String,
@@ -104,9 +754,11 @@ pub struct AiPrompt {
 ///         },
 ///     ]), // Optional conversation history
 ///     new_prompt: "Tell me more about Rust.".to_string(), // New user prompt
+///     images: vec![], // Optional images attached to the new prompt
+///     audio: vec![], // Optional audio clips attached to the new prompt
 /// };
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Question {
     /// An optional system prompt to instruct the AI on how to behave.
     /// For example, "You are a helpful assistant."
@@ -116,4 +768,52 @@ pub struct Question {
     pub messages: Option<Vec<AiPrompt>>,
     /// The new prompt or question from the user.
     pub new_prompt: String,
+    /// Images sent along with the new prompt, for vision models.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageSource>,
+    /// Audio clips sent along with the new prompt, for models that take audio input.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audio: Vec<AudioInput>,
+}
+
+/// An audio clip sent with a question, as base64 data.
+///
+/// Sent as an OpenAI `input_audio` content part, so it reaches audio models such as
+/// `gpt-4o-audio-preview` through OpenAI and the OpenAI-compatible providers.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::AudioInput;
+///
+/// let question = Question {
+///     new_prompt: "Transcribe and summarize this voice note.".to_string(),
+///     audio: vec![AudioInput::from_bytes("wav", &std::fs::read("note.wav")?)],
+///     ..question
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AudioInput {
+    /// Encoding of the clip, e.g. `wav` or `mp3`.
+    pub format: String,
+    /// The clip, base64 encoded.
+    pub data: String,
+}
+
+impl AudioInput {
+    /// Encodes `bytes`, a clip in `format`, as base64.
+    pub fn from_bytes(format: &str, bytes: &[u8]) -> Self {
+        Self {
+            format: format.to_string(),
+            data: BASE64_STANDARD.encode(bytes),
+        }
+    }
+
+    /// The clip as an OpenAI `input_audio` content part.
+    pub(crate) fn openai_part(&self) -> Value {
+        serde_json::json!({
+            "type": "input_audio",
+            "input_audio": { "data": self.data, "format": self.format }
+        })
+    }
 }
@@ -0,0 +1,249 @@
+use crate::config::{AiConfig, AiPrompt, Question};
+use crate::error::AppError;
+use crate::provider::{provider, Completion};
+use crate::stream::ask_question_stream;
+use crate::transcript::Role;
+use futures_util::StreamExt;
+use std::fmt;
+
+/// A scripted backend for the provider under test to talk to.
+///
+/// For an HTTP provider this is usually a mock server speaking the provider's protocol; for
+/// an in-process one, whatever it would otherwise call.
+pub trait Harness: Send + Sync {
+    /// Selects the provider under test, built-in or registered with
+    /// `provider::register_provider`, and points it at this backend.
+    fn ai_config(&self) -> AiConfig;
+
+    /// Makes the backend answer every following request with `reply`.
+    fn reply(&self, reply: Reply);
+
+    /// The turns of the last request the backend received, system prompt first if one was
+    /// sent, as (role, text) pairs.
+    fn received(&self) -> Vec<(Role, String)>;
+}
+
+/// What the scripted backend answers with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// An answer, cut off by the token limit when `truncated` is set.
+    Answer { text: String, truncated: bool },
+    /// A failed request, with its HTTP status or the closest equivalent.
+    Failure { status: u16 },
+}
+
+/// A contract the provider under test broke.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The check that failed: `history`, `system_prompt`, `truncation`, `errors` or
+    /// `streaming`.
+    pub check: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.check, self.message)
+    }
+}
+
+/// Runs every check against the provider `harness` selects and returns what it got wrong.
+///
+/// The provider is held to what the crate expects of the built-in clients:
+/// - history: prior messages reach the backend in order, as alternating user and assistant
+///   turns, followed by the new prompt;
+/// - system prompt: sent as the first turn when set; otherwise at most a default of the
+///   provider's own is sent in its place;
+/// - truncation: answers cut off by the token limit are reported as `Completion::truncated`;
+/// - errors: failed requests are an `AppError::ApiError` naming the status;
+/// - streaming: the deltas of `ask_question_stream` add up to the answer, and failures end
+///   the stream with an error rather than quietly.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::conformance::{check_provider, Harness};
+///
+/// let violations = check_provider(&MockBackend::start()).await;
+/// assert!(violations.is_empty(), "{:#?}", violations);
+/// ```
+pub async fn check_provider(harness: &impl Harness) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    check_history(harness, &mut violations).await;
+    check_system_prompt(harness, &mut violations).await;
+    check_truncation(harness, &mut violations).await;
+    check_errors(harness, &mut violations).await;
+    check_streaming(harness, &mut violations).await;
+    violations
+}
+
+const ANSWER: &str = "The answer is 42.";
+
+fn answer(truncated: bool) -> Reply {
+    Reply::Answer {
+        text: ANSWER.to_string(),
+        truncated,
+    }
+}
+
+fn question(system_prompt: Option<&str>) -> Question {
+    Question {
+        system_prompt: system_prompt.map(str::to_string),
+        messages: Some(vec![
+            AiPrompt {
+                content: "Pick a number.".to_string(),
+                output: "Seven.".to_string(),
+            },
+            AiPrompt {
+                content: "Double it.".to_string(),
+                output: "Fourteen.".to_string(),
+            },
+        ]),
+        new_prompt: "Now triple it and add the first.".to_string(),
+        ..Default::default()
+    }
+}
+
+/// The turns `question` should reach the backend as.
+fn expected_turns(question: &Question) -> Vec<(Role, String)> {
+    let mut turns = Vec::new();
+    if let Some(system_prompt) = &question.system_prompt {
+        turns.push((Role::System, system_prompt.clone()));
+    }
+    for message in question.messages.iter().flatten() {
+        turns.push((Role::User, message.content.clone()));
+        turns.push((Role::Assistant, message.output.clone()));
+    }
+    turns.push((Role::User, question.new_prompt.clone()));
+    turns
+}
+
+async fn ask(harness: &impl Harness, question: Question) -> crate::error::Result<Completion> {
+    let ai_config = harness.ai_config();
+    provider(&ai_config)?.ask(&ai_config, question).await
+}
+
+fn violation(check: &'static str, message: String) -> Violation {
+    Violation { check, message }
+}
+
+/// Checks `question` reached the backend as it should and was answered with `ANSWER`.
+async fn check_sent(
+    harness: &impl Harness,
+    check: &'static str,
+    question: Question,
+    violations: &mut Vec<Violation>,
+) {
+    let expected = expected_turns(&question);
+    let has_system_prompt = question.system_prompt.is_some();
+    match ask(harness, question).await {
+        Ok(completion) if completion.answer != ANSWER => violations.push(violation(
+            check,
+            format!(
+                "expected the answer {:?}, got {:?}",
+                ANSWER, completion.answer
+            ),
+        )),
+        Ok(_) => {}
+        Err(e) => violations.push(violation(check, format!("the question failed: {}", e))),
+    }
+    let mut received = harness.received();
+    if !has_system_prompt && matches!(received.first(), Some((Role::System, _))) {
+        received.remove(0);
+    }
+    if received != expected {
+        violations.push(violation(
+            check,
+            format!("expected the turns {:?}, got {:?}", expected, received),
+        ));
+    }
+}
+
+async fn check_history(harness: &impl Harness, violations: &mut Vec<Violation>) {
+    harness.reply(answer(false));
+    check_sent(harness, "history", question(None), violations).await;
+}
+
+async fn check_system_prompt(harness: &impl Harness, violations: &mut Vec<Violation>) {
+    harness.reply(answer(false));
+    let question = question(Some("Answer with a number only."));
+    check_sent(harness, "system_prompt", question, violations).await;
+}
+
+async fn check_truncation(harness: &impl Harness, violations: &mut Vec<Violation>) {
+    for truncated in [true, false] {
+        harness.reply(answer(truncated));
+        match ask(harness, question(None)).await {
+            Ok(completion) if completion.truncated != truncated => violations.push(violation(
+                "truncation",
+                format!(
+                    "expected `truncated: {}` for an answer {} the token limit",
+                    truncated,
+                    if truncated { "cut off by" } else { "within" }
+                ),
+            )),
+            Ok(_) => {}
+            Err(e) => violations.push(violation(
+                "truncation",
+                format!("the question failed: {}", e),
+            )),
+        }
+    }
+}
+
+async fn check_errors(harness: &impl Harness, violations: &mut Vec<Violation>) {
+    for status in [401, 429, 500] {
+        harness.reply(Reply::Failure { status });
+        match ask(harness, question(None)).await {
+            Err(AppError::ApiError { failure_str, .. })
+                if failure_str.contains(&status.to_string()) => {}
+            Err(e) => violations.push(violation(
+                "errors",
+                format!("expected an ApiError naming status {}, got {:?}", status, e),
+            )),
+            Ok(completion) => violations.push(violation(
+                "errors",
+                format!(
+                    "status {} was answered with {:?}",
+                    status, completion.answer
+                ),
+            )),
+        }
+    }
+}
+
+async fn check_streaming(harness: &impl Harness, violations: &mut Vec<Violation>) {
+    let ai_config = harness.ai_config();
+
+    harness.reply(answer(false));
+    match ask_question_stream(&ai_config, question(None)).await {
+        Ok(stream) => {
+            let deltas: Vec<_> = stream.collect().await;
+            match deltas.into_iter().collect::<crate::error::Result<String>>() {
+                Ok(streamed) if streamed != ANSWER => violations.push(violation(
+                    "streaming",
+                    format!("the deltas add up to {:?}, not {:?}", streamed, ANSWER),
+                )),
+                Ok(_) => {}
+                Err(e) => {
+                    violations.push(violation("streaming", format!("the stream failed: {}", e)))
+                }
+            }
+        }
+        Err(e) => violations.push(violation(
+            "streaming",
+            format!("the stream did not open: {}", e),
+        )),
+    }
+
+    harness.reply(Reply::Failure { status: 500 });
+    if let Ok(stream) = ask_question_stream(&ai_config, question(None)).await {
+        let deltas: Vec<_> = stream.collect().await;
+        if deltas.iter().all(Result::is_ok) {
+            violations.push(violation(
+                "streaming",
+                "a failed request ended the stream without an error".to_string(),
+            ));
+        }
+    }
+}
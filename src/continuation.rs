@@ -0,0 +1,114 @@
+use crate::ask_ai::complete;
+use crate::config::{AiConfig, AiPrompt, Question};
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Default follow-up prompt asking for the rest of a truncated answer.
+pub const CONTINUE_PROMPT: &str =
+    "Continue exactly where your previous answer stopped, without repeating anything.";
+
+/// Automatic continuation of answers cut off by the token limit.
+///
+/// When the provider reports that an answer stopped because it hit `max_token` (OpenAI
+/// `finish_reason: "length"`, Anthropic `stop_reason: "max_tokens"`, Ollama
+/// `done_reason: "length"`), the answer so far is sent back as the assistant's turn with a
+/// follow-up `prompt`, and the next segment is appended. This repeats until an answer ends on
+/// its own or `max_continuations` follow-ups were sent, so long outputs come back whole.
+/// Segments are joined as they are; the last one may still be truncated.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::continuation::AutoContinue;
+///
+/// let ai_config = AiConfig {
+///     max_token: Some(1024),
+///     auto_continue: Some(AutoContinue {
+///         max_continuations: 5,
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// let long_answer = ask_question(&ai_config, question).await?;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AutoContinue {
+    /// At most this many follow-up requests per question.
+    #[serde(default = "default_max_continuations")]
+    pub max_continuations: u32,
+    /// The follow-up prompt. Defaults to `CONTINUE_PROMPT`.
+    #[serde(default = "default_prompt")]
+    pub prompt: String,
+}
+
+fn default_max_continuations() -> u32 {
+    3
+}
+
+fn default_prompt() -> String {
+    CONTINUE_PROMPT.to_string()
+}
+
+impl Default for AutoContinue {
+    fn default() -> Self {
+        Self {
+            max_continuations: default_max_continuations(),
+            prompt: default_prompt(),
+        }
+    }
+}
+
+impl AutoContinue {
+    /// Asks for the rest of `answer`, the truncated answer to `question`.
+    pub(crate) async fn finish(
+        &self,
+        ai_config: &AiConfig,
+        question: Question,
+        mut answer: String,
+    ) -> Result<String> {
+        let ai_config = &without_prefill(ai_config);
+        for _ in 0..self.max_continuations {
+            let follow_up = follow_up(ai_config, &question, &answer, &self.prompt);
+            let (segment, truncated) = complete(ai_config, follow_up).await?;
+            answer.push_str(&segment);
+            if !truncated {
+                break;
+            }
+        }
+        Ok(answer)
+    }
+}
+
+/// `question` answered with `answer` so far, followed by `prompt` asking for the rest.
+pub(crate) fn follow_up(
+    ai_config: &AiConfig,
+    question: &Question,
+    answer: &str,
+    prompt: &str,
+) -> Question {
+    let mut messages = question.messages.clone().unwrap_or_default();
+    messages.push(AiPrompt {
+        content: ai_config.new_prompt(question),
+        output: answer.to_string(),
+    });
+    Question {
+        system_prompt: question.system_prompt.clone(),
+        messages: Some(messages),
+        new_prompt: prompt.to_string(),
+        ..question.clone()
+    }
+}
+
+/// `ai_config` for follow-up requests: the answer so far already starts with the Anthropic
+/// prefill, so it is not sent again.
+pub(crate) fn without_prefill(ai_config: &AiConfig) -> Cow<'_, AiConfig> {
+    if ai_config.anthropic_prefill().is_none() {
+        return Cow::Borrowed(ai_config);
+    }
+    let mut ai_config = ai_config.clone();
+    if let Some(options) = &mut ai_config.anthropic {
+        options.prefill = None;
+    }
+    Cow::Owned(ai_config)
+}
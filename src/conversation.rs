@@ -0,0 +1,194 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, AiPrompt, Question};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Number of leading exchanges shown to the model when generating a title.
+const TITLE_EXCHANGES: usize = 2;
+/// Maximum characters of each input/output shown to the model when generating a title.
+const TITLE_EXCERPT_CHARS: usize = 500;
+
+/// A stored multi-turn conversation: an optional system prompt and the completed exchanges.
+///
+/// Unlike `Question`, every turn of a `Conversation` has both the user's input and the AI's
+/// output, which makes it the unit for persisting, exporting and importing chat transcripts.
+/// Use `Conversation::question` to continue it through `ask_question`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::conversation::Conversation;
+///
+/// let mut conversation = Conversation::new(Some("You are a helpful assistant.".to_string()));
+/// conversation.push("What is Rust?", "Rust is a systems programming language...");
+///
+/// let question = conversation.question("Tell me more about Rust.");
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Conversation {
+    /// A short human-readable title, see `Conversation::generate_title`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// An optional system prompt to instruct the AI on how to behave.
+    pub system_prompt: Option<String>,
+    /// The completed exchanges, oldest first.
+    pub messages: Vec<AiPrompt>,
+}
+
+impl Conversation {
+    /// Creates an empty conversation.
+    pub fn new(system_prompt: Option<String>) -> Self {
+        Self {
+            title: None,
+            system_prompt,
+            messages: vec![],
+        }
+    }
+
+    /// Appends a completed exchange.
+    pub fn push(&mut self, content: &str, output: &str) {
+        self.messages.push(AiPrompt {
+            content: content.to_string(),
+            output: output.to_string(),
+        });
+    }
+
+    /// Builds the `Question` that continues this conversation with `new_prompt`.
+    pub fn question(&self, new_prompt: &str) -> Question {
+        Question {
+            system_prompt: self.system_prompt.clone(),
+            messages: if self.messages.is_empty() {
+                None
+            } else {
+                Some(self.messages.clone())
+            },
+            new_prompt: new_prompt.to_string(),
+            images: vec![],
+            audio: vec![],
+        }
+    }
+
+    /// Asks `new_prompt` in the context of this conversation and records the exchange.
+    pub async fn ask(&mut self, ai_config: &AiConfig, new_prompt: &str) -> Result<String> {
+        let answer = ask_question(ai_config, self.question(new_prompt)).await?;
+        self.push(new_prompt, &answer);
+        Ok(answer)
+    }
+
+    /// Replaces the user's input of exchange `index` and asks the model again.
+    ///
+    /// Like editing a message in a chat UI, every exchange after `index` is discarded, since
+    /// it answered a different question. The conversation is only modified if the model call
+    /// succeeds.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// // Rewrite the first question; the rest of the conversation is dropped
+    /// let answer = conversation.edit(&ai_config, 0, "What is Go?").await?;
+    /// ```
+    pub async fn edit(
+        &mut self,
+        ai_config: &AiConfig,
+        index: usize,
+        new_text: &str,
+    ) -> Result<String> {
+        if index >= self.messages.len() {
+            return Err(AppError::UnexpectedError(format!(
+                "Cannot edit exchange {}: the conversation has {} exchanges",
+                index,
+                self.messages.len()
+            )));
+        }
+
+        let mut question = self.question(new_text);
+        question.messages = Some(self.messages[..index].to_vec()).filter(|m| !m.is_empty());
+        let answer = ask_question(ai_config, question).await?;
+
+        self.messages.truncate(index);
+        self.push(new_text, &answer);
+        Ok(answer)
+    }
+
+    /// Asks the last exchange's input again and replaces its output with the new answer.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// let another_answer = conversation.regenerate(&ai_config).await?;
+    /// ```
+    pub async fn regenerate(&mut self, ai_config: &AiConfig) -> Result<String> {
+        let Some(last) = self.messages.len().checked_sub(1) else {
+            return Err(AppError::UnexpectedError(
+                "Cannot regenerate: the conversation is empty".to_string(),
+            ));
+        };
+
+        let prompt = self.messages[last].content.clone();
+        self.edit(ai_config, last, &prompt).await
+    }
+
+    /// Asks the model configured in `ai_config` (ideally a small, cheap one) for a short title
+    /// summarizing the first exchanges, and stores it in `title`.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// let cheap = AiConfig {
+    ///     llm: Framework::OpenAI,
+    ///     model: "gpt-4o-mini".to_string(),
+    ///     max_token: Some(20),
+    /// };
+    /// let title = conversation.generate_title(&cheap).await?;
+    /// ```
+    pub async fn generate_title(&mut self, ai_config: &AiConfig) -> Result<String> {
+        if self.messages.is_empty() {
+            return Err(AppError::UnexpectedError(
+                "Cannot generate a title for an empty conversation".to_string(),
+            ));
+        }
+
+        let excerpt = self
+            .messages
+            .iter()
+            .take(TITLE_EXCHANGES)
+            .map(|msg| {
+                format!(
+                    "User: {}\nAssistant: {}",
+                    truncate_chars(&msg.content, TITLE_EXCERPT_CHARS),
+                    truncate_chars(&msg.output, TITLE_EXCERPT_CHARS)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let question = Question {
+            system_prompt: Some(
+                "Write a short title (at most six words) for the conversation below. \
+                 Reply with the title only, without quotes or trailing punctuation."
+                    .to_string(),
+            ),
+            messages: None,
+            new_prompt: excerpt,
+            images: vec![],
+            audio: vec![],
+        };
+
+        let answer = ask_question(ai_config, question).await?;
+        let title = answer
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'' || c == '*')
+            .trim_end_matches('.')
+            .trim()
+            .to_string();
+
+        self.title = Some(title.clone());
+        Ok(title)
+    }
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
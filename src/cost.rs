@@ -0,0 +1,312 @@
+use crate::ask_ai::ask_question;
+use crate::audit::{AuditDecision, AuditEvent};
+use crate::config::{AiConfig, Framework, Question};
+use crate::error::Result;
+use crate::tenant::{estimate_tokens, prompt_tokens};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Model, day and tag of a report line.
+type CostKey = (Option<String>, Option<String>, Option<String>);
+
+/// Price of a model in USD per million tokens.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+/// Model prices, looked up by exact name first and then by the longest matching prefix
+/// (so `gpt-4o` also prices `gpt-4o-2024-08-06`). Unpriced models cost 0.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Pricing {
+    pub models: BTreeMap<String, ModelPrice>,
+}
+
+impl Pricing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the price of `model` (or model prefix), in USD per million tokens.
+    pub fn price(mut self, model: &str, input_per_million: f64, output_per_million: f64) -> Self {
+        self.models.insert(
+            model.to_string(),
+            ModelPrice {
+                input_per_million,
+                output_per_million,
+            },
+        );
+        self
+    }
+
+    /// Cost in USD of a call to `model`, or 0 when it has no price.
+    pub fn cost(&self, model: &str, prompt_tokens: u64, answer_tokens: u64) -> f64 {
+        let price = self.models.get(model).or_else(|| {
+            self.models
+                .iter()
+                .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+                .max_by_key(|(prefix, _)| prefix.len())
+                .map(|(_, price)| price)
+        });
+        price.map_or(0.0, |price| {
+            (prompt_tokens as f64 * price.input_per_million
+                + answer_tokens as f64 * price.output_per_million)
+                / 1_000_000.0
+        })
+    }
+}
+
+/// One answered call and what it cost.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UsageRecord {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub framework: Framework,
+    pub model: String,
+    /// Estimated from text length.
+    pub prompt_tokens: u64,
+    pub answer_tokens: u64,
+    /// USD, from the ledger's `Pricing`.
+    pub cost: f64,
+    /// Free-form labels (feature, team, customer...) to break costs down by.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A dimension a cost report is broken down by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostGroup {
+    Model,
+    /// UTC calendar day.
+    Day,
+    /// A record with several tags counts towards each of them; untagged records are left out.
+    Tag,
+}
+
+/// One line of a cost report. Dimensions the report is not grouped by are `None`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CostLine {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// `YYYY-MM-DD`, UTC.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub answer_tokens: u64,
+    pub cost: f64,
+}
+
+/// Usage and cost totals, ready for export.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CostReport {
+    pub lines: Vec<CostLine>,
+}
+
+impl CostReport {
+    /// Renders the report as CSV, with a header row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("model,day,tag,requests,prompt_tokens,answer_tokens,cost_usd\n");
+        for line in &self.lines {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{:.6}\n",
+                csv_field(line.model.as_deref()),
+                csv_field(line.day.as_deref()),
+                csv_field(line.tag.as_deref()),
+                line.requests,
+                line.prompt_tokens,
+                line.answer_tokens,
+                line.cost
+            ));
+        }
+        csv
+    }
+
+    /// Renders the report as a JSON array of lines.
+    pub fn to_json(&self) -> String {
+        // Strings and numbers always serialize
+        serde_json::to_string_pretty(&self.lines).unwrap_or_default()
+    }
+}
+
+/// Accumulates usage records and prices them, for spend reports.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::cost::{CostGroup, Pricing, UsageLedger};
+///
+/// let ledger = UsageLedger::new(Pricing::new().price("gpt-4o", 2.5, 10.0));
+/// let answer = ledger.ask_question(&ai_config, question, &["support-bot"]).await?;
+///
+/// let report = ledger.report(&[CostGroup::Day, CostGroup::Model]);
+/// std::fs::write("costs.csv", report.to_csv())?;
+/// ```
+#[derive(Debug, Default)]
+pub struct UsageLedger {
+    pricing: Pricing,
+    records: Mutex<Vec<UsageRecord>>,
+}
+
+impl UsageLedger {
+    pub fn new(pricing: Pricing) -> Self {
+        Self {
+            pricing,
+            records: Mutex::default(),
+        }
+    }
+
+    /// Asks a question and records its usage under `tags` when it is answered.
+    pub async fn ask_question(
+        &self,
+        ai_config: &AiConfig,
+        question: Question,
+        tags: &[&str],
+    ) -> Result<String> {
+        let prompt_tokens = prompt_tokens(&question);
+        let answer = ask_question(ai_config, question).await?;
+
+        self.record(
+            ai_config.llm.clone(),
+            &ai_config.model,
+            prompt_tokens,
+            estimate_tokens(&answer),
+            tags,
+        );
+        Ok(answer)
+    }
+
+    /// Records a call made outside the ledger, priced now.
+    pub fn record(
+        &self,
+        framework: Framework,
+        model: &str,
+        prompt_tokens: u64,
+        answer_tokens: u64,
+        tags: &[&str],
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.push(UsageRecord {
+            timestamp,
+            framework,
+            model: model.to_string(),
+            prompt_tokens,
+            answer_tokens,
+            cost: self.pricing.cost(model, prompt_tokens, answer_tokens),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        });
+    }
+
+    /// Adds the answered questions of an audit log, tagged with their actor.
+    pub fn import_audit(&self, events: &[AuditEvent]) {
+        for event in events {
+            let AuditDecision::Answered {
+                prompt_tokens,
+                answer_tokens,
+            } = event.decision
+            else {
+                continue;
+            };
+            self.push(UsageRecord {
+                timestamp: event.timestamp,
+                framework: event.framework.clone(),
+                model: event.model.clone(),
+                prompt_tokens,
+                answer_tokens,
+                cost: self
+                    .pricing
+                    .cost(&event.model, prompt_tokens, answer_tokens),
+                tags: vec![event.actor.clone()],
+            });
+        }
+    }
+
+    /// All records so far, oldest first.
+    pub fn records(&self) -> Vec<UsageRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Totals grouped by `groups` (all records in one line when empty), sorted by key.
+    pub fn report(&self, groups: &[CostGroup]) -> CostReport {
+        let mut totals: BTreeMap<CostKey, CostLine> = BTreeMap::new();
+
+        for record in self.records() {
+            let model = groups
+                .contains(&CostGroup::Model)
+                .then(|| record.model.clone());
+            let day = groups
+                .contains(&CostGroup::Day)
+                .then(|| utc_day(record.timestamp));
+            let tags: Vec<Option<String>> = if groups.contains(&CostGroup::Tag) {
+                record.tags.iter().cloned().map(Some).collect()
+            } else {
+                vec![None]
+            };
+
+            for tag in tags {
+                let key = (model.clone(), day.clone(), tag);
+                let line = totals.entry(key.clone()).or_insert_with(|| CostLine {
+                    model: key.0,
+                    day: key.1,
+                    tag: key.2,
+                    ..Default::default()
+                });
+                line.requests += 1;
+                line.prompt_tokens += record.prompt_tokens;
+                line.answer_tokens += record.answer_tokens;
+                line.cost += record.cost;
+            }
+        }
+
+        CostReport {
+            lines: totals.into_values().collect(),
+        }
+    }
+
+    fn push(&self, record: UsageRecord) {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(record);
+    }
+}
+
+/// A CSV field, quoted when needed.
+pub(crate) fn csv_field(value: Option<&str>) -> String {
+    let value = value.unwrap_or_default();
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The UTC calendar day of a Unix timestamp, as `YYYY-MM-DD`.
+pub(crate) fn utc_day(timestamp: u64) -> String {
+    // Civil-from-days, after Howard Hinnant's date algorithms
+    let days = (timestamp / SECS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,367 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Question};
+use crate::cost::csv_field;
+use crate::error::{AppError, Result};
+use crate::tenant::{estimate_tokens, prompt_tokens};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// One input row, by column name.
+pub type DatasetRow = BTreeMap<String, String>;
+
+/// Rows of inputs read from a CSV or JSONL file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dataset {
+    /// Column names, in file order for CSV and first-seen order for JSONL.
+    pub columns: Vec<String>,
+    pub rows: Vec<DatasetRow>,
+}
+
+impl Dataset {
+    /// Reads `path`, as CSV for a `.csv` file and as JSONL for `.jsonl`, `.ndjson` or `.json`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        match FileFormat::of(path)? {
+            FileFormat::Csv => Self::from_csv(&data),
+            FileFormat::Jsonl => Self::from_jsonl(&data),
+        }
+    }
+
+    /// Parses CSV with a header row. Quoted fields may contain commas, quotes (doubled) and
+    /// line breaks.
+    pub fn from_csv(csv: &str) -> Result<Self> {
+        let mut records = parse_csv(csv)?.into_iter();
+        let columns = records.next().unwrap_or_default();
+
+        let mut rows = vec![];
+        for (line, record) in records.enumerate() {
+            if record.len() != columns.len() {
+                return Err(AppError::UnexpectedError(format!(
+                    "CSV row {} has {} fields, expected {}",
+                    line + 1,
+                    record.len(),
+                    columns.len()
+                )));
+            }
+            rows.push(columns.iter().cloned().zip(record).collect());
+        }
+        Ok(Self { columns, rows })
+    }
+
+    /// Parses one JSON object per line. String values are used as is, other values as JSON.
+    pub fn from_jsonl(jsonl: &str) -> Result<Self> {
+        let mut dataset = Self::default();
+        for (line, text) in jsonl.lines().enumerate() {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let Ok(Value::Object(object)) = serde_json::from_str(text) else {
+                return Err(AppError::UnexpectedError(format!(
+                    "JSONL line {} is not a JSON object",
+                    line + 1
+                )));
+            };
+
+            let mut row = DatasetRow::new();
+            for (column, value) in object {
+                if !dataset.columns.contains(&column) {
+                    dataset.columns.push(column.clone());
+                }
+                let value = match value {
+                    Value::String(value) => value,
+                    value => value.to_string(),
+                };
+                row.insert(column, value);
+            }
+            dataset.rows.push(row);
+        }
+        Ok(dataset)
+    }
+}
+
+/// The outcome of one input row.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RowResult {
+    /// Index of the row in the dataset.
+    pub row: usize,
+    pub input: DatasetRow,
+    pub answer: Option<String>,
+    /// The error of the last attempt, when every attempt failed.
+    pub error: Option<String>,
+    pub attempts: u32,
+    /// Estimated from text length, like `cost::UsageLedger`.
+    pub prompt_tokens: u64,
+    pub answer_tokens: u64,
+}
+
+/// Runs a dataset through a model: each row is rendered through a prompt template, asked
+/// with bounded concurrency and retried when it fails, and the results are written with
+/// their token usage.
+///
+/// Templates reference columns as `{{column}}`. Every row is rendered before anything is
+/// sent, so a missing column fails the run up front instead of halfway through.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::dataset::DatasetRunner;
+///
+/// let runner = DatasetRunner {
+///     template: "Classify the sentiment of this review: {{review}}".to_string(),
+///     concurrency: 8,
+///     ..Default::default()
+/// };
+/// // Input columns plus answer, error, attempts and token usage
+/// let results = runner.run(&ai_config, "reviews.csv", "labels.csv").await?;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DatasetRunner {
+    /// The prompt sent for each row.
+    pub template: String,
+    /// A system prompt, also rendered per row.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// How many rows are asked at once.
+    pub concurrency: usize,
+    /// Attempts per row, including the first.
+    pub max_attempts: u32,
+    /// Wait before the first retry of a row, doubled for each further retry.
+    pub retry_delay_ms: u64,
+}
+
+impl Default for DatasetRunner {
+    fn default() -> Self {
+        Self {
+            template: String::new(),
+            system_prompt: None,
+            concurrency: 4,
+            max_attempts: 3,
+            retry_delay_ms: 1000,
+        }
+    }
+}
+
+impl DatasetRunner {
+    /// Runs every row of the `input` file and writes the results to `output`, as CSV for a
+    /// `.csv` file and as JSONL otherwise (see `Dataset::read`).
+    ///
+    /// Failed rows are written with their error and do not stop the run.
+    pub async fn run(
+        &self,
+        ai_config: &AiConfig,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<Vec<RowResult>> {
+        let output = output.as_ref();
+        let format = FileFormat::of(output)?;
+        let dataset = Dataset::read(input)?;
+
+        let results = self.run_rows(ai_config, &dataset.rows).await?;
+        let data = match format {
+            FileFormat::Csv => to_csv(&dataset.columns, &results),
+            FileFormat::Jsonl => to_jsonl(&results),
+        };
+        fs::write(output, data).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to write {}: {}", output.display(), e))
+        })?;
+        Ok(results)
+    }
+
+    /// Runs `rows` and returns their results, in row order.
+    pub async fn run_rows(
+        &self,
+        ai_config: &AiConfig,
+        rows: &[DatasetRow],
+    ) -> Result<Vec<RowResult>> {
+        let questions = rows
+            .iter()
+            .map(|row| self.question(row))
+            .collect::<Result<Vec<_>>>()?;
+
+        let results = stream::iter(rows.iter().zip(questions).enumerate())
+            .map(|(index, (row, question))| self.run_row(ai_config, index, row, question))
+            .buffered(self.concurrency.max(1))
+            .collect()
+            .await;
+        Ok(results)
+    }
+
+    /// The question for `row`.
+    pub fn question(&self, row: &DatasetRow) -> Result<Question> {
+        Ok(Question {
+            system_prompt: self
+                .system_prompt
+                .as_deref()
+                .map(|template| render(template, row))
+                .transpose()?,
+            messages: None,
+            new_prompt: render(&self.template, row)?,
+            images: vec![],
+            audio: vec![],
+        })
+    }
+
+    async fn run_row(
+        &self,
+        ai_config: &AiConfig,
+        index: usize,
+        row: &DatasetRow,
+        question: Question,
+    ) -> RowResult {
+        let mut result = RowResult {
+            row: index,
+            input: row.clone(),
+            answer: None,
+            error: None,
+            attempts: 0,
+            prompt_tokens: 0,
+            answer_tokens: 0,
+        };
+
+        while result.attempts < self.max_attempts.max(1) {
+            if result.attempts > 0 {
+                let delay = self
+                    .retry_delay_ms
+                    .saturating_mul(1 << (result.attempts - 1).min(16));
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+            result.attempts += 1;
+            result.prompt_tokens += prompt_tokens(&question);
+
+            match ask_question(ai_config, question.clone()).await {
+                Ok(answer) => {
+                    result.answer_tokens += estimate_tokens(&answer);
+                    result.answer = Some(answer);
+                    result.error = None;
+                    break;
+                }
+                Err(e) => result.error = Some(e.to_string()),
+            }
+        }
+        result
+    }
+}
+
+/// Replaces each `{{column}}` of `template` with its value in `row`.
+fn render(template: &str, row: &DatasetRow) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let column = rest[start + 2..start + end].trim();
+        let value = row.get(column).ok_or_else(|| {
+            AppError::UnexpectedError(format!("Template references missing column `{}`", column))
+        })?;
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FileFormat {
+    Csv,
+    Jsonl,
+}
+
+impl FileFormat {
+    fn of(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Ok(FileFormat::Csv),
+            Some("jsonl" | "ndjson" | "json") => Ok(FileFormat::Jsonl),
+            _ => Err(AppError::UnexpectedError(format!(
+                "Unsupported dataset file {}: expected .csv or .jsonl",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// The records of a CSV document, header included.
+fn parse_csv(csv: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(AppError::UnexpectedError(
+            "Invalid CSV: unterminated quoted field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    // Skip blank lines
+    records.retain(|record| record.len() > 1 || !record[0].is_empty());
+    Ok(records)
+}
+
+fn to_csv(columns: &[String], results: &[RowResult]) -> String {
+    let mut header: Vec<String> = columns.iter().map(|c| csv_field(Some(c))).collect();
+    header.extend(
+        [
+            "answer",
+            "error",
+            "attempts",
+            "prompt_tokens",
+            "answer_tokens",
+        ]
+        .map(String::from),
+    );
+
+    let mut csv = header.join(",") + "\n";
+    for result in results {
+        let mut fields: Vec<String> = columns
+            .iter()
+            .map(|column| csv_field(result.input.get(column).map(String::as_str)))
+            .collect();
+        fields.push(csv_field(result.answer.as_deref()));
+        fields.push(csv_field(result.error.as_deref()));
+        fields.push(result.attempts.to_string());
+        fields.push(result.prompt_tokens.to_string());
+        fields.push(result.answer_tokens.to_string());
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn to_jsonl(results: &[RowResult]) -> String {
+    results
+        .iter()
+        // Strings and numbers always serialize
+        .map(|result| serde_json::to_string(result).unwrap_or_default() + "\n")
+        .collect()
+}
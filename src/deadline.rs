@@ -0,0 +1,56 @@
+use crate::config::AiConfig;
+use crate::error::{AppError, Result};
+use crate::stream::AnswerStream;
+use async_stream::try_stream;
+use futures_util::StreamExt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::{timeout_at, Instant};
+
+/// When the call's `AiConfig::deadline_ms` budget runs out, if it has one.
+///
+/// Nested calls (fallbacks, hedges) start their own budget, but stay bounded by the caller's.
+pub(crate) fn start(ai_config: &AiConfig) -> Option<Instant> {
+    ai_config
+        .deadline_ms
+        .map(|ms| Instant::now() + Duration::from_millis(ms))
+}
+
+/// Runs `call` within the configured deadline, if any.
+pub(crate) async fn within<T>(
+    ai_config: &AiConfig,
+    call: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match start(ai_config) {
+        Some(deadline) => timeout_at(deadline, call)
+            .await
+            .map_err(|_| exceeded(ai_config))?,
+        None => call.await,
+    }
+}
+
+/// Ends `stream` with `AppError::DeadlineExceeded` if it is still running at `deadline`.
+pub(crate) fn bound_stream(
+    stream: AnswerStream,
+    deadline: Instant,
+    ai_config: &AiConfig,
+) -> AnswerStream {
+    let ai_config = ai_config.clone();
+    Box::pin(try_stream! {
+        let mut stream = stream;
+        loop {
+            match timeout_at(deadline, stream.next()).await {
+                Ok(Some(delta)) => yield delta?,
+                Ok(None) => break,
+                Err(_) => Err(exceeded(&ai_config))?,
+            }
+        }
+    })
+}
+
+pub(crate) fn exceeded(ai_config: &AiConfig) -> AppError {
+    AppError::DeadlineExceeded {
+        model_name: ai_config.model.to_string(),
+        deadline_ms: ai_config.deadline_ms.unwrap_or_default(),
+    }
+}
@@ -0,0 +1,149 @@
+use crate::ask_ai::{
+    api_key, ensure_local, openai_endpoint_request, send_request, sensitive_header,
+};
+use crate::config::{AiConfig, Framework};
+use crate::error::{AppError, Result};
+use crate::http::http_client;
+use crate::ollama::ollama_url;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::RequestBuilder;
+use serde_json::Value;
+use std::env;
+
+/// Cohere's embed endpoint, unless `COHERE_API_URL` overrides it.
+const COHERE_API_URL: &str = "https://api.cohere.com/v2/embed";
+
+/// Embeds `text` with the embedding model named by `ai_config.model`.
+///
+/// See `get_embeddings` for the supported providers.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::embeddings::get_embedding;
+///
+/// let ai_config = AiConfig {
+///     llm: Framework::OpenAI,
+///     model: "text-embedding-3-small".to_string(),
+///     ..Default::default()
+/// };
+/// let vector = get_embedding(&ai_config, "How do closures work in Rust?").await?;
+/// ```
+pub async fn get_embedding(ai_config: &AiConfig, text: &str) -> Result<Vec<f32>> {
+    get_embeddings(ai_config, &[text])
+        .await?
+        .pop()
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "No embedding in response".to_string(),
+        })
+}
+
+/// Embeds every text of `texts` in one request, returning the vectors in the same order.
+///
+/// `ai_config.model` names the embedding model. Supported providers:
+/// - OpenAI, Mistral and LM Studio: the `embeddings` endpoint next to chat completions, so
+///   `AiConfig::base_url` and the `*_API_URL` overrides apply.
+/// - Ollama: `/api/embed` (e.g. `nomic-embed-text`).
+/// - Cohere, as `Framework::Custom("cohere")`: the v2 `embed` endpoint with
+///   `COHERE_API_KEY`, unless `COHERE_API_URL` overrides it. Texts are embedded as
+///   `search_document`.
+///
+/// Other providers fail with `AppError::ModelError`.
+pub async fn get_embeddings(ai_config: &AiConfig, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    if texts.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (builder, payload) = match &ai_config.llm {
+        Framework::OpenAI | Framework::Mistral | Framework::LmStudio => (
+            openai_endpoint_request(ai_config, "embeddings")?,
+            serde_json::json!({
+                "model": ai_config.model,
+                "input": texts,
+                "encoding_format": "float"
+            }),
+        ),
+        Framework::Ollama => {
+            let api_url = format!("{}api/embed", ollama_url(ai_config)?);
+            ensure_local(ai_config, &api_url)?;
+            (
+                http_client(ai_config)?
+                    .post(api_url)
+                    .header(CONTENT_TYPE, "application/json"),
+                serde_json::json!({ "model": ai_config.model, "input": texts }),
+            )
+        }
+        Framework::Custom(name) if name.eq_ignore_ascii_case("cohere") => (
+            cohere_request(ai_config, "embed")?,
+            serde_json::json!({
+                "model": ai_config.model,
+                "texts": texts,
+                "input_type": "search_document",
+                "embedding_types": ["float"]
+            }),
+        ),
+        llm => {
+            return Err(AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: format!("{} has no embeddings API", llm),
+            })
+        }
+    };
+
+    let resp = send_request(builder.json(&payload), ai_config).await?;
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    let vectors = match &ai_config.llm {
+        Framework::Ollama => vectors(&response["embeddings"]),
+        Framework::Custom(_) => vectors(&response["embeddings"]["float"]),
+        // OpenAI-compatible servers may list embeddings out of order
+        _ => {
+            let mut data = response["data"].as_array().cloned().unwrap_or_default();
+            data.sort_by_key(|item| item["index"].as_u64());
+            data.iter()
+                .map(|item| vector(&item["embedding"]))
+                .collect::<Option<Vec<_>>>()
+        }
+    };
+    match vectors {
+        Some(vectors) if vectors.len() == texts.len() => Ok(vectors),
+        _ => Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract embeddings from response".to_string(),
+        }),
+    }
+}
+
+/// Prepares an authenticated POST to the Cohere endpoint `path`, next to the embed endpoint.
+pub(crate) fn cohere_request(ai_config: &AiConfig, path: &str) -> Result<RequestBuilder> {
+    let api_url = env::var("COHERE_API_URL").unwrap_or_else(|_| COHERE_API_URL.to_string());
+    let api_url = match api_url.strip_suffix("embed") {
+        Some(base) => format!("{}{}", base, path),
+        None => api_url,
+    };
+    ensure_local(ai_config, &api_url)?;
+    let api_key = api_key(ai_config, "COHERE_API_KEY")?;
+    Ok(http_client(ai_config)?
+        .post(api_url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(
+            AUTHORIZATION,
+            sensitive_header(&format!("Bearer {}", api_key.expose_secret()))?,
+        ))
+}
+
+fn vectors(value: &Value) -> Option<Vec<Vec<f32>>> {
+    value.as_array()?.iter().map(vector).collect()
+}
+
+fn vector(value: &Value) -> Option<Vec<f32>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|x| x.as_f64().map(|x| x as f32))
+        .collect()
+}
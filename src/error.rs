@@ -1,5 +1,8 @@
+use crate::capabilities::Capability;
+use crate::limits::PayloadMeasure;
 use std::error::Error;
 use std::fmt;
+use std::time::SystemTime;
 
 #[derive(Debug)]
 pub enum AppError {
@@ -12,6 +15,49 @@ pub enum AppError {
         failure_str: String,
     },
     UnexpectedError(String),
+    /// A tenant used up its request or token quota for the current window.
+    QuotaExceeded {
+        tenant: String,
+        reset_at: SystemTime,
+    },
+    /// Local-only mode refused to send a question to a remote endpoint.
+    RemoteEndpointBlocked {
+        model_name: String,
+        endpoint: String,
+    },
+    /// A question exceeded a configured payload limit and was not sent.
+    PayloadTooLarge {
+        measure: PayloadMeasure,
+        size: u64,
+        limit: u64,
+    },
+    /// The model lacks a capability the request needs, and `UnsupportedPolicy::Error` is set.
+    UnsupportedCapability {
+        model_name: String,
+        capability: Capability,
+    },
+    /// The new prompt was empty and `EmptyPromptPolicy::Reject` is set.
+    EmptyPrompt {
+        model_name: String,
+    },
+    /// The call's `AiConfig::deadline_ms` budget ran out before it completed.
+    DeadlineExceeded {
+        model_name: String,
+        deadline_ms: u64,
+    },
+    /// No answer validated against the requested JSON Schema within the allowed attempts.
+    SchemaMismatch {
+        model_name: String,
+        attempts: u32,
+        /// Validation errors of the last answer.
+        errors: Vec<String>,
+    },
+    /// The prompt was flagged by moderation under `AiConfig::moderate_prompts`, and not sent.
+    ContentFlagged {
+        model_name: String,
+        /// The flagged moderation categories.
+        categories: Vec<String>,
+    },
 }
 
 // Human-readable string representation
@@ -39,6 +85,81 @@ impl fmt::Display for AppError {
                     model_name, failure_str
                 )
             }
+            AppError::QuotaExceeded { tenant, reset_at } => {
+                let reset_in = reset_at
+                    .duration_since(SystemTime::now())
+                    .unwrap_or_default()
+                    .as_secs();
+                write!(
+                    f,
+                    "Quota exceeded for tenant {}. Resets in {}s",
+                    tenant, reset_in
+                )
+            }
+            AppError::RemoteEndpointBlocked {
+                model_name,
+                endpoint,
+            } => {
+                write!(
+                    f,
+                    "Refusing to send to remote {} endpoint {}: local-only mode is enabled",
+                    model_name, endpoint
+                )
+            }
+            AppError::PayloadTooLarge {
+                measure,
+                size,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "Payload too large: {} {} exceeds the limit of {}",
+                    size, measure, limit
+                )
+            }
+            AppError::UnsupportedCapability {
+                model_name,
+                capability,
+            } => {
+                write!(f, "Model {} does not support {}", model_name, capability)
+            }
+            AppError::EmptyPrompt { model_name } => {
+                write!(f, "Refusing to send an empty prompt to {}", model_name)
+            }
+            AppError::DeadlineExceeded {
+                model_name,
+                deadline_ms,
+            } => {
+                write!(
+                    f,
+                    "Deadline of {}ms exceeded while asking {}",
+                    deadline_ms, model_name
+                )
+            }
+            AppError::SchemaMismatch {
+                model_name,
+                attempts,
+                errors,
+            } => {
+                write!(
+                    f,
+                    "No answer from {} matched the JSON Schema after {} attempts: {}",
+                    model_name,
+                    attempts,
+                    errors.join("; ")
+                )
+            }
+            AppError::ContentFlagged {
+                model_name,
+                categories,
+            } => {
+                write!(
+                    f,
+                    "Prompt for {} flagged by moderation: {}",
+                    model_name,
+                    categories.join(", ")
+                )
+            }
         }
     }
 }
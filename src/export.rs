@@ -0,0 +1,118 @@
+use crate::config::{AiPrompt, Question};
+use crate::conversation::Conversation;
+use serde_json::Value;
+
+/// Exports conversations as an OpenAI chat fine-tuning dataset (JSONL).
+///
+/// Each conversation becomes one line of the form
+/// `{"messages": [{"role": "system", ...}, {"role": "user", ...}, {"role": "assistant", ...}]}`.
+/// Conversations without any completed exchange are skipped, since they carry no training
+/// signal.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::export::to_openai_finetune_jsonl;
+///
+/// std::fs::write("train.jsonl", to_openai_finetune_jsonl(&conversations))?;
+/// ```
+pub fn to_openai_finetune_jsonl(conversations: &[Conversation]) -> String {
+    to_jsonl(conversations, |conversation| {
+        let mut messages = vec![];
+        if let Some(sys_prompt) = &conversation.system_prompt {
+            messages.push(serde_json::json!({
+                "role": "system",
+                "content": sys_prompt
+            }));
+        }
+        messages.extend(turns(&conversation.messages));
+        serde_json::json!({ "messages": messages })
+    })
+}
+
+/// Writes a `Question` as a standard OpenAI `messages` array, the inverse of
+/// `import::from_openai_messages`.
+///
+/// Only what the question itself holds is included: no system message without a system
+/// prompt, and the new prompt as is, without `AiConfig::prompts` defaults.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::export::to_openai_messages;
+///
+/// let messages = to_openai_messages(&question);
+/// assert_eq!(messages[0]["role"], "system");
+/// ```
+pub fn to_openai_messages(question: &Question) -> Value {
+    let mut messages = vec![];
+    if let Some(sys_prompt) = &question.system_prompt {
+        messages.push(serde_json::json!({
+            "role": "system",
+            "content": sys_prompt
+        }));
+    }
+    messages.extend(turns(question.messages.as_deref().unwrap_or_default()));
+    messages.push(serde_json::json!({
+        "role": "user",
+        "content": question.new_prompt
+    }));
+    Value::Array(messages)
+}
+
+/// Exports conversations in the Anthropic (Claude) fine-tuning format (JSONL).
+///
+/// Each conversation becomes one line of the form
+/// `{"system": "...", "messages": [{"role": "user", ...}, {"role": "assistant", ...}]}`,
+/// the layout accepted for Claude fine-tuning jobs. Conversations without any completed
+/// exchange are skipped.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::export::to_anthropic_finetune_jsonl;
+///
+/// std::fs::write("train.jsonl", to_anthropic_finetune_jsonl(&conversations))?;
+/// ```
+pub fn to_anthropic_finetune_jsonl(conversations: &[Conversation]) -> String {
+    to_jsonl(conversations, |conversation| {
+        let mut example = serde_json::json!({ "messages": turns(&conversation.messages) });
+        if let Some(sys_prompt) = &conversation.system_prompt {
+            example["system"] = Value::String(sys_prompt.to_string());
+        }
+        example
+    })
+}
+
+fn to_jsonl(conversations: &[Conversation], example: impl Fn(&Conversation) -> Value) -> String {
+    conversations
+        .iter()
+        .filter(|conversation| {
+            conversation
+                .messages
+                .iter()
+                .any(|msg| !msg.content.is_empty() && !msg.output.is_empty())
+        })
+        .map(|conversation| format!("{}\n", example(conversation)))
+        .collect()
+}
+
+/// The user/assistant messages of the exchanges, skipping empty halves of an exchange.
+fn turns(exchanges: &[AiPrompt]) -> Vec<Value> {
+    let mut messages = vec![];
+    for msg in exchanges.iter() {
+        if !msg.content.is_empty() {
+            messages.push(serde_json::json!({
+                "role": "user",
+                "content": msg.content
+            }));
+        }
+        if !msg.output.is_empty() {
+            messages.push(serde_json::json!({
+                "role": "assistant",
+                "content": msg.output
+            }));
+        }
+    }
+    messages
+}
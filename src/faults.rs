@@ -0,0 +1,155 @@
+use crate::config::AiConfig;
+use crate::error::{AppError, Result};
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Failures and latency injected into every provider request, for chaos-testing retry and
+/// fallback logic.
+///
+/// Faults are raised before the request is sent, as the same errors a real failure gives
+/// (`AppError::ApiError` with the status or timeout in its message). Rates are probabilities
+/// between 0 and 1, drawn independently per request; their sum should not exceed 1.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::faults::{FaultInjection, Latency};
+///
+/// // One request in five is rate limited, and every request takes 200-800ms longer
+/// let ai_config = AiConfig {
+///     faults: Some(FaultInjection {
+///         rate_limit_rate: 0.2,
+///         latency: Some(Latency::Uniform { min_ms: 200, max_ms: 800 }),
+///         seed: Some(7),
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FaultInjection {
+    /// Share of requests failing with `429 Too Many Requests`.
+    #[serde(default)]
+    pub rate_limit_rate: f64,
+    /// Share of requests failing with `500 Internal Server Error`.
+    #[serde(default)]
+    pub server_error_rate: f64,
+    /// Share of requests timing out.
+    #[serde(default)]
+    pub timeout_rate: f64,
+    /// How long an injected timeout waits before failing.
+    #[serde(default)]
+    pub timeout_after_ms: u64,
+    /// Delay added before every request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<Latency>,
+    /// Makes the sequence of faults and delays reproducible; random when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Position in the seeded sequence, shared by clones so it continues across them.
+    #[serde(skip)]
+    pub sequence: FaultSequence,
+}
+
+/// How far a seeded `FaultInjection` has drawn; start from `Default::default()`.
+#[derive(Debug, Clone, Default)]
+pub struct FaultSequence(Arc<AtomicU64>);
+
+/// How injected delays are distributed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum Latency {
+    Fixed {
+        ms: u64,
+    },
+    Uniform {
+        min_ms: u64,
+        max_ms: u64,
+    },
+    /// Clamped at zero.
+    Normal {
+        mean_ms: f64,
+        std_dev_ms: f64,
+    },
+}
+
+/// An injected failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    RateLimit,
+    ServerError,
+    Timeout,
+}
+
+impl FaultInjection {
+    /// Waits out the injected latency, then fails if a fault is drawn.
+    pub(crate) async fn inject(&self, ai_config: &AiConfig) -> Result<()> {
+        if let Some(delay) = self.delay() {
+            tokio::time::sleep(delay).await;
+        }
+
+        let fault = match self.next_unit() {
+            r if r < self.rate_limit_rate => Fault::RateLimit,
+            r if r < self.rate_limit_rate + self.server_error_rate => Fault::ServerError,
+            r if r < self.rate_limit_rate + self.server_error_rate + self.timeout_rate => {
+                Fault::Timeout
+            }
+            _ => return Ok(()),
+        };
+
+        let failure_str = match fault {
+            Fault::RateLimit => "Status 429 Too Many Requests: injected fault".to_string(),
+            Fault::ServerError => "Status 500 Internal Server Error: injected fault".to_string(),
+            Fault::Timeout => {
+                tokio::time::sleep(Duration::from_millis(self.timeout_after_ms)).await;
+                "Request error: operation timed out (injected fault)".to_string()
+            }
+        };
+        Err(AppError::ApiError {
+            model_name: ai_config.llm.to_string(),
+            failure_str,
+        })
+    }
+
+    fn delay(&self) -> Option<Duration> {
+        let ms = match self.latency? {
+            Latency::Fixed { ms } => ms as f64,
+            Latency::Uniform { min_ms, max_ms } => {
+                min_ms as f64 + self.next_unit() * max_ms.saturating_sub(min_ms) as f64
+            }
+            Latency::Normal {
+                mean_ms,
+                std_dev_ms,
+            } => {
+                // Box-Muller transform
+                let (u1, u2) = (1.0 - self.next_unit(), self.next_unit());
+                let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+                mean_ms + z * std_dev_ms
+            }
+        };
+        Some(Duration::from_millis(ms.max(0.0).round() as u64))
+    }
+
+    /// A uniform draw in `[0, 1)`.
+    fn next_unit(&self) -> f64 {
+        let bits = match self.seed {
+            Some(seed) => {
+                let draw = self.sequence.0.fetch_add(1, Ordering::Relaxed);
+                splitmix64(seed.wrapping_add(draw.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+            }
+            None => OsRng.next_u64(),
+        };
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Output function of the SplitMix64 generator.
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,181 @@
+use crate::config::{AiConfig, Framework};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A hard constraint on the shape of the answer, enforced by the model server while
+/// decoding, for local models that otherwise drift from the requested format.
+///
+/// Supported backends:
+/// - Ollama (`Framework::Ollama`): `JsonSchema`, sent as `format`.
+/// - llama.cpp and vLLM servers behind the OpenAI-compatible API (`Framework::OpenAI` with
+///   `AiConfig::base_url` pointing at them): `Gbnf` as llama.cpp's `grammar` and vLLM's
+///   `guided_grammar`, `Regex` as vLLM's `guided_regex`, and `JsonSchema` as llama.cpp's
+///   `json_schema` and vLLM's `guided_json`. Each server ignores the other's fields; hosted
+///   OpenAI rejects them.
+///
+/// Other combinations fail before anything is sent, rather than returning unconstrained
+/// output.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::grammar::OutputConstraint;
+///
+/// // A llama.cpp server answering only "yes" or "no"
+/// let ai_config = AiConfig {
+///     llm: Framework::OpenAI,
+///     constraint: Some(OutputConstraint::Gbnf(r#"root ::= "yes" | "no""#.to_string())),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputConstraint {
+    /// A GBNF grammar the answer must follow.
+    Gbnf(String),
+    /// A regular expression the whole answer must match.
+    Regex(String),
+    /// A JSON Schema the answer must validate against.
+    JsonSchema(Value),
+}
+
+impl OutputConstraint {
+    /// Fails when the configured provider cannot enforce this constraint.
+    pub(crate) fn check(&self, ai_config: &AiConfig) -> Result<()> {
+        let supported = matches!(
+            (&ai_config.llm, self),
+            (Framework::OpenAI, _) | (Framework::Ollama, OutputConstraint::JsonSchema(_))
+        );
+        if supported {
+            return Ok(());
+        }
+        Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!(
+                "{} cannot enforce a {} constraint",
+                ai_config.llm,
+                self.kind()
+            ),
+        })
+    }
+
+    /// Adds the constraint to a `framework` request payload.
+    pub(crate) fn apply(&self, framework: Framework, payload: &mut Value) {
+        let fields = match (framework, self) {
+            (Framework::OpenAI, OutputConstraint::Gbnf(grammar)) => vec![
+                ("grammar", Value::from(grammar.as_str())),
+                ("guided_grammar", Value::from(grammar.as_str())),
+            ],
+            (Framework::OpenAI, OutputConstraint::Regex(regex)) => {
+                vec![("guided_regex", Value::from(regex.as_str()))]
+            }
+            (Framework::OpenAI, OutputConstraint::JsonSchema(schema)) => vec![
+                ("json_schema", schema.clone()),
+                ("guided_json", schema.clone()),
+            ],
+            (Framework::Ollama, OutputConstraint::JsonSchema(schema)) => {
+                vec![("format", schema.clone())]
+            }
+            // Rejected by `check` before anything is sent
+            _ => vec![],
+        };
+        for (key, value) in fields {
+            payload[key] = value;
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            OutputConstraint::Gbnf(_) => "GBNF grammar",
+            OutputConstraint::Regex(_) => "regex",
+            OutputConstraint::JsonSchema(_) => "JSON Schema",
+        }
+    }
+}
+
+/// Asks the model for a JSON answer, so callers get machine-parseable output.
+///
+/// Providers with a JSON mode enforce it natively: OpenAI and the OpenAI-compatible providers
+/// through `response_format`, Ollama through `format`, which takes the schema itself (Ollama
+/// 0.5 or later), so local models decode only answers that follow it. Anthropic, Bedrock and
+/// Replicate have no such mode, so the request (and schema) is written into the system prompt
+/// instead.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::grammar::ResponseFormat;
+/// use serde_json::json;
+///
+/// let ai_config = AiConfig {
+///     response_format: Some(ResponseFormat::JsonSchema {
+///         name: "city".to_string(),
+///         schema: json!({
+///             "type": "object",
+///             "properties": { "name": { "type": "string" }, "population": { "type": "integer" } },
+///             "required": ["name", "population"]
+///         }),
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Any JSON object.
+    JsonObject,
+    /// A JSON object following `schema`. `name` identifies the schema to OpenAI.
+    JsonSchema { name: String, schema: Value },
+}
+
+impl ResponseFormat {
+    /// Whether `framework` has a JSON mode of its own.
+    pub(crate) fn is_native(framework: &Framework) -> bool {
+        matches!(
+            framework,
+            Framework::OpenAI
+                | Framework::Mistral
+                | Framework::Groq
+                | Framework::OpenRouter
+                | Framework::HuggingFace
+                | Framework::LmStudio
+                | Framework::Ollama
+                | Framework::Custom(_)
+        )
+    }
+
+    /// Adds the format to a `framework` request payload, for providers with a JSON mode.
+    pub(crate) fn apply(&self, framework: Framework, payload: &mut Value) {
+        match (framework, self) {
+            (Framework::Ollama, ResponseFormat::JsonObject) => payload["format"] = "json".into(),
+            (Framework::Ollama, ResponseFormat::JsonSchema { schema, .. }) => {
+                payload["format"] = schema.clone()
+            }
+            (framework, _) if !Self::is_native(&framework) => {}
+            (_, ResponseFormat::JsonObject) => {
+                payload["response_format"] = serde_json::json!({ "type": "json_object" })
+            }
+            (_, ResponseFormat::JsonSchema { name, schema }) => {
+                payload["response_format"] = serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": name, "schema": schema, "strict": true }
+                })
+            }
+        }
+    }
+
+    /// The system prompt addition asking for JSON, for providers without a JSON mode.
+    pub(crate) fn instruction(&self) -> String {
+        match self {
+            ResponseFormat::JsonObject => {
+                "Respond only with a valid JSON object, without any other text.".to_string()
+            }
+            ResponseFormat::JsonSchema { schema, .. } => format!(
+                "Respond only with a valid JSON object matching this JSON Schema, without any \
+                 other text:\n{}",
+                schema
+            ),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::ask_ai::dispatch;
+use crate::config::{AiConfig, Question};
+use crate::error::Result;
+use futures_util::future::{select, Either};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::time::Duration;
+
+/// Hedged requests: if the answer has not arrived after `after_ms`, the same question is sent
+/// again, to `to` or to the same provider, and whichever answer comes first is returned. The
+/// other request is cancelled.
+///
+/// A failed request does not end the race early: the answer of the other one is awaited, and
+/// the error only returned if both fail. Hedging trades extra requests on slow calls for
+/// lower tail latency, so it suits interactive use. A `to` target is held to the local-only
+/// mode of the config it hedges for.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::hedge::Hedge;
+///
+/// // After 2s without an answer, also ask Anthropic
+/// let ai_config = AiConfig {
+///     hedge: Some(Hedge {
+///         after_ms: 2000,
+///         to: Some(Box::new(AiConfig::default_for(Framework::Anthropic))),
+///     }),
+///     ..ai_config
+/// };
+/// let answer = ask_question(&ai_config, question).await?;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Hedge {
+    /// How long the first request may take before the hedge is sent.
+    pub after_ms: u64,
+    /// Where the hedge goes; the same configuration when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Box<AiConfig>>,
+}
+
+impl Hedge {
+    pub(crate) async fn ask(&self, ai_config: &AiConfig, question: Question) -> Result<String> {
+        let target = match &self.to {
+            Some(to) => to.on_behalf_of(ai_config),
+            None => Cow::Borrowed(ai_config),
+        };
+        let first = Box::pin(dispatch(ai_config, question.clone()));
+        let delay = Box::pin(tokio::time::sleep(Duration::from_millis(self.after_ms)));
+        let first = match select(first, delay).await {
+            Either::Left((answer, _)) => return answer,
+            Either::Right((_, first)) => first,
+        };
+
+        let second = Box::pin(dispatch(&target, question));
+        // Bound, so the losing request is dropped before `target` it may borrow
+        let answer = match select(first, second).await {
+            Either::Left((Ok(answer), _)) | Either::Right((Ok(answer), _)) => Ok(answer),
+            Either::Left((Err(_), other)) | Either::Right((Err(_), other)) => other.await,
+        };
+        answer
+    }
+}
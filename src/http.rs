@@ -0,0 +1,118 @@
+use crate::config::AiConfig;
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Which HTTP version to speak to provider endpoints.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersion {
+    /// Negotiate: HTTP/2 over TLS when the server offers it, HTTP/1.1 otherwise.
+    #[default]
+    Auto,
+    /// Always HTTP/1.1.
+    Http1,
+    /// Always HTTP/2, without negotiation (also over plain-text connections).
+    Http2,
+}
+
+/// Connection tuning for the HTTP client used to reach providers.
+///
+/// Clients are shared: every `AiConfig` with the same options reuses one connection pool.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::http::{HttpOptions, HttpVersion};
+///
+/// let ai_config = AiConfig {
+///     http: Some(HttpOptions {
+///         pool_max_idle_per_host: Some(64),
+///         pool_idle_timeout_secs: Some(90),
+///         http_version: HttpVersion::Http2,
+///         tcp_keepalive_secs: Some(30),
+///         unix_socket: None,
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+pub struct HttpOptions {
+    /// Maximum idle connections kept per host.
+    #[serde(default)]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle pooled connection is kept, in seconds.
+    #[serde(default)]
+    pub pool_idle_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub http_version: HttpVersion,
+    /// Interval of TCP keep-alive probes, in seconds.
+    #[serde(default)]
+    pub tcp_keepalive_secs: Option<u64>,
+    /// Connects through this Unix domain socket instead of TCP (Unix only).
+    ///
+    /// The endpoint URL still provides the scheme, `Host` header and path, so a sidecar on
+    /// `/run/llm.sock` is reached with e.g. `OLLAMA_API_URL=http://localhost`.
+    #[serde(default)]
+    pub unix_socket: Option<PathBuf>,
+}
+
+/// Returns the shared HTTP client for the connection options of `ai_config`.
+pub(crate) fn http_client(ai_config: &AiConfig) -> Result<reqwest::Client> {
+    static CLIENTS: OnceLock<Mutex<HashMap<HttpOptions, reqwest::Client>>> = OnceLock::new();
+
+    let options = ai_config.http.clone().unwrap_or_default();
+    let mut clients = CLIENTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(client) = clients.get(&options) {
+        // Clients are handles to a shared pool, cloning is cheap
+        return Ok(client.clone());
+    }
+
+    let client = build_client(&options)?;
+    clients.insert(options, client.clone());
+    Ok(client)
+}
+
+fn build_client(options: &HttpOptions) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(max_idle) = options.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(secs) = options.pool_idle_timeout_secs {
+        builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = options.tcp_keepalive_secs {
+        builder = builder.tcp_keepalive(Duration::from_secs(secs));
+    }
+    if let Some(path) = &options.unix_socket {
+        builder = unix_socket(builder, path)?;
+    }
+    builder = match options.http_version {
+        HttpVersion::Auto => builder,
+        HttpVersion::Http1 => builder.http1_only(),
+        HttpVersion::Http2 => builder.http2_prior_knowledge(),
+    };
+
+    builder
+        .build()
+        .map_err(|e| AppError::UnexpectedError(format!("Failed to build HTTP client: {}", e)))
+}
+
+#[cfg(unix)]
+fn unix_socket(builder: reqwest::ClientBuilder, path: &Path) -> Result<reqwest::ClientBuilder> {
+    Ok(builder.unix_socket(path))
+}
+
+#[cfg(not(unix))]
+fn unix_socket(_: reqwest::ClientBuilder, path: &Path) -> Result<reqwest::ClientBuilder> {
+    Err(AppError::UnexpectedError(format!(
+        "Unix domain socket `{}` is not supported on this platform",
+        path.display()
+    )))
+}
@@ -0,0 +1,121 @@
+use crate::ask_ai::{openai_endpoint_request, send_request};
+use crate::config::{AiConfig, Framework};
+use crate::error::{AppError, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Options of an image generation request. Unset options take the provider's default.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ImageOptions {
+    /// Image size, e.g. `1024x1024`, `1536x1024` or `auto`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// Image quality: `low`, `medium`, `high` or `auto` for gpt-image models, `standard` or
+    /// `hd` for DALL·E 3.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    /// Number of images to generate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    /// Return URLs, valid for an hour, rather than bytes. Only DALL·E models return URLs,
+    /// gpt-image models always return bytes.
+    #[serde(default)]
+    pub urls: bool,
+}
+
+/// A generated image.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratedImage {
+    /// The image file, PNG unless the model was asked otherwise.
+    Bytes(Vec<u8>),
+    /// A URL to download the image from.
+    Url(String),
+}
+
+/// Generates images from `prompt` with the image model named by `ai_config.model`, e.g.
+/// `gpt-image-1` or `dall-e-3`.
+///
+/// Uses OpenAI's `images/generations` endpoint next to chat completions, so
+/// `AiConfig::base_url` and `OPENAI_API_URL` apply. Other providers fail with
+/// `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::images::{generate_image, GeneratedImage, ImageOptions};
+///
+/// let ai_config = AiConfig {
+///     llm: Framework::OpenAI,
+///     model: "gpt-image-1".to_string(),
+///     ..Default::default()
+/// };
+/// let options = ImageOptions {
+///     size: Some("1024x1024".to_string()),
+///     ..Default::default()
+/// };
+/// for image in generate_image(&ai_config, "A crab made of rust, watercolor", &options).await? {
+///     if let GeneratedImage::Bytes(png) = image {
+///         std::fs::write("crab.png", png)?;
+///     }
+/// }
+/// ```
+pub async fn generate_image(
+    ai_config: &AiConfig,
+    prompt: &str,
+    options: &ImageOptions,
+) -> Result<Vec<GeneratedImage>> {
+    if ai_config.llm != Framework::OpenAI {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no image generation API", ai_config.llm),
+        });
+    }
+
+    let mut payload = serde_json::json!({
+        "model": ai_config.model,
+        "prompt": prompt,
+    });
+    if let Some(size) = &options.size {
+        payload["size"] = serde_json::json!(size);
+    }
+    if let Some(quality) = &options.quality {
+        payload["quality"] = serde_json::json!(quality);
+    }
+    if let Some(n) = options.n {
+        payload["n"] = serde_json::json!(n);
+    }
+    // gpt-image models reject `response_format`, they only return base64 data
+    if ai_config.model.starts_with("dall-e") {
+        payload["response_format"] =
+            serde_json::json!(if options.urls { "url" } else { "b64_json" });
+    }
+
+    let builder = openai_endpoint_request(ai_config, "images/generations")?;
+    let resp = send_request(builder.json(&payload), ai_config).await?;
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    let images = response["data"]
+        .as_array()
+        .map(|data| data.iter().map(generated_image).collect::<Option<Vec<_>>>())
+        .unwrap_or_default();
+    match images {
+        Some(images) if !images.is_empty() => Ok(images),
+        _ => Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract images from response".to_string(),
+        }),
+    }
+}
+
+fn generated_image(item: &Value) -> Option<GeneratedImage> {
+    if let Some(data) = item["b64_json"].as_str() {
+        return BASE64_STANDARD.decode(data).ok().map(GeneratedImage::Bytes);
+    }
+    item["url"]
+        .as_str()
+        .map(|url| GeneratedImage::Url(url.to_string()))
+}
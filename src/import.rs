@@ -0,0 +1,248 @@
+use crate::config::{AiPrompt, Question};
+use crate::conversation::Conversation;
+use crate::error::{AppError, Result};
+use serde_json::Value;
+
+/// Imports the `conversations.json` file of a ChatGPT data export.
+///
+/// ChatGPT stores each conversation as a tree of message nodes (edits and regenerations
+/// create branches); the branch ending at `current_node`, i.e. the one shown in the UI, is
+/// the one imported. Non-text parts (images, tool traffic) are skipped.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::import::from_chatgpt_export;
+///
+/// let conversations = from_chatgpt_export(&std::fs::read_to_string("conversations.json")?)?;
+/// let question = conversations[0].question("Let's pick up where we left off.");
+/// let answer = ask_question(&ai_config, question).await?;
+/// ```
+pub fn from_chatgpt_export(json: &str) -> Result<Vec<Conversation>> {
+    parse_export(json, "ChatGPT")?
+        .iter()
+        .map(chatgpt_conversation)
+        .collect()
+}
+
+/// Imports the `conversations.json` file of a Claude (claude.ai) data export.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::import::from_claude_export;
+///
+/// let conversations = from_claude_export(&std::fs::read_to_string("conversations.json")?)?;
+/// ```
+pub fn from_claude_export(json: &str) -> Result<Vec<Conversation>> {
+    parse_export(json, "Claude")?
+        .iter()
+        .map(|conversation| {
+            let chat_messages = conversation["chat_messages"].as_array().ok_or_else(|| {
+                AppError::UnexpectedError(
+                    "Invalid Claude export: conversation without `chat_messages`".to_string(),
+                )
+            })?;
+
+            let mut turns = vec![];
+            for message in chat_messages {
+                let role = match message["sender"].as_str() {
+                    Some("human") => "user",
+                    Some("assistant") => "assistant",
+                    _ => continue,
+                };
+                let text = match message["text"].as_str() {
+                    Some(text) if !text.is_empty() => text.to_string(),
+                    _ => joined_text(&message["content"]),
+                };
+                turns.push((role, text));
+            }
+
+            Ok(pair_turns(None, turns))
+        })
+        .collect()
+}
+
+/// Reads a standard OpenAI `messages` array into a `Question`, ready for `ask_question`.
+///
+/// Accepts the array itself or any object holding it under `messages`, such as a chat
+/// completion request or a line of a fine-tuning dataset. The final user message becomes the
+/// new prompt, earlier turns the history, and `system` (or `developer`) messages the system
+/// prompt. Content may be a string or a list of parts, of which only the text is kept; tool
+/// messages are skipped.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::import::from_openai_messages;
+///
+/// let question = from_openai_messages(r#"[
+///     { "role": "system", "content": "You are terse." },
+///     { "role": "user", "content": "What is Rust?" }
+/// ]"#)?;
+/// let answer = ask_question(&ai_config, question).await?;
+/// ```
+pub fn from_openai_messages(json: &str) -> Result<Question> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| AppError::UnexpectedError(format!("Invalid OpenAI messages: {}", e)))?;
+    let messages = match &value {
+        Value::Array(messages) => messages,
+        Value::Object(object) => object
+            .get("messages")
+            .and_then(Value::as_array)
+            .ok_or_else(|| {
+                AppError::UnexpectedError(
+                    "Invalid OpenAI messages: object without `messages`".to_string(),
+                )
+            })?,
+        _ => {
+            return Err(AppError::UnexpectedError(
+                "Invalid OpenAI messages: expected a list of messages".to_string(),
+            ))
+        }
+    };
+
+    let mut system_prompts = vec![];
+    let mut turns = vec![];
+    for message in messages {
+        let text = match &message["content"] {
+            Value::String(text) => text.to_string(),
+            content => joined_text(content),
+        };
+        match message["role"].as_str() {
+            Some("system") | Some("developer") if !text.is_empty() => system_prompts.push(text),
+            Some("user") => turns.push(("user", text)),
+            Some("assistant") => turns.push(("assistant", text)),
+            _ => {}
+        }
+    }
+    let system_prompt = (!system_prompts.is_empty()).then(|| system_prompts.join("\n\n"));
+
+    let mut conversation = pair_turns(system_prompt, turns);
+    let new_prompt = match conversation.messages.pop() {
+        Some(last) if last.output.is_empty() => last.content,
+        _ => {
+            return Err(AppError::UnexpectedError(
+                "Invalid OpenAI messages: the last message must be from the user".to_string(),
+            ))
+        }
+    };
+    Ok(conversation.question(&new_prompt))
+}
+
+/// Accepts either a list of conversations (the export file) or a single conversation object.
+fn parse_export(json: &str, source: &str) -> Result<Vec<Value>> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| AppError::UnexpectedError(format!("Invalid {} export: {}", source, e)))?;
+
+    match value {
+        Value::Array(conversations) => Ok(conversations),
+        Value::Object(_) => Ok(vec![value]),
+        _ => Err(AppError::UnexpectedError(format!(
+            "Invalid {} export: expected a list of conversations",
+            source
+        ))),
+    }
+}
+
+fn chatgpt_conversation(conversation: &Value) -> Result<Conversation> {
+    let mapping = conversation["mapping"].as_object().ok_or_else(|| {
+        AppError::UnexpectedError(
+            "Invalid ChatGPT export: conversation without `mapping`".to_string(),
+        )
+    })?;
+
+    // Walk from the displayed leaf back to the root, then replay in order
+    let mut path = vec![];
+    let mut node_id = conversation["current_node"].as_str();
+    while let Some(id) = node_id {
+        let Some(node) = mapping.get(id) else {
+            break;
+        };
+        path.push(node);
+        node_id = node["parent"].as_str();
+    }
+    path.reverse();
+
+    let mut system_prompt = None;
+    let mut turns = vec![];
+    for node in path {
+        let message = &node["message"];
+        if message["content"]["content_type"].as_str() != Some("text") {
+            continue;
+        }
+        let text = message["content"]["parts"]
+            .as_array()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default();
+
+        match message["author"]["role"].as_str() {
+            Some("system") if !text.is_empty() => system_prompt = Some(text),
+            Some("user") => turns.push(("user", text)),
+            Some("assistant") => turns.push(("assistant", text)),
+            _ => {}
+        }
+    }
+
+    Ok(pair_turns(system_prompt, turns))
+}
+
+/// Concatenates the text blocks of a content array.
+fn joined_text(content: &Value) -> String {
+    content
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|block| block["type"].as_str() == Some("text"))
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// Folds a linear list of user/assistant messages into exchanges.
+///
+/// Consecutive assistant messages are merged into one output; a user message that never got
+/// an answer becomes an exchange with an empty output.
+fn pair_turns(system_prompt: Option<String>, turns: Vec<(&str, String)>) -> Conversation {
+    let mut conversation = Conversation::new(system_prompt);
+    let mut pending: Option<AiPrompt> = None;
+
+    for (role, text) in turns {
+        if text.is_empty() {
+            continue;
+        }
+        if role == "user" {
+            if let Some(prompt) = pending.take() {
+                conversation.messages.push(prompt);
+            }
+            pending = Some(AiPrompt {
+                content: text,
+                output: String::new(),
+            });
+            continue;
+        }
+
+        let prompt = pending.get_or_insert_with(|| AiPrompt {
+            content: String::new(),
+            output: String::new(),
+        });
+        if !prompt.output.is_empty() {
+            prompt.output.push_str("\n\n");
+        }
+        prompt.output.push_str(&text);
+    }
+    if let Some(prompt) = pending {
+        conversation.messages.push(prompt);
+    }
+
+    conversation
+}
@@ -0,0 +1,272 @@
+#[cfg(feature = "async-openai")]
+mod async_openai_types {
+    use crate::config::{AiConfig, Framework, Question, ReasoningEffort};
+    use crate::error::{AppError, Result};
+    use crate::import::from_openai_messages;
+    use crate::params::GenerationParams;
+    use ::async_openai::types::{
+        ChatCompletionRequestAssistantMessage, ChatCompletionRequestAssistantMessageContent,
+        ChatCompletionRequestMessage, ChatCompletionRequestSystemMessage,
+        ChatCompletionRequestSystemMessageContent, ChatCompletionRequestUserMessage,
+        ChatCompletionRequestUserMessageContent, CreateChatCompletionRequest,
+        ReasoningEffort as OpenAiReasoningEffort, Stop,
+    };
+
+    /// The question as `async-openai` messages: system prompt, history, then the new prompt.
+    impl From<&Question> for Vec<ChatCompletionRequestMessage> {
+        fn from(question: &Question) -> Self {
+            let mut messages = vec![];
+            if let Some(sys_prompt) = &question.system_prompt {
+                messages.push(ChatCompletionRequestMessage::System(
+                    ChatCompletionRequestSystemMessage {
+                        content: ChatCompletionRequestSystemMessageContent::Text(
+                            sys_prompt.to_string(),
+                        ),
+                        name: None,
+                    },
+                ));
+            }
+            for msg in question.messages.iter().flatten() {
+                if !msg.content.is_empty() {
+                    messages.push(user_message(&msg.content));
+                }
+                if !msg.output.is_empty() {
+                    messages.push(ChatCompletionRequestMessage::Assistant(
+                        ChatCompletionRequestAssistantMessage {
+                            content: Some(ChatCompletionRequestAssistantMessageContent::Text(
+                                msg.output.to_string(),
+                            )),
+                            ..Default::default()
+                        },
+                    ));
+                }
+            }
+            messages.push(user_message(&question.new_prompt));
+            messages
+        }
+    }
+
+    fn user_message(text: &str) -> ChatCompletionRequestMessage {
+        ChatCompletionRequestMessage::User(ChatCompletionRequestUserMessage {
+            content: ChatCompletionRequestUserMessageContent::Text(text.to_string()),
+            name: None,
+        })
+    }
+
+    /// Reads the messages of an `async-openai` request; the last one must be from the user.
+    impl TryFrom<&CreateChatCompletionRequest> for Question {
+        type Error = AppError;
+
+        fn try_from(request: &CreateChatCompletionRequest) -> Result<Self> {
+            let json = serde_json::to_string(&request.messages)
+                .map_err(|e| AppError::UnexpectedError(format!("Invalid messages: {}", e)))?;
+            from_openai_messages(&json)
+        }
+    }
+
+    /// An OpenAI configuration with the model, answer limit, seed, reasoning effort and
+    /// sampling parameters of an `async-openai` request.
+    impl From<&CreateChatCompletionRequest> for AiConfig {
+        fn from(request: &CreateChatCompletionRequest) -> Self {
+            let stop = match &request.stop {
+                Some(Stop::String(stop)) => vec![stop.to_string()],
+                Some(Stop::StringArray(stop)) => stop.clone(),
+                None => vec![],
+            };
+            let params = GenerationParams {
+                temperature: request.temperature.map(f64::from),
+                top_p: request.top_p.map(f64::from),
+                top_k: None,
+                stop,
+                presence_penalty: request.presence_penalty.map(f64::from),
+                frequency_penalty: request.frequency_penalty.map(f64::from),
+            };
+            #[allow(deprecated)]
+            let max_token = request.max_completion_tokens.or(request.max_tokens);
+
+            let reasoning_effort = request
+                .reasoning_effort
+                .as_ref()
+                .map(|effort| match effort {
+                    OpenAiReasoningEffort::Minimal => ReasoningEffort::Minimal,
+                    OpenAiReasoningEffort::Low => ReasoningEffort::Low,
+                    OpenAiReasoningEffort::Medium => ReasoningEffort::Medium,
+                    OpenAiReasoningEffort::High => ReasoningEffort::High,
+                });
+
+            AiConfig {
+                llm: Framework::OpenAI,
+                model: request.model.to_string(),
+                max_token,
+                seed: request.seed.map(|seed| seed as i32),
+                reasoning_effort,
+                params: (params != GenerationParams::default()).then_some(params),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Builds the `async-openai` request for `question`, with the model, answer limit, seed,
+    /// reasoning effort and sampling parameters of `ai_config`.
+    ///
+    /// Only text is converted, here and in the `From`/`TryFrom` impls between `Question`,
+    /// `AiConfig` and the `async-openai` and `genai` request types.
+    ///
+    /// Parameters are passed as configured, without `GenerationParams::translate`; the
+    /// system prompt and empty prompt defaults of `AiConfig::prompts` are not applied.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// use ask_ai::interop::to_async_openai_request;
+    ///
+    /// let request = to_async_openai_request(&ai_config, &question);
+    /// let response = async_openai::Client::new().chat().create(request).await?;
+    /// ```
+    pub fn to_async_openai_request(
+        ai_config: &AiConfig,
+        question: &Question,
+    ) -> CreateChatCompletionRequest {
+        let params = ai_config.params.clone().unwrap_or_default();
+        CreateChatCompletionRequest {
+            model: ai_config.model.to_string(),
+            messages: question.into(),
+            max_completion_tokens: ai_config.max_token,
+            seed: ai_config.seed.map(i64::from),
+            reasoning_effort: ai_config.reasoning_effort.map(|effort| match effort {
+                ReasoningEffort::Minimal => OpenAiReasoningEffort::Minimal,
+                ReasoningEffort::Low => OpenAiReasoningEffort::Low,
+                ReasoningEffort::Medium => OpenAiReasoningEffort::Medium,
+                ReasoningEffort::High => OpenAiReasoningEffort::High,
+            }),
+            temperature: params.temperature.map(|value| value as f32),
+            top_p: params.top_p.map(|value| value as f32),
+            presence_penalty: params.presence_penalty.map(|value| value as f32),
+            frequency_penalty: params.frequency_penalty.map(|value| value as f32),
+            stop: (!params.stop.is_empty()).then_some(Stop::StringArray(params.stop)),
+            ..Default::default()
+        }
+    }
+
+    /// The inverse of `to_async_openai_request`, for sending an `async-openai` request through
+    /// `ask_question`.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// use ask_ai::interop::from_async_openai_request;
+    ///
+    /// let (ai_config, question) = from_async_openai_request(&request)?;
+    /// let answer = ask_question(&ai_config, question).await?;
+    /// ```
+    pub fn from_async_openai_request(
+        request: &CreateChatCompletionRequest,
+    ) -> Result<(AiConfig, Question)> {
+        Ok((request.into(), request.try_into()?))
+    }
+}
+
+#[cfg(feature = "async-openai")]
+pub use async_openai_types::{from_async_openai_request, to_async_openai_request};
+
+#[cfg(feature = "genai")]
+mod genai_types {
+    use crate::config::{AiConfig, AiPrompt, Question};
+    use crate::error::{AppError, Result};
+    use ::genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatRole};
+
+    /// The question as a `genai` request: system prompt, history, then the new prompt.
+    impl From<&Question> for ChatRequest {
+        fn from(question: &Question) -> Self {
+            let mut messages = vec![];
+            for msg in question.messages.iter().flatten() {
+                if !msg.content.is_empty() {
+                    messages.push(ChatMessage::user(msg.content.as_str()));
+                }
+                if !msg.output.is_empty() {
+                    messages.push(ChatMessage::assistant(msg.output.as_str()));
+                }
+            }
+            messages.push(ChatMessage::user(question.new_prompt.as_str()));
+
+            let request = ChatRequest::new(messages);
+            match &question.system_prompt {
+                Some(sys_prompt) => request.with_system(sys_prompt.as_str()),
+                None => request,
+            }
+        }
+    }
+
+    /// Reads a `genai` request; its last message must be from the user.
+    impl TryFrom<&ChatRequest> for Question {
+        type Error = AppError;
+
+        fn try_from(request: &ChatRequest) -> Result<Self> {
+            let mut system_prompts: Vec<String> = request.system.iter().cloned().collect();
+            let mut exchanges: Vec<AiPrompt> = vec![];
+            let mut new_prompt: Option<String> = None;
+
+            for message in &request.messages {
+                let text = message.content.joined_texts().unwrap_or_default();
+                if text.is_empty() {
+                    continue;
+                }
+                match message.role {
+                    ChatRole::System => system_prompts.push(text),
+                    ChatRole::User => {
+                        if let Some(content) = new_prompt.replace(text) {
+                            exchanges.push(AiPrompt {
+                                content,
+                                output: String::new(),
+                            });
+                        }
+                    }
+                    ChatRole::Assistant => {
+                        let content = new_prompt.take().unwrap_or_default();
+                        match exchanges.last_mut() {
+                            // Consecutive assistant messages make one output
+                            Some(last) if content.is_empty() && !last.output.is_empty() => {
+                                last.output.push_str("\n\n");
+                                last.output.push_str(&text);
+                            }
+                            _ => exchanges.push(AiPrompt {
+                                content,
+                                output: text,
+                            }),
+                        }
+                    }
+                    ChatRole::Tool => {}
+                }
+            }
+
+            let new_prompt = new_prompt.ok_or_else(|| {
+                AppError::UnexpectedError(
+                    "Invalid genai request: the last message must be from the user".to_string(),
+                )
+            })?;
+            Ok(Question {
+                system_prompt: (!system_prompts.is_empty()).then(|| system_prompts.join("\n\n")),
+                messages: (!exchanges.is_empty()).then_some(exchanges),
+                new_prompt,
+                images: vec![],
+                audio: vec![],
+            })
+        }
+    }
+
+    /// The answer limit, seed and sampling parameters of `ai_config` as `genai` options.
+    /// `genai` has no penalties or `top_k`, so these are left out.
+    impl From<&AiConfig> for ChatOptions {
+        fn from(ai_config: &AiConfig) -> Self {
+            let params = ai_config.params.clone().unwrap_or_default();
+            ChatOptions {
+                temperature: params.temperature,
+                max_tokens: ai_config.max_token,
+                top_p: params.top_p,
+                stop_sequences: params.stop,
+                seed: ai_config.seed.map(|seed| seed as u64),
+                ..Default::default()
+            }
+        }
+    }
+}
@@ -38,10 +38,82 @@
 //!
 //! ## Features
 //!
-//! - Support for multiple Framework providers: OpenAI, Anthropic, and Ollama.
+//! - Support for multiple Framework providers: OpenAI, Anthropic, Ollama, LM Studio, AWS Bedrock, Mistral, Groq, OpenRouter, Hugging Face and Replicate.
 //! - Unified interface to interact with different APIs.
 //! - Ease of adding system-level prompts to guide responses.
 //! - Support for maintaining chat history (multi-turn conversations).
+//! - Image inputs (`Question::images`, URLs or base64 data, `transcript::ImageSource::from_bytes`), sent as OpenAI `image_url` parts, Anthropic image blocks and Ollama `images`, so local vision models like llava and llama3.2-vision can be queried too.
+//! - Audio inputs (`Question::audio`, `config::AudioInput::from_bytes`) for audio models such as `gpt-4o-audio-preview`, sent as OpenAI `input_audio` parts.
+//! - Embeddings (`embeddings::get_embedding`, `embeddings::get_embeddings`) from OpenAI, Mistral, LM Studio, Ollama and Cohere, for building search next to chat.
+//! - An in-memory vector store (`vectorstore::VectorStore`) with cosine-similarity top-k search over embeddings, to prototype retrieval without an external database.
+//! - Retrieval-augmented generation (`rag::Rag`): documents are chunked (configurable size and overlap), embedded and stored, and the top-k chunks for each question are added to its system prompt before it is asked.
+//! - Reranking (`rerank::rerank`) with Cohere Rerank, or any chat model (e.g. a small local one on Ollama) scoring each document against the query; `Rag::reranker` reorders retrieved chunks with it.
+//! - Image generation (`images::generate_image`) with OpenAI's gpt-image and DALL·E models, returning image bytes or URLs.
+//! - Audio transcription (`transcription::transcribe_audio`) with OpenAI's and Groq's Whisper endpoints, from a file or bytes, as text, JSON or subtitles.
+//! - OpenAI Realtime sessions over WebSocket (`realtime::connect`, feature `realtime`): audio and text streamed in, spoken and written answers streamed back as typed events.
+//! - Moderation (`moderation::moderate`) with OpenAI's moderation endpoint, and `AiConfig::moderate_prompts` to screen every prompt before it is sent.
+//! - Provider-side safety settings (`AiConfig::safety`, `safety::SafetySettings`): Bedrock guardrails applied to the prompt and the answer, with the guardrail's verdict and trace returned by `ask_question_raw`.
+//! - OpenAI Assistants and threads (`assistants::create_assistant`, `create_thread`, `ask_thread`): conversations stored server-side, with messages added and runs polled to completion.
+//! - OpenAI's Batch API (`batch::submit_batch`, `wait_for_batch`, `batch_results`): many questions answered within 24 hours at half price, for large offline workloads. `watch_batch` reports progress and re-attaches to a batch by id after a restart, and `unanswered` picks out the questions a cancelled or expired batch left to resubmit.
+//! - Token counting for Claude models (`anthropic::count_tokens`) with Anthropic's `count_tokens` endpoint, to check a conversation against the context window before sending it.
+//! - Anthropic citations (`anthropic::ask_with_citations`): answers from attached documents, with the passages each part of the answer cites.
+//! - Anthropic's server-side web search (`AnthropicOptions::web_search`): Claude searches the web while answering, and `anthropic::ask_with_citations` returns the pages found and cited.
+//! - Anthropic beta tools such as computer use (`AnthropicOptions::tools`), with replies parsed block by block, thinking and tool calls included (`anthropic::ask_with_blocks`).
+//! - Streaming answers (`ask_question_stream`), with a helper to pipe them into any `AsyncWrite`.
+//! - Tool calling (`tools::run_with_tools`) with async Rust closures and optional per-call approval, over both the OpenAI and the Anthropic tool protocols.
+//! - MCP servers as tools (`mcp::McpClient`, feature `mcp`): `ToolRegistry::with_mcp` registers every tool a Model Context Protocol server offers, e.g. filesystem or database access, without glue code.
+//! - Privacy mode (`privacy::PrivacyConfig`): sensitive values are pseudonymized before they leave the machine and restored in the answer.
+//! - Local-only mode (`AiConfig::local_only`) for air-gapped deployments: questions never go to remote providers.
+//! - Deterministic record/replay of whole conversations for CI (`replay::Replay`), plus `AiConfig::seed`.
+//! - Snapshot-test friendly responses: `ask_ai::ask_question_raw` plus `normalize::Normalizer`.
+//! - HMAC request signing (`signing::RequestSigning`) for authenticated internal gateways.
+//! - Configurable User-Agent and application headers (`config::ClientMetadata`), including OpenRouter's app headers and per-user ids.
+//! - Shared, tunable connection pools (`http::HttpOptions`): pool size, idle timeout, HTTP/2, TCP keep-alive, Unix domain sockets.
+//! - Payload size guard (`limits::PayloadLimits`): fail fast or truncate history over a byte or token budget.
+//! - Prompt compression (`compress::Compression`): dedupe, drop filler and optionally rewrite long context with a small model, towards a token budget.
+//! - Language detection (`locale::detect_language`) and locale-aware defaults: answer in the prompt's language, per-language system prompts.
+//! - Markdown extraction (`markdown`): fenced code blocks with language tags, lists and tables as typed values.
+//! - Code edits as patches (`patch::ask_for_patch`): asks for a unified diff and parses, validates and applies it.
+//! - Configurable Anthropic API version and `anthropic-beta` feature flags (`config::AnthropicOptions`).
+//! - Provider-independent sampling parameters (`params::GenerationParams`), translated per provider; unsupported ones are dropped with a warning.
+//! - OpenAI reasoning models (o-series, gpt-5): `max_token` sent as `max_completion_tokens`, `AiConfig::reasoning_effort`, and sampling parameters they reject left out.
+//! - Capability queries (`capabilities::capabilities`): tools, vision, streaming, JSON Schema and context size per model.
+//! - Graceful degradation (`capabilities::UnsupportedPolicy`): error, strip the unsupported part, or reroute to a capable fallback model.
+//! - Cost reports (`cost::UsageLedger`): priced usage records, totals per model, day and tag, exported as CSV or JSON.
+//! - Shared tenant quotas across replicas (`quota::QuotaStore`, Redis-backed with the `redis` feature).
+//! - Answer caching in memory, on disk or in Redis (`cache::Cache`), behind a pluggable `cache::ResponseCache` trait, with configurable keys, TTLs and stale-while-revalidate.
+//! - Fault and latency injection (`faults::FaultInjection`) for chaos-testing retry and fallback logic.
+//! - Configurable default system prompt and empty-prompt handling (`config::PromptDefaults`).
+//! - Pre-flight question validation (`Question::validate`) with structured `validation::ValidationError`s.
+//! - OpenAI `messages` JSON in and out (`import::from_openai_messages`, `export::to_openai_messages`), so transcripts from other tools and datasets can be asked directly.
+//! - Conversions to and from `async-openai` and `genai` request types (`interop`, features `async-openai` and `genai`), for migrating projects.
+//! - A `tower::Service<Question>` (`service::AskService`, feature `tower`), so tower timeout, retry, rate-limit and load-shed layers wrap LLM calls like the rest of a service stack.
+//! - Server-sent events for axum chat endpoints (`sse::sse_response`, feature `axum`), with event framing and keep-alives.
+//! - Python bindings (`bindings/python`, built with maturin): `ask_question`, streaming and conversations from Python, sharing one configured client.
+//! - Swift and Kotlin bindings (`bindings/uniffi`, generated with uniffi) for mobile apps, with cost tracking built in.
+//! - Node.js bindings (`bindings/node`, built with napi-rs): ask, stream and conversations from JavaScript and TypeScript, for Electron apps and Node backends.
+//! - Hedged requests (`hedge::Hedge`): a second request after a latency threshold, to another provider or the same one; the first answer wins.
+//! - Per-call deadlines (`AiConfig::deadline_ms`): one time budget covering fallbacks, hedges, tool rounds and the whole streamed answer, failing with `AppError::DeadlineExceeded`.
+//! - Context-length recovery (`overflow::ContextOverflow`): questions rejected for not fitting the context window are retried on a larger-context model or without their oldest exchanges, and `ask_question_raw` reports which.
+//! - Auto-continue (`continuation::AutoContinue`): answers cut off by the token limit get follow-up requests, up to a cap, and come back as one text.
+//! - Resumable streams (`stream::StreamResume`): when the connection drops mid-answer, the streamed part is sent back for a continuation and the stream carries on instead of restarting.
+//! - A persistent outbox (`outbox::Outbox`, feature `sqlite`): questions are stored before they are sent, so pending jobs survive crashes and resume on restart.
+//! - Anthropic prefill (`AnthropicOptions::prefill`): start Claude's reply with given text, e.g. `{` to force JSON; the answer includes it.
+//! - Grammar-constrained decoding for local models (`grammar::OutputConstraint`): GBNF grammars, regexes or JSON Schemas enforced by Ollama, llama.cpp and vLLM servers.
+//! - Raw completions and fill-in-the-middle on Ollama (`ollama::generate`, `ollama::fill_in_middle`) through `/api/generate`, without chat templating, for base models and code completion.
+//! - Ollama model management (`ollama::list_models`, `show_model`, `pull_model` with progress reports, `delete_model`), and `ollama::ensure_model` to pull the configured model before the first question.
+//! - Ollama model options (`AiConfig::ollama`): context size, `num_predict`, sampling, mirostat, repeat penalty, GPU layers, threads, keep-alive, and any other option by name.
+//! - JSON output mode (`AiConfig::response_format`): any JSON object or one following a schema, through OpenAI's `response_format` and Ollama's `format`, and through the system prompt for Anthropic.
+//! - JSON Schema validation of answers (`schema::ask_validated`), re-prompting with the validation errors until an answer matches or the attempts run out.
+//! - Incremental JSON streaming (`partial::partial_json`, `partial::json_items`): partial values and completed list items while a structured answer is still generated.
+//! - A dataset runner (`dataset::DatasetRunner`): CSV or JSONL rows rendered through a prompt template, asked with bounded concurrency and retries, with answers and token usage written to a results file.
+//! - Parameter sweeps (`sweep::Sweep`): the same questions over a grid of models, temperatures and system prompts, with caller-scored answers averaged per combination.
+//! - Provider-independent transcripts (`transcript::Transcript`): OpenAI, Anthropic and Ollama requests and responses read into one form (roles, text, images, tool calls and results, usage, timestamps) and written back out in any of the three.
+//! - Custom providers (`provider::Provider`, `provider::register_provider`): plug in your own backend under a `Framework::Custom` name, with caching, privacy mode, hedging and the other options applied as for the built-in clients; `provider::OpenAICompatibleProvider` reaches any OpenAI-compatible server.
+//! - A provider conformance suite (`conformance::check_provider`, feature `conformance`): checks any `Provider` against a scripted backend for history mapping, system prompts, truncation, error types and streaming, so new backends can prove they behave like the built-in ones.
+//! - OpenAI-compatible servers (`AiConfig::base_url`): LM Studio, vLLM, llama.cpp server or LocalAI as `Framework::OpenAI` at their own address, without overriding `OPENAI_API_URL` or sending the OpenAI key.
+//! - vLLM extensions (`config::VllmOptions`): guided decoding to a JSON Schema, regex or list of choices, and `best_of` sampling, for vLLM servers behind `Framework::OpenAI`.
+//! - Multi-tenant keys, limits and quotas (`tenant::TenantRegistry`), with an append-only audit log (`audit::AuditLog`).
 //! - Error handling for API failures, model errors, and unexpected behavior.
 //!
 //! ---
@@ -50,7 +122,7 @@
 //!
 //! Before you can use the crate, you need to configure it through the `AiConfig` structure. This configuration tells the system:
 //!
-//! 1. Which Framework provider to use (`Framework::OpenAI`, `Framework::Anthropic`, or `Framework::Ollama`).
+//! 1. Which Framework provider to use (`Framework::OpenAI`, `Framework::Anthropic`, `Framework::Ollama`, `Framework::LmStudio`, `Framework::Bedrock`, `Framework::Mistral`, `Framework::Groq`, `Framework::OpenRouter`, `Framework::HuggingFace` or `Framework::Replicate`), also parsed from a name with `"anthropic".parse()`.
 //! 2. The specific model you want to query, e.g., `"chatgpt-4o-latest"` for OpenAI or `"claude-2"` for Anthropic.
 //! 3. (Optional) Maximum tokens for the response output.
 //!
@@ -63,9 +135,18 @@
 //!     llm: Framework::OpenAI,           // Specify Framework provider
 //!     model: "chatgpt-4o-latest".to_string(), // Specify model
 //!     max_token: Some(1000),      // Optional: Limit max tokens in response
+//!     ..Default::default()        // Optional: explicit `api_key`, ...
 //! };
 //! ```
 //!
+//! Or start from a provider's default model and token limit:
+//!
+//! ```rust
+//! use ask_ai::config::{AiConfig, Framework};
+//!
+//! let ai_config = AiConfig::default_for(Framework::Anthropic);
+//! ```
+//!
 //! ---
 //!
 //! ## Usage
@@ -83,12 +164,15 @@
 //!         llm: Framework::OpenAI,
 //!         model: "chatgpt-4o-latest".to_string(),
 //!         max_token: Some(1000),
+//!         ..Default::default()
 //!     };
 //!
 //!     let question = Question {
 //!         system_prompt: None,      // Optional system prompt
 //!         messages: None,           // No previous history
 //!         new_prompt: "What is Rust?".to_string(),
+//!         images: vec![],
+//!         audio: vec![],
 //!     };
 //!
 //!     match ask_question(&ai_config, question).await {
@@ -108,6 +192,8 @@
 //!     system_prompt: Some("You are an expert Rust programmer. Answer concisely.".to_string()), // Custom prompt
 //!     messages: None,
 //!     new_prompt: "How do closures work in Rust?".to_string(),
+//!     images: vec![],
+//!     audio: vec![],
 //! };
 //! ```
 //!
@@ -133,6 +219,8 @@
 //!     system_prompt: None,
 //!     messages: Some(previous_messages), // Include chat history
 //!     new_prompt: "What are Rust's main drawbacks?".to_string(),
+//!     images: vec![],
+//!     audio: vec![],
 //! };
 //! ```
 //!
@@ -142,11 +230,35 @@
 //!
 //! This crate requires API keys to interface with the Framework providers. Store these keys as environment variables to keep them secure. Below is a list of required variables:
 //!
-//! | Provider   | Environment Variable      |
-//! |------------|---------------------------|
-//! | OpenAI     | `OPENAI_API_KEY`          |
-//! | Anthropic  | `ANTHROPIC_API_KEY`       |
-//! | Ollama     | No key required currently |
+//! | Provider     | Environment Variable      |
+//! |--------------|---------------------------|
+//! | OpenAI       | `OPENAI_API_KEY`          |
+//! | Anthropic    | `ANTHROPIC_API_KEY`       |
+//! | Ollama       | No key required currently |
+//! | LM Studio    | No key required currently |
+//! | Mistral      | `MISTRAL_API_KEY`         |
+//! | Groq         | `GROQ_API_KEY`            |
+//! | OpenRouter   | `OPENROUTER_API_KEY`      |
+//! | Hugging Face | `HF_TOKEN`                |
+//! | Replicate    | `REPLICATE_API_TOKEN`     |
+//! | Bedrock      | `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` (and `AWS_SESSION_TOKEN`), or a Bedrock API key in `AWS_BEARER_TOKEN_BEDROCK` |
+//! | Cohere       | `COHERE_API_KEY`          |
+//!
+//! The Ollama client targets `http://127.0.0.1:11434` by default; set `OLLAMA_API_URL`, or `AiConfig::base_url` per config, to reach another host.
+//!
+//! The LM Studio client targets LM Studio's server on `http://localhost:1234/v1` (or `LMSTUDIO_API_URL`); with an empty model it asks the server which models are loaded (`lmstudio::loaded_models`) and uses the first.
+//!
+//! The OpenRouter client sends `ClientMetadata::app_url` and `app_title` as the `HTTP-Referer` and `X-Title` headers OpenRouter attributes usage by, defaulting to this crate.
+//!
+//! The Hugging Face client targets the serverless Inference API's chat route; set `HUGGINGFACE_API_URL` to a dedicated Inference Endpoint's `/v1/chat/completions` to query it instead.
+//!
+//! The Replicate client creates a prediction in sync mode and polls it until it finishes; earlier exchanges are written into the model's single `prompt` input. Set `REPLICATE_API_URL` to reach another API root.
+//!
+//! The Bedrock client calls the Converse API in `AWS_REGION` (or `AWS_DEFAULT_REGION`, else `us-east-1`), signing requests with SigV4; set `BEDROCK_API_URL` to reach another endpoint.
+//!
+//! Embeddings (`embeddings::get_embedding`, `get_embeddings`) use the OpenAI, Mistral and LM Studio `embeddings` endpoints, Ollama's `/api/embed` (also as `ollama::embed`, which never leaves the local network, for offline pipelines), and Cohere's v2 `embed` endpoint through `Framework::Custom("cohere")`; set `COHERE_API_URL` to reach another host.
+//!
+//! Reranking (`rerank::rerank`) uses Cohere's v2 `rerank` endpoint, next to the `embed` one, through `Framework::Custom("cohere")`. Other providers rerank with their chat model, which scores each document from 0 to 10.
 //!
 //! For security, avoid hardcoding API keys into your application code. Use a `.env` file or a secret storage mechanism.
 //!
@@ -154,11 +266,21 @@
 //!
 //! ## Error Handling
 //!
-//! All interactions with Framework return `Result<String>`. Errors are encapsulated using the `AppError` enum, which defines three main error types:
+//! All interactions with Framework return `Result<String>`. Errors are encapsulated using the `AppError` enum, which defines eleven main error types:
 //!
 //! 1. **ModelError**: Occurs when querying a specific model fails.
 //! 2. **ApiError**: Indicates an issue with the API key or API call.
 //! 3. **UnexpectedError**: For any other unforeseen issues.
+//! 4. **QuotaExceeded**: A tenant ran out of requests or tokens for the current window; carries the reset time.
+//! 5. **RemoteEndpointBlocked**: `AiConfig::local_only` is set and the question would have left the machine or local network.
+//! 6. **PayloadTooLarge**: the question exceeds `AiConfig::limits` (body bytes or estimated prompt tokens) and was not sent.
+//! 7. **UnsupportedCapability**: the request needs a capability the model lacks (e.g. tools) and `AiConfig::on_unsupported` is `UnsupportedPolicy::Error`.
+//! 8. **EmptyPrompt**: the new prompt was empty and `AiConfig::prompts` rejects empty prompts instead of substituting them.
+//! 9. **DeadlineExceeded**: the call, including its fallbacks, hedges, tool rounds and streamed answer, took longer than `AiConfig::deadline_ms`.
+//! 10. **SchemaMismatch**: no answer to `schema::ask_validated` matched the JSON Schema within the allowed attempts; carries the last validation errors.
+//! 11. **ContentFlagged**: `AiConfig::moderate_prompts` is set and the moderation endpoint flagged the prompt, which was not sent; carries the flagged categories.
+//!
+//! API keys and bearer tokens are scrubbed from `failure_str` before an error is returned.
 //!
 //! ### Example: Handling Errors Gracefully
 //!
@@ -175,6 +297,24 @@
 //!         AppError::UnexpectedError(msg) => {
 //!             eprintln!("Unexpected Error: {}", msg);
 //!         },
+//!         AppError::QuotaExceeded { tenant, reset_at } => {
+//!             eprintln!("Quota exceeded for {} until {:?}", tenant, reset_at);
+//!         },
+//!         AppError::RemoteEndpointBlocked { endpoint, .. } => {
+//!             eprintln!("Blocked remote endpoint: {}", endpoint);
+//!         },
+//!         AppError::PayloadTooLarge { measure, size, limit } => {
+//!             eprintln!("Too large: {} {} (limit {})", size, measure, limit);
+//!         },
+//!         AppError::UnsupportedCapability { model_name, capability } => {
+//!             eprintln!("{} does not support {}", model_name, capability);
+//!         },
+//!         AppError::EmptyPrompt { model_name } => {
+//!             eprintln!("Empty prompt for {}", model_name);
+//!         },
+//!         AppError::DeadlineExceeded { deadline_ms, .. } => {
+//!             eprintln!("No answer within {}ms", deadline_ms);
+//!         },
 //!     },
 //! }
 //! ```
@@ -193,8 +333,74 @@
 //! 4. Push changes and open a pull request.
 //!
 
+pub mod anthropic;
 pub mod ask_ai;
+pub mod assistants;
+pub mod audit;
+pub mod batch;
+pub mod bedrock;
+pub mod cache;
+pub mod capabilities;
+pub mod compress;
 pub mod config;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+pub mod continuation;
+pub mod conversation;
+pub mod cost;
+pub mod dataset;
+mod deadline;
+pub mod embeddings;
 pub mod error;
+pub mod export;
+pub mod faults;
+pub mod grammar;
+pub mod hedge;
+pub mod http;
+pub mod images;
+pub mod import;
+pub mod interop;
+pub mod limits;
+pub mod lmstudio;
+pub mod locale;
+pub mod markdown;
+#[cfg(feature = "mcp")]
+pub mod mcp;
+pub mod moderation;
+pub mod normalize;
+pub mod ollama;
+#[cfg(feature = "sqlite")]
+pub mod outbox;
+pub mod overflow;
+pub mod params;
+pub mod partial;
+pub mod patch;
+pub mod privacy;
+pub mod provider;
+pub mod quota;
+pub mod rag;
+#[cfg(feature = "realtime")]
+pub mod realtime;
+pub mod replay;
+pub mod replicate;
+pub mod rerank;
+pub mod safety;
+pub mod schema;
+pub mod secret;
+#[cfg(feature = "tower")]
+pub mod service;
+pub mod signing;
+#[cfg(feature = "axum")]
+pub mod sse;
+pub mod store;
+pub mod stream;
+pub mod sweep;
+pub mod tenant;
+pub mod tools;
+pub mod transcript;
+pub mod transcription;
+pub mod validation;
+pub mod vectorstore;
 
 pub use ask_ai::ask_question;
+pub use stream::ask_question_stream;
@@ -0,0 +1,106 @@
+use crate::ask_ai::build_payload;
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use crate::tenant::prompt_tokens;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What to do with a question over a payload limit.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitPolicy {
+    /// Fail with `AppError::PayloadTooLarge` before anything is sent.
+    #[default]
+    Fail,
+    /// Drop the oldest history messages until the question fits, and fail only if it does not
+    /// fit without any history.
+    Truncate,
+}
+
+/// The measure a payload limit applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadMeasure {
+    /// Bytes of the serialized request body.
+    Bytes,
+    /// Estimated prompt tokens (about four characters per token).
+    Tokens,
+}
+
+impl fmt::Display for PayloadMeasure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadMeasure::Bytes => write!(f, "bytes"),
+            PayloadMeasure::Tokens => write!(f, "tokens"),
+        }
+    }
+}
+
+/// Upper bounds on what a single request may send, checked before the request is made.
+///
+/// Oversized questions fail fast with `AppError::PayloadTooLarge` (or are truncated, see
+/// `LimitPolicy`) instead of a slow round trip that ends in a provider 400.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::limits::{LimitPolicy, PayloadLimits};
+///
+/// let ai_config = AiConfig {
+///     limits: Some(PayloadLimits {
+///         max_payload_bytes: Some(512 * 1024),
+///         max_prompt_tokens: Some(100_000),
+///         on_exceed: LimitPolicy::Truncate,
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PayloadLimits {
+    /// Maximum size of the serialized request body, in bytes.
+    #[serde(default)]
+    pub max_payload_bytes: Option<usize>,
+    /// Maximum estimated tokens of the system prompt, history and new prompt together.
+    #[serde(default)]
+    pub max_prompt_tokens: Option<u64>,
+    #[serde(default)]
+    pub on_exceed: LimitPolicy,
+}
+
+impl PayloadLimits {
+    /// Returns `question` if it fits, truncating its history when the policy allows.
+    pub(crate) fn enforce(&self, ai_config: &AiConfig, mut question: Question) -> Result<Question> {
+        loop {
+            let Some(error) = self.check(ai_config, &question) else {
+                return Ok(question);
+            };
+            match (self.on_exceed, question.messages.as_mut()) {
+                (LimitPolicy::Truncate, Some(messages)) if !messages.is_empty() => {
+                    messages.remove(0);
+                }
+                _ => return Err(error),
+            }
+        }
+    }
+
+    /// Returns the error for the first limit `question` exceeds, if any.
+    pub(crate) fn check(&self, ai_config: &AiConfig, question: &Question) -> Option<AppError> {
+        let exceeded = |measure, size: u64, limit: u64| {
+            (size > limit).then_some(AppError::PayloadTooLarge {
+                measure,
+                size,
+                limit,
+            })
+        };
+
+        let tokens = self
+            .max_prompt_tokens
+            .and_then(|limit| exceeded(PayloadMeasure::Tokens, prompt_tokens(question), limit));
+        tokens.or_else(|| {
+            let limit = self.max_payload_bytes?;
+            // A payload of strings and numbers always serializes
+            let bytes = serde_json::to_vec(&build_payload(question, ai_config))
+                .map_or(0, |body| body.len());
+            exceeded(PayloadMeasure::Bytes, bytes as u64, limit as u64)
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use crate::ask_ai::{ensure_local, send_request};
+use crate::config::{AiConfig, Framework};
+use crate::error::{AppError, Result};
+use crate::http::http_client;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::env;
+
+/// LM Studio's local server on its default port.
+const DEFAULT_API_URL: &str = "http://localhost:1234/v1";
+
+/// The root of LM Studio's OpenAI-compatible API: `AiConfig::base_url`, else the
+/// `LMSTUDIO_API_URL` environment variable, else `http://localhost:1234/v1`.
+pub(crate) fn lmstudio_url(ai_config: &AiConfig) -> String {
+    let url = match &ai_config.base_url {
+        Some(base_url) => base_url.clone(),
+        None => env::var("LMSTUDIO_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string()),
+    };
+    url.trim_end_matches('/').to_string()
+}
+
+/// Probes the LM Studio server and lists the models it serves: the loaded ones, or every
+/// downloaded one when just-in-time loading is on.
+///
+/// Fails with `AppError::ApiError` when nothing answers on the port, so it doubles as a check
+/// that LM Studio is running.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::lmstudio::loaded_models;
+///
+/// let ai_config = AiConfig::default_for(Framework::LmStudio);
+/// if let Ok(models) = loaded_models(&ai_config).await {
+///     println!("LM Studio is serving {:?}", models);
+/// }
+/// ```
+pub async fn loaded_models(ai_config: &AiConfig) -> Result<Vec<String>> {
+    let api_url = format!("{}/models", lmstudio_url(ai_config));
+    ensure_local(ai_config, &api_url)?;
+
+    let resp = send_request(http_client(ai_config)?.get(&api_url), ai_config).await?;
+    let response: Value = resp.json().await.map_err(|e| AppError::ApiError {
+        model_name: Framework::LmStudio.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    Ok(response["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| model["id"].as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+/// `ai_config`, with the first model LM Studio serves when none is configured.
+pub(crate) async fn with_loaded_model(ai_config: &AiConfig) -> Result<Cow<'_, AiConfig>> {
+    if !ai_config.model.is_empty() {
+        return Ok(Cow::Borrowed(ai_config));
+    }
+
+    let model = loaded_models(ai_config)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::ModelError {
+            model_name: Framework::LmStudio.to_string(),
+            failure_str: "LM Studio has no model loaded".to_string(),
+        })?;
+    Ok(Cow::Owned(AiConfig {
+        model,
+        ..ai_config.clone()
+    }))
+}
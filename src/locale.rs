@@ -0,0 +1,77 @@
+use crate::config::Question;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A language detected in a prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    /// ISO 639-3 code, e.g. `spa`.
+    pub code: &'static str,
+    /// English name, e.g. `Spanish`.
+    pub name: &'static str,
+}
+
+/// Detects the language of `text`, or `None` when the text is too short or ambiguous to tell.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::locale::detect_language;
+///
+/// let language = detect_language("¿Cuál es la capital de Francia y cuántos habitantes tiene?");
+/// assert_eq!(language.map(|l| l.code), Some("spa"));
+/// ```
+pub fn detect_language(text: &str) -> Option<Language> {
+    let info = whatlang::detect(text).filter(|info| info.is_reliable())?;
+    Some(Language {
+        code: info.lang().code(),
+        name: info.lang().eng_name(),
+    })
+}
+
+/// Language-aware defaults, applied from the language detected in the new prompt.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::locale::LocaleConfig;
+///
+/// let ai_config = AiConfig {
+///     locale: Some(LocaleConfig {
+///         match_language: true,
+///         system_prompts: [("deu".to_string(), "Du bist ein hilfreicher Assistent.".to_string())]
+///             .into(),
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct LocaleConfig {
+    /// Append an instruction to respond in the language of the prompt.
+    #[serde(default)]
+    pub match_language: bool,
+    /// System prompts by ISO 639-3 language code, used when the question has none.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub system_prompts: BTreeMap<String, String>,
+}
+
+impl LocaleConfig {
+    /// Returns `question` with the defaults for its detected language.
+    pub(crate) fn apply(&self, mut question: Question) -> Question {
+        let Some(language) = detect_language(&question.new_prompt) else {
+            return question;
+        };
+
+        if question.system_prompt.is_none() {
+            question.system_prompt = self.system_prompts.get(language.code).cloned();
+        }
+        if self.match_language {
+            let instruction = format!("Respond in {}.", language.name);
+            question.system_prompt = Some(match question.system_prompt {
+                Some(prompt) if !prompt.is_empty() => format!("{}\n\n{}", prompt, instruction),
+                _ => instruction,
+            });
+        }
+        question
+    }
+}
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+
+/// A fenced code block from an answer.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// Language tag of the fence (` ```rust `), if any.
+    pub language: Option<String>,
+    pub code: String,
+}
+
+/// A bulleted or numbered list from an answer. Nested items are flattened into the list.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct List {
+    pub ordered: bool,
+    pub items: Vec<String>,
+}
+
+/// A pipe table from an answer.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    /// Cells by row, padded or cut to the number of headers.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Returns the fenced code blocks (` ``` ` or `~~~`) of `text`, in order.
+///
+/// An unterminated block runs to the end of the text, as in a truncated answer.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::markdown::extract_code_blocks;
+///
+/// let answer = ask_question(&ai_config, question).await?;
+/// for block in extract_code_blocks(&answer) {
+///     if block.language.as_deref() == Some("rust") {
+///         std::fs::write("generated.rs", &block.code)?;
+///     }
+/// }
+/// ```
+pub fn extract_code_blocks(text: &str) -> Vec<CodeBlock> {
+    blocks(text)
+        .into_iter()
+        .filter_map(|block| match block {
+            Block::Code(code) => Some(code),
+            Block::Prose(_) => None,
+        })
+        .collect()
+}
+
+/// Returns the lists of `text`, outside code blocks.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::markdown::extract_lists;
+///
+/// let steps = extract_lists(&answer).into_iter().find(|list| list.ordered);
+/// ```
+pub fn extract_lists(text: &str) -> Vec<List> {
+    let mut lists = Vec::new();
+    for lines in prose(text) {
+        let mut current: Option<List> = None;
+        for line in lines {
+            match (list_item(line), current.as_mut()) {
+                (Some((ordered, item)), Some(list))
+                    if list.ordered == ordered || line.starts_with([' ', '\t']) =>
+                {
+                    list.items.push(item.to_string())
+                }
+                (Some((ordered, item)), _) => {
+                    lists.extend(current.replace(List {
+                        ordered,
+                        items: vec![item.to_string()],
+                    }));
+                }
+                // Blank lines between items keep the list going
+                (None, _) if line.trim().is_empty() => {}
+                (None, Some(list)) if line.starts_with([' ', '\t']) => {
+                    // Continuation of the previous item
+                    if let Some(last) = list.items.last_mut() {
+                        last.push(' ');
+                        last.push_str(line.trim());
+                    }
+                }
+                (None, _) => lists.extend(current.take()),
+            }
+        }
+        lists.extend(current);
+    }
+    lists
+}
+
+/// Returns the pipe tables of `text`, outside code blocks.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::markdown::extract_tables;
+///
+/// for table in extract_tables(&answer) {
+///     println!("{}", table.headers.join(", "));
+/// }
+/// ```
+pub fn extract_tables(text: &str) -> Vec<Table> {
+    let mut tables = Vec::new();
+    for lines in prose(text) {
+        let mut i = 0;
+        while i + 1 < lines.len() {
+            let (Some(headers), true) = (table_row(lines[i]), is_separator(lines[i + 1])) else {
+                i += 1;
+                continue;
+            };
+            let mut table = Table {
+                headers,
+                rows: Vec::new(),
+            };
+            i += 2;
+            while let Some(mut row) = lines.get(i).and_then(|line| table_row(line)) {
+                row.resize(table.headers.len(), String::new());
+                table.rows.push(row);
+                i += 1;
+            }
+            tables.push(table);
+        }
+    }
+    tables
+}
+
+enum Block<'a> {
+    Prose(Vec<&'a str>),
+    Code(CodeBlock),
+}
+
+/// Splits `text` into runs of prose lines and fenced code blocks.
+fn blocks(text: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut prose = Vec::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        let Some((fence, info)) = fence(line) else {
+            prose.push(line);
+            continue;
+        };
+        if !prose.is_empty() {
+            blocks.push(Block::Prose(std::mem::take(&mut prose)));
+        }
+
+        let mut code = Vec::new();
+        for line in lines.by_ref() {
+            let trimmed = line.trim();
+            // A closing fence is at least as long as the opening one, without an info string
+            if trimmed.len() >= fence.len() && trimmed.chars().all(|c| fence.starts_with(c)) {
+                break;
+            }
+            code.push(line);
+        }
+        let language = info.split_whitespace().next().map(str::to_string);
+        blocks.push(Block::Code(CodeBlock {
+            language,
+            code: code.join("\n"),
+        }));
+    }
+    if !prose.is_empty() {
+        blocks.push(Block::Prose(prose));
+    }
+    blocks
+}
+
+fn prose(text: &str) -> impl Iterator<Item = Vec<&str>> {
+    blocks(text).into_iter().filter_map(|block| match block {
+        Block::Prose(lines) => Some(lines),
+        Block::Code(_) => None,
+    })
+}
+
+/// The fence (e.g. ` ``` `) and info string of an opening fence line.
+fn fence(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(marker).len();
+    if len < 3 {
+        return None;
+    }
+    let (fence, info) = trimmed.split_at(len);
+    Some((fence, info.trim()))
+}
+
+/// Whether `line` is a list item, and its text.
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    let trimmed = line.trim_start();
+    if let Some(item) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| trimmed.strip_prefix(bullet))
+    {
+        return Some((false, item.trim()));
+    }
+
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    let rest = &trimmed[digits..];
+    let item = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "));
+    match (digits, item) {
+        (1..=9, Some(item)) => Some((true, item.trim())),
+        _ => None,
+    }
+}
+
+/// The cells of a pipe table row.
+fn table_row(line: &str) -> Option<Vec<String>> {
+    let trimmed = line.trim();
+    if !trimmed.contains('|') {
+        return None;
+    }
+    let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    Some(
+        inner
+            .split('|')
+            .map(|cell| cell.trim().to_string())
+            .collect(),
+    )
+}
+
+/// Whether `line` is a table header separator, e.g. `|---|:--:|`.
+fn is_separator(line: &str) -> bool {
+    table_row(line).is_some_and(|cells| {
+        cells.iter().all(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+        })
+    })
+}
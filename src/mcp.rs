@@ -0,0 +1,313 @@
+use crate::error::{AppError, Result};
+use crate::tools::ToolRegistry;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+
+/// MCP revision sent in `initialize`; servers answer with the one they speak.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// JSON-RPC error code for methods the client does not implement.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+type Reader = BufReader<Box<dyn AsyncRead + Send + Unpin>>;
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// A tool offered by an MCP server.
+#[derive(Debug, Clone)]
+pub struct McpTool {
+    /// Name the server knows the tool by.
+    pub name: String,
+    /// What the tool does, as written by the server.
+    pub description: String,
+    /// JSON Schema describing the tool's arguments.
+    pub input_schema: Value,
+}
+
+/// A connection to a Model Context Protocol server.
+///
+/// Speaks MCP's JSON-RPC over a pair of byte streams, one message per line: usually the
+/// standard input and output of a server started with `McpClient::spawn`. Requests are sent
+/// one at a time; cloning the client shares the connection.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::mcp::McpClient;
+/// use ask_ai::tools::{run_with_tools, ToolRegistry};
+///
+/// let files = McpClient::spawn("npx", &["-y", "@modelcontextprotocol/server-filesystem", "."]).await?;
+/// let registry = ToolRegistry::new().with_mcp(&files).await?;
+/// let answer = run_with_tools(&ai_config, question, &registry).await?;
+/// ```
+#[derive(Clone)]
+pub struct McpClient {
+    inner: Arc<Connection>,
+}
+
+struct Connection {
+    server_name: String,
+    streams: Mutex<(Reader, Writer)>,
+    next_id: AtomicU64,
+    /// The server process, if the client started it; killed when the last clone is dropped.
+    _child: Option<Child>,
+}
+
+impl McpClient {
+    /// Starts `command` with `args` as a stdio MCP server and initializes a session with it.
+    ///
+    /// The server's standard error is left attached to this process's, so its logs stay
+    /// visible.
+    pub async fn spawn(command: &str, args: &[&str]) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| AppError::ApiError {
+                model_name: format!("mcp:{}", command),
+                failure_str: format!("Failed to start MCP server: {}", e),
+            })?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(AppError::UnexpectedError(
+                "MCP server started without piped stdio".to_string(),
+            ));
+        };
+        Self::initialize(Box::new(stdout), Box::new(stdin), Some(child)).await
+    }
+
+    /// Initializes a session over an already open connection: `reader` carries the server's
+    /// messages, `writer` the client's.
+    pub async fn connect<R, W>(reader: R, writer: W) -> Result<Self>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Self::initialize(Box::new(reader), Box::new(writer), None).await
+    }
+
+    async fn initialize(
+        reader: Box<dyn AsyncRead + Send + Unpin>,
+        writer: Writer,
+        child: Option<Child>,
+    ) -> Result<Self> {
+        let mut connection = Connection {
+            server_name: "mcp".to_string(),
+            streams: Mutex::new((BufReader::new(reader), writer)),
+            next_id: AtomicU64::new(1),
+            _child: child,
+        };
+        let result = connection
+            .request(
+                "initialize",
+                serde_json::json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                }),
+            )
+            .await?;
+        if let Some(name) = result["serverInfo"]["name"].as_str() {
+            connection.server_name = name.to_string();
+        }
+        connection
+            .notify("notifications/initialized", serde_json::json!({}))
+            .await?;
+
+        Ok(McpClient {
+            inner: Arc::new(connection),
+        })
+    }
+
+    /// The name the server gave in its `serverInfo`.
+    pub fn server_name(&self) -> &str {
+        &self.inner.server_name
+    }
+
+    /// Lists every tool the server offers, following pagination cursors.
+    pub async fn list_tools(&self) -> Result<Vec<McpTool>> {
+        let mut tools = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let result = self.inner.request("tools/list", params).await?;
+            for tool in result["tools"].as_array().into_iter().flatten() {
+                let Some(name) = tool["name"].as_str() else {
+                    continue;
+                };
+                tools.push(McpTool {
+                    name: name.to_string(),
+                    description: tool["description"].as_str().unwrap_or_default().to_string(),
+                    input_schema: tool["inputSchema"].clone(),
+                });
+            }
+            match result["nextCursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => return Ok(tools),
+            }
+        }
+    }
+
+    /// Runs the server's tool `name` with `arguments`, returning its text output.
+    ///
+    /// A result the server flags with `isError` fails with `AppError::ModelError` carrying the
+    /// tool's output, so it can be shown to the model.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<String> {
+        let result = self
+            .inner
+            .request(
+                "tools/call",
+                serde_json::json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+
+        let mut output: Vec<&str> = vec![];
+        for content in result["content"].as_array().into_iter().flatten() {
+            match content["type"].as_str() {
+                Some("text") => output.extend(content["text"].as_str()),
+                Some("resource") => output.extend(content["resource"]["text"].as_str()),
+                _ => {}
+            }
+        }
+        let output = match (output.is_empty(), &result["structuredContent"]) {
+            (true, structured) if !structured.is_null() => structured.to_string(),
+            _ => output.join("\n"),
+        };
+
+        if result["isError"].as_bool().unwrap_or(false) {
+            return Err(AppError::ModelError {
+                model_name: format!("mcp:{}", self.inner.server_name),
+                failure_str: output,
+            });
+        }
+        Ok(output)
+    }
+}
+
+impl Connection {
+    /// Sends a request and waits for its response, answering any requests the server makes
+    /// in the meantime.
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut streams = self.streams.lock().await;
+        let (reader, writer) = &mut *streams;
+        self.send(
+            writer,
+            &serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+        )
+        .await?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader
+                .read_line(&mut line)
+                .await
+                .map_err(|e| self.error(format!("Failed to read from MCP server: {}", e)))?;
+            if read == 0 {
+                return Err(self.error("MCP server closed the connection".to_string()));
+            }
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+
+            if let Some(server_method) = message["method"].as_str() {
+                // Notifications need no answer; requests get an empty result for `ping` and
+                // "method not found" otherwise.
+                if !message["id"].is_null() {
+                    let reply = match server_method {
+                        "ping" => serde_json::json!({
+                            "jsonrpc": "2.0", "id": message["id"], "result": {}
+                        }),
+                        _ => serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": message["id"],
+                            "error": { "code": METHOD_NOT_FOUND, "message": "Method not found" }
+                        }),
+                    };
+                    self.send(writer, &reply).await?;
+                }
+                continue;
+            }
+            if message["id"].as_u64() != Some(id) {
+                continue;
+            }
+
+            if let Some(error) = message.get("error") {
+                return Err(self.error(format!(
+                    "`{}` failed: {}",
+                    method,
+                    error["message"].as_str().unwrap_or("unknown error")
+                )));
+            }
+            return Ok(message["result"].clone());
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let mut streams = self.streams.lock().await;
+        self.send(
+            &mut streams.1,
+            &serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        )
+        .await
+    }
+
+    async fn send(&self, writer: &mut Writer, message: &Value) -> Result<()> {
+        let mut line = message.to_string();
+        line.push('\n');
+        let written = match writer.write_all(line.as_bytes()).await {
+            Ok(()) => writer.flush().await,
+            Err(e) => Err(e),
+        };
+        written.map_err(|e| self.error(format!("Failed to write to MCP server: {}", e)))
+    }
+
+    fn error(&self, failure_str: String) -> AppError {
+        AppError::ApiError {
+            model_name: format!("mcp:{}", self.server_name),
+            failure_str,
+        }
+    }
+}
+
+impl ToolRegistry {
+    /// Registers every tool offered by the MCP server behind `client`.
+    ///
+    /// Calls are forwarded to the server and its output handed back to the model; tools the
+    /// server reports as failed reach the model as tool errors. A tool sharing a name with one
+    /// already registered replaces it.
+    pub async fn with_mcp(mut self, client: &McpClient) -> Result<Self> {
+        for tool in client.list_tools().await? {
+            let client = client.clone();
+            let name = tool.name.clone();
+            self = self.register(
+                &tool.name,
+                &tool.description,
+                tool.input_schema,
+                move |arguments| {
+                    let client = client.clone();
+                    let name = name.clone();
+                    async move {
+                        match client.call_tool(&name, arguments).await {
+                            Ok(output) => Ok(output),
+                            Err(AppError::ModelError { failure_str, .. }) => Err(failure_str),
+                            Err(e) => Err(e.to_string()),
+                        }
+                    }
+                },
+            );
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::ask_ai::{openai_endpoint_request, send_request};
+use crate::config::{AiConfig, Framework, Question};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The moderation model prompts are screened with under `AiConfig::moderate_prompts`.
+pub const MODERATION_MODEL: &str = "omni-moderation-latest";
+
+/// The verdict of the moderation endpoint on a text.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ModerationResult {
+    /// Whether any category was flagged.
+    pub flagged: bool,
+    /// Each category (e.g. `harassment`, `self-harm/intent`) and whether it was flagged.
+    #[serde(default)]
+    pub categories: BTreeMap<String, bool>,
+    /// Each category's score, from 0 to 1.
+    #[serde(default)]
+    pub category_scores: BTreeMap<String, f32>,
+}
+
+impl ModerationResult {
+    /// The flagged categories, in alphabetical order.
+    pub fn flagged_categories(&self) -> Vec<String> {
+        self.categories
+            .iter()
+            .filter(|(_, flagged)| **flagged)
+            .map(|(category, _)| category.to_string())
+            .collect()
+    }
+}
+
+/// Checks `text` against OpenAI's usage policies with the moderation model named by
+/// `ai_config.model`, e.g. `omni-moderation-latest`.
+///
+/// Uses the `moderations` endpoint next to chat completions, so `AiConfig::base_url` and
+/// `OPENAI_API_URL` apply. Moderation is free, and other providers fail with
+/// `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::moderation::{moderate, MODERATION_MODEL};
+///
+/// let ai_config = AiConfig {
+///     llm: Framework::OpenAI,
+///     model: MODERATION_MODEL.to_string(),
+///     ..Default::default()
+/// };
+/// let result = moderate(&ai_config, &user_input).await?;
+/// if result.flagged {
+///     println!("Rejected: {}", result.flagged_categories().join(", "));
+/// }
+/// ```
+pub async fn moderate(ai_config: &AiConfig, text: &str) -> Result<ModerationResult> {
+    if ai_config.llm != Framework::OpenAI {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no moderation API", ai_config.llm),
+        });
+    }
+
+    let payload = serde_json::json!({ "model": ai_config.model, "input": text });
+    let builder = openai_endpoint_request(ai_config, "moderations")?;
+    let resp = send_request(builder.json(&payload), ai_config).await?;
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    response["results"]
+        .get(0)
+        .and_then(|result| serde_json::from_value(result.clone()).ok())
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract moderation result from response".to_string(),
+        })
+}
+
+/// Fails with `AppError::ContentFlagged` when the new prompt of `question` is flagged.
+///
+/// Prompts are screened with `MODERATION_MODEL` on OpenAI. The key and base URL of
+/// `ai_config` are used when it is an OpenAI config, `OPENAI_API_KEY` otherwise.
+pub(crate) async fn screen(ai_config: &AiConfig, question: &Question) -> Result<()> {
+    let moderation_config = if ai_config.llm == Framework::OpenAI {
+        AiConfig {
+            model: MODERATION_MODEL.to_string(),
+            ..ai_config.clone()
+        }
+    } else {
+        AiConfig {
+            llm: Framework::OpenAI,
+            model: MODERATION_MODEL.to_string(),
+            local_only: ai_config.local_only,
+            client: ai_config.client.clone(),
+            http: ai_config.http.clone(),
+            ..Default::default()
+        }
+    };
+
+    let result = moderate(&moderation_config, &question.new_prompt).await?;
+    if result.flagged {
+        return Err(AppError::ContentFlagged {
+            model_name: ai_config.model.to_string(),
+            categories: result.flagged_categories(),
+        });
+    }
+    Ok(())
+}
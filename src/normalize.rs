@@ -0,0 +1,118 @@
+use serde_json::{Map, Value};
+
+/// Keys that change on every call (ids, timestamps, timings) and are dropped by default.
+const VOLATILE_KEYS: [&str; 11] = [
+    "id",
+    "created",
+    "created_at",
+    "request_id",
+    "system_fingerprint",
+    "total_duration",
+    "load_duration",
+    "prompt_eval_duration",
+    "eval_duration",
+    "x-request-id",
+    "request-id",
+];
+
+/// Turns responses into a stable form for snapshot tests.
+///
+/// Object keys are sorted, volatile fields such as ids and timestamps are removed, and string
+/// whitespace can optionally be canonicalized (line endings, trailing spaces, blank runs).
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::normalize::Normalizer;
+///
+/// let normalizer = Normalizer::default()
+///     .strip_key("usage")
+///     .canonicalize_whitespace(true);
+///
+/// insta::assert_json_snapshot!(normalizer.normalize(&response));
+/// insta::assert_snapshot!(normalizer.normalize_text(&answer));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    strip_keys: Vec<String>,
+    canonicalize_whitespace: bool,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self {
+            strip_keys: VOLATILE_KEYS.iter().map(|key| key.to_string()).collect(),
+            canonicalize_whitespace: false,
+        }
+    }
+}
+
+impl Normalizer {
+    /// Also removes `key`, at any depth.
+    pub fn strip_key(mut self, key: &str) -> Self {
+        self.strip_keys.push(key.to_string());
+        self
+    }
+
+    /// Keeps `key` even though it is volatile by default.
+    pub fn keep_key(mut self, key: &str) -> Self {
+        self.strip_keys.retain(|k| k != key);
+        self
+    }
+
+    /// Canonicalizes whitespace in every string (see `normalize_text`).
+    pub fn canonicalize_whitespace(mut self, enabled: bool) -> Self {
+        self.canonicalize_whitespace = enabled;
+        self
+    }
+
+    /// Returns a normalized copy of a JSON response.
+    pub fn normalize(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<(&String, &Value)> = map
+                    .iter()
+                    .filter(|(key, _)| !self.strip_keys.contains(key))
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+
+                // Inserted in sorted order, so the result is stable with or without
+                // serde_json's `preserve_order` feature
+                let mut sorted = Map::new();
+                for (key, value) in entries {
+                    sorted.insert(key.clone(), self.normalize(value));
+                }
+                Value::Object(sorted)
+            }
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.normalize(v)).collect()),
+            Value::String(text) if self.canonicalize_whitespace => {
+                Value::String(self.normalize_text(text))
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// Returns `text` with whitespace canonicalized when enabled, unchanged otherwise.
+    ///
+    /// Canonical whitespace means `\n` line endings, no trailing spaces, at most one blank line
+    /// in a row, and no leading or trailing blank lines.
+    pub fn normalize_text(&self, text: &str) -> String {
+        if !self.canonicalize_whitespace {
+            return text.to_string();
+        }
+
+        let text = text.replace("\r\n", "\n");
+        let mut lines: Vec<&str> = Vec::new();
+        for line in text.split('\n') {
+            let line = line.trim_end();
+            if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+                continue;
+            }
+            lines.push(line);
+        }
+        while lines.last() == Some(&"") {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+}
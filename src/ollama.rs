@@ -0,0 +1,492 @@
+use crate::ask_ai::{ensure_local, send_request};
+use crate::config::{AiConfig, Framework};
+use crate::deadline;
+use crate::embeddings::get_embeddings;
+use crate::error::{AppError, Result};
+use crate::http::http_client;
+use crate::stream::{parse_chunk, response_lines};
+use futures_util::{pin_mut, StreamExt};
+use ollama_rs::Ollama;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+
+/// Builds the Ollama client used by the crate.
+///
+/// The client points at `http://127.0.0.1:11434` unless the `OLLAMA_API_URL` environment
+/// variable is set (e.g. to reach a remote Ollama server, or an httpmock server in tests).
+pub(crate) fn ollama_client() -> Result<Ollama> {
+    match env::var("OLLAMA_API_URL") {
+        Ok(api_url) => Ollama::try_new(api_url).map_err(|e| AppError::ApiError {
+            model_name: Framework::Ollama.to_string(),
+            failure_str: format!("Invalid OLLAMA_API_URL: {}", e),
+        }),
+        Err(_) => Ok(Ollama::default()),
+    }
+}
+
+/// The root of the Ollama API, ending in `/`: `AiConfig::base_url`, else `OLLAMA_API_URL`,
+/// else `http://127.0.0.1:11434/`.
+pub(crate) fn ollama_url(ai_config: &AiConfig) -> Result<String> {
+    match &ai_config.base_url {
+        Some(base_url) => Ok(format!("{}/", base_url.trim_end_matches('/'))),
+        None => Ok(ollama_client()?.url_str().to_string()),
+    }
+}
+
+/// Creates a new model on the Ollama server of `ai_config` from the contents of a Modelfile.
+///
+/// This lets applications bake a custom system prompt or default parameters into a derived
+/// model once, and then query it by `name` through `ask_question` like any other Ollama model.
+/// Returns the final status reported by Ollama (usually `"success"`).
+///
+/// The Modelfile's `FROM`, `SYSTEM`, `TEMPLATE`, `PARAMETER`, `MESSAGE` and `LICENSE`
+/// instructions are sent as the fields of `/api/create`; `ADAPTER` is not supported, as the
+/// adapter would have to be uploaded first.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::ollama::create_model;
+///
+/// let modelfile = "FROM llama3\nSYSTEM You are a terse Rust expert.\nPARAMETER temperature 0.2";
+/// let status = create_model(&ai_config, "rust-expert", modelfile).await?;
+/// assert_eq!(status, "success");
+/// ```
+pub async fn create_model(ai_config: &AiConfig, name: &str, modelfile: &str) -> Result<String> {
+    #[derive(Deserialize)]
+    struct Status {
+        status: String,
+    }
+
+    let model_error = |failure_str: String| AppError::ModelError {
+        model_name: name.to_string(),
+        failure_str: format!("Failed to create model: {}", failure_str),
+    };
+    let payload = create_payload(name, modelfile).map_err(model_error)?;
+    let builder = management_request(ai_config, Method::POST, "create")?.json(&payload);
+    let resp = send_request(builder, ai_config)
+        .await
+        .map_err(|e| match e {
+            AppError::ApiError { failure_str, .. } => model_error(failure_str),
+            other => other,
+        })?;
+    let status: Status = resp
+        .json()
+        .await
+        .map_err(|e| model_error(format!("Failed to parse JSON response: {}", e)))?;
+    Ok(status.status)
+}
+
+/// The `/api/create` request for the model `name` described by `modelfile`.
+fn create_payload(name: &str, modelfile: &str) -> std::result::Result<Value, String> {
+    let mut payload = serde_json::json!({ "model": name, "stream": false });
+    let mut lines = modelfile.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (instruction, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let args = modelfile_value(args.trim(), &mut lines)?;
+        match instruction.to_uppercase().as_str() {
+            "FROM" => payload["from"] = args.into(),
+            "SYSTEM" => payload["system"] = args.into(),
+            "TEMPLATE" => payload["template"] = args.into(),
+            "LICENSE" => push(&mut payload["license"], args.into()),
+            "PARAMETER" => {
+                let (key, value) = args
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| format!("PARAMETER without a value: {}", args))?;
+                let value = parameter_value(value.trim());
+                match key {
+                    // The only parameter that may repeat
+                    "stop" => push(&mut payload["parameters"]["stop"], value),
+                    _ => payload["parameters"][key] = value,
+                }
+            }
+            "MESSAGE" => {
+                let (role, content) = args
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| format!("MESSAGE without content: {}", args))?;
+                push(
+                    &mut payload["messages"],
+                    serde_json::json!({ "role": role, "content": content.trim() }),
+                );
+            }
+            "ADAPTER" => return Err("ADAPTER instructions are not supported".to_string()),
+            other => return Err(format!("Unknown Modelfile instruction {}", other)),
+        }
+    }
+    Ok(payload)
+}
+
+/// The arguments of a Modelfile instruction, reading on to the closing `"""` of a multi-line
+/// value and dropping the quotes around a single-line one.
+fn modelfile_value<'a>(
+    args: &str,
+    lines: &mut impl Iterator<Item = &'a str>,
+) -> std::result::Result<String, String> {
+    let Some(rest) = args.strip_prefix(r#"""""#) else {
+        return Ok(unquote(args).to_string());
+    };
+    if let Some(value) = rest.strip_suffix(r#"""""#) {
+        return Ok(value.to_string());
+    }
+    let mut value = rest.to_string();
+    for line in lines.by_ref() {
+        value.push('\n');
+        if let Some(last) = line.strip_suffix(r#"""""#) {
+            value.push_str(last);
+            return Ok(value.trim_start_matches('\n').to_string());
+        }
+        value.push_str(line);
+    }
+    Err(r#"Unterminated """ in Modelfile"#.to_string())
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// A `PARAMETER` value, as the JSON type Ollama expects for it.
+fn parameter_value(value: &str) -> Value {
+    if let Ok(int) = value.parse::<i64>() {
+        return int.into();
+    }
+    if let Ok(float) = value.parse::<f64>() {
+        return float.into();
+    }
+    match value {
+        "true" => true.into(),
+        "false" => false.into(),
+        _ => unquote(value).into(),
+    }
+}
+
+/// Appends `item` to the array at `slot`, creating it if needed.
+fn push(slot: &mut Value, item: Value) {
+    match slot {
+        Value::Array(items) => items.push(item),
+        _ => *slot = Value::Array(vec![item]),
+    }
+}
+
+/// Embeds `texts` with a local embedding model such as `nomic-embed-text` or
+/// `mxbai-embed-large`, through Ollama's `/api/embed`.
+///
+/// Runs in local-only mode: when `OLLAMA_API_URL` names a remote host, it fails with
+/// `AppError::RemoteEndpointBlocked` rather than send the texts away, so pipelines built on it
+/// stay fully offline. Pull the model first (`ollama pull nomic-embed-text`).
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::ollama::embed;
+///
+/// let vectors = embed("nomic-embed-text", &["Rust closures", "Python lambdas"]).await?;
+/// assert_eq!(vectors.len(), 2);
+/// ```
+pub async fn embed(model: &str, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: model.to_string(),
+        local_only: true,
+        ..Default::default()
+    };
+    get_embeddings(&ai_config, texts).await
+}
+
+/// Completes `prompt` as is with the model named by `ai_config.model`, through Ollama's
+/// `/api/generate` in raw mode.
+///
+/// Unlike `ask_question`, no chat template or system prompt is applied: the model continues
+/// the text, which suits base models and code completion. The seed and generation settings
+/// of `ai_config` apply; the cache, privacy mode and other request hooks do not. Other
+/// providers fail with `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::ollama::generate;
+///
+/// let ai_config = AiConfig {
+///     llm: Framework::Ollama,
+///     model: "qwen2.5-coder:1.5b-base".to_string(),
+///     ..Default::default()
+/// };
+/// let completion = generate(&ai_config, "fn fibonacci(n: u64) -> u64 {\n").await?;
+/// ```
+pub async fn generate(ai_config: &AiConfig, prompt: &str) -> Result<String> {
+    completion(ai_config, build_generate_payload(ai_config, prompt, None)).await
+}
+
+/// Completes the code between `prefix` and `suffix` (fill-in-the-middle), through Ollama's
+/// `/api/generate`.
+///
+/// The model's template places the prefix and suffix, so it must support insertion (e.g.
+/// `qwen2.5-coder`, `codellama:code`, `starcoder2`). Otherwise like `generate`.
+pub async fn fill_in_middle(ai_config: &AiConfig, prefix: &str, suffix: &str) -> Result<String> {
+    completion(
+        ai_config,
+        build_generate_payload(ai_config, prefix, Some(suffix)),
+    )
+    .await
+}
+
+/// Builds the Ollama `/api/generate` payload completing `prompt`, followed by `suffix` when
+/// filling in the middle.
+pub fn build_generate_payload(ai_config: &AiConfig, prompt: &str, suffix: Option<&str>) -> Value {
+    let mut payload = serde_json::json!({
+        "model": ai_config.model,
+        "prompt": prompt,
+        "stream": false
+    });
+    match suffix {
+        Some(suffix) => payload["suffix"] = suffix.into(),
+        None => payload["raw"] = true.into(),
+    }
+    if let Some(seed) = ai_config.seed {
+        payload["options"] = serde_json::json!({ "seed": seed });
+    }
+    if let Some(params) = &ai_config.params {
+        params.apply(Framework::Ollama, &ai_config.model, &mut payload);
+    }
+    if let Some(options) = &ai_config.ollama {
+        options.apply(&mut payload);
+    }
+    if let Some(constraint) = &ai_config.constraint {
+        constraint.apply(Framework::Ollama, &mut payload);
+    }
+    if let Some(format) = &ai_config.response_format {
+        format.apply(Framework::Ollama, &mut payload);
+    }
+    payload
+}
+
+async fn completion(ai_config: &AiConfig, payload: Value) -> Result<String> {
+    if ai_config.llm != Framework::Ollama {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no generate API", ai_config.llm),
+        });
+    }
+    let api_url = format!("{}api/generate", ollama_url(ai_config)?);
+    ensure_local(ai_config, &api_url)?;
+    let builder = http_client(ai_config)?
+        .post(api_url)
+        .header(CONTENT_TYPE, "application/json")
+        .json(&payload);
+
+    deadline::within(ai_config, async {
+        let resp = send_request(builder, ai_config).await?;
+        let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Failed to parse JSON response: {}", e),
+        })?;
+        response["response"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: "Failed to extract response from Ollama response".to_string(),
+            })
+    })
+    .await
+}
+
+/// The architecture and size of a local model.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ModelDetails {
+    /// File format, e.g. `gguf`.
+    #[serde(default)]
+    pub format: String,
+    /// Model family, e.g. `llama` or `qwen2`.
+    #[serde(default)]
+    pub family: String,
+    /// Parameter count, e.g. `8.0B`.
+    #[serde(default)]
+    pub parameter_size: String,
+    /// Quantization, e.g. `Q4_K_M`.
+    #[serde(default)]
+    pub quantization_level: String,
+}
+
+/// A model available on the Ollama server.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LocalModel {
+    /// The name to use as `AiConfig::model`, e.g. `llama3.1:8b`.
+    pub name: String,
+    /// Size on disk, in bytes.
+    pub size: u64,
+    pub digest: String,
+    pub modified_at: String,
+    #[serde(default)]
+    pub details: ModelDetails,
+}
+
+/// What Ollama knows of a local model.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ModelInfo {
+    #[serde(default)]
+    pub modelfile: String,
+    /// Default parameters, one per line.
+    #[serde(default)]
+    pub parameters: String,
+    /// The prompt template.
+    #[serde(default)]
+    pub template: String,
+    #[serde(default)]
+    pub license: String,
+    #[serde(default)]
+    pub details: ModelDetails,
+    /// What the model supports, e.g. `completion`, `tools`, `vision`, `insert`.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Architecture metadata, e.g. `llama.context_length`.
+    #[serde(default)]
+    pub model_info: Value,
+}
+
+/// A progress report of `pull_model`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PullProgress {
+    /// What is being done, e.g. `pulling manifest`, `pulling <digest>` or `success`.
+    pub status: String,
+    /// The layer being downloaded.
+    #[serde(default)]
+    pub digest: Option<String>,
+    /// The layer's size, in bytes.
+    #[serde(default)]
+    pub total: Option<u64>,
+    /// The bytes of the layer downloaded so far.
+    #[serde(default)]
+    pub completed: Option<u64>,
+}
+
+impl PullProgress {
+    /// The share of the layer downloaded, from 0 to 1, while downloading.
+    pub fn fraction(&self) -> Option<f64> {
+        match (self.completed, self.total) {
+            (Some(completed), Some(total)) if total > 0 => Some(completed as f64 / total as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Lists the models on the Ollama server of `ai_config`.
+///
+/// Like the other model management functions, this reaches the server `ask_question` would,
+/// with the HTTP options, local-only mode and request hooks of `ai_config`.
+pub async fn list_models(ai_config: &AiConfig) -> Result<Vec<LocalModel>> {
+    #[derive(Deserialize)]
+    struct Tags {
+        models: Vec<LocalModel>,
+    }
+
+    let builder = management_request(ai_config, Method::GET, "tags")?;
+    let tags: Tags = parse(ai_config, builder).await?;
+    Ok(tags.models)
+}
+
+/// Shows the template, parameters, details and capabilities of the model `name`.
+pub async fn show_model(ai_config: &AiConfig, name: &str) -> Result<ModelInfo> {
+    let builder = management_request(ai_config, Method::POST, "show")?
+        .json(&serde_json::json!({ "model": name }));
+    parse(ai_config, builder).await
+}
+
+/// Deletes the model `name` from the Ollama server.
+pub async fn delete_model(ai_config: &AiConfig, name: &str) -> Result<()> {
+    let builder = management_request(ai_config, Method::DELETE, "delete")?
+        .json(&serde_json::json!({ "model": name }));
+    send_request(builder, ai_config).await.map(|_| ())
+}
+
+/// Downloads the model `name` from the Ollama library, calling `on_progress` with each
+/// progress report Ollama streams.
+///
+/// Already downloaded layers are skipped, so pulling a present model only checks it is up
+/// to date.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::ollama::pull_model;
+///
+/// pull_model(&ai_config, "llama3.1:8b", |progress| match progress.fraction() {
+///     Some(fraction) => println!("{}: {:.0}%", progress.status, fraction * 100.0),
+///     None => println!("{}", progress.status),
+/// })
+/// .await?;
+/// ```
+pub async fn pull_model(
+    ai_config: &AiConfig,
+    name: &str,
+    mut on_progress: impl FnMut(&PullProgress),
+) -> Result<()> {
+    let builder = management_request(ai_config, Method::POST, "pull")?
+        .json(&serde_json::json!({ "model": name, "stream": true }));
+    let resp = send_request(builder, ai_config).await?;
+
+    let lines = response_lines(resp, ai_config);
+    pin_mut!(lines);
+    while let Some(line) = lines.next().await {
+        // Failed pulls report an `error` in the stream
+        let chunk = parse_chunk(&line?, ai_config)?;
+        let progress: PullProgress =
+            serde_json::from_value(chunk).map_err(|e| AppError::ModelError {
+                model_name: name.to_string(),
+                failure_str: format!("Failed to parse pull progress: {}", e),
+            })?;
+        on_progress(&progress);
+    }
+    Ok(())
+}
+
+/// Pulls the model of `ai_config` unless the Ollama server already has it, so `ask_question`
+/// can be called right after. Returns whether it was pulled.
+///
+/// A model named without a tag is looked up as `:latest`, like Ollama does.
+pub async fn ensure_model(
+    ai_config: &AiConfig,
+    on_progress: impl FnMut(&PullProgress),
+) -> Result<bool> {
+    let name = if ai_config.model.contains(':') {
+        ai_config.model.to_string()
+    } else {
+        format!("{}:latest", ai_config.model)
+    };
+    if list_models(ai_config)
+        .await?
+        .iter()
+        .any(|model| model.name == name)
+    {
+        return Ok(false);
+    }
+    pull_model(ai_config, &name, on_progress).await?;
+    Ok(true)
+}
+
+/// Prepares a request to the Ollama endpoint `/api/<path>`.
+fn management_request(ai_config: &AiConfig, method: Method, path: &str) -> Result<RequestBuilder> {
+    let api_url = format!("{}api/{}", ollama_url(ai_config)?, path);
+    ensure_local(ai_config, &api_url)?;
+    Ok(http_client(ai_config)?.request(method, api_url))
+}
+
+async fn parse<T: serde::de::DeserializeOwned>(
+    ai_config: &AiConfig,
+    builder: RequestBuilder,
+) -> Result<T> {
+    let resp = send_request(builder, ai_config).await?;
+    resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })
+}
@@ -0,0 +1,282 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use crate::store::sqlite_error;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where a job of the outbox stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Submitted and not answered yet, including jobs interrupted by a crash.
+    Pending,
+    /// Answered; the answer is stored with the job.
+    Done,
+    /// The last attempt failed; the error is stored with the job.
+    Failed,
+}
+
+impl JobStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(status: &str) -> Result<Self> {
+        match status {
+            "pending" => Ok(JobStatus::Pending),
+            "done" => Ok(JobStatus::Done),
+            "failed" => Ok(JobStatus::Failed),
+            other => Err(AppError::UnexpectedError(format!(
+                "Unknown outbox job status `{}`",
+                other
+            ))),
+        }
+    }
+}
+
+/// A question submitted to the outbox, and its outcome so far.
+#[derive(Debug, Clone)]
+pub struct OutboxJob {
+    pub id: i64,
+    pub question: Question,
+    pub status: JobStatus,
+    /// How many times the question was sent.
+    pub attempts: u32,
+    pub answer: Option<String>,
+    pub error: Option<String>,
+    /// Submission time, in seconds since the Unix epoch.
+    pub submitted_at: u64,
+}
+
+/// A persistent queue of questions in a SQLite database (requires the `sqlite` feature).
+///
+/// Questions are written to the database before they are sent, and their answers (or errors)
+/// when they come back, so no submitted question is lost when the process crashes or is
+/// restarted: the next `run_pending` on the same database picks up every job still pending.
+/// Delivery is at least once: a job interrupted mid-request is sent again.
+///
+/// Jobs are answered with the configuration the outbox was opened with. Failed jobs stay
+/// failed until `retry_failed` puts them back in the queue.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::outbox::Outbox;
+///
+/// let outbox = Outbox::open("pipeline.db", ai_config)?;
+/// // Jobs left over by a previous run are answered first
+/// outbox.run_pending().await?;
+///
+/// let id = outbox.submit(&question)?;
+/// for job in outbox.run_pending().await? {
+///     println!("{}: {:?}", job.id, job.answer);
+/// }
+/// ```
+pub struct Outbox {
+    conn: Mutex<Connection>,
+    ai_config: AiConfig,
+}
+
+impl Outbox {
+    /// Opens (and creates if needed) the outbox database at `path`.
+    pub fn open(path: impl AsRef<Path>, ai_config: AiConfig) -> Result<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS outbox (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                question TEXT NOT NULL,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                answer TEXT,
+                error TEXT,
+                submitted_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(sqlite_error)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            ai_config,
+        })
+    }
+
+    /// Persists `question` as a pending job and returns its id. Nothing is sent yet.
+    pub fn submit(&self, question: &Question) -> Result<i64> {
+        let json = serde_json::to_string(question).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to serialize question: {}", e))
+        })?;
+        let submitted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO outbox (question, status, submitted_at) VALUES (?1, ?2, ?3)",
+            params![json, JobStatus::Pending.as_str(), submitted_at as i64],
+        )
+        .map_err(sqlite_error)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Answers every pending job, oldest first, and returns them with their outcome.
+    ///
+    /// A failed question marks its job failed and does not stop the run; only database errors
+    /// do.
+    pub async fn run_pending(&self) -> Result<Vec<OutboxJob>> {
+        let pending = self.jobs(Some(JobStatus::Pending))?;
+
+        let mut processed = Vec::with_capacity(pending.len());
+        for mut job in pending {
+            // The outcome is recorded in the job
+            let _ = self.process(&mut job).await?;
+            processed.push(job);
+        }
+        Ok(processed)
+    }
+
+    /// Submits `question` and answers it right away, like `ask_question`, with the job
+    /// persisted until it is answered.
+    pub async fn ask(&self, question: Question) -> Result<String> {
+        let mut job = self.job(self.submit(&question)?)?;
+        self.process(&mut job).await?
+    }
+
+    /// The job with `id`.
+    pub fn job(&self, id: i64) -> Result<OutboxJob> {
+        let row = self
+            .conn()
+            .query_row(
+                "SELECT id, question, status, attempts, answer, error, submitted_at
+                 FROM outbox WHERE id = ?1",
+                [id],
+                read_row,
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .ok_or_else(|| AppError::UnexpectedError(format!("No outbox job {}", id)))?;
+        into_job(row)
+    }
+
+    /// All jobs with `status`, or all jobs, oldest first.
+    pub fn jobs(&self, status: Option<JobStatus>) -> Result<Vec<OutboxJob>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, question, status, attempts, answer, error, submitted_at
+                 FROM outbox WHERE ?1 IS NULL OR status = ?1 ORDER BY id",
+            )
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map([status.map(JobStatus::as_str)], read_row)
+            .map_err(sqlite_error)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(sqlite_error)?;
+        rows.into_iter().map(into_job).collect()
+    }
+
+    /// Puts every failed job back in the queue and returns how many there were.
+    pub fn retry_failed(&self) -> Result<usize> {
+        self.conn()
+            .execute(
+                "UPDATE outbox SET status = ?1, error = NULL WHERE status = ?2",
+                params![JobStatus::Pending.as_str(), JobStatus::Failed.as_str()],
+            )
+            .map_err(sqlite_error)
+    }
+
+    /// Removes answered jobs and returns how many there were.
+    pub fn purge_done(&self) -> Result<usize> {
+        self.conn()
+            .execute(
+                "DELETE FROM outbox WHERE status = ?1",
+                [JobStatus::Done.as_str()],
+            )
+            .map_err(sqlite_error)
+    }
+
+    /// Sends the question of `job` and records the outcome, which is returned unless the
+    /// database itself fails.
+    async fn process(&self, job: &mut OutboxJob) -> Result<Result<String>> {
+        // Counted before sending, so crashes mid-request still show up as attempts
+        job.attempts += 1;
+        self.conn()
+            .execute(
+                "UPDATE outbox SET attempts = ?1 WHERE id = ?2",
+                params![job.attempts, job.id],
+            )
+            .map_err(sqlite_error)?;
+
+        let outcome = ask_question(&self.ai_config, job.question.clone()).await;
+        match &outcome {
+            Ok(answer) => {
+                job.status = JobStatus::Done;
+                job.answer = Some(answer.clone());
+                job.error = None;
+            }
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(e.to_string());
+            }
+        }
+        self.conn()
+            .execute(
+                "UPDATE outbox SET status = ?1, answer = ?2, error = ?3 WHERE id = ?4",
+                params![job.status.as_str(), job.answer, job.error, job.id],
+            )
+            .map_err(sqlite_error)?;
+        Ok(outcome)
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock cannot leave the connection half-updated
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The columns of an outbox row, as stored.
+type JobRow = (
+    i64,
+    String,
+    String,
+    u32,
+    Option<String>,
+    Option<String>,
+    i64,
+);
+
+fn read_row(row: &Row<'_>) -> rusqlite::Result<JobRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+    ))
+}
+
+fn into_job(
+    (id, question, status, attempts, answer, error, submitted_at): JobRow,
+) -> Result<OutboxJob> {
+    let question = serde_json::from_str(&question).map_err(|e| {
+        AppError::UnexpectedError(format!("Failed to parse outbox job {}: {}", id, e))
+    })?;
+    Ok(OutboxJob {
+        id,
+        question,
+        status: JobStatus::parse(&status)?,
+        attempts,
+        answer,
+        error,
+        submitted_at: submitted_at as u64,
+    })
+}
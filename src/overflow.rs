@@ -0,0 +1,145 @@
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::future::Future;
+
+/// Error texts providers use when a request does not fit the model's context window.
+const CONTEXT_LENGTH_ERRORS: [&str; 5] = [
+    "context_length_exceeded",
+    "maximum context length",
+    "prompt is too long",
+    "context window",
+    "exceeds the maximum number of tokens",
+];
+
+/// Recovery from context-length errors: when the provider rejects a question for not fitting
+/// the model's context window, it is sent again to a larger-context `fallback` model, or with
+/// the oldest exchanges of the conversation dropped.
+///
+/// The fallback is tried first, with the whole conversation. With `truncate` set, the oldest
+/// half of the remaining exchanges is then dropped before each new attempt (against the
+/// fallback, if any), until the question fits or no exchanges are left. The system prompt and
+/// the new prompt are never dropped. The fallback is held to the local-only mode of the config
+/// it stands in for.
+///
+/// `ask_question_raw` reports what was done under `ask_ai.context_overflow` in the response,
+/// see `OverflowAction`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::overflow::ContextOverflow;
+///
+/// // Long conversations move to the 1M-token model, and lose their oldest turns if needed
+/// let ai_config = AiConfig {
+///     on_context_overflow: Some(ContextOverflow {
+///         fallback: Some(Box::new(AiConfig {
+///             model: "gpt-4.1".to_string(),
+///             ..ai_config.clone()
+///         })),
+///         truncate: true,
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ContextOverflow {
+    /// Larger-context configuration to send the question to instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Box<AiConfig>>,
+    /// Drop the oldest exchanges and try again when the question still does not fit.
+    #[serde(default)]
+    pub truncate: bool,
+}
+
+/// What was done to answer a question that overflowed the context window.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum OverflowAction {
+    /// The question was answered by the fallback model.
+    Fallback { model: String },
+    /// The question was answered without its `dropped` oldest exchanges, by `model`.
+    Truncated { model: String, dropped: usize },
+}
+
+/// Whether `error` is a provider rejecting a request that does not fit the context window.
+pub fn is_context_length_error(error: &AppError) -> bool {
+    match error {
+        AppError::ApiError { failure_str, .. } | AppError::ModelError { failure_str, .. } => {
+            let failure = failure_str.to_lowercase();
+            CONTEXT_LENGTH_ERRORS
+                .iter()
+                .any(|pattern| failure.contains(pattern))
+        }
+        _ => false,
+    }
+}
+
+impl ContextOverflow {
+    /// The fallback, held to the local-only mode of `ai_config` as it gets the whole
+    /// conversation. Passed to `send`, which cannot own it.
+    pub(crate) fn fallback_for<'a>(&'a self, ai_config: &AiConfig) -> Option<Cow<'a, AiConfig>> {
+        self.fallback
+            .as_ref()
+            .map(|fallback| fallback.on_behalf_of(ai_config))
+    }
+
+    /// Sends `question` with `send`, recovering from context-length errors with `fallback`
+    /// (from `fallback_for`) and truncation.
+    pub(crate) async fn send<'a, T, F>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        fallback: Option<&'a AiConfig>,
+        question: Question,
+        send: impl Fn(&'a AiConfig, Question) -> F,
+    ) -> Result<(T, Option<OverflowAction>)>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let error = match send(ai_config, question.clone()).await {
+            Err(e) if is_context_length_error(&e) => e,
+            result => return result.map(|answer| (answer, None)),
+        };
+
+        let target = match fallback {
+            Some(fallback) => {
+                let action = OverflowAction::Fallback {
+                    model: fallback.model.clone(),
+                };
+                match send(fallback, question.clone()).await {
+                    Err(e) if is_context_length_error(&e) => {}
+                    result => return result.map(|answer| (answer, Some(action))),
+                }
+                fallback
+            }
+            None => ai_config,
+        };
+        if !self.truncate {
+            return Err(error);
+        }
+
+        let mut question = question;
+        let mut dropped = 0;
+        loop {
+            let messages = question.messages.get_or_insert_with(Vec::new);
+            if messages.is_empty() {
+                return Err(error);
+            }
+            let drop = messages.len().div_ceil(2);
+            messages.drain(..drop);
+            dropped += drop;
+
+            match send(target, question.clone()).await {
+                Err(e) if is_context_length_error(&e) => {}
+                result => {
+                    let action = OverflowAction::Truncated {
+                        model: target.model.clone(),
+                        dropped,
+                    };
+                    return result.map(|answer| (answer, Some(action)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use crate::capabilities::capabilities;
+use crate::config::Framework;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// OpenAI accepts at most this many stop sequences.
+const OPENAI_MAX_STOP: usize = 4;
+
+/// Sampling parameters reasoning models reject.
+const REASONING_UNSUPPORTED: [&str; 4] = [
+    "temperature",
+    "top_p",
+    "presence_penalty",
+    "frequency_penalty",
+];
+
+/// Sampling parameters in one provider-independent set.
+///
+/// Each provider receives them under its own field names; parameters a provider does not
+/// support are dropped and out-of-range values are clamped, each with a `ParamWarning`,
+/// instead of failing the request.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::params::GenerationParams;
+///
+/// let ai_config = AiConfig {
+///     params: Some(GenerationParams {
+///         temperature: Some(1.5),
+///         top_k: Some(40),
+///         stop: vec!["\n\n".to_string()],
+///         ..Default::default()
+///     }),
+///     ..ai_config
+/// };
+///
+/// // Anthropic: temperature clamped to 1.0; OpenAI: top_k dropped
+/// let warnings = ai_config.params.unwrap().translate(Framework::OpenAI).warnings;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GenerationParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Sequences that end the answer when generated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+}
+
+/// A parameter that was not passed on as given.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ParamWarning {
+    /// The provider has no such parameter.
+    Dropped { param: String },
+    /// The value was outside the provider's range, or over its limit.
+    Adjusted {
+        param: String,
+        from: Value,
+        to: Value,
+    },
+}
+
+/// The provider fields for a `GenerationParams`, and what had to change to get there.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Translation {
+    /// Fields to merge into the request payload.
+    pub fields: Map<String, Value>,
+    pub warnings: Vec<ParamWarning>,
+}
+
+impl GenerationParams {
+    /// Maps the parameters onto the fields `framework` accepts.
+    pub fn translate(&self, framework: Framework) -> Translation {
+        let mut out = Translation::default();
+        let (temperature_max, penalties, top_k) = match framework {
+            Framework::OpenAI => (2.0, true, false),
+            Framework::Anthropic => (1.0, false, true),
+            Framework::Ollama => (f64::MAX, true, true),
+            Framework::LmStudio => (2.0, true, true),
+            Framework::Bedrock => (1.0, false, false),
+            Framework::Mistral => (1.5, true, false),
+            Framework::Groq => (2.0, true, false),
+            Framework::OpenRouter => (2.0, true, true),
+            Framework::HuggingFace => (2.0, true, false),
+            Framework::Replicate => (5.0, true, true),
+            // Most other providers follow OpenAI
+            Framework::Custom(_) => (2.0, true, false),
+        };
+
+        if let Some(temperature) = self.temperature {
+            let clamped = out.clamp("temperature", temperature, 0.0, temperature_max);
+            out.fields.insert("temperature".to_string(), clamped);
+        }
+        if let Some(top_p) = self.top_p {
+            let clamped = out.clamp("top_p", top_p, 0.0, 1.0);
+            out.fields.insert("top_p".to_string(), clamped);
+        }
+        match (self.top_k, top_k) {
+            (Some(value), true) => {
+                out.fields.insert("top_k".to_string(), value.into());
+            }
+            (Some(_), false) => out.dropped("top_k"),
+            (None, _) => {}
+        }
+        for (param, value) in [
+            ("presence_penalty", self.presence_penalty),
+            ("frequency_penalty", self.frequency_penalty),
+        ] {
+            match (value, penalties) {
+                (Some(value), true) => {
+                    let clamped = out.clamp(param, value, -2.0, 2.0);
+                    out.fields.insert(param.to_string(), clamped);
+                }
+                (Some(_), false) => out.dropped(param),
+                (None, _) => {}
+            }
+        }
+        if !self.stop.is_empty() {
+            let (name, stop) = match framework {
+                Framework::OpenAI | Framework::Groq if self.stop.len() > OPENAI_MAX_STOP => {
+                    let stop = self.stop[..OPENAI_MAX_STOP].to_vec();
+                    out.warnings.push(ParamWarning::Adjusted {
+                        param: "stop".to_string(),
+                        from: self.stop.clone().into(),
+                        to: stop.clone().into(),
+                    });
+                    ("stop", stop)
+                }
+                Framework::Anthropic => ("stop_sequences", self.stop.clone()),
+                Framework::Bedrock => ("stopSequences", self.stop.clone()),
+                _ => ("stop", self.stop.clone()),
+            };
+            out.fields.insert(name.to_string(), stop.into());
+        }
+
+        // Ollama takes sampling parameters as model options
+        if framework == Framework::Ollama && !out.fields.is_empty() {
+            let options = std::mem::take(&mut out.fields);
+            out.fields
+                .insert("options".to_string(), Value::Object(options));
+        }
+        // Bedrock's Converse API takes them as inference configuration
+        if framework == Framework::Bedrock && !out.fields.is_empty() {
+            let mut config = std::mem::take(&mut out.fields);
+            if let Some(top_p) = config.remove("top_p") {
+                config.insert("topP".to_string(), top_p);
+            }
+            out.fields
+                .insert("inferenceConfig".to_string(), Value::Object(config));
+        }
+        // Replicate takes them as model input, with stop sequences comma-separated
+        if framework == Framework::Replicate && !out.fields.is_empty() {
+            let mut input = std::mem::take(&mut out.fields);
+            if let Some(Value::Array(stop)) = input.remove("stop") {
+                let stop: Vec<&str> = stop.iter().filter_map(Value::as_str).collect();
+                input.insert("stop_sequences".to_string(), stop.join(",").into());
+            }
+            out.fields.insert("input".to_string(), Value::Object(input));
+        }
+        out
+    }
+
+    /// Like `translate`, for `model` on `framework`: sampling parameters are also dropped
+    /// for reasoning models (see `Capabilities::reasoning`), which reject them.
+    pub fn translate_for_model(&self, framework: Framework, model: &str) -> Translation {
+        let reasoning = capabilities(framework.clone(), model).reasoning;
+        let mut out = self.translate(framework);
+        if reasoning {
+            for param in REASONING_UNSUPPORTED {
+                if out.fields.remove(param).is_some() {
+                    out.dropped(param);
+                }
+            }
+        }
+        out
+    }
+
+    /// Merges the fields translated for `model` into `payload`, one level deep for nested
+    /// objects.
+    pub(crate) fn apply(&self, framework: Framework, model: &str, payload: &mut Value) {
+        let Value::Object(payload) = payload else {
+            return;
+        };
+        for (key, value) in self.translate_for_model(framework, model).fields {
+            match (payload.get_mut(&key), value) {
+                (Some(Value::Object(existing)), Value::Object(value)) => existing.extend(value),
+                (_, value) => {
+                    payload.insert(key, value);
+                }
+            }
+        }
+    }
+}
+
+impl Translation {
+    fn dropped(&mut self, param: &str) {
+        self.warnings.push(ParamWarning::Dropped {
+            param: param.to_string(),
+        });
+    }
+
+    fn clamp(&mut self, param: &str, value: f64, min: f64, max: f64) -> Value {
+        let clamped = value.clamp(min, max);
+        if clamped != value {
+            self.warnings.push(ParamWarning::Adjusted {
+                param: param.to_string(),
+                from: value.into(),
+                to: clamped.into(),
+            });
+        }
+        clamped.into()
+    }
+}
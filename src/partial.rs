@@ -0,0 +1,188 @@
+use crate::error::{AppError, Result};
+use crate::stream::AnswerStream;
+use async_stream::try_stream;
+use futures_util::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::pin::Pin;
+
+/// A stream of values parsed from an answer while it is generated.
+pub type JsonStream<T> = Pin<Box<dyn Stream<Item = Result<T>> + Send>>;
+
+/// Parses the longest usable JSON value from a prefix of a JSON document.
+///
+/// Open strings, arrays and objects are closed; a member or item still being written is left
+/// out, except for strings, which show the text so far. Anything before the first `{` or `[`
+/// (such as a Markdown code fence) is skipped. Returns `None` until a container has started.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::partial::parse_partial_json;
+///
+/// let value = parse_partial_json(r#"{"title": "Rust", "tags": ["fast", "sa"#).unwrap();
+/// assert_eq!(value, json!({ "title": "Rust", "tags": ["fast", "sa"] }));
+/// ```
+pub fn parse_partial_json(text: &str) -> Option<Value> {
+    let start = text.find(['{', '['])?;
+    let text = &text[start..];
+
+    // Places where the document can be cut and closed: after an opening bracket, or before a
+    // separating comma
+    let mut cuts: Vec<(usize, Vec<char>)> = vec![];
+    let mut closers: Vec<char> = vec![];
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut end = text.len();
+    for (pos, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                closers.push(if c == '{' { '}' } else { ']' });
+                cuts.push((pos + 1, closers.clone()));
+            }
+            '}' | ']' => {
+                closers.pop();
+                if closers.is_empty() {
+                    end = pos + 1;
+                    break;
+                }
+            }
+            ',' => cuts.push((pos, closers.clone())),
+            _ => {}
+        }
+    }
+
+    let text = &text[..end];
+    let mut attempt = text.to_string();
+    if in_string {
+        if escaped {
+            attempt.pop();
+        }
+        attempt.push('"');
+    }
+    attempt.extend(closers.iter().rev());
+    if let Ok(value) = serde_json::from_str(&attempt) {
+        return Some(value);
+    }
+
+    cuts.into_iter().rev().find_map(|(pos, closers)| {
+        let mut attempt = text[..pos].to_string();
+        attempt.extend(closers.iter().rev());
+        serde_json::from_str(&attempt).ok()
+    })
+}
+
+/// Turns an answer stream into a stream of partial JSON values: after each fragment, the
+/// value parsed so far (see `parse_partial_json`), whenever it changed. The last item is the
+/// complete value; an answer that does not end as valid JSON fails the stream.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::partial::partial_json;
+///
+/// let stream = ask_question_stream(&ai_config, question).await?;
+/// let mut updates = partial_json(stream);
+/// while let Some(value) = updates.next().await {
+///     render(&value?);
+/// }
+/// ```
+pub fn partial_json(answer: AnswerStream) -> JsonStream<Value> {
+    Box::pin(try_stream! {
+        let mut answer = answer;
+        let mut text = String::new();
+        let mut last = None;
+        while let Some(delta) = answer.next().await {
+            text.push_str(&delta?);
+            let value = parse_partial_json(&text);
+            if value.is_some() && value != last {
+                yield value.clone().unwrap_or_default();
+                last = value;
+            }
+        }
+        let complete = complete_json(&text)?;
+        if Some(&complete) != last.as_ref() {
+            yield complete;
+        }
+    })
+}
+
+/// Streams the items of the array at `pointer` (a JSON pointer such as `/steps`, or `""` for
+/// a top-level array) as `T`, each as soon as it is complete, so lists can be rendered item
+/// by item while the answer is generated.
+///
+/// An item is complete once the next one has started, or when the answer ends.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::partial::json_items;
+///
+/// #[derive(Deserialize)]
+/// struct Step { title: String, detail: String }
+///
+/// let stream = ask_question_stream(&ai_config, question).await?;
+/// let mut steps = json_items::<Step>(stream, "/steps");
+/// while let Some(step) = steps.next().await {
+///     println!("{}", step?.title);
+/// }
+/// ```
+pub fn json_items<T>(answer: AnswerStream, pointer: &str) -> JsonStream<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let pointer = pointer.to_string();
+    Box::pin(try_stream! {
+        let mut answer = answer;
+        let mut text = String::new();
+        let mut emitted = 0;
+        while let Some(delta) = answer.next().await {
+            text.push_str(&delta?);
+            let Some(value) = parse_partial_json(&text) else {
+                continue;
+            };
+            let items = items_at(&value, &pointer);
+            // The last item may still be written
+            while emitted + 1 < items.len() {
+                yield item(&items[emitted])?;
+                emitted += 1;
+            }
+        }
+
+        let value = complete_json(&text)?;
+        for value in items_at(&value, &pointer).iter().skip(emitted) {
+            yield item(value)?;
+        }
+    })
+}
+
+fn items_at<'a>(value: &'a Value, pointer: &str) -> &'a [Value] {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn item<T: DeserializeOwned>(value: &Value) -> Result<T> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| AppError::UnexpectedError(format!("Failed to parse streamed item: {}", e)))
+}
+
+/// Parses a whole answer, allowing text around the JSON document such as a code fence.
+fn complete_json(text: &str) -> Result<Value> {
+    let start = text.find(['{', '[']).unwrap_or_default();
+    let end = text.rfind(['}', ']']).map_or(text.len(), |end| end + 1);
+    serde_json::from_str(text.get(start..end).unwrap_or_default())
+        .map_err(|e| AppError::UnexpectedError(format!("Answer is not valid JSON: {}", e)))
+}
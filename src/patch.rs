@@ -0,0 +1,279 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use crate::markdown::extract_code_blocks;
+use serde::{Deserialize, Serialize};
+
+/// Instruction added to the system prompt by `ask_for_patch`.
+pub const PATCH_INSTRUCTIONS: &str = "Answer only with a unified diff (as produced by \
+    `diff -u` or `git diff`) that makes the requested change. Use `--- a/<path>` and \
+    `+++ b/<path>` file headers, `@@ -start,count +start,count @@` hunk headers with correct \
+    line counts, and three lines of context. Do not add any explanation.";
+
+/// A unified diff, possibly touching several files.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Patch {
+    pub files: Vec<FilePatch>,
+}
+
+/// The changes to a single file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// Path before the change, `None` for a new file.
+    pub old_path: Option<String>,
+    /// Path after the change, `None` for a deleted file.
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// A contiguous change, with its surrounding context.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// First line of the hunk in the original file, 1-based.
+    pub old_start: usize,
+    pub old_lines: usize,
+    /// First line of the hunk in the changed file, 1-based.
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Added(String),
+    Removed(String),
+}
+
+impl Patch {
+    /// Parses and validates a unified diff: every file needs both headers and at least one
+    /// hunk, and every hunk exactly the line counts of its header.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// use ask_ai::patch::Patch;
+    ///
+    /// let patch = Patch::parse(&std::fs::read_to_string("fix.diff")?)?;
+    /// for file in &patch.files {
+    ///     println!("{:?}: {} hunks", file.new_path, file.hunks.len());
+    /// }
+    /// ```
+    pub fn parse(diff: &str) -> Result<Patch> {
+        let mut files: Vec<FilePatch> = Vec::new();
+        let mut lines = diff.lines().peekable();
+
+        while let Some(line) = lines.next() {
+            if let Some(old_path) = line.strip_prefix("--- ") {
+                let new_path = lines
+                    .next()
+                    .and_then(|line| line.strip_prefix("+++ "))
+                    .ok_or_else(|| invalid(format!("`{}` has no `+++` header", line)))?;
+                files.push(FilePatch {
+                    old_path: path(old_path),
+                    new_path: path(new_path),
+                    hunks: Vec::new(),
+                });
+            } else if line.starts_with("@@") {
+                let file = files
+                    .last_mut()
+                    .ok_or_else(|| invalid(format!("hunk `{}` before any file header", line)))?;
+                let mut hunk = hunk_header(line)?;
+
+                let (mut old_seen, mut new_seen) = (0, 0);
+                while old_seen < hunk.old_lines || new_seen < hunk.new_lines {
+                    let Some(line) = lines.next() else { break };
+                    // Models often drop the single space of empty context lines
+                    let (marker, text) = match line.chars().next() {
+                        Some(marker) => (marker, &line[marker.len_utf8()..]),
+                        None => (' ', ""),
+                    };
+                    match marker {
+                        ' ' => {
+                            old_seen += 1;
+                            new_seen += 1;
+                            hunk.lines.push(HunkLine::Context(text.to_string()));
+                        }
+                        '-' => {
+                            old_seen += 1;
+                            hunk.lines.push(HunkLine::Removed(text.to_string()));
+                        }
+                        '+' => {
+                            new_seen += 1;
+                            hunk.lines.push(HunkLine::Added(text.to_string()));
+                        }
+                        // "\ No newline at end of file"
+                        '\\' => {}
+                        _ => break,
+                    }
+                }
+                if (old_seen, new_seen) != (hunk.old_lines, hunk.new_lines) {
+                    return Err(invalid(format!(
+                        "hunk `{}` has {} original and {} changed lines",
+                        line, old_seen, new_seen
+                    )));
+                }
+                while lines.next_if(|line| line.starts_with('\\')).is_some() {}
+                file.hunks.push(hunk);
+            }
+            // Anything else (`diff --git`, `index`, prose) is not part of the patch
+        }
+
+        if files.is_empty() {
+            return Err(invalid("no file headers found".to_string()));
+        }
+        if let Some(file) = files.iter().find(|file| file.hunks.is_empty()) {
+            return Err(invalid(format!("{} has no hunks", file.display_path())));
+        }
+        Ok(Patch { files })
+    }
+}
+
+impl FilePatch {
+    /// The path shown for this file: the new path, or the old one for a deletion.
+    pub fn display_path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or("/dev/null")
+    }
+
+    /// Applies the hunks to `original`, returning the changed content.
+    ///
+    /// Each hunk is matched at its stated line first, then anywhere after the previous hunk,
+    /// since models get line numbers wrong more often than context.
+    pub fn apply(&self, original: &str) -> Result<String> {
+        let lines: Vec<&str> = original.lines().collect();
+        let mut result: Vec<&str> = Vec::new();
+        let mut cursor = 0;
+
+        for hunk in &self.hunks {
+            let old: Vec<&str> = hunk
+                .lines
+                .iter()
+                .filter_map(|line| match line {
+                    HunkLine::Context(text) | HunkLine::Removed(text) => Some(text.as_str()),
+                    HunkLine::Added(_) => None,
+                })
+                .collect();
+            let matches_at = |at: usize| lines.get(at..at + old.len()) == Some(&old[..]);
+
+            let stated = hunk.old_start.saturating_sub(1).max(cursor);
+            let at = if matches_at(stated) {
+                stated
+            } else {
+                (cursor..=lines.len())
+                    .find(|at| matches_at(*at))
+                    .ok_or_else(|| {
+                        invalid(format!(
+                            "hunk at line {} does not match {}",
+                            hunk.old_start,
+                            self.display_path()
+                        ))
+                    })?
+            };
+
+            result.extend(&lines[cursor..at]);
+            result.extend(hunk.lines.iter().filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Added(text) => Some(text.as_str()),
+                HunkLine::Removed(_) => None,
+            }));
+            cursor = at + old.len();
+        }
+        result.extend(&lines[cursor..]);
+
+        let mut changed = result.join("\n");
+        if original.ends_with('\n') && !changed.is_empty() {
+            changed.push('\n');
+        }
+        Ok(changed)
+    }
+}
+
+/// Asks for a change as a unified diff, and parses the answer into a `Patch`.
+///
+/// Put the current file contents in the prompt (or history); the diff may be fenced in the
+/// answer. An answer that is not a valid diff fails with `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::patch::ask_for_patch;
+///
+/// let source = std::fs::read_to_string("src/main.rs")?;
+/// let question = Question {
+///     system_prompt: None,
+///     messages: None,
+///     new_prompt: format!("Rename `run` to `start` in src/main.rs:\n\n{}", source),
+///     images: vec![],
+///     audio: vec![],
+/// };
+///
+/// let patch = ask_for_patch(&ai_config, question).await?;
+/// std::fs::write("src/main.rs", patch.files[0].apply(&source)?)?;
+/// ```
+pub async fn ask_for_patch(ai_config: &AiConfig, mut question: Question) -> Result<Patch> {
+    question.system_prompt = Some(match question.system_prompt {
+        Some(prompt) if !prompt.is_empty() => format!("{}\n\n{}", prompt, PATCH_INSTRUCTIONS),
+        _ => PATCH_INSTRUCTIONS.to_string(),
+    });
+    let answer = ask_question(ai_config, question).await?;
+
+    let fenced: Vec<String> = extract_code_blocks(&answer)
+        .into_iter()
+        .filter(|block| matches!(block.language.as_deref(), Some("diff" | "patch")))
+        .map(|block| block.code)
+        .collect();
+    let diff = if fenced.is_empty() {
+        answer
+    } else {
+        fenced.join("\n")
+    };
+
+    Patch::parse(&diff).map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: e.to_string(),
+    })
+}
+
+fn invalid(reason: String) -> AppError {
+    AppError::UnexpectedError(format!("Invalid patch: {}", reason))
+}
+
+/// A header path without its `a/` or `b/` prefix and timestamp, `None` for `/dev/null`.
+fn path(header: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Parses `@@ -old_start,old_lines +new_start,new_lines @@`, counts defaulting to 1.
+fn hunk_header(line: &str) -> Result<Hunk> {
+    let range = |part: Option<&str>, sign: char| -> Option<(usize, usize)> {
+        let part = part?.strip_prefix(sign)?;
+        match part.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((part.parse().ok()?, 1)),
+        }
+    };
+
+    let mut parts = line.trim_start_matches('@').split_whitespace();
+    let old = range(parts.next(), '-');
+    let new = range(parts.next(), '+');
+    let (Some((old_start, old_lines)), Some((new_start, new_lines))) = (old, new) else {
+        return Err(invalid(format!("malformed hunk header `{}`", line)));
+    };
+    Ok(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+    })
+}
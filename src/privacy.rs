@@ -0,0 +1,254 @@
+use crate::config::{AiPrompt, Question};
+use crate::error::{AppError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Longest placeholder held back while streaming, waiting for its closing bracket.
+const MAX_PLACEHOLDER_LEN: usize = 48;
+
+/// How sensitive values are replaced before a question leaves the machine.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyMode {
+    /// Replace each distinct value with a numbered placeholder (`[EMAIL_1]`) and put the
+    /// original back into the answer.
+    #[default]
+    Pseudonymize,
+    /// Replace values with their label (`[EMAIL]`); nothing is restored.
+    Strip,
+}
+
+/// A kind of sensitive value, matched by a regular expression.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PrivacyRule {
+    /// Name used in placeholders, e.g. `EMAIL`.
+    pub label: String,
+    /// Regular expression matching the sensitive values.
+    pub pattern: String,
+}
+
+impl PrivacyRule {
+    pub fn new(label: &str, pattern: &str) -> Self {
+        Self {
+            label: label.to_uppercase(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    /// Matches one exact value, e.g. a customer or project name.
+    pub fn literal(label: &str, value: &str) -> Self {
+        Self::new(label, &regex::escape(value))
+    }
+
+    /// Matches email addresses.
+    pub fn email() -> Self {
+        Self::new("EMAIL", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}")
+    }
+
+    /// Matches phone numbers written with an optional country code and separators.
+    pub fn phone() -> Self {
+        Self::new("PHONE", r"\+?\d[\d ().-]{7,}\d")
+    }
+}
+
+/// Privacy mode settings: which values never leave the machine, and how they are replaced.
+///
+/// Set on `AiConfig::privacy`, it applies to `ask_question`, `ask_question_stream` and
+/// everything built on them. Placeholders in the answer are restored locally.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::privacy::{PrivacyConfig, PrivacyMode, PrivacyRule};
+///
+/// let ai_config = AiConfig {
+///     privacy: Some(PrivacyConfig {
+///         mode: PrivacyMode::Pseudonymize,
+///         rules: vec![PrivacyRule::email(), PrivacyRule::literal("CLIENT", "Acme Corp")],
+///     }),
+///     ..ai_config
+/// };
+///
+/// // The provider sees "Draft a reply to [EMAIL_1] at [CLIENT_1]"
+/// let answer = ask_question(&ai_config, question).await?;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PrivacyConfig {
+    #[serde(default)]
+    pub mode: PrivacyMode,
+    pub rules: Vec<PrivacyRule>,
+}
+
+impl PrivacyConfig {
+    /// Replaces sensitive values in every part of `question`, returning the redacted question
+    /// and the mapping needed to restore the answer.
+    pub fn redact(&self, question: Question) -> Result<(Question, Redactions)> {
+        let rules = self.rules()?;
+        let mut redactions = Redactions::default();
+        let mut redact = |text: String| self.replace(&rules, text, &mut redactions);
+
+        let question = Question {
+            system_prompt: question.system_prompt.map(&mut redact),
+            messages: question.messages.map(|messages| {
+                messages
+                    .into_iter()
+                    .map(|prompt| AiPrompt {
+                        content: redact(prompt.content),
+                        output: redact(prompt.output),
+                    })
+                    .collect()
+            }),
+            new_prompt: redact(question.new_prompt),
+            ..question
+        };
+        Ok((question, redactions))
+    }
+
+    /// Replaces sensitive values in `text`, e.g. a tool result going back to the model,
+    /// adding them to the `redactions` of the question it answers.
+    pub fn redact_text(&self, text: &str, redactions: &mut Redactions) -> Result<String> {
+        let rules = self.rules()?;
+        Ok(self.replace(&rules, text.to_string(), redactions))
+    }
+
+    fn rules(&self) -> Result<Vec<(&str, Regex)>> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|regex| (rule.label.as_str(), regex))
+                    .map_err(|e| {
+                        AppError::UnexpectedError(format!(
+                            "Invalid privacy pattern for {}: {}",
+                            rule.label, e
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    fn replace(
+        &self,
+        rules: &[(&str, Regex)],
+        text: String,
+        redactions: &mut Redactions,
+    ) -> String {
+        rules.iter().fold(text, |text, (label, regex)| {
+            regex
+                .replace_all(&text, |caps: &regex::Captures| {
+                    redactions.placeholder(self.mode, label, &caps[0])
+                })
+                .into_owned()
+        })
+    }
+}
+
+/// What was redacted from a question, and how to put it back.
+#[derive(Debug, Clone, Default)]
+pub struct Redactions {
+    /// Placeholder to original value; empty in `Strip` mode.
+    originals: HashMap<String, String>,
+    /// Original value to placeholder, so repeated values share one placeholder.
+    placeholders: HashMap<String, String>,
+    counts: HashMap<String, usize>,
+    labels: Vec<String>,
+}
+
+impl Redactions {
+    fn placeholder(&mut self, mode: PrivacyMode, label: &str, value: &str) -> String {
+        self.labels.push(label.to_string());
+        if mode == PrivacyMode::Strip {
+            return format!("[{}]", label);
+        }
+        if let Some(placeholder) = self.placeholders.get(value) {
+            return placeholder.clone();
+        }
+
+        let count = self.counts.entry(label.to_string()).or_insert(0);
+        *count += 1;
+        let placeholder = format!("[{}_{}]", label, count);
+        self.placeholders
+            .insert(value.to_string(), placeholder.clone());
+        self.originals
+            .insert(placeholder.clone(), value.to_string());
+        placeholder
+    }
+
+    /// Labels of every value that was replaced, in order of appearance.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Puts the original values back in place of their placeholders.
+    pub fn restore(&self, text: &str) -> String {
+        self.originals
+            .iter()
+            .fold(text.to_string(), |text, (placeholder, original)| {
+                text.replace(placeholder, original)
+            })
+    }
+
+    /// Restores placeholders in every string of a JSON response.
+    pub(crate) fn restore_json(&self, value: Value) -> Value {
+        match value {
+            Value::String(text) => Value::String(self.restore(&text)),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.restore_json(v)).collect())
+            }
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, v)| (key, self.restore_json(v)))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Restores placeholders in a streamed answer, where one may be split across deltas.
+    pub fn restorer(self) -> Restorer {
+        Restorer {
+            redactions: self,
+            pending: String::new(),
+        }
+    }
+}
+
+/// Incremental placeholder restoration for streamed answers.
+#[derive(Debug)]
+pub struct Restorer {
+    redactions: Redactions,
+    pending: String,
+}
+
+impl Restorer {
+    /// Feeds the next delta, returning the text that is safe to emit.
+    ///
+    /// A trailing `[` that may open a placeholder is held back until it is closed (or grows
+    /// too long to be one).
+    pub fn push(&mut self, delta: &str) -> String {
+        self.pending.push_str(delta);
+        let restored = self.redactions.restore(&self.pending);
+
+        let hold_from = restored
+            .rfind('[')
+            .filter(|&start| {
+                !restored[start..].contains(']') && restored.len() - start < MAX_PLACEHOLDER_LEN
+            })
+            .unwrap_or(restored.len());
+
+        self.pending = restored[hold_from..].to_string();
+        restored[..hold_from].to_string()
+    }
+
+    /// Returns whatever is still held back once the stream ends.
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        self.redactions.restore(&rest)
+    }
+}
@@ -0,0 +1,342 @@
+use crate::ask_ai::{
+    anthropic_request, build_anthropic_payload, build_payload, get_anthropic_response,
+    get_ollama_response, get_openai_response, ollama_http_request, openai_request, send_raw,
+    unsupported_provider,
+};
+use crate::bedrock::{bedrock_request, build_bedrock_payload, get_bedrock_response};
+use crate::config::{AiConfig, Framework, Question};
+use crate::error::Result;
+use crate::lmstudio::with_loaded_model;
+use crate::replicate::{get_replicate_response, replicate_prediction};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+type Registry = Mutex<HashMap<String, Arc<dyn Provider>>>;
+
+/// A provider's answer to one request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completion {
+    pub answer: String,
+    /// Whether the answer was cut off by the token limit, for `AiConfig::auto_continue`.
+    pub truncated: bool,
+}
+
+/// A backend questions are sent to.
+///
+/// `ask_question` resolves the provider for `AiConfig::llm`, either one of the built-in
+/// clients or one registered under a `Framework::Custom` name with `register_provider`, and
+/// hands it the question once everything else is applied: caching, replay, hedging,
+/// privacy mode, compression, limits and context overflow all work unchanged for custom
+/// providers. Streaming a custom provider (or Bedrock or Replicate) yields its whole answer
+/// as one chunk.
+///
+/// The provider is responsible for honouring `AiConfig::local_only` and the model, token
+/// limit and sampling parameters in `ai_config`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::provider::{register_provider, BoxFuture, Completion, Provider};
+///
+/// struct Echo;
+///
+/// impl Provider for Echo {
+///     fn ask<'a>(
+///         &'a self,
+///         _ai_config: &'a AiConfig,
+///         question: Question,
+///     ) -> BoxFuture<'a, Result<Completion>> {
+///         Box::pin(async move {
+///             Ok(Completion {
+///                 answer: question.new_prompt,
+///                 truncated: false,
+///             })
+///         })
+///     }
+/// }
+///
+/// register_provider("echo", Echo);
+/// let ai_config = AiConfig {
+///     llm: "echo".parse()?,
+///     ..ai_config
+/// };
+/// let answer = ask_question(&ai_config, question).await?;
+/// ```
+pub trait Provider: Send + Sync {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>>;
+
+    /// The provider's full JSON response, for `ask_question_raw`.
+    ///
+    /// Defaults to the answer of `ask` in the shape of an OpenAI chat completion, with a
+    /// `length` finish reason when it was truncated.
+    fn ask_raw<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move {
+            let Completion { answer, truncated } = self.ask(ai_config, question).await?;
+            Ok(serde_json::json!({
+                "object": "chat.completion",
+                "model": ai_config.model,
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": answer },
+                    "finish_reason": if truncated { "length" } else { "stop" }
+                }]
+            }))
+        })
+    }
+}
+
+/// A chat completions client for OpenAI's protocol: OpenAI itself, and the compatible APIs
+/// of Mistral, Groq, OpenRouter and Hugging Face.
+///
+/// Questions go to the endpoint and key of `framework`, whatever `AiConfig::llm` is, so it
+/// can also be registered for a custom name, e.g. to reach an OpenAI-compatible server at
+/// `AiConfig::base_url`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenAICompatibleProvider {
+    framework: Framework,
+}
+
+impl OpenAICompatibleProvider {
+    pub fn new(framework: Framework) -> Self {
+        Self { framework }
+    }
+
+    /// `ai_config`, speaking to the endpoint of `framework`.
+    fn config<'a>(&self, ai_config: &'a AiConfig) -> Cow<'a, AiConfig> {
+        if ai_config.llm == self.framework {
+            return Cow::Borrowed(ai_config);
+        }
+        Cow::Owned(AiConfig {
+            llm: self.framework.clone(),
+            ..ai_config.clone()
+        })
+    }
+}
+
+impl Provider for OpenAICompatibleProvider {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let ai_config = self.config(ai_config);
+            let (answer, truncated) = get_openai_response(question, &ai_config).await?;
+            Ok(Completion { answer, truncated })
+        })
+    }
+
+    fn ask_raw<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move {
+            let ai_config = self.config(ai_config);
+            let builder = openai_request(&ai_config)?.json(&build_payload(&question, &ai_config));
+            send_raw(builder, &ai_config).await
+        })
+    }
+}
+
+/// The Anthropic messages client.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnthropicProvider;
+
+impl Provider for AnthropicProvider {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let (answer, truncated) = get_anthropic_response(question, ai_config).await?;
+            Ok(Completion { answer, truncated })
+        })
+    }
+
+    fn ask_raw<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move {
+            let payload = build_anthropic_payload(&question, ai_config);
+            send_raw(anthropic_request(ai_config)?.json(&payload), ai_config).await
+        })
+    }
+}
+
+/// The Ollama chat client.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OllamaProvider;
+
+impl Provider for OllamaProvider {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let (answer, truncated) = get_ollama_response(question, ai_config).await?;
+            Ok(Completion { answer, truncated })
+        })
+    }
+
+    fn ask_raw<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move {
+            send_raw(ollama_http_request(question, ai_config, false)?, ai_config).await
+        })
+    }
+}
+
+/// The LM Studio client, an OpenAI-compatible local server.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LmStudioProvider;
+
+impl Provider for LmStudioProvider {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let ai_config = with_loaded_model(ai_config).await?;
+            let (answer, truncated) = get_openai_response(question, &ai_config).await?;
+            Ok(Completion { answer, truncated })
+        })
+    }
+
+    fn ask_raw<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move {
+            let ai_config = with_loaded_model(ai_config).await?;
+            let builder = openai_request(&ai_config)?.json(&build_payload(&question, &ai_config));
+            send_raw(builder, &ai_config).await
+        })
+    }
+}
+
+/// The AWS Bedrock Converse client.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BedrockProvider;
+
+impl Provider for BedrockProvider {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let (answer, truncated) = get_bedrock_response(question, ai_config).await?;
+            Ok(Completion { answer, truncated })
+        })
+    }
+
+    fn ask_raw<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move {
+            let payload = build_bedrock_payload(&question, ai_config);
+            send_raw(bedrock_request(ai_config, &payload)?, ai_config).await
+        })
+    }
+}
+
+/// The Replicate predictions client.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReplicateProvider;
+
+impl Provider for ReplicateProvider {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let (answer, truncated) = get_replicate_response(question, ai_config).await?;
+            Ok(Completion { answer, truncated })
+        })
+    }
+
+    /// The finished prediction, rather than the one first returned.
+    fn ask_raw<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move { replicate_prediction(&question, ai_config).await })
+    }
+}
+
+/// Makes `provider` answer questions for `Framework::Custom(name)`, ignoring case. Replaces
+/// any provider registered under the same name.
+///
+/// Built-in providers cannot be replaced: `openai`, `anthropic`, `ollama`, `lmstudio`,
+/// `bedrock`, `mistral`, `groq`, `openrouter`, `huggingface` and `replicate` parse to their
+/// own `Framework` variants, never to `Framework::Custom`.
+pub fn register_provider(name: &str, provider: impl Provider + 'static) {
+    registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.trim().to_lowercase(), Arc::new(provider));
+}
+
+/// Removes the provider registered under `name`. Returns whether there was one.
+pub fn unregister_provider(name: &str) -> bool {
+    registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&name.trim().to_lowercase())
+        .is_some()
+}
+
+/// The provider for `AiConfig::llm`.
+pub(crate) fn provider(ai_config: &AiConfig) -> Result<Arc<dyn Provider>> {
+    match &ai_config.llm {
+        framework @ (Framework::OpenAI
+        | Framework::Mistral
+        | Framework::Groq
+        | Framework::OpenRouter
+        | Framework::HuggingFace) => Ok(Arc::new(OpenAICompatibleProvider::new(framework.clone()))),
+        Framework::Anthropic => Ok(Arc::new(AnthropicProvider)),
+        Framework::Ollama => Ok(Arc::new(OllamaProvider)),
+        Framework::LmStudio => Ok(Arc::new(LmStudioProvider)),
+        Framework::Bedrock => Ok(Arc::new(BedrockProvider)),
+        Framework::Replicate => Ok(Arc::new(ReplicateProvider)),
+        Framework::Custom(name) => registry()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&name.to_lowercase())
+            .cloned()
+            .ok_or_else(|| unsupported_provider(ai_config)),
+    }
+}
+
+fn registry() -> &'static Registry {
+    static PROVIDERS: OnceLock<Registry> = OnceLock::new();
+    PROVIDERS.get_or_init(Default::default)
+}
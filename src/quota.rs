@@ -0,0 +1,366 @@
+use crate::error::{AppError, Result};
+use crate::tenant::{TenantQuota, TenantUsage};
+use futures_util::future::BoxFuture;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Where tenant quota usage is counted.
+///
+/// `TenantRegistry` counts in memory by default, so every process has its own quota. A shared
+/// store (e.g. `RedisQuotaStore`) lets several replicas draw from one quota.
+pub trait QuotaStore: fmt::Debug + Send + Sync {
+    /// Counts a request and its prompt `tokens` in the current window, or fails with
+    /// `AppError::QuotaExceeded` if the quota is used up.
+    fn reserve<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+        tokens: u64,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Adds answer `tokens` to the current window.
+    fn add_tokens<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+        tokens: u64,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Returns the usage in the current window, if anything was counted in it.
+    fn usage<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+    ) -> BoxFuture<'a, Result<Option<TenantUsage>>>;
+
+    /// Forgets a tenant's usage.
+    fn clear<'a>(&'a self, tenant_id: &'a str, quota: &'a TenantQuota)
+        -> BoxFuture<'a, Result<()>>;
+}
+
+/// Lets one store be shared by several registries.
+impl<T: QuotaStore + ?Sized> QuotaStore for Arc<T> {
+    fn reserve<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+        tokens: u64,
+    ) -> BoxFuture<'a, Result<()>> {
+        (**self).reserve(tenant_id, quota, tokens)
+    }
+
+    fn add_tokens<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+        tokens: u64,
+    ) -> BoxFuture<'a, Result<()>> {
+        (**self).add_tokens(tenant_id, quota, tokens)
+    }
+
+    fn usage<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+    ) -> BoxFuture<'a, Result<Option<TenantUsage>>> {
+        (**self).usage(tenant_id, quota)
+    }
+
+    fn clear<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+    ) -> BoxFuture<'a, Result<()>> {
+        (**self).clear(tenant_id, quota)
+    }
+}
+
+/// Counts usage in process memory. Windows start with a tenant's first request.
+#[derive(Debug, Default)]
+pub struct MemoryQuotaStore {
+    usage: Mutex<HashMap<String, TenantUsage>>,
+}
+
+impl MemoryQuotaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn usage_map(&self) -> std::sync::MutexGuard<'_, HashMap<String, TenantUsage>> {
+        self.usage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn reserve_now(&self, tenant_id: &str, quota: &TenantQuota, tokens: u64) -> Result<()> {
+        let now = SystemTime::now();
+        let window = Duration::from_secs(quota.window_secs);
+        let mut usage = self.usage_map();
+        let usage = usage.entry(tenant_id.to_string()).or_insert(TenantUsage {
+            requests: 0,
+            tokens: 0,
+            window_start: now,
+        });
+
+        let elapsed = now.duration_since(usage.window_start).unwrap_or_default();
+        if elapsed >= window {
+            *usage = TenantUsage {
+                requests: 0,
+                tokens: 0,
+                window_start: now,
+            };
+        }
+
+        if exhausted(quota, usage) {
+            return Err(AppError::QuotaExceeded {
+                tenant: tenant_id.to_string(),
+                reset_at: usage.window_start + window,
+            });
+        }
+
+        usage.requests += 1;
+        usage.tokens += tokens;
+        Ok(())
+    }
+}
+
+impl QuotaStore for MemoryQuotaStore {
+    fn reserve<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        quota: &'a TenantQuota,
+        tokens: u64,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.reserve_now(tenant_id, quota, tokens) })
+    }
+
+    fn add_tokens<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        _: &'a TenantQuota,
+        tokens: u64,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if let Some(usage) = self.usage_map().get_mut(tenant_id) {
+                usage.tokens += tokens;
+            }
+            Ok(())
+        })
+    }
+
+    fn usage<'a>(
+        &'a self,
+        tenant_id: &'a str,
+        _: &'a TenantQuota,
+    ) -> BoxFuture<'a, Result<Option<TenantUsage>>> {
+        Box::pin(async move { Ok(self.usage_map().get(tenant_id).copied()) })
+    }
+
+    fn clear<'a>(&'a self, tenant_id: &'a str, _: &'a TenantQuota) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.usage_map().remove(tenant_id);
+            Ok(())
+        })
+    }
+}
+
+fn exhausted(quota: &TenantQuota, usage: &TenantUsage) -> bool {
+    let out_of_requests = quota.max_requests.is_some_and(|max| usage.requests >= max);
+    let out_of_tokens = quota.max_tokens.is_some_and(|max| usage.tokens >= max);
+    out_of_requests || out_of_tokens
+}
+
+#[cfg(feature = "redis")]
+pub use redis_store::RedisQuotaStore;
+
+#[cfg(feature = "redis")]
+mod redis_store {
+    use super::QuotaStore;
+    use crate::error::{AppError, Result};
+    use crate::tenant::{TenantQuota, TenantUsage};
+    use futures_util::future::BoxFuture;
+    use redis::aio::MultiplexedConnection;
+    use std::fmt;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// Checks and counts a reservation atomically, so concurrent replicas cannot overshoot.
+    const RESERVE: &str = r"
+        local requests = tonumber(redis.call('HGET', KEYS[1], 'requests') or '0')
+        local tokens = tonumber(redis.call('HGET', KEYS[1], 'tokens') or '0')
+        local max_requests = tonumber(ARGV[1])
+        local max_tokens = tonumber(ARGV[2])
+        if (max_requests >= 0 and requests >= max_requests)
+            or (max_tokens >= 0 and tokens >= max_tokens) then
+            return 0
+        end
+        redis.call('HINCRBY', KEYS[1], 'requests', 1)
+        redis.call('HINCRBY', KEYS[1], 'tokens', ARGV[3])
+        redis.call('EXPIRE', KEYS[1], ARGV[4])
+        return 1
+    ";
+
+    /// Adds tokens to a window that is still live; a new window is left to the next reservation.
+    const ADD_TOKENS: &str = r"
+        if redis.call('EXISTS', KEYS[1]) == 1 then
+            redis.call('HINCRBY', KEYS[1], 'tokens', ARGV[1])
+        end
+        return 1
+    ";
+
+    /// Counts usage in Redis, shared by every replica using the same server and key prefix.
+    ///
+    /// Windows are aligned to multiples of `window_secs` since the Unix epoch, so all replicas
+    /// agree on when they reset. Each window is one hash that expires with the window.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// use ask_ai::quota::RedisQuotaStore;
+    ///
+    /// let store = RedisQuotaStore::open("redis://quota.internal:6379", "ask_ai:quota").await?;
+    /// let registry = TenantRegistry::new().with_quota_store(store);
+    /// ```
+    #[derive(Clone)]
+    pub struct RedisQuotaStore {
+        connection: MultiplexedConnection,
+        prefix: String,
+    }
+
+    impl fmt::Debug for RedisQuotaStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RedisQuotaStore")
+                .field("prefix", &self.prefix)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RedisQuotaStore {
+        /// Connects to the Redis server at `url`; keys are named `<prefix>:<tenant>:<window>`.
+        pub async fn open(url: &str, prefix: &str) -> Result<Self> {
+            let client = redis::Client::open(url).map_err(redis_error)?;
+            let connection = client
+                .get_multiplexed_async_connection()
+                .await
+                .map_err(redis_error)?;
+
+            Ok(Self {
+                connection,
+                prefix: prefix.to_string(),
+            })
+        }
+
+        /// The key and start of the current window.
+        fn window(&self, tenant_id: &str, quota: &TenantQuota) -> (String, u64) {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let start = now - now % quota.window_secs.max(1);
+            (format!("{}:{}:{}", self.prefix, tenant_id, start), start)
+        }
+
+        async fn query<T: redis::FromRedisValue>(&self, command: &redis::Cmd) -> Result<T> {
+            // Multiplexed connections are cheap to clone and share one socket
+            let mut connection = self.connection.clone();
+            command
+                .query_async(&mut connection)
+                .await
+                .map_err(redis_error)
+        }
+
+        async fn reserve_now(
+            &self,
+            tenant_id: &str,
+            quota: &TenantQuota,
+            tokens: u64,
+        ) -> Result<()> {
+            let (key, start) = self.window(tenant_id, quota);
+            let limit = |max: Option<u64>| max.map_or(-1, |max| max as i64);
+
+            let mut connection = self.connection.clone();
+            let reserved: bool = redis::Script::new(RESERVE)
+                .key(&key)
+                .arg(limit(quota.max_requests.map(u64::from)))
+                .arg(limit(quota.max_tokens))
+                .arg(tokens)
+                .arg(quota.window_secs)
+                .invoke_async(&mut connection)
+                .await
+                .map_err(redis_error)?;
+            if reserved {
+                return Ok(());
+            }
+
+            Err(AppError::QuotaExceeded {
+                tenant: tenant_id.to_string(),
+                reset_at: UNIX_EPOCH + Duration::from_secs(start + quota.window_secs),
+            })
+        }
+    }
+
+    impl QuotaStore for RedisQuotaStore {
+        fn reserve<'a>(
+            &'a self,
+            tenant_id: &'a str,
+            quota: &'a TenantQuota,
+            tokens: u64,
+        ) -> BoxFuture<'a, Result<()>> {
+            Box::pin(self.reserve_now(tenant_id, quota, tokens))
+        }
+
+        fn add_tokens<'a>(
+            &'a self,
+            tenant_id: &'a str,
+            quota: &'a TenantQuota,
+            tokens: u64,
+        ) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                let (key, _) = self.window(tenant_id, quota);
+                let mut connection = self.connection.clone();
+                redis::Script::new(ADD_TOKENS)
+                    .key(&key)
+                    .arg(tokens)
+                    .invoke_async::<i64>(&mut connection)
+                    .await
+                    .map(|_| ())
+                    .map_err(redis_error)
+            })
+        }
+
+        fn usage<'a>(
+            &'a self,
+            tenant_id: &'a str,
+            quota: &'a TenantQuota,
+        ) -> BoxFuture<'a, Result<Option<TenantUsage>>> {
+            Box::pin(async move {
+                let (key, start) = self.window(tenant_id, quota);
+                let (requests, tokens): (Option<u32>, Option<u64>) = self
+                    .query(redis::cmd("HMGET").arg(&key).arg("requests").arg("tokens"))
+                    .await?;
+
+                Ok(requests.map(|requests| TenantUsage {
+                    requests,
+                    tokens: tokens.unwrap_or_default(),
+                    window_start: UNIX_EPOCH + Duration::from_secs(start),
+                }))
+            })
+        }
+
+        fn clear<'a>(
+            &'a self,
+            tenant_id: &'a str,
+            quota: &'a TenantQuota,
+        ) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                let (key, _) = self.window(tenant_id, quota);
+                self.query::<i64>(redis::cmd("DEL").arg(&key))
+                    .await
+                    .map(|_| ())
+            })
+        }
+    }
+
+    fn redis_error(e: redis::RedisError) -> AppError {
+        AppError::UnexpectedError(format!("Redis quota store error: {}", e))
+    }
+}
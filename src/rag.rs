@@ -0,0 +1,214 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Question};
+use crate::error::Result;
+use crate::rerank::rerank;
+use crate::vectorstore::{SearchHit, VectorStore};
+use serde::{Deserialize, Serialize};
+
+/// With a reranker, this many candidates per retrieved chunk are fetched for it to order.
+const RERANK_CANDIDATES: usize = 4;
+
+const CONTEXT_PROMPT: &str = "Answer using the context below. If it does not contain the \
+    answer, say so rather than guessing.";
+
+/// Retrieval-augmented generation: documents are chunked and embedded into a vector store, and
+/// each question is answered with the chunks most similar to it in the system prompt.
+///
+/// Chunks hold at most `chunk_size` characters, cut at whitespace where possible, and repeat the
+/// last `chunk_overlap` characters of the previous chunk so that a sentence split between two
+/// chunks is still found whole in one of them. `embedding` names the embedding model (see
+/// `embeddings::get_embeddings`); the answering model is passed to `ask`, and may be any provider.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::rag::Rag;
+///
+/// let mut rag = Rag::new(AiConfig {
+///     llm: Framework::Ollama,
+///     model: "nomic-embed-text".to_string(),
+///     ..Default::default()
+/// });
+/// rag.add_document("ownership", &std::fs::read_to_string("ownership.md")?).await?;
+/// rag.add_document("borrowing", &std::fs::read_to_string("borrowing.md")?).await?;
+///
+/// let answer = rag.ask(&ai_config, question).await?;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Rag {
+    /// The model documents and questions are embedded with.
+    pub embedding: AiConfig,
+    /// Maximum characters per chunk.
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Characters each chunk repeats from the end of the previous one.
+    #[serde(default = "default_chunk_overlap")]
+    pub chunk_overlap: usize,
+    /// Number of chunks retrieved for each question.
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    /// Optional reranking model (see `rerank::rerank`): the embedding search then fetches
+    /// `4 * top_k` candidates, of which the `top_k` ranked highest are kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reranker: Option<AiConfig>,
+    /// The embedded chunks, stored as `{document id}#{chunk index}`.
+    #[serde(default)]
+    pub store: VectorStore,
+}
+
+fn default_chunk_size() -> usize {
+    1000
+}
+
+fn default_chunk_overlap() -> usize {
+    200
+}
+
+fn default_top_k() -> usize {
+    4
+}
+
+impl Rag {
+    /// An empty pipeline embedding with `embedding`, with 1000-character chunks overlapping by
+    /// 200 and 4 chunks retrieved per question.
+    pub fn new(embedding: AiConfig) -> Self {
+        Self {
+            embedding,
+            chunk_size: default_chunk_size(),
+            chunk_overlap: default_chunk_overlap(),
+            top_k: default_top_k(),
+            reranker: None,
+            store: VectorStore::new(),
+        }
+    }
+
+    /// Chunks and embeds `text`, replacing the chunks of any document stored under `id`.
+    /// Returns the number of chunks stored.
+    pub async fn add_document(&mut self, id: &str, text: &str) -> Result<usize> {
+        let chunks = chunk_text(text, self.chunk_size, self.chunk_overlap);
+        let ids: Vec<String> = (0..chunks.len()).map(|n| chunk_id(id, n)).collect();
+        let texts: Vec<(&str, &str)> = ids
+            .iter()
+            .zip(&chunks)
+            .map(|(id, chunk)| (id.as_str(), chunk.as_str()))
+            .collect();
+        self.store.insert_texts(&self.embedding, &texts).await?;
+
+        // Drop the chunks left over from a longer previous version of the document
+        let mut n = chunks.len();
+        while self.store.remove(&chunk_id(id, n)) {
+            n += 1;
+        }
+        Ok(chunks.len())
+    }
+
+    /// The `top_k` chunks most similar to `query`, most similar first.
+    ///
+    /// With a reranker, the chunks are in its order and scored with its relevance scores.
+    pub async fn retrieve(&self, query: &str) -> Result<Vec<SearchHit>> {
+        if self.store.is_empty() {
+            return Ok(vec![]);
+        }
+        let Some(reranker) = &self.reranker else {
+            return self
+                .store
+                .search_text(&self.embedding, query, self.top_k)
+                .await;
+        };
+
+        let candidates = self
+            .store
+            .search_text(&self.embedding, query, self.top_k * RERANK_CANDIDATES)
+            .await?;
+        let documents: Vec<&str> = candidates.iter().map(|hit| hit.text.as_str()).collect();
+        let ranked = rerank(reranker, query, &documents).await?;
+        Ok(ranked
+            .into_iter()
+            .take(self.top_k)
+            .map(|result| SearchHit {
+                score: result.score,
+                ..candidates[result.index].clone()
+            })
+            .collect())
+    }
+
+    /// Retrieves the chunks most similar to the question's new prompt, adds them to its system
+    /// prompt, and asks it with `ai_config`.
+    ///
+    /// The question's own system prompt, or else the configured default, comes first. Without
+    /// stored chunks the question is asked as is.
+    pub async fn ask(&self, ai_config: &AiConfig, mut question: Question) -> Result<String> {
+        let hits = self.retrieve(&question.new_prompt).await?;
+        if !hits.is_empty() {
+            let system_prompt = question
+                .system_prompt
+                .clone()
+                .or_else(|| ai_config.prompts.as_ref()?.system_prompt.clone());
+            question.system_prompt = Some(context_prompt(system_prompt.as_deref(), &hits));
+        }
+        ask_question(ai_config, question).await
+    }
+}
+
+fn chunk_id(document_id: &str, n: usize) -> String {
+    format!("{}#{}", document_id, n)
+}
+
+/// The system prompt carrying the retrieved chunks, after `system_prompt` if any.
+fn context_prompt(system_prompt: Option<&str>, hits: &[SearchHit]) -> String {
+    let mut prompt = match system_prompt {
+        Some(system_prompt) => format!("{}\n\n{}\n\nContext:", system_prompt, CONTEXT_PROMPT),
+        None => format!("{}\n\nContext:", CONTEXT_PROMPT),
+    };
+    for (n, hit) in hits.iter().enumerate() {
+        prompt.push_str(&format!("\n\n[{}] {}", n + 1, hit.text));
+    }
+    prompt
+}
+
+/// Splits `text` into chunks of at most `chunk_size` characters, each starting `chunk_overlap`
+/// characters before the end of the previous one.
+///
+/// Chunks end at the last whitespace of their second half when there is one, and overlaps start
+/// at a word, so that words are not cut. Leading and trailing whitespace is trimmed, and blank
+/// chunks are dropped.
+pub fn chunk_text(text: &str, chunk_size: usize, chunk_overlap: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let chunk_size = chunk_size.max(1);
+    let chunk_overlap = chunk_overlap.min(chunk_size - 1);
+
+    let mut chunks = vec![];
+    let mut start = 0;
+    while start < chars.len() {
+        if chars[start].is_whitespace() {
+            start += 1;
+            continue;
+        }
+        let mut end = (start + chunk_size).min(chars.len());
+        if end < chars.len() {
+            if let Some(space) = chars[start + chunk_size / 2..end]
+                .iter()
+                .rposition(|c| c.is_whitespace())
+            {
+                end = start + chunk_size / 2 + space;
+            }
+        }
+        let chunk: String = chars[start..end].iter().collect();
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk.trim().to_string());
+        }
+        if end == chars.len() {
+            break;
+        }
+        let mut next = end.saturating_sub(chunk_overlap).max(start + 1);
+        // Start the overlap at a word rather than mid-word
+        if !chars[next - 1].is_whitespace() {
+            next = match chars[next..end].iter().position(|c| c.is_whitespace()) {
+                Some(space) => next + space + 1,
+                None => end,
+            };
+        }
+        start = next;
+    }
+    chunks
+}
@@ -0,0 +1,355 @@
+use crate::ask_ai::openai_endpoint;
+use crate::config::{AiConfig, Framework};
+use crate::error::{AppError, Result};
+use crate::secret::scrub_secrets;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// An event sent to a Realtime session.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum ClientEvent {
+    /// Changes the session: instructions, voice, modalities, turn detection, tools, ...
+    #[serde(rename = "session.update")]
+    SessionUpdate { session: Value },
+    /// Adds audio to the input buffer, base64-encoded in the session's input format (by
+    /// default 24 kHz mono PCM16).
+    #[serde(rename = "input_audio_buffer.append")]
+    AudioAppend { audio: String },
+    /// Turns the input buffer into a user message. Not needed with server voice detection.
+    #[serde(rename = "input_audio_buffer.commit")]
+    AudioCommit,
+    #[serde(rename = "input_audio_buffer.clear")]
+    AudioClear,
+    /// Adds an item (message, function call output) to the conversation.
+    #[serde(rename = "conversation.item.create")]
+    ItemCreate { item: Value },
+    /// Asks the model to respond, optionally with options for this response only.
+    #[serde(rename = "response.create")]
+    ResponseCreate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        response: Option<Value>,
+    },
+    /// Stops the response in progress.
+    #[serde(rename = "response.cancel")]
+    ResponseCancel,
+}
+
+/// An error reported by a Realtime session. The session stays open.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RealtimeError {
+    #[serde(default, rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// An event received from a Realtime session.
+///
+/// Names follow the generally available API; the beta names of the same events (e.g.
+/// `response.text.delta`) are accepted too. Events not listed here are kept as `Other`, with
+/// their JSON.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum ServerEvent {
+    #[serde(rename = "session.created")]
+    SessionCreated { session: Value },
+    #[serde(rename = "session.updated")]
+    SessionUpdated { session: Value },
+    #[serde(rename = "error")]
+    Error { error: RealtimeError },
+    /// Server voice detection heard the user start speaking.
+    #[serde(rename = "input_audio_buffer.speech_started")]
+    SpeechStarted {
+        #[serde(default)]
+        item_id: String,
+    },
+    #[serde(rename = "input_audio_buffer.speech_stopped")]
+    SpeechStopped {
+        #[serde(default)]
+        item_id: String,
+    },
+    #[serde(rename = "input_audio_buffer.committed")]
+    AudioCommitted {
+        #[serde(default)]
+        item_id: String,
+    },
+    #[serde(
+        rename = "conversation.item.added",
+        alias = "conversation.item.created"
+    )]
+    ItemAdded { item: Value },
+    /// The transcript of the user's audio, with input transcription enabled.
+    #[serde(rename = "conversation.item.input_audio_transcription.completed")]
+    InputTranscript {
+        #[serde(default)]
+        item_id: String,
+        transcript: String,
+    },
+    #[serde(rename = "response.created")]
+    ResponseCreated { response: Value },
+    #[serde(rename = "response.output_text.delta", alias = "response.text.delta")]
+    TextDelta {
+        #[serde(default)]
+        response_id: String,
+        delta: String,
+    },
+    #[serde(rename = "response.output_text.done", alias = "response.text.done")]
+    TextDone {
+        #[serde(default)]
+        response_id: String,
+        text: String,
+    },
+    /// A chunk of the spoken answer, in the session's output format (by default 24 kHz mono
+    /// PCM16).
+    #[serde(rename = "response.output_audio.delta", alias = "response.audio.delta")]
+    AudioDelta {
+        #[serde(default)]
+        response_id: String,
+        #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+        delta: Vec<u8>,
+    },
+    #[serde(rename = "response.output_audio.done", alias = "response.audio.done")]
+    AudioDone {
+        #[serde(default)]
+        response_id: String,
+    },
+    #[serde(
+        rename = "response.output_audio_transcript.delta",
+        alias = "response.audio_transcript.delta"
+    )]
+    TranscriptDelta {
+        #[serde(default)]
+        response_id: String,
+        delta: String,
+    },
+    #[serde(
+        rename = "response.output_audio_transcript.done",
+        alias = "response.audio_transcript.done"
+    )]
+    TranscriptDone {
+        #[serde(default)]
+        response_id: String,
+        transcript: String,
+    },
+    /// The model called a function tool; answer with a `function_call_output` item.
+    #[serde(rename = "response.function_call_arguments.done")]
+    FunctionCall {
+        call_id: String,
+        #[serde(default)]
+        name: String,
+        arguments: String,
+    },
+    /// The response is over, with its status and usage.
+    #[serde(rename = "response.done")]
+    ResponseDone { response: Value },
+    #[serde(untagged)]
+    Other(Value),
+}
+
+fn to_base64<S: Serializer>(data: &[u8], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(data))
+}
+
+fn from_base64<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+}
+
+/// A live speech-to-speech session with an OpenAI realtime model, over a WebSocket.
+///
+/// Events flow both ways at once: audio or text goes in with `send_*`, and the answer comes
+/// back as `ServerEvent`s from `next_event`, as it is generated. To send while receiving,
+/// `split` the session and give each half its own task.
+pub struct RealtimeSession {
+    sender: RealtimeSender,
+    receiver: RealtimeReceiver,
+}
+
+/// The sending half of a `RealtimeSession`.
+pub struct RealtimeSender {
+    sink: SplitSink<Socket, Message>,
+    ai_config: AiConfig,
+}
+
+/// The receiving half of a `RealtimeSession`.
+pub struct RealtimeReceiver {
+    stream: SplitStream<Socket>,
+    ai_config: AiConfig,
+}
+
+/// Opens a Realtime session with the model named by `ai_config.model`, e.g. `gpt-realtime`
+/// or `gpt-4o-realtime-preview`.
+///
+/// Connects to the `realtime` endpoint next to chat completions, over `wss://` (or `ws://`
+/// for a plain HTTP base), so `AiConfig::base_url` and `OPENAI_API_URL` apply; the HTTP
+/// client options do not. Other providers fail with `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::realtime::{connect, ServerEvent};
+///
+/// let mut session = connect(&ai_config).await?;
+/// session.send_text("Tell me a joke.").await?;
+/// while let Some(event) = session.next_event().await? {
+///     match event {
+///         ServerEvent::AudioDelta { delta, .. } => speaker.play(&delta),
+///         ServerEvent::TranscriptDelta { delta, .. } => print!("{}", delta),
+///         ServerEvent::ResponseDone { .. } => break,
+///         _ => {}
+///     }
+/// }
+/// session.close().await?;
+/// ```
+pub async fn connect(ai_config: &AiConfig) -> Result<RealtimeSession> {
+    if ai_config.llm != Framework::OpenAI {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no Realtime API", ai_config.llm),
+        });
+    }
+
+    let (api_url, api_key) = openai_endpoint(ai_config, "realtime")?;
+    // `https` becomes `wss`, and `http` becomes `ws`
+    let url = format!(
+        "{}?model={}",
+        api_url.replacen("http", "ws", 1),
+        ai_config.model
+    );
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| socket_error(ai_config, e))?;
+    if let Some(api_key) = api_key {
+        let mut value = HeaderValue::from_str(&format!("Bearer {}", api_key.expose_secret()))
+            .map_err(|_| {
+                AppError::UnexpectedError("API key is not a valid header value".to_string())
+            })?;
+        value.set_sensitive(true);
+        request.headers_mut().insert("Authorization", value);
+    }
+
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| socket_error(ai_config, e))?;
+    let (sink, stream) = socket.split();
+    Ok(RealtimeSession {
+        sender: RealtimeSender {
+            sink,
+            ai_config: ai_config.clone(),
+        },
+        receiver: RealtimeReceiver {
+            stream,
+            ai_config: ai_config.clone(),
+        },
+    })
+}
+
+impl RealtimeSession {
+    /// Splits the session, to send and receive from separate tasks.
+    pub fn split(self) -> (RealtimeSender, RealtimeReceiver) {
+        (self.sender, self.receiver)
+    }
+
+    pub async fn send(&mut self, event: &ClientEvent) -> Result<()> {
+        self.sender.send(event).await
+    }
+
+    pub async fn send_text(&mut self, text: &str) -> Result<()> {
+        self.sender.send_text(text).await
+    }
+
+    pub async fn send_audio(&mut self, audio: &[u8]) -> Result<()> {
+        self.sender.send_audio(audio).await
+    }
+
+    pub async fn next_event(&mut self) -> Result<Option<ServerEvent>> {
+        self.receiver.next_event().await
+    }
+
+    pub async fn close(self) -> Result<()> {
+        self.sender.close().await
+    }
+}
+
+impl RealtimeSender {
+    /// Sends `event` to the session.
+    pub async fn send(&mut self, event: &ClientEvent) -> Result<()> {
+        let text = serde_json::to_string(event)
+            .map_err(|e| AppError::UnexpectedError(format!("Invalid event: {}", e)))?;
+        self.sink
+            .send(Message::text(text))
+            .await
+            .map_err(|e| socket_error(&self.ai_config, e))
+    }
+
+    /// Adds a user text message to the conversation and asks for a response.
+    pub async fn send_text(&mut self, text: &str) -> Result<()> {
+        let item = serde_json::json!({
+            "type": "message",
+            "role": "user",
+            "content": [{ "type": "input_text", "text": text }]
+        });
+        self.send(&ClientEvent::ItemCreate { item }).await?;
+        self.send(&ClientEvent::ResponseCreate { response: None })
+            .await
+    }
+
+    /// Appends raw audio, in the session's input format, to the input buffer.
+    pub async fn send_audio(&mut self, audio: &[u8]) -> Result<()> {
+        self.send(&ClientEvent::AudioAppend {
+            audio: STANDARD.encode(audio),
+        })
+        .await
+    }
+
+    /// Closes the session.
+    pub async fn close(mut self) -> Result<()> {
+        self.sink
+            .close()
+            .await
+            .map_err(|e| socket_error(&self.ai_config, e))
+    }
+}
+
+impl RealtimeReceiver {
+    /// The next event of the session, or `None` once it is closed.
+    pub async fn next_event(&mut self) -> Result<Option<ServerEvent>> {
+        while let Some(message) = self.stream.next().await {
+            match message.map_err(|e| socket_error(&self.ai_config, e))? {
+                Message::Text(text) => {
+                    return serde_json::from_str(&text).map(Some).map_err(|e| {
+                        AppError::ModelError {
+                            model_name: self.ai_config.model.to_string(),
+                            failure_str: format!("Failed to parse realtime event: {}", e),
+                        }
+                    });
+                }
+                Message::Close(_) => return Ok(None),
+                // Pings are answered by the socket itself
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn socket_error(ai_config: &AiConfig, e: impl std::fmt::Display) -> AppError {
+    AppError::ApiError {
+        model_name: ai_config.llm.to_string(),
+        failure_str: scrub_secrets(&format!("WebSocket error: {}", e), ai_config),
+    }
+}
@@ -0,0 +1,215 @@
+use crate::ask_ai::dispatch;
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Whether a `Replay` talks to the provider or answers from its cassette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Send questions to the provider and append each exchange to the cassette.
+    Record,
+    /// Answer from the cassette, in order, without any network access.
+    Replay,
+}
+
+/// One recorded question and its answer.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Interaction {
+    /// Canonical JSON of everything that shapes the answer: provider, model, parameters and
+    /// the full question.
+    pub request: String,
+    pub answer: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+struct ReplayState {
+    cassette: Cassette,
+    cursor: usize,
+}
+
+/// Pins a whole (multi-turn) interaction to a cassette file and replays it byte-for-byte.
+///
+/// Set it on `AiConfig::replay` and every question asked with that config, including through
+/// `Conversation`, `TenantRegistry` or `ask_question_stream`, goes through the cassette. In
+/// replay mode each question must match the recorded one exactly, so a changed prompt fails
+/// the test instead of silently drifting. Combine with `AiConfig::seed` when recording to keep
+/// re-recordings stable.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::replay::Replay;
+///
+/// // `ASK_AI_REPLAY=record cargo test` refreshes the cassette; plain `cargo test` replays it
+/// let replay = Replay::from_env("tests/cassettes/onboarding.json")?;
+/// let ai_config = AiConfig { replay: Some(replay.clone()), seed: Some(42), ..ai_config };
+///
+/// let mut conversation = Conversation::new(None);
+/// conversation.ask(&ai_config, "Hi!").await?;
+/// conversation.ask(&ai_config, "What can you do?").await?;
+/// replay.finish()?;
+/// ```
+#[derive(Clone)]
+pub struct Replay {
+    path: PathBuf,
+    mode: ReplayMode,
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl Replay {
+    /// Starts recording a new cassette at `path`, replacing any previous one.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::with_cassette(path.into(), ReplayMode::Record, Cassette::default())
+    }
+
+    /// Loads the cassette at `path` for replay.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let data = fs::read(&path).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let cassette = serde_json::from_slice(&data).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to parse {}: {}", path.display(), e))
+        })?;
+
+        Ok(Self::with_cassette(path, ReplayMode::Replay, cassette))
+    }
+
+    /// Records when the `ASK_AI_REPLAY` environment variable is `record`, replays otherwise.
+    pub fn from_env(path: impl Into<PathBuf>) -> Result<Self> {
+        match env::var("ASK_AI_REPLAY").as_deref() {
+            Ok("record") => Ok(Self::record(path)),
+            _ => Self::open(path),
+        }
+    }
+
+    fn with_cassette(path: PathBuf, mode: ReplayMode, cassette: Cassette) -> Self {
+        Self {
+            path,
+            mode,
+            state: Arc::new(Mutex::new(ReplayState {
+                cassette,
+                cursor: 0,
+            })),
+        }
+    }
+
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    /// Checks that a replay used every recorded interaction.
+    pub fn finish(&self) -> Result<()> {
+        let state = self.state();
+        let remaining = state.cassette.interactions.len() - state.cursor;
+        if self.mode == ReplayMode::Replay && remaining > 0 {
+            return Err(AppError::UnexpectedError(format!(
+                "Replay of {} finished with {} unused interaction(s)",
+                self.path.display(),
+                remaining
+            )));
+        }
+        Ok(())
+    }
+
+    /// Answers `question` from the cassette, or asks the provider and records the answer.
+    pub(crate) async fn ask(&self, ai_config: &AiConfig, question: Question) -> Result<String> {
+        let request = request_key(ai_config, &question);
+        match self.mode {
+            ReplayMode::Replay => self.next_answer(&request),
+            ReplayMode::Record => {
+                let answer = dispatch(ai_config, question).await?;
+                self.append(Interaction {
+                    request,
+                    answer: answer.clone(),
+                })?;
+                Ok(answer)
+            }
+        }
+    }
+
+    fn next_answer(&self, request: &str) -> Result<String> {
+        let mut state = self.state();
+        let index = state.cursor;
+        let interaction = state.cassette.interactions.get(index).ok_or_else(|| {
+            AppError::UnexpectedError(format!(
+                "Replay of {} has no interaction {} for request {}",
+                self.path.display(),
+                index,
+                request
+            ))
+        })?;
+        if interaction.request != request {
+            return Err(AppError::UnexpectedError(format!(
+                "Replay mismatch at interaction {} of {}: recorded {}, got {}",
+                index,
+                self.path.display(),
+                interaction.request,
+                request
+            )));
+        }
+
+        let answer = interaction.answer.clone();
+        state.cursor += 1;
+        Ok(answer)
+    }
+
+    fn append(&self, interaction: Interaction) -> Result<()> {
+        let mut state = self.state();
+        state.cassette.interactions.push(interaction);
+
+        // Written after every exchange, so an aborted run still leaves a usable prefix
+        let json = serde_json::to_string_pretty(&state.cassette).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to serialize cassette: {}", e))
+        })?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                AppError::UnexpectedError(format!("Failed to create {}: {}", dir.display(), e))
+            })?;
+        }
+        fs::write(&self.path, json).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to write {}: {}", self.path.display(), e))
+        })
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replay")
+            .field("path", &self.path)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Canonical form of a request. `serde_json` objects keep their keys sorted, so the same
+/// question always yields the same bytes.
+fn request_key(ai_config: &AiConfig, question: &Question) -> String {
+    let mut key = serde_json::json!({
+        "framework": ai_config.llm,
+        "model": ai_config.model,
+        "max_token": ai_config.max_token,
+        "seed": ai_config.seed,
+        "system_prompt": question.system_prompt,
+        "messages": question.messages,
+        "new_prompt": question.new_prompt,
+    });
+    // Only when set, so cassettes recorded without it still match
+    if let Some(effort) = ai_config.reasoning_effort {
+        key["reasoning_effort"] = serde_json::json!(effort);
+    }
+    key.to_string()
+}
@@ -0,0 +1,214 @@
+use crate::ask_ai::{api_key, ensure_local, send_request, sensitive_header};
+use crate::config::{AiConfig, Framework, Question};
+use crate::error::{AppError, Result};
+use crate::http::http_client;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::RequestBuilder;
+use serde_json::Value;
+use std::env;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Replicate's API root, unless `REPLICATE_API_URL` overrides it.
+const DEFAULT_API_URL: &str = "https://api.replicate.com/v1";
+
+/// Pause between polls of a prediction that is still running.
+const POLL_INTERVAL_MS: u64 = 500;
+
+/// How long a prediction may run before it is canceled and reported as failed.
+const MAX_WAIT: Duration = Duration::from_secs(600);
+
+/// Builds the create-prediction payload for `question`.
+///
+/// Replicate's chat models take a single `prompt` and `system_prompt`, so earlier exchanges
+/// are written into the prompt as a `User:`/`Assistant:` dialogue. A model given as
+/// `owner/name:version` is pinned to that version; sampling parameters go under `input`.
+pub fn build_replicate_payload(question: &Question, ai_config: &AiConfig) -> Value {
+    let new_prompt = ai_config.new_prompt(question);
+    let mut turns = vec![];
+    for msg in question.messages.iter().flatten() {
+        if !msg.content.is_empty() {
+            turns.push(format!("User: {}", msg.content));
+        }
+        if !msg.output.is_empty() {
+            turns.push(format!("Assistant: {}", msg.output));
+        }
+    }
+    let prompt = if turns.is_empty() {
+        new_prompt
+    } else {
+        turns.push(format!("User: {}", new_prompt));
+        turns.push("Assistant:".to_string());
+        turns.join("\n\n")
+    };
+
+    let mut payload = serde_json::json!({
+        "input": {
+            "prompt": prompt,
+            "system_prompt": ai_config.system_prompt(
+                question,
+                "You are a helpful assistant. Answer the question concisely.",
+            )
+        }
+    });
+    if let Some(max_tokens) = ai_config.max_token {
+        payload["input"]["max_tokens"] = max_tokens.into();
+    }
+    if let Some(seed) = ai_config.seed {
+        payload["input"]["seed"] = seed.into();
+    }
+    if let Some((_, version)) = ai_config.model.split_once(':') {
+        payload["version"] = version.into();
+    }
+    if let Some(params) = &ai_config.params {
+        params.apply(Framework::Replicate, &ai_config.model, &mut payload);
+    }
+    payload
+}
+
+/// Prepares an authenticated POST creating a prediction, in sync mode: Replicate holds the
+/// response until the prediction finishes or a minute has passed.
+///
+/// The API root is `https://api.replicate.com/v1` unless the `REPLICATE_API_URL` environment
+/// variable overrides it.
+pub(crate) fn replicate_request(ai_config: &AiConfig) -> Result<RequestBuilder> {
+    let base_url = env::var("REPLICATE_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+    let base_url = base_url.trim_end_matches('/');
+    let api_url = match ai_config.model.split_once(':') {
+        Some(_) => format!("{}/predictions", base_url),
+        None => format!("{}/models/{}/predictions", base_url, ai_config.model),
+    };
+    ensure_local(ai_config, &api_url)?;
+
+    Ok(
+        authorized(ai_config, http_client(ai_config)?.post(&api_url))?
+            .header(CONTENT_TYPE, "application/json")
+            .header("Prefer", "wait"),
+    )
+}
+
+/// Creates a prediction for `question` and polls it until it finishes. Returns the finished
+/// prediction.
+///
+/// A prediction still running after 10 minutes, e.g. stuck in `starting` while no hardware is
+/// free, is canceled and fails with `AppError::ModelError`.
+pub(crate) async fn replicate_prediction(
+    question: &Question,
+    ai_config: &AiConfig,
+) -> Result<Value> {
+    let payload = build_replicate_payload(question, ai_config);
+    let mut prediction = parse(
+        send_request(replicate_request(ai_config)?.json(&payload), ai_config).await?,
+        ai_config,
+    )
+    .await?;
+
+    let started = Instant::now();
+    loop {
+        match prediction["status"].as_str() {
+            Some("succeeded") => return Ok(prediction),
+            Some("failed") => {
+                return Err(AppError::ModelError {
+                    model_name: ai_config.model.to_string(),
+                    failure_str: format!("Prediction failed: {}", prediction["error"]),
+                })
+            }
+            Some("canceled") => {
+                return Err(AppError::ModelError {
+                    model_name: ai_config.model.to_string(),
+                    failure_str: "Prediction was canceled".to_string(),
+                })
+            }
+            _ => {}
+        }
+
+        if started.elapsed() >= MAX_WAIT {
+            cancel(&prediction, ai_config).await;
+            return Err(AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: format!(
+                    "Prediction did not finish within {} seconds and was canceled",
+                    MAX_WAIT.as_secs()
+                ),
+            });
+        }
+
+        let url = prediction["urls"]["get"]
+            .as_str()
+            .ok_or_else(|| AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: "Replicate prediction has no status URL".to_string(),
+            })?
+            .to_string();
+        ensure_local(ai_config, &url)?;
+        tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        let builder = authorized(ai_config, http_client(ai_config)?.get(&url))?;
+        prediction = parse(send_request(builder, ai_config).await?, ai_config).await?;
+    }
+}
+
+/// Cancels a running prediction through its `cancel` URL. Best effort: the prediction is
+/// given up on either way.
+async fn cancel(prediction: &Value, ai_config: &AiConfig) {
+    let Some(url) = prediction["urls"]["cancel"].as_str() else {
+        return;
+    };
+    if ensure_local(ai_config, url).is_err() {
+        return;
+    }
+    if let Ok(client) = http_client(ai_config) {
+        if let Ok(builder) = authorized(ai_config, client.post(url)) {
+            let _ = send_request(builder, ai_config).await;
+        }
+    }
+}
+
+/// Sends `question` to a Replicate model. Also returns whether the answer was cut off by the
+/// token limit.
+pub(crate) async fn get_replicate_response(
+    question: Question,
+    ai_config: &AiConfig,
+) -> Result<(String, bool)> {
+    let prediction = replicate_prediction(&question, ai_config).await?;
+    replicate_answer(&prediction, ai_config)
+}
+
+/// The answer of a finished prediction, and whether it used up `max_token`.
+///
+/// Language models return their output as a list of tokens, others as one string.
+fn replicate_answer(prediction: &Value, ai_config: &AiConfig) -> Result<(String, bool)> {
+    let answer = match &prediction["output"] {
+        Value::String(output) => output.clone(),
+        Value::Array(tokens) => tokens.iter().filter_map(Value::as_str).collect(),
+        _ => {
+            return Err(AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: "Failed to extract output from Replicate prediction".to_string(),
+            })
+        }
+    };
+
+    let truncated = match (
+        ai_config.max_token,
+        prediction["metrics"]["output_token_count"].as_u64(),
+    ) {
+        (Some(max_token), Some(count)) => count >= u64::from(max_token),
+        _ => false,
+    };
+    Ok((answer, truncated))
+}
+
+fn authorized(ai_config: &AiConfig, builder: RequestBuilder) -> Result<RequestBuilder> {
+    let api_key = api_key(ai_config, "REPLICATE_API_TOKEN")?;
+    Ok(builder.header(
+        AUTHORIZATION,
+        sensitive_header(&format!("Bearer {}", api_key.expose_secret()))?,
+    ))
+}
+
+async fn parse(resp: reqwest::Response, ai_config: &AiConfig) -> Result<Value> {
+    resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })
+}
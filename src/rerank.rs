@@ -0,0 +1,143 @@
+use crate::ask_ai::{ask_question, send_request};
+use crate::config::{AiConfig, Framework, Question};
+use crate::embeddings::cohere_request;
+use crate::error::{AppError, Result};
+use futures_util::future::try_join_all;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+const JUDGE_PROMPT: &str = "You judge how relevant a document is to a search query. Reply \
+    with a single integer from 0 (unrelated) to 10 (answers the query exactly), and nothing else.";
+
+/// A document's position in the reranked list.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RerankResult {
+    /// Index of the document in the list given to `rerank`.
+    pub index: usize,
+    /// Relevance to the query, from 0 to 1.
+    pub score: f32,
+}
+
+/// Orders `documents` by relevance to `query`, most relevant first.
+///
+/// - Cohere, as `Framework::Custom("cohere")`: the v2 `rerank` endpoint (e.g. `rerank-v3.5`),
+///   next to the embed endpoint, so `COHERE_API_URL` applies.
+/// - Any other provider: the model reads the query with each document and scores it from 0 to
+///   10, the scores being asked concurrently. Meant for a small local model on Ollama acting as
+///   a cross-encoder, when no rerank API is at hand.
+///
+/// Reranking the candidates of an embedding search, fetched generously, often beats the
+/// embedding order, as the reranker sees the query and document together.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::rerank::rerank;
+///
+/// let reranker = AiConfig {
+///     llm: Framework::Custom("cohere".to_string()),
+///     model: "rerank-v3.5".to_string(),
+///     ..Default::default()
+/// };
+/// let documents = ["Traits define shared behavior.", "Each value has one owner."];
+/// let ranked = rerank(&reranker, "Who owns a value?", &documents).await?;
+/// assert_eq!(ranked[0].index, 1);
+/// ```
+pub async fn rerank(
+    ai_config: &AiConfig,
+    query: &str,
+    documents: &[&str],
+) -> Result<Vec<RerankResult>> {
+    if documents.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut results = match &ai_config.llm {
+        Framework::Custom(name) if name.eq_ignore_ascii_case("cohere") => {
+            cohere_rerank(ai_config, query, documents).await?
+        }
+        _ => {
+            try_join_all(
+                documents
+                    .iter()
+                    .enumerate()
+                    .map(|(index, document)| judge(ai_config, query, index, document)),
+            )
+            .await?
+        }
+    };
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(results)
+}
+
+async fn cohere_rerank(
+    ai_config: &AiConfig,
+    query: &str,
+    documents: &[&str],
+) -> Result<Vec<RerankResult>> {
+    let payload = serde_json::json!({
+        "model": ai_config.model,
+        "query": query,
+        "documents": documents
+    });
+    let resp = send_request(
+        cohere_request(ai_config, "rerank")?.json(&payload),
+        ai_config,
+    )
+    .await?;
+    let response: Value = resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })?;
+
+    response["results"]
+        .as_array()
+        .and_then(|results| {
+            results
+                .iter()
+                .map(|result| {
+                    Some(RerankResult {
+                        index: result["index"].as_u64()? as usize,
+                        score: result["relevance_score"].as_f64()? as f32,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()
+        })
+        .filter(|results| results.iter().all(|result| result.index < documents.len()))
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract rerank results from response".to_string(),
+        })
+}
+
+/// Has the model score the relevance of `document` to `query`.
+async fn judge(
+    ai_config: &AiConfig,
+    query: &str,
+    index: usize,
+    document: &str,
+) -> Result<RerankResult> {
+    let question = Question {
+        system_prompt: Some(JUDGE_PROMPT.to_string()),
+        messages: None,
+        new_prompt: format!("Query: {}\n\nDocument: {}", query, document),
+        images: vec![],
+        audio: vec![],
+    };
+    let answer = ask_question(ai_config, question).await?;
+
+    static SCORE_REGEX: OnceLock<Regex> = OnceLock::new();
+    let score = SCORE_REGEX
+        .get_or_init(|| Regex::new(r"\d+(\.\d+)?").expect("valid regex"))
+        .find(&answer)
+        .and_then(|score| score.as_str().parse::<f32>().ok())
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("No relevance score in answer: {}", answer),
+        })?;
+    Ok(RerankResult {
+        index,
+        score: score.clamp(0.0, 10.0) / 10.0,
+    })
+}
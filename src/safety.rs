@@ -0,0 +1,98 @@
+use crate::config::{AiConfig, Framework};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Provider-side content filtering, configured once and translated for the backend questions
+/// go to.
+///
+/// Supported backends:
+/// - Bedrock (`Framework::Bedrock`): `guardrail`, sent as the Converse API's
+///   `guardrailConfig` and applied by Bedrock to both the prompt and the answer.
+///
+/// Other combinations fail before anything is sent, rather than returning unfiltered output.
+/// What the filter decided is under `ask_ai.safety` in the response of
+/// `ask_ai::ask_question_raw` (see `SafetyVerdict`).
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::safety::{Guardrail, SafetySettings};
+///
+/// let ai_config = AiConfig {
+///     llm: Framework::Bedrock,
+///     safety: Some(SafetySettings {
+///         guardrail: Some(Guardrail {
+///             id: "gr-support-bot".to_string(),
+///             version: "3".to_string(),
+///             trace: true,
+///         }),
+///     }),
+///     ..ai_config
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct SafetySettings {
+    /// A Bedrock guardrail to apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guardrail: Option<Guardrail>,
+}
+
+/// A guardrail created in Amazon Bedrock.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Guardrail {
+    /// The guardrail's identifier or ARN.
+    pub id: String,
+    /// A published version number, or `DRAFT`.
+    pub version: String,
+    /// Return the guardrail's assessments, as `SafetyVerdict::trace`.
+    #[serde(default)]
+    pub trace: bool,
+}
+
+/// What the provider's content filter decided about a question.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SafetyVerdict {
+    /// Whether the filter blocked or rewrote the prompt or the answer. The answer is then the
+    /// filter's own message.
+    pub intervened: bool,
+    /// The provider's assessments, when tracing was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Value>,
+}
+
+impl SafetySettings {
+    /// Fails when the configured provider cannot apply these settings.
+    pub(crate) fn check(&self, ai_config: &AiConfig) -> Result<()> {
+        if self.guardrail.is_none() || ai_config.llm == Framework::Bedrock {
+            return Ok(());
+        }
+        Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} cannot apply a guardrail", ai_config.llm),
+        })
+    }
+
+    /// Adds the settings to a `framework` request payload.
+    pub(crate) fn apply(&self, framework: Framework, payload: &mut Value) {
+        // Other frameworks are rejected by `check` before anything is sent
+        if let (Framework::Bedrock, Some(guardrail)) = (framework, &self.guardrail) {
+            payload["guardrailConfig"] = serde_json::json!({
+                "guardrailIdentifier": guardrail.id,
+                "guardrailVersion": guardrail.version,
+                "trace": if guardrail.trace { "enabled" } else { "disabled" },
+            });
+        }
+    }
+
+    /// Reads the filter's verdict from a `framework` response, if these settings asked for one.
+    pub(crate) fn verdict(&self, framework: &Framework, response: &Value) -> Option<SafetyVerdict> {
+        match (framework, &self.guardrail) {
+            (Framework::Bedrock, Some(_)) => Some(SafetyVerdict {
+                intervened: response["stopReason"] == "guardrail_intervened",
+                trace: response["trace"].get("guardrail").cloned(),
+            }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Question};
+use crate::continuation::follow_up;
+use crate::deadline;
+use crate::error::{AppError, Result};
+use crate::grammar::ResponseFormat;
+use crate::markdown::extract_code_blocks;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Validation of answers against a JSON Schema, with re-prompts on mismatch.
+///
+/// Each answer is parsed as JSON (a fenced code block is accepted too) and checked against
+/// `schema`. When it fails, the answer is sent back with the validation errors and a request to
+/// correct it, up to `max_attempts` answers in total.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::schema::{ask_validated, SchemaValidation};
+/// use serde_json::json;
+///
+/// let validation = SchemaValidation {
+///     schema: json!({
+///         "type": "object",
+///         "properties": { "city": { "type": "string" }, "population": { "type": "integer" } },
+///         "required": ["city", "population"]
+///     }),
+///     max_attempts: 3,
+/// };
+/// let city = ask_validated(&ai_config, question, &validation).await?;
+/// println!("{} has {} inhabitants", city["city"], city["population"]);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaValidation {
+    /// The JSON Schema answers must validate against.
+    pub schema: Value,
+    /// At most this many answers per question, the first one included.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+/// Asks a question whose answer must be JSON valid against `validation.schema`, and returns
+/// the parsed answer.
+///
+/// Unless `AiConfig::response_format` is set, the schema is also sent as the response format,
+/// so providers with a JSON mode enforce it while decoding. Fails with
+/// `AppError::SchemaMismatch`, carrying the last validation errors, when no answer validates
+/// within `max_attempts`.
+pub async fn ask_validated(
+    ai_config: &AiConfig,
+    question: Question,
+    validation: &SchemaValidation,
+) -> Result<Value> {
+    deadline::within(ai_config, validate_answers(ai_config, question, validation)).await
+}
+
+async fn validate_answers(
+    ai_config: &AiConfig,
+    mut question: Question,
+    validation: &SchemaValidation,
+) -> Result<Value> {
+    let validator = jsonschema::validator_for(&validation.schema)
+        .map_err(|e| AppError::UnexpectedError(format!("Invalid JSON Schema: {}", e)))?;
+    let ai_config = with_response_format(ai_config, &validation.schema);
+
+    let mut errors = vec![];
+    for _ in 0..validation.max_attempts.max(1) {
+        let answer = ask_question(&ai_config, question.clone()).await?;
+        errors = match parse_answer(&answer) {
+            Ok(value) => {
+                let errors: Vec<String> = validator
+                    .iter_errors(&value)
+                    .map(|e| match e.instance_path.to_string() {
+                        path if path.is_empty() => e.to_string(),
+                        path => format!("{}: {}", path, e),
+                    })
+                    .collect();
+                if errors.is_empty() {
+                    return Ok(value);
+                }
+                errors
+            }
+            Err(e) => vec![e],
+        };
+        question = follow_up(&ai_config, &question, &answer, &retry_prompt(&errors));
+    }
+
+    Err(AppError::SchemaMismatch {
+        model_name: ai_config.model.to_string(),
+        attempts: validation.max_attempts.max(1),
+        errors,
+    })
+}
+
+/// `ai_config` with `schema` as its response format, unless it already has one.
+fn with_response_format<'a>(ai_config: &'a AiConfig, schema: &Value) -> Cow<'a, AiConfig> {
+    if ai_config.response_format.is_some() || ai_config.constraint.is_some() {
+        return Cow::Borrowed(ai_config);
+    }
+    Cow::Owned(AiConfig {
+        response_format: Some(ResponseFormat::JsonSchema {
+            name: "response".to_string(),
+            schema: schema.clone(),
+        }),
+        ..ai_config.clone()
+    })
+}
+
+/// The JSON in `answer`: the whole answer, or its first fenced code block.
+fn parse_answer(answer: &str) -> std::result::Result<Value, String> {
+    serde_json::from_str(answer.trim()).or_else(|e| {
+        extract_code_blocks(answer)
+            .first()
+            .and_then(|block| serde_json::from_str(&block.code).ok())
+            .ok_or_else(|| format!("the answer is not valid JSON: {}", e))
+    })
+}
+
+/// The follow-up prompt listing what was wrong with the previous answer.
+fn retry_prompt(errors: &[String]) -> String {
+    let mut prompt =
+        "Your answer does not match the required JSON Schema. Problems found:\n".to_string();
+    for error in errors {
+        prompt.push_str(&format!("- {}\n", error));
+    }
+    prompt.push_str("Reply again with only the corrected JSON.");
+    prompt
+}
@@ -0,0 +1,123 @@
+use crate::config::AiConfig;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+
+/// Text substituted for anything that looks like a credential.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Environment variables holding provider credentials, scrubbed from error strings.
+const KEY_ENV_VARS: [&str; 11] = [
+    "OPENAI_API_KEY",
+    "ANTHROPIC_API_KEY",
+    "MISTRAL_API_KEY",
+    "GROQ_API_KEY",
+    "OPENROUTER_API_KEY",
+    "HF_TOKEN",
+    "REPLICATE_API_TOKEN",
+    "COHERE_API_KEY",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "AWS_BEARER_TOKEN_BEDROCK",
+];
+
+/// Minimum length of a `sk-` token before it is treated as an API key.
+const MIN_KEY_LEN: usize = 16;
+
+/// A credential that never shows up in `Debug` or `Display` output.
+///
+/// The value is only reachable through `expose_secret`, so it cannot end up in logs by
+/// accident. It still serializes as plain text, so configuration files round-trip.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::secret::SecretString;
+///
+/// let key = SecretString::from("sk-my-key");
+/// assert_eq!(format!("{:?}", key), "SecretString([REDACTED])");
+/// assert_eq!(key.expose_secret(), "sk-my-key");
+/// ```
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    /// Returns the secret value. Keep the result out of logs and error messages.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretString({})", REDACTED)
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Removes credentials from text bound for an error message: the configured and environment
+/// API keys, bearer tokens and anything shaped like a `sk-` key.
+pub(crate) fn scrub_secrets(text: &str, ai_config: &AiConfig) -> String {
+    let mut scrubbed = text.to_string();
+
+    let configured = ai_config
+        .api_key
+        .as_ref()
+        .map(|key| key.expose_secret().to_string());
+    let from_env = KEY_ENV_VARS.iter().filter_map(|var| env::var(var).ok());
+    for secret in configured.into_iter().chain(from_env) {
+        if !secret.is_empty() {
+            scrubbed = scrubbed.replace(&secret, REDACTED);
+        }
+    }
+
+    scrubbed = mask_after(&scrubbed, "Bearer ", 1);
+    mask_after(&scrubbed, "sk-", MIN_KEY_LEN)
+}
+
+/// Replaces the token following each standalone `prefix` with `REDACTED`, if it is at least
+/// `min_len` characters long.
+fn mask_after(text: &str, prefix: &str, min_len: usize) -> String {
+    let is_token_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(prefix) {
+        let standalone = !rest[..start].ends_with(is_token_char);
+        let after = &rest[start + prefix.len()..];
+        let token_len = after.find(|c| !is_token_char(c)).unwrap_or(after.len());
+
+        out.push_str(&rest[..start + prefix.len()]);
+        if standalone && token_len >= min_len {
+            out.push_str(REDACTED);
+            rest = &after[token_len..];
+        } else {
+            rest = after;
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
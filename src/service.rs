@@ -0,0 +1,57 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use futures_util::future::BoxFuture;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// `ask_question` as a `tower::Service<Question>`, so tower middleware (timeouts, retries,
+/// rate and concurrency limits, load shedding) wraps LLM calls like any other service.
+///
+/// The service is always ready; clones share the configuration.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::service::AskService;
+/// use tower::{ServiceBuilder, ServiceExt};
+///
+/// let service = ServiceBuilder::new()
+///     .timeout(Duration::from_secs(30))
+///     .concurrency_limit(4)
+///     .service(AskService::new(ai_config));
+/// let answer = service.oneshot(question).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct AskService {
+    ai_config: Arc<AiConfig>,
+}
+
+impl AskService {
+    pub fn new(ai_config: AiConfig) -> Self {
+        Self {
+            ai_config: Arc::new(ai_config),
+        }
+    }
+
+    /// The configuration every question is asked with.
+    pub fn ai_config(&self) -> &AiConfig {
+        &self.ai_config
+    }
+}
+
+impl Service<Question> for AskService {
+    type Response = String;
+    type Error = AppError;
+    type Future = BoxFuture<'static, Result<String>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, question: Question) -> Self::Future {
+        let ai_config = Arc::clone(&self.ai_config);
+        Box::pin(async move { ask_question(&ai_config, question).await })
+    }
+}
@@ -0,0 +1,49 @@
+use crate::error::Result;
+use async_stream::stream;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::{pin_mut, Stream, StreamExt};
+use std::convert::Infallible;
+
+/// Turns an answer stream into an axum server-sent events response.
+///
+/// Each fragment is sent as an unnamed event, so `EventSource.onmessage` receives it;
+/// multi-line fragments are split over several `data:` lines as the SSE format requires.
+/// The stream ends with a `done` event (data `[DONE]`; browsers drop events without data),
+/// or an `error` event carrying the error message.
+/// Comments are sent every 15 seconds of silence to keep proxies from closing the
+/// connection; call `keep_alive` on the result to change that.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::sse::sse_response;
+///
+/// async fn chat(State(ai_config): State<AiConfig>, Json(question): Json<String>) -> impl IntoResponse {
+///     let question = Question { system_prompt: None, messages: None, new_prompt: question, images: vec![], audio: vec![] };
+///     match ask_question_stream(&ai_config, question).await {
+///         Ok(stream) => sse_response(stream).into_response(),
+///         Err(e) => (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+///     }
+/// }
+/// ```
+pub fn sse_response<S>(
+    answer: S,
+) -> Sse<impl Stream<Item = std::result::Result<Event, Infallible>> + Send>
+where
+    S: Stream<Item = Result<String>> + Send + 'static,
+{
+    let events = stream! {
+        pin_mut!(answer);
+        while let Some(delta) = answer.next().await {
+            match delta {
+                Ok(delta) => yield Ok(Event::default().data(delta)),
+                Err(e) => {
+                    yield Ok(Event::default().event("error").data(e.to_string()));
+                    return;
+                }
+            }
+        }
+        yield Ok(Event::default().event("done").data("[DONE]"));
+    };
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
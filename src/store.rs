@@ -0,0 +1,302 @@
+use crate::conversation::Conversation;
+use crate::error::{AppError, Result};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use std::fs;
+use std::path::PathBuf;
+
+/// Length in bytes of the random nonce prefixed to every encrypted record.
+const NONCE_LEN: usize = 24;
+
+/// Persistence for conversations, keyed by a caller-chosen id.
+pub trait ConversationStore {
+    /// Inserts or replaces the conversation stored under `id`.
+    fn save(&self, id: &str, conversation: &Conversation) -> Result<()>;
+    /// Loads the conversation stored under `id`.
+    fn load(&self, id: &str) -> Result<Conversation>;
+    /// Removes the conversation stored under `id`, if any.
+    fn delete(&self, id: &str) -> Result<()>;
+    /// Lists the ids of all stored conversations, sorted.
+    fn list(&self) -> Result<Vec<String>>;
+}
+
+/// Authenticated encryption for stored conversations (XChaCha20-Poly1305).
+///
+/// Each record is sealed with a fresh random nonce, and the conversation id is bound as
+/// associated data so an encrypted record cannot be swapped under another id unnoticed.
+#[derive(Clone)]
+struct Cipher(XChaCha20Poly1305);
+
+impl Cipher {
+    fn new(key: &[u8; 32]) -> Self {
+        Self(XChaCha20Poly1305::new(key.into()))
+    }
+
+    fn seal(&self, id: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: plaintext,
+            aad: id.as_bytes(),
+        };
+        let ciphertext = self.0.encrypt(&nonce, payload).map_err(|_| {
+            AppError::UnexpectedError(format!("Failed to encrypt conversation `{}`", id))
+        })?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(sealed)
+    }
+
+    fn open(&self, id: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        let failure = || {
+            AppError::UnexpectedError(format!(
+                "Failed to decrypt conversation `{}`: wrong key or corrupted data",
+                id
+            ))
+        };
+        if sealed.len() < NONCE_LEN {
+            return Err(failure());
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: id.as_bytes(),
+        };
+        self.0
+            .decrypt(XNonce::from_slice(nonce), payload)
+            .map_err(|_| failure())
+    }
+}
+
+/// Serializes a conversation, sealing it when a cipher is configured.
+fn encode(cipher: &Option<Cipher>, id: &str, conversation: &Conversation) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec(conversation).map_err(|e| {
+        AppError::UnexpectedError(format!("Failed to serialize conversation: {}", e))
+    })?;
+
+    match cipher {
+        Some(cipher) => cipher.seal(id, &json),
+        None => Ok(json),
+    }
+}
+
+fn decode(cipher: &Option<Cipher>, id: &str, data: &[u8]) -> Result<Conversation> {
+    let json = match cipher {
+        Some(cipher) => cipher.open(id, data)?,
+        None => data.to_vec(),
+    };
+
+    serde_json::from_slice(&json).map_err(|e| {
+        AppError::UnexpectedError(format!("Failed to parse conversation `{}`: {}", id, e))
+    })
+}
+
+/// Stores each conversation as a file in a directory: `<id>.json`, or `<id>.enc` once
+/// encryption is enabled.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::store::{ConversationStore, JsonStore};
+///
+/// let store = JsonStore::new("conversations")?.with_encryption(&key);
+/// store.save("support-42", &conversation)?;
+/// let conversation = store.load("support-42")?;
+/// ```
+#[derive(Clone)]
+pub struct JsonStore {
+    dir: PathBuf,
+    cipher: Option<Cipher>,
+}
+
+impl JsonStore {
+    /// Opens (and creates if needed) a store in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+
+        Ok(Self { dir, cipher: None })
+    }
+
+    /// Encrypts everything written from now on with `key`, and expects it to read.
+    pub fn with_encryption(mut self, key: &[u8; 32]) -> Self {
+        self.cipher = Some(Cipher::new(key));
+        self
+    }
+
+    fn extension(&self) -> &'static str {
+        if self.cipher.is_some() {
+            "enc"
+        } else {
+            "json"
+        }
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        // Ids become file names, so keep them from escaping the directory
+        let valid = !id.is_empty()
+            && !id.starts_with('.')
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(AppError::UnexpectedError(format!(
+                "Invalid conversation id `{}`",
+                id
+            )));
+        }
+
+        Ok(self.dir.join(format!("{}.{}", id, self.extension())))
+    }
+}
+
+impl ConversationStore for JsonStore {
+    fn save(&self, id: &str, conversation: &Conversation) -> Result<()> {
+        let path = self.path(id)?;
+        let data = encode(&self.cipher, id, conversation)?;
+
+        // Write then rename, so a crash never leaves a half-written conversation
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| {
+                AppError::UnexpectedError(format!("Failed to write {}: {}", path.display(), e))
+            })
+    }
+
+    fn load(&self, id: &str) -> Result<Conversation> {
+        let path = self.path(id)?;
+        let data = fs::read(&path).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+
+        decode(&self.cipher, id, &data)
+    }
+
+    fn delete(&self, id: &str) -> Result<()> {
+        let path = self.path(id)?;
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(AppError::UnexpectedError(
+                format!("Failed to delete {}: {}", path.display(), e),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            AppError::UnexpectedError(format!("Failed to list {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut ids: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(self.extension()))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+}
+
+/// Stores conversations in a single SQLite database file (requires the `sqlite` feature).
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::store::{ConversationStore, SqliteStore};
+///
+/// let store = SqliteStore::open("conversations.db")?.with_encryption(&key);
+/// store.save("support-42", &conversation)?;
+/// ```
+#[cfg(feature = "sqlite")]
+pub struct SqliteStore {
+    conn: std::sync::Mutex<rusqlite::Connection>,
+    cipher: Option<Cipher>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Opens (and creates if needed) the database at `path`.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let conn = rusqlite::Connection::open(path).map_err(sqlite_error)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS conversations (id TEXT PRIMARY KEY, data BLOB NOT NULL)",
+            [],
+        )
+        .map_err(sqlite_error)?;
+
+        Ok(Self {
+            conn: std::sync::Mutex::new(conn),
+            cipher: None,
+        })
+    }
+
+    /// Encrypts everything written from now on with `key`, and expects it to read.
+    pub fn with_encryption(mut self, key: &[u8; 32]) -> Self {
+        self.cipher = Some(Cipher::new(key));
+        self
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, rusqlite::Connection> {
+        // A panic while holding the lock cannot leave the connection half-updated
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub(crate) fn sqlite_error(e: rusqlite::Error) -> AppError {
+    AppError::UnexpectedError(format!("SQLite error: {}", e))
+}
+
+#[cfg(feature = "sqlite")]
+impl ConversationStore for SqliteStore {
+    fn save(&self, id: &str, conversation: &Conversation) -> Result<()> {
+        let data = encode(&self.cipher, id, conversation)?;
+        self.conn()
+            .execute(
+                "INSERT OR REPLACE INTO conversations (id, data) VALUES (?1, ?2)",
+                rusqlite::params![id, data],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn load(&self, id: &str) -> Result<Conversation> {
+        let data: Vec<u8> = self
+            .conn()
+            .query_row(
+                "SELECT data FROM conversations WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(sqlite_error)?;
+
+        decode(&self.cipher, id, &data)
+    }
+
+    fn delete(&self, id: &str) -> Result<()> {
+        self.conn()
+            .execute("DELETE FROM conversations WHERE id = ?1", [id])
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT id FROM conversations ORDER BY id")
+            .map_err(sqlite_error)?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(sqlite_error)?
+            .collect::<std::result::Result<Vec<String>, _>>()
+            .map_err(sqlite_error)?;
+        Ok(ids)
+    }
+}
@@ -0,0 +1,427 @@
+use crate::ask_ai::{
+    anthropic_request, ask_question, build_anthropic_payload, build_payload, ollama_http_request,
+    openai_request, prepare, send_request,
+};
+use crate::capabilities::{degrade, Capability, Degradation};
+use crate::config::{AiConfig, Framework, Question};
+use crate::continuation::{follow_up, without_prefill, CONTINUE_PROMPT};
+use crate::deadline;
+use crate::error::{AppError, Result};
+use crate::lmstudio::with_loaded_model;
+use crate::provider::provider;
+use crate::secret::scrub_secrets;
+use async_stream::try_stream;
+use futures_util::{pin_mut, Stream, StreamExt};
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::pin::Pin;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::time::timeout_at;
+
+/// A stream of answer deltas (text fragments, in order) produced by `ask_question_stream`.
+pub type AnswerStream = Pin<Box<dyn Stream<Item = Result<String>> + Send>>;
+
+/// Prefix of the errors of streams cut off before the answer was complete.
+const STREAM_INTERRUPTED: &str = "Stream interrupted";
+
+/// Resumption of answer streams whose connection drops mid-answer.
+///
+/// Instead of failing (and making the caller start over), the question is sent again with
+/// the part of the answer already streamed as the assistant's turn and a follow-up `prompt`,
+/// and the new stream's fragments continue the old one. A stream counts as interrupted when
+/// the connection fails, or closes before the provider's end marker (`[DONE]`,
+/// `message_stop`, `"done": true`). Errors the provider sends in the stream are not retried.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::stream::StreamResume;
+///
+/// let ai_config = AiConfig {
+///     stream_resume: Some(StreamResume::default()),
+///     ..ai_config
+/// };
+/// let stream = ask_question_stream(&ai_config, question).await?;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StreamResume {
+    /// At most this many resumptions per answer.
+    #[serde(default = "default_max_resumes")]
+    pub max_resumes: u32,
+    /// The follow-up prompt. Defaults to `continuation::CONTINUE_PROMPT`.
+    #[serde(default = "default_resume_prompt")]
+    pub prompt: String,
+}
+
+fn default_max_resumes() -> u32 {
+    2
+}
+
+fn default_resume_prompt() -> String {
+    CONTINUE_PROMPT.to_string()
+}
+
+impl Default for StreamResume {
+    fn default() -> Self {
+        Self {
+            max_resumes: default_max_resumes(),
+            prompt: default_resume_prompt(),
+        }
+    }
+}
+
+/// Asks a question and streams the answer back as it is generated.
+///
+/// Each item of the returned stream is the next fragment of the answer; concatenating them
+/// yields the same text `ask_question` would have returned.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::stream::ask_question_stream;
+/// use futures_util::StreamExt;
+///
+/// let mut stream = ask_question_stream(&ai_config, question).await?;
+/// while let Some(delta) = stream.next().await {
+///     print!("{}", delta?);
+/// }
+/// ```
+pub async fn ask_question_stream(ai_config: &AiConfig, question: Question) -> Result<AnswerStream> {
+    let Some(deadline) = deadline::start(ai_config) else {
+        return open_stream(ai_config, question).await;
+    };
+    let stream = timeout_at(deadline, open_stream(ai_config, question))
+        .await
+        .map_err(|_| deadline::exceeded(ai_config))??;
+    Ok(deadline::bound_stream(stream, deadline, ai_config))
+}
+
+async fn open_stream(ai_config: &AiConfig, question: Question) -> Result<AnswerStream> {
+    if let Some(replay) = &ai_config.replay {
+        // Replayed (and recorded) answers arrive as a single delta
+        let answer = replay.ask(ai_config, question).await?;
+        return Ok(Box::pin(futures_util::stream::once(
+            async move { Ok(answer) },
+        )));
+    }
+
+    match degrade(ai_config, Capability::Streaming)? {
+        Degradation::Supported => {}
+        Degradation::Strip => {
+            let answer = ask_question(ai_config, question).await?;
+            return Ok(Box::pin(futures_util::stream::once(
+                async move { Ok(answer) },
+            )));
+        }
+        Degradation::Fallback(fallback) => {
+            return Box::pin(ask_question_stream(fallback, question)).await;
+        }
+    }
+
+    let stream = match &ai_config.on_context_overflow {
+        Some(overflow) => {
+            let fallback = overflow.fallback_for(ai_config);
+            overflow
+                .send(
+                    ai_config,
+                    fallback.as_deref(),
+                    question.clone(),
+                    stream_question,
+                )
+                .await
+                .map(|(stream, _)| stream)
+        }
+        None => stream_question(ai_config, question.clone()).await,
+    }?;
+    match &ai_config.stream_resume {
+        Some(resume) => Ok(resume.wrap(stream, ai_config, question)),
+        None => Ok(stream),
+    }
+}
+
+impl StreamResume {
+    /// Continues `stream`, the answer to `question`, on a new request when it is interrupted.
+    fn wrap(&self, stream: AnswerStream, ai_config: &AiConfig, question: Question) -> AnswerStream {
+        let resume = self.clone();
+        let ai_config = without_prefill(ai_config).into_owned();
+        Box::pin(try_stream! {
+            let mut stream = stream;
+            let mut answer = String::new();
+            let mut resumes = 0;
+            while let Some(delta) = stream.next().await {
+                match delta {
+                    Ok(delta) => {
+                        answer.push_str(&delta);
+                        yield delta;
+                    }
+                    Err(e) if is_interruption(&e) && resumes < resume.max_resumes => {
+                        resumes += 1;
+                        let rest = follow_up(&ai_config, &question, &answer, &resume.prompt);
+                        stream = stream_question(&ai_config, rest).await?;
+                    }
+                    Err(e) => Err(e)?,
+                }
+            }
+        })
+    }
+}
+
+/// The provider's whole answer as a single fragment, for Bedrock, Replicate and custom
+/// providers.
+fn single_chunk_stream(question: Question, ai_config: &AiConfig) -> Result<AnswerStream> {
+    let provider = provider(ai_config)?;
+    let ai_config = ai_config.clone();
+    Ok(Box::pin(try_stream! {
+        yield provider.ask(&ai_config, question).await?.answer;
+    }))
+}
+
+fn is_interruption(error: &AppError) -> bool {
+    matches!(error, AppError::ApiError { failure_str, .. } if failure_str.starts_with(STREAM_INTERRUPTED))
+}
+
+/// The error ending a stream that closed before the provider's end marker, when
+/// `AiConfig::stream_resume` is set. Without it, such streams end quietly.
+fn closed_early(ai_config: &AiConfig) -> Result<()> {
+    match ai_config.stream_resume {
+        Some(_) => Err(AppError::ApiError {
+            model_name: ai_config.llm.to_string(),
+            failure_str: format!(
+                "{}: the connection closed before the answer was complete",
+                STREAM_INTERRUPTED
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+async fn stream_question(ai_config: &AiConfig, question: Question) -> Result<AnswerStream> {
+    let (question, redactions) = prepare(ai_config, question).await?;
+
+    let stream = match ai_config.llm {
+        Framework::OpenAI
+        | Framework::Mistral
+        | Framework::Groq
+        | Framework::OpenRouter
+        | Framework::HuggingFace => stream_openai_response(question, ai_config).await,
+        Framework::Anthropic => stream_anthropic_response(question, ai_config).await,
+        Framework::Ollama => stream_ollama_response(question, ai_config).await,
+        Framework::LmStudio => {
+            stream_openai_response(question, &*with_loaded_model(ai_config).await?).await
+        }
+        Framework::Bedrock | Framework::Replicate | Framework::Custom(_) => {
+            single_chunk_stream(question, ai_config)
+        }
+    }?;
+    if redactions.is_empty() {
+        return Ok(stream);
+    }
+
+    let mut restorer = redactions.restorer();
+    Ok(Box::pin(try_stream! {
+        pin_mut!(stream);
+        while let Some(delta) = stream.next().await {
+            let text = restorer.push(&delta?);
+            if !text.is_empty() {
+                yield text;
+            }
+        }
+        let rest = restorer.finish();
+        if !rest.is_empty() {
+            yield rest;
+        }
+    }))
+}
+
+/// Drives an answer stream into any `AsyncWrite` (stdout, a file, a socket, ...).
+///
+/// When `flush_per_delta` is set, the writer is flushed after every fragment so the text shows
+/// up immediately (useful for terminals and proxies); otherwise it is only flushed at the end.
+/// Returns the full answer once the stream is exhausted.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::stream::{ask_question_stream, stream_to_writer};
+///
+/// let stream = ask_question_stream(&ai_config, question).await?;
+/// let answer = stream_to_writer(stream, &mut tokio::io::stdout(), true).await?;
+/// ```
+pub async fn stream_to_writer<S, W>(
+    mut stream: S,
+    writer: &mut W,
+    flush_per_delta: bool,
+) -> Result<String>
+where
+    S: Stream<Item = Result<String>> + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut answer = String::new();
+    while let Some(delta) = stream.next().await {
+        let delta = delta?;
+        writer
+            .write_all(delta.as_bytes())
+            .await
+            .map_err(|e| AppError::UnexpectedError(format!("Failed to write answer: {}", e)))?;
+        if flush_per_delta {
+            writer
+                .flush()
+                .await
+                .map_err(|e| AppError::UnexpectedError(format!("Failed to flush answer: {}", e)))?;
+        }
+        answer.push_str(&delta);
+    }
+    writer
+        .flush()
+        .await
+        .map_err(|e| AppError::UnexpectedError(format!("Failed to flush answer: {}", e)))?;
+
+    Ok(answer)
+}
+
+async fn stream_openai_response(question: Question, ai_config: &AiConfig) -> Result<AnswerStream> {
+    let mut payload = build_payload(&question, ai_config);
+    payload["stream"] = Value::Bool(true);
+    let resp = send_request(openai_request(ai_config)?.json(&payload), ai_config).await?;
+
+    let lines = response_lines(resp, ai_config);
+    let ai_config = ai_config.clone();
+    Ok(Box::pin(try_stream! {
+        pin_mut!(lines);
+        while let Some(line) = lines.next().await {
+            let line = line?;
+            // Server-sent events: only `data:` lines carry chunks
+            let Some(data) = line.strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                return;
+            }
+            let chunk = parse_chunk(data, &ai_config)?;
+            if let Some(delta) = chunk["choices"][0]["delta"]["content"].as_str() {
+                if !delta.is_empty() {
+                    yield delta.to_string();
+                }
+            }
+        }
+        closed_early(&ai_config)?;
+    }))
+}
+
+async fn stream_anthropic_response(
+    question: Question,
+    ai_config: &AiConfig,
+) -> Result<AnswerStream> {
+    let mut payload = build_anthropic_payload(&question, ai_config);
+    payload["stream"] = Value::Bool(true);
+    let resp = send_request(anthropic_request(ai_config)?.json(&payload), ai_config).await?;
+
+    let lines = response_lines(resp, ai_config);
+    let ai_config = ai_config.clone();
+    Ok(Box::pin(try_stream! {
+        if let Some(prefill) = ai_config.anthropic_prefill() {
+            yield prefill.to_string();
+        }
+        pin_mut!(lines);
+        while let Some(line) = lines.next().await {
+            let line = line?;
+            // Server-sent events: the `event:` line is repeated as `type` in the data
+            let Some(data) = line.strip_prefix("data:") else {
+                continue;
+            };
+            let event = parse_chunk(data.trim(), &ai_config)?;
+            match event["type"].as_str() {
+                Some("content_block_delta") => {
+                    if let Some(delta) = event["delta"]["text"].as_str() {
+                        if !delta.is_empty() {
+                            yield delta.to_string();
+                        }
+                    }
+                }
+                Some("message_stop") => return,
+                _ => {}
+            }
+        }
+        closed_early(&ai_config)?;
+    }))
+}
+
+async fn stream_ollama_response(question: Question, ai_config: &AiConfig) -> Result<AnswerStream> {
+    let builder = ollama_http_request(question, ai_config, true)?;
+    let resp = send_request(builder, ai_config).await?;
+
+    let lines = response_lines(resp, ai_config);
+    let ai_config = ai_config.clone();
+    Ok(Box::pin(try_stream! {
+        pin_mut!(lines);
+        while let Some(line) = lines.next().await {
+            // Newline-delimited JSON, one chat response per line
+            let chunk = parse_chunk(&line?, &ai_config)?;
+            if let Some(delta) = chunk["message"]["content"].as_str() {
+                if !delta.is_empty() {
+                    yield delta.to_string();
+                }
+            }
+            if chunk["done"].as_bool() == Some(true) {
+                return;
+            }
+        }
+        closed_early(&ai_config)?;
+    }))
+}
+
+/// Splits a streamed response body into non-empty, trimmed lines.
+///
+/// Bytes are buffered until a full line is available so multi-byte characters and JSON
+/// documents split across network chunks are reassembled correctly.
+pub(crate) fn response_lines(
+    resp: Response,
+    ai_config: &AiConfig,
+) -> impl Stream<Item = Result<String>> {
+    let model_name = ai_config.llm.to_string();
+    try_stream! {
+        let mut body = resp.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| AppError::ApiError {
+                model_name: model_name.clone(),
+                failure_str: format!("{}: {}", STREAM_INTERRUPTED, e),
+            })?;
+            buffer.extend_from_slice(&chunk);
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line).trim().to_string();
+                if !line.is_empty() {
+                    yield line;
+                }
+            }
+        }
+        let rest = String::from_utf8_lossy(&buffer).trim().to_string();
+        if !rest.is_empty() {
+            yield rest;
+        }
+    }
+}
+
+/// Parses one streamed JSON chunk, surfacing in-band provider errors as `ModelError`.
+pub(crate) fn parse_chunk(data: &str, ai_config: &AiConfig) -> Result<Value> {
+    let chunk: Value = serde_json::from_str(data).map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse stream chunk: {}", e),
+    })?;
+
+    if let Some(error) = chunk.get("error") {
+        let message = error["message"]
+            .as_str()
+            .or_else(|| error.as_str())
+            .unwrap_or("unknown error");
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: scrub_secrets(&format!("Stream error: {}", message), ai_config),
+        });
+    }
+
+    Ok(chunk)
+}
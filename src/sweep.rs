@@ -0,0 +1,200 @@
+use crate::ask_ai::ask_question;
+use crate::config::{AiConfig, Question};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+/// A parameter sweep: the same questions asked with every combination of models,
+/// temperatures and system prompts, each answer scored, and the scores averaged per
+/// combination.
+///
+/// An empty axis keeps the base configuration's value (or the question's own system prompt),
+/// so a sweep over temperatures alone needs only `temperatures`. Scores come from the caller,
+/// e.g. an exact-match or rubric check against expected answers looked up by question index.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::sweep::Sweep;
+///
+/// let sweep = Sweep {
+///     models: vec!["gpt-4o-mini".to_string(), "gpt-4o".to_string()],
+///     temperatures: vec![0.0, 0.7],
+///     ..Default::default()
+/// };
+/// let report = sweep
+///     .run(&ai_config, &questions, |index, answer| {
+///         (answer.trim() == expected[index]) as u8 as f64
+///     })
+///     .await;
+/// println!("{:?}", report.best());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Sweep {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub temperatures: Vec<f64>,
+    /// System prompts replacing those of the questions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub system_prompts: Vec<String>,
+    /// How many questions are asked at once, across all combinations.
+    pub concurrency: usize,
+}
+
+impl Default for Sweep {
+    fn default() -> Self {
+        Self {
+            models: vec![],
+            temperatures: vec![],
+            system_prompts: vec![],
+            concurrency: 4,
+        }
+    }
+}
+
+/// One combination of a sweep. `None` keeps the base configuration's value.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SweepPoint {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+}
+
+impl SweepPoint {
+    /// `base` with this combination applied.
+    pub fn config(&self, base: &AiConfig) -> AiConfig {
+        let mut ai_config = base.clone();
+        if let Some(model) = &self.model {
+            ai_config.model = model.clone();
+        }
+        if let Some(temperature) = self.temperature {
+            ai_config
+                .params
+                .get_or_insert_with(Default::default)
+                .temperature = Some(temperature);
+        }
+        ai_config
+    }
+
+    /// `question` with this combination applied.
+    pub fn question(&self, question: &Question) -> Question {
+        let mut question = question.clone();
+        if let Some(system_prompt) = &self.system_prompt {
+            question.system_prompt = Some(system_prompt.clone());
+        }
+        question
+    }
+}
+
+/// The scores of one combination.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PointResult {
+    pub point: SweepPoint,
+    /// One score per question, `None` where asking failed.
+    pub scores: Vec<Option<f64>>,
+    /// The mean over all questions, failed ones counting as 0.
+    pub mean_score: f64,
+    /// How many questions failed.
+    pub errors: usize,
+}
+
+/// The results of a sweep, one per combination in grid order.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SweepReport {
+    pub results: Vec<PointResult>,
+}
+
+impl SweepReport {
+    /// The combination with the highest mean score; the first one on a tie.
+    pub fn best(&self) -> Option<&PointResult> {
+        self.results.iter().reduce(|best, result| {
+            if result.mean_score > best.mean_score {
+                result
+            } else {
+                best
+            }
+        })
+    }
+
+    /// The results from highest to lowest mean score.
+    pub fn ranked(&self) -> Vec<&PointResult> {
+        let mut ranked: Vec<_> = self.results.iter().collect();
+        ranked.sort_by(|a, b| b.mean_score.total_cmp(&a.mean_score));
+        ranked
+    }
+}
+
+impl Sweep {
+    /// Every combination of the grid: models vary slowest, system prompts fastest.
+    pub fn points(&self) -> Vec<SweepPoint> {
+        fn axis<T: Clone>(values: &[T]) -> Vec<Option<T>> {
+            if values.is_empty() {
+                vec![None]
+            } else {
+                values.iter().cloned().map(Some).collect()
+            }
+        }
+
+        let mut points = vec![];
+        for model in axis(&self.models) {
+            for temperature in axis(&self.temperatures) {
+                for system_prompt in axis(&self.system_prompts) {
+                    points.push(SweepPoint {
+                        model: model.clone(),
+                        temperature,
+                        system_prompt,
+                    });
+                }
+            }
+        }
+        points
+    }
+
+    /// Asks every question at every combination and scores each answer with
+    /// `score(question_index, answer)`.
+    pub async fn run<F>(&self, base: &AiConfig, questions: &[Question], score: F) -> SweepReport
+    where
+        F: Fn(usize, &str) -> f64,
+    {
+        let points = self.points();
+        let configs: Vec<AiConfig> = points.iter().map(|point| point.config(base)).collect();
+
+        let asks = points.iter().zip(&configs).flat_map(|(point, ai_config)| {
+            questions
+                .iter()
+                .map(move |question| ask_question(ai_config, point.question(question)))
+        });
+        let answers: Vec<_> = stream::iter(asks)
+            .buffered(self.concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut answers = answers.into_iter();
+        let results = points
+            .into_iter()
+            .map(|point| {
+                let scores: Vec<Option<f64>> = (0..questions.len())
+                    .zip(answers.by_ref())
+                    .map(|(index, answer)| answer.ok().map(|answer| score(index, &answer)))
+                    .collect();
+                let errors = scores.iter().filter(|score| score.is_none()).count();
+                let mean_score = if scores.is_empty() {
+                    0.0
+                } else {
+                    scores.iter().flatten().sum::<f64>() / scores.len() as f64
+                };
+                PointResult {
+                    point,
+                    scores,
+                    mean_score,
+                    errors,
+                }
+            })
+            .collect();
+        SweepReport { results }
+    }
+}
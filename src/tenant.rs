@@ -0,0 +1,250 @@
+use crate::ask_ai::ask_question;
+use crate::audit::{AuditLog, AuditedQuestion};
+use crate::config::{AiConfig, Question};
+use crate::error::{AppError, Result};
+use crate::quota::{MemoryQuotaStore, QuotaStore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::SystemTime;
+
+/// Limits applied to every question asked on behalf of a tenant.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TenantLimits {
+    /// Upper bound on response tokens. A larger (or missing) `max_token` is clamped to it.
+    pub max_token: Option<u32>,
+    /// Maximum number of prior exchanges sent with a question; older ones are dropped.
+    pub max_history: Option<usize>,
+    /// Requests and tokens allowed per window, checked before every dispatch.
+    pub quota: Option<TenantQuota>,
+}
+
+/// Requests and tokens a tenant may use per fixed time window.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TenantQuota {
+    /// Length of the window in seconds. Usage resets when it elapses.
+    pub window_secs: u64,
+    /// Maximum number of questions per window.
+    pub max_requests: Option<u32>,
+    /// Maximum number of tokens per window, prompts and answers included.
+    ///
+    /// Tokens are estimated from text length (about four characters per token), as not every
+    /// provider reports usage.
+    pub max_tokens: Option<u64>,
+}
+
+/// A tenant's usage in the current quota window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TenantUsage {
+    pub requests: u32,
+    pub tokens: u64,
+    /// When the window started; usage resets `window_secs` later.
+    pub window_start: SystemTime,
+}
+
+/// Everything needed to answer questions for one tenant.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TenantConfig {
+    /// Provider, default model and API key used for this tenant's questions.
+    pub ai_config: AiConfig,
+    /// Limits enforced on this tenant's questions.
+    #[serde(default)]
+    pub limits: TenantLimits,
+}
+
+/// Maps tenant ids to their own provider keys, default models and limits.
+///
+/// The registry can be shared (e.g. in an `Arc`) and updated while in use, so tenants can be
+/// onboarded or reconfigured without restarting the service. Quotas are counted per process
+/// unless a shared store is set with `with_quota_store`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::config::{AiConfig, Framework};
+/// use ask_ai::tenant::{TenantConfig, TenantLimits, TenantQuota, TenantRegistry};
+///
+/// let registry = TenantRegistry::new();
+/// registry.insert(
+///     "acme",
+///     TenantConfig {
+///         ai_config: AiConfig {
+///             llm: Framework::Anthropic,
+///             model: "claude-3-5-haiku-latest".to_string(),
+///             api_key: Some(acme_key.into()),
+///             ..Default::default()
+///         },
+///         limits: TenantLimits {
+///             max_token: Some(500),
+///             max_history: Some(10),
+///             quota: Some(TenantQuota { window_secs: 3600, max_requests: Some(100), max_tokens: None }),
+///         },
+///     },
+/// );
+///
+/// // Fails with `AppError::QuotaExceeded` once the hourly quota is used up
+/// let answer = registry.ask_question_for("acme", question).await?;
+/// ```
+#[derive(Debug)]
+pub struct TenantRegistry {
+    tenants: RwLock<HashMap<String, TenantConfig>>,
+    quota_store: Arc<dyn QuotaStore>,
+    audit: Option<AuditLog>,
+}
+
+impl Default for TenantRegistry {
+    fn default() -> Self {
+        Self {
+            tenants: RwLock::default(),
+            quota_store: Arc::new(MemoryQuotaStore::new()),
+            audit: None,
+        }
+    }
+}
+
+impl TenantRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts quota usage in `store` instead of process memory, e.g. to share quotas between
+    /// replicas.
+    pub fn with_quota_store(mut self, store: impl QuotaStore + 'static) -> Self {
+        self.quota_store = Arc::new(store);
+        self
+    }
+
+    /// Records every question asked through the registry, including quota blocks.
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Adds a tenant, replacing any previous configuration for `tenant_id`.
+    pub fn insert(&self, tenant_id: &str, config: TenantConfig) {
+        self.write().insert(tenant_id.to_string(), config);
+    }
+
+    /// Removes a tenant and its usage, returning its configuration.
+    pub async fn remove(&self, tenant_id: &str) -> Option<TenantConfig> {
+        let config = self.write().remove(tenant_id)?;
+        if let Some(quota) = &config.limits.quota {
+            // The tenant is gone either way, a stale counter only expires later
+            let _ = self.quota_store.clear(tenant_id, quota).await;
+        }
+        Some(config)
+    }
+
+    /// Returns a copy of a tenant's configuration.
+    pub fn get(&self, tenant_id: &str) -> Option<TenantConfig> {
+        self.read().get(tenant_id).cloned()
+    }
+
+    /// Lists the registered tenant ids, sorted.
+    pub fn tenant_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.read().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Resolves the configuration for `tenant_id` and applies its limits to `question`.
+    pub fn resolve(&self, tenant_id: &str, mut question: Question) -> Result<(AiConfig, Question)> {
+        let tenant = self
+            .get(tenant_id)
+            .ok_or_else(|| AppError::UnexpectedError(format!("Unknown tenant `{}`", tenant_id)))?;
+        let mut ai_config = tenant.ai_config;
+
+        if let Some(cap) = tenant.limits.max_token {
+            ai_config.max_token = Some(ai_config.max_token.map_or(cap, |max| max.min(cap)));
+        }
+        if let (Some(max_history), Some(messages)) =
+            (tenant.limits.max_history, question.messages.as_mut())
+        {
+            let excess = messages.len().saturating_sub(max_history);
+            messages.drain(..excess);
+        }
+
+        Ok((ai_config, question))
+    }
+
+    /// Returns a tenant's usage in its current quota window, if it asked anything yet.
+    ///
+    /// `None` as well when the tenant has no quota, or the quota store cannot be reached.
+    pub async fn usage_for(&self, tenant_id: &str) -> Option<TenantUsage> {
+        let quota = self.get(tenant_id)?.limits.quota?;
+        self.quota_store
+            .usage(tenant_id, &quota)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Asks a question with the configuration and limits of `tenant_id`.
+    ///
+    /// Returns `AppError::QuotaExceeded` without contacting the provider when the tenant has
+    /// used up its quota for the current window.
+    pub async fn ask_question_for(&self, tenant_id: &str, question: Question) -> Result<String> {
+        let (ai_config, question) = self.resolve(tenant_id, question)?;
+        let audited = AuditedQuestion::new(&ai_config, &question);
+
+        let result = self
+            .dispatch(tenant_id, &ai_config, question, audited.prompt_tokens)
+            .await;
+        if let Some(audit) = &self.audit {
+            audit.record_outcome(tenant_id, &ai_config, &audited, &result)?;
+        }
+        result
+    }
+
+    /// Sends a resolved question, enforcing and updating the tenant's quota.
+    async fn dispatch(
+        &self,
+        tenant_id: &str,
+        ai_config: &AiConfig,
+        question: Question,
+        prompt_tokens: u64,
+    ) -> Result<String> {
+        let quota = self.get(tenant_id).and_then(|tenant| tenant.limits.quota);
+        if let Some(quota) = &quota {
+            self.quota_store
+                .reserve(tenant_id, quota, prompt_tokens)
+                .await?;
+        }
+
+        let answer = ask_question(ai_config, question).await?;
+        if let Some(quota) = &quota {
+            self.quota_store
+                .add_tokens(tenant_id, quota, estimate_tokens(&answer))
+                .await?;
+        }
+        Ok(answer)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, TenantConfig>> {
+        self.tenants.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, TenantConfig>> {
+        self.tenants.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Rough token count of `text`, at about four characters per token.
+pub(crate) fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Estimated tokens of everything sent with `question`.
+pub(crate) fn prompt_tokens(question: &Question) -> u64 {
+    let history = question
+        .messages
+        .iter()
+        .flatten()
+        .map(|prompt| estimate_tokens(&prompt.content) + estimate_tokens(&prompt.output))
+        .sum::<u64>();
+
+    question.system_prompt.as_deref().map_or(0, estimate_tokens)
+        + history
+        + estimate_tokens(&question.new_prompt)
+}
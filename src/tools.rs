@@ -0,0 +1,542 @@
+use crate::ask_ai::{
+    anthropic_request, ask_question, build_anthropic_payload, build_ollama_openai_payload,
+    build_payload, openai_request, prepare, send_request,
+};
+use crate::capabilities::{degrade, Capability, Degradation};
+use crate::config::{AiConfig, Framework, Question};
+use crate::continuation::without_prefill;
+use crate::deadline;
+use crate::error::{AppError, Result};
+use crate::lmstudio::with_loaded_model;
+use crate::privacy::Redactions;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Upper bound on model -> tool -> model round trips before `run_with_tools` gives up.
+const MAX_TOOL_ROUNDS: usize = 10;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type ToolHandler =
+    Arc<dyn Fn(Value) -> BoxFuture<std::result::Result<String, String>> + Send + Sync>;
+type ToolApprover = Arc<dyn Fn(ToolCall) -> BoxFuture<ToolApproval> + Send + Sync>;
+type ArgumentValidator = Arc<dyn Fn(&Value) -> std::result::Result<(), String> + Send + Sync>;
+
+/// A tool invocation requested by the model.
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    /// Provider-assigned id used to match the tool result to the call.
+    pub id: String,
+    /// Name of the registered tool the model wants to run.
+    pub name: String,
+    /// Arguments chosen by the model, as parsed JSON.
+    pub arguments: Value,
+}
+
+/// Decision returned by an approval callback for a single tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolApproval {
+    /// Run the tool.
+    Approve,
+    /// Skip the tool; the reason is sent back to the model as the tool's error output.
+    Deny(String),
+}
+
+/// Limits enforced by `run_with_tools` on what the model is allowed to execute.
+///
+/// Calls rejected by the allowlist, a validator or a timeout are reported back to the model as
+/// tool errors. Exceeding `max_calls` aborts the whole conversation with an error, so a model
+/// stuck in a loop cannot run tools indefinitely.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::tools::ToolPolicy;
+/// use std::time::Duration;
+///
+/// let policy = ToolPolicy::new()
+///     .allow("read_file")
+///     .validate("read_file", |args| match args["path"].as_str() {
+///         Some(path) if !path.contains("..") => Ok(()),
+///         _ => Err("path must stay inside the workspace".to_string()),
+///     })
+///     .timeout("read_file", Duration::from_secs(2))
+///     .max_calls(20);
+/// let registry = registry.with_policy(policy);
+/// ```
+#[derive(Clone, Default)]
+pub struct ToolPolicy {
+    allowed: Option<HashSet<String>>,
+    validators: HashMap<String, ArgumentValidator>,
+    timeouts: HashMap<String, Duration>,
+    default_timeout: Option<Duration>,
+    max_calls: Option<usize>,
+}
+
+impl ToolPolicy {
+    /// Creates a policy that allows every registered tool, without limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `name` to the allowlist. Once any tool is allowed, all others are refused.
+    pub fn allow(mut self, name: &str) -> Self {
+        self.allowed
+            .get_or_insert_with(HashSet::new)
+            .insert(name.to_string());
+        self
+    }
+
+    /// Checks the arguments of every call to `name` before it runs.
+    pub fn validate<F>(mut self, name: &str, validator: F) -> Self
+    where
+        F: Fn(&Value) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        self.validators
+            .insert(name.to_string(), Arc::new(validator));
+        self
+    }
+
+    /// Bounds how long a single call to `name` may run.
+    pub fn timeout(mut self, name: &str, timeout: Duration) -> Self {
+        self.timeouts.insert(name.to_string(), timeout);
+        self
+    }
+
+    /// Bounds how long any tool without its own `timeout` may run.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Bounds the number of tool calls over a whole `run_with_tools` conversation.
+    pub fn max_calls(mut self, max_calls: usize) -> Self {
+        self.max_calls = Some(max_calls);
+        self
+    }
+
+    /// Returns the reason `call` is refused, if it is.
+    fn check(&self, call: &ToolCall) -> Option<String> {
+        if let Some(allowed) = &self.allowed {
+            if !allowed.contains(&call.name) {
+                return Some(format!("tool `{}` is not allowed by policy", call.name));
+            }
+        }
+        self.validators
+            .get(&call.name)
+            .and_then(|validator| validator(&call.arguments).err())
+            .map(|e| format!("invalid arguments for `{}`: {}", call.name, e))
+    }
+
+    fn timeout_for(&self, name: &str) -> Option<Duration> {
+        self.timeouts.get(name).copied().or(self.default_timeout)
+    }
+}
+
+#[derive(Clone)]
+struct Tool {
+    name: String,
+    description: String,
+    parameters: Value,
+    handler: ToolHandler,
+}
+
+/// The set of tools the model may call during `run_with_tools`.
+///
+/// Each tool is an async Rust closure receiving the model's JSON arguments and returning the
+/// text handed back to the model (`Err` values are reported to the model as tool errors).
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::tools::{ToolApproval, ToolRegistry};
+/// use serde_json::json;
+///
+/// let registry = ToolRegistry::new()
+///     .register(
+///         "get_weather",
+///         "Current weather for a city",
+///         json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+///         |args| async move { Ok(format!("Sunny in {}", args["city"])) },
+///     )
+///     .with_approval(|call| async move {
+///         if call.name == "get_weather" {
+///             ToolApproval::Approve
+///         } else {
+///             ToolApproval::Deny("not allowed".to_string())
+///         }
+///     });
+/// ```
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Tool>,
+    approver: Option<ToolApprover>,
+    policy: ToolPolicy,
+}
+
+impl ToolRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a tool. `parameters` is the JSON Schema describing its arguments.
+    pub fn register<F, Fut>(
+        mut self,
+        name: &str,
+        description: &str,
+        parameters: Value,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<String, String>> + Send + 'static,
+    {
+        self.tools.retain(|tool| tool.name != name);
+        self.tools.push(Tool {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+            handler: Arc::new(move |args| Box::pin(handler(args))),
+        });
+        self
+    }
+
+    /// Requires every tool call to be confirmed by `approver` before it runs.
+    ///
+    /// The callback may await anything (a prompt to a human, a message on a channel, ...).
+    /// A `ToolApproval::Deny` skips the tool and reports the reason to the model instead.
+    pub fn with_approval<F, Fut>(mut self, approver: F) -> Self
+    where
+        F: Fn(ToolCall) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ToolApproval> + Send + 'static,
+    {
+        self.approver = Some(Arc::new(move |call| Box::pin(approver(call))));
+        self
+    }
+
+    /// Enforces `policy` on every tool call made through this registry.
+    pub fn with_policy(mut self, policy: ToolPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Tool definitions in OpenAI's `tools` format.
+    fn openai_tools(&self) -> Value {
+        self.tools
+            .iter()
+            .map(|tool| {
+                serde_json::json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Tool definitions in Anthropic's `tools` format.
+    fn anthropic_tools(&self) -> Value {
+        self.tools
+            .iter()
+            .map(|tool| {
+                serde_json::json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": tool.parameters
+                })
+            })
+            .collect()
+    }
+
+    /// Counts one more call against the policy's `max_calls`, failing once it is exceeded.
+    fn count_call(&self, calls_made: &mut usize, ai_config: &AiConfig) -> Result<()> {
+        *calls_made += 1;
+        match self.policy.max_calls {
+            Some(max_calls) if *calls_made > max_calls => Err(AppError::ModelError {
+                model_name: ai_config.model.to_string(),
+                failure_str: format!("Exceeded the limit of {} tool calls", max_calls),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs one tool call through approval and its handler. Every failure is returned as the
+    /// text the model sees, so it can tell what went wrong.
+    async fn execute(&self, call: ToolCall) -> std::result::Result<String, String> {
+        let Some(tool) = self.tools.iter().find(|tool| tool.name == call.name) else {
+            return Err(format!("unknown tool `{}`", call.name));
+        };
+
+        if let Some(reason) = self.policy.check(&call) {
+            return Err(reason);
+        }
+
+        if let Some(approver) = &self.approver {
+            if let ToolApproval::Deny(reason) = approver(call.clone()).await {
+                return Err(format!("tool call denied: {}", reason));
+            }
+        }
+
+        let run = (tool.handler)(call.arguments);
+        match self.policy.timeout_for(&call.name) {
+            Some(limit) => match tokio::time::timeout(limit, run).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("tool `{}` timed out after {:?}", call.name, limit)),
+            },
+            None => run.await,
+        }
+    }
+}
+
+/// Asks a question while letting the model call the tools in `registry`.
+///
+/// The crate runs the model -> tool -> model loop until the model produces a final answer,
+/// which is returned. Tool errors, unknown tools and denied calls are reported back to the
+/// model rather than aborting the loop; only exceeding the policy's `max_calls` aborts it.
+///
+/// The question goes through the same checks and transformations as with `ask_question`
+/// (privacy mode, moderation, limits, ...), and redacted values are restored in the answer.
+/// Under privacy mode, tools are called with the original values and their outputs are
+/// redacted before they go back to the model.
+///
+/// Both tool protocols are handled: OpenAI's, also spoken by Mistral, Groq, OpenRouter,
+/// Hugging Face, LM Studio and Ollama's OpenAI-compatible endpoint, and Anthropic's. An
+/// Anthropic prefill is not sent, as the reply has to be free to start with a tool call.
+///
+/// With `AiConfig::on_unsupported` set, a model without tool support fails early, answers
+/// without tools, or hands the run to the fallback model.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::tools::run_with_tools;
+///
+/// let answer = run_with_tools(&ai_config, question, &registry).await?;
+/// ```
+pub async fn run_with_tools(
+    ai_config: &AiConfig,
+    question: Question,
+    registry: &ToolRegistry,
+) -> Result<String> {
+    deadline::within(ai_config, run_tool_loop(ai_config, question, registry)).await
+}
+
+async fn run_tool_loop(
+    ai_config: &AiConfig,
+    question: Question,
+    registry: &ToolRegistry,
+) -> Result<String> {
+    match degrade(ai_config, Capability::Tools)? {
+        Degradation::Supported => {}
+        Degradation::Strip => return ask_question(ai_config, question).await,
+        Degradation::Fallback(fallback) => {
+            return Box::pin(run_with_tools(fallback, question, registry)).await;
+        }
+    }
+
+    match &ai_config.llm {
+        Framework::OpenAI
+        | Framework::Mistral
+        | Framework::Groq
+        | Framework::OpenRouter
+        | Framework::HuggingFace
+        | Framework::Ollama => run_openai_tools(question, ai_config, registry).await,
+        Framework::LmStudio => {
+            let ai_config = with_loaded_model(ai_config).await?;
+            run_openai_tools(question, &ai_config, registry).await
+        }
+        Framework::Anthropic => {
+            run_anthropic_tools(question, &without_prefill(ai_config), registry).await
+        }
+        other => Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Tool execution is not supported for {} yet", other),
+        }),
+    }
+}
+
+async fn run_openai_tools(
+    question: Question,
+    ai_config: &AiConfig,
+    registry: &ToolRegistry,
+) -> Result<String> {
+    let (question, mut redactions) = prepare(ai_config, question).await?;
+    let mut payload = match ai_config.llm {
+        Framework::Ollama => build_ollama_openai_payload(&question, ai_config),
+        _ => build_payload(&question, ai_config),
+    };
+    payload["tools"] = registry.openai_tools();
+
+    let mut calls_made = 0;
+    for _ in 0..MAX_TOOL_ROUNDS {
+        let resp = send_request(openai_request(ai_config)?.json(&payload), ai_config).await?;
+        let response = parse_response(resp, ai_config).await?;
+
+        let message = response["choices"][0]["message"].clone();
+        let calls = message["tool_calls"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        if calls.is_empty() {
+            let answer = message["content"]
+                .as_str()
+                .ok_or_else(|| AppError::ModelError {
+                    model_name: ai_config.model.to_string(),
+                    failure_str: "Failed to extract content from OpenAI response".to_string(),
+                })?
+                .to_string();
+            return Ok(redactions.restore(&answer));
+        }
+
+        // The assistant turn carrying the calls must precede their results
+        let mut turn = vec![message];
+        for call in calls {
+            registry.count_call(&mut calls_made, ai_config)?;
+
+            let id = call["id"].as_str().unwrap_or_default().to_string();
+            let name = call["function"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            let raw_arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+
+            let output = match serde_json::from_str(raw_arguments) {
+                Ok(arguments) => {
+                    registry
+                        .execute(ToolCall {
+                            id: id.clone(),
+                            name,
+                            arguments: redactions.restore_json(arguments),
+                        })
+                        .await
+                }
+                Err(e) => Err(format!("invalid tool arguments: {}", e)),
+            };
+            // OpenAI's protocol has no error flag on tool results
+            let output = output.unwrap_or_else(|e| format!("Error: {}", e));
+            let output = redact_output(ai_config, &output, &mut redactions)?;
+
+            turn.push(serde_json::json!({
+                "role": "tool",
+                "tool_call_id": id,
+                "content": output
+            }));
+        }
+        if let Some(messages) = payload["messages"].as_array_mut() {
+            messages.extend(turn);
+        }
+    }
+
+    Err(AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("No final answer after {} tool rounds", MAX_TOOL_ROUNDS),
+    })
+}
+
+async fn run_anthropic_tools(
+    question: Question,
+    ai_config: &AiConfig,
+    registry: &ToolRegistry,
+) -> Result<String> {
+    let (question, mut redactions) = prepare(ai_config, question).await?;
+    let mut payload = build_anthropic_payload(&question, ai_config);
+    // Next to any server-side tools the options enabled
+    let mut tools = payload["tools"].as_array().cloned().unwrap_or_default();
+    tools.extend(
+        registry
+            .anthropic_tools()
+            .as_array()
+            .cloned()
+            .unwrap_or_default(),
+    );
+    payload["tools"] = Value::from(tools);
+
+    let mut calls_made = 0;
+    for _ in 0..MAX_TOOL_ROUNDS {
+        let resp = send_request(anthropic_request(ai_config)?.json(&payload), ai_config).await?;
+        let response = parse_response(resp, ai_config).await?;
+
+        let content =
+            response["content"]
+                .as_array()
+                .cloned()
+                .ok_or_else(|| AppError::ModelError {
+                    model_name: ai_config.model.to_string(),
+                    failure_str: "Failed to extract content from Anthropic response".to_string(),
+                })?;
+        let calls: Vec<&Value> = content
+            .iter()
+            .filter(|block| block["type"] == "tool_use")
+            .collect();
+        if calls.is_empty() {
+            let answer: String = content
+                .iter()
+                .filter_map(|block| block["text"].as_str())
+                .collect();
+            return Ok(redactions.restore(&answer));
+        }
+
+        // All results of a turn go back in one user message
+        let mut results = vec![];
+        for call in calls {
+            registry.count_call(&mut calls_made, ai_config)?;
+
+            let id = call["id"].as_str().unwrap_or_default().to_string();
+            let output = registry
+                .execute(ToolCall {
+                    id: id.clone(),
+                    name: call["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: redactions.restore_json(call["input"].clone()),
+                })
+                .await;
+            results.push(match output {
+                Ok(output) => serde_json::json!({
+                    "type": "tool_result",
+                    "tool_use_id": id,
+                    "content": redact_output(ai_config, &output, &mut redactions)?
+                }),
+                Err(e) => serde_json::json!({
+                    "type": "tool_result",
+                    "tool_use_id": id,
+                    "content": redact_output(ai_config, &e, &mut redactions)?,
+                    "is_error": true
+                }),
+            });
+        }
+        if let Some(messages) = payload["messages"].as_array_mut() {
+            messages.push(serde_json::json!({ "role": "assistant", "content": content }));
+            messages.push(serde_json::json!({ "role": "user", "content": results }));
+        }
+    }
+
+    Err(AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("No final answer after {} tool rounds", MAX_TOOL_ROUNDS),
+    })
+}
+
+/// A tool's output, redacted like the question before it goes back to the model.
+fn redact_output(
+    ai_config: &AiConfig,
+    output: &str,
+    redactions: &mut Redactions,
+) -> Result<String> {
+    match &ai_config.privacy {
+        Some(privacy) => privacy.redact_text(output, redactions),
+        None => Ok(output.to_string()),
+    }
+}
+
+async fn parse_response(resp: reqwest::Response, ai_config: &AiConfig) -> Result<Value> {
+    resp.json().await.map_err(|e| AppError::ModelError {
+        model_name: ai_config.model.to_string(),
+        failure_str: format!("Failed to parse JSON response: {}", e),
+    })
+}
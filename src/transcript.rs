@@ -0,0 +1,778 @@
+use crate::config::Framework;
+use crate::conversation::Conversation;
+use crate::error::{AppError, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Who a transcript message is from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    /// The result of a tool call, sent back to the model.
+    Tool,
+}
+
+/// Where an image comes from.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ImageSource {
+    Url {
+        url: String,
+    },
+    Base64 {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        media_type: Option<String>,
+        data: String,
+    },
+}
+
+impl ImageSource {
+    /// Encodes `bytes`, an image of type `media_type` (e.g. `image/png`), as base64.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// use ask_ai::transcript::ImageSource;
+    ///
+    /// // Ask a local vision model such as llava or llama3.2-vision about a photo
+    /// let question = Question {
+    ///     new_prompt: "What is in this picture?".to_string(),
+    ///     images: vec![ImageSource::from_bytes("image/jpeg", &std::fs::read("photo.jpg")?)],
+    ///     ..question
+    /// };
+    /// ```
+    pub fn from_bytes(media_type: &str, bytes: &[u8]) -> Self {
+        ImageSource::Base64 {
+            media_type: Some(media_type.to_string()),
+            data: BASE64_STANDARD.encode(bytes),
+        }
+    }
+
+    /// The image as an OpenAI `image_url` content part; base64 data goes in a `data:` URL.
+    pub(crate) fn openai_part(&self) -> Value {
+        let url = match self {
+            ImageSource::Url { url } => url.clone(),
+            ImageSource::Base64 { media_type, data } => format!(
+                "data:{};base64,{}",
+                media_type.as_deref().unwrap_or("image/png"),
+                data
+            ),
+        };
+        json!({ "type": "image_url", "image_url": { "url": url } })
+    }
+
+    /// The image as an Anthropic `image` content block.
+    pub(crate) fn anthropic_block(&self) -> Value {
+        json!({
+            "type": "image",
+            "source": match self {
+                ImageSource::Url { url } => json!({ "type": "url", "url": url }),
+                ImageSource::Base64 { media_type, data } => json!({
+                    "type": "base64",
+                    "media_type": media_type.as_deref().unwrap_or("image/png"),
+                    "data": data,
+                }),
+            },
+        })
+    }
+}
+
+/// One part of a message's content.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    /// A tool invocation requested by the model.
+    ToolCall {
+        id: String,
+        name: String,
+        arguments: Value,
+    },
+    /// The output of the tool call `call_id`.
+    ToolResult {
+        call_id: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+}
+
+/// Tokens reported by the provider for the call that produced a message.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TranscriptUsage {
+    pub prompt_tokens: u64,
+    pub answer_tokens: u64,
+}
+
+/// One message of a transcript.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TranscriptMessage {
+    pub role: Role,
+    pub content: Vec<ContentPart>,
+    /// Seconds since the Unix epoch, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// The model that wrote an assistant message, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TranscriptUsage>,
+}
+
+impl TranscriptMessage {
+    /// A message holding `text` alone.
+    pub fn text(role: Role, text: &str) -> Self {
+        Self {
+            role,
+            content: vec![ContentPart::Text {
+                text: text.to_string(),
+            }],
+            timestamp: None,
+            model: None,
+            usage: None,
+        }
+    }
+
+    /// The text parts, joined by blank lines.
+    pub fn joined_text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// A conversation in one provider-independent form: roles, text, images, tool calls and
+/// their results, with token usage and timestamps where the source has them.
+///
+/// Transcripts are read from and written to the request and response bodies of OpenAI,
+/// Anthropic and Ollama, so logs from every provider can be stored and analyzed together.
+/// Anthropic tool results, which arrive inside user messages, become `Role::Tool` messages
+/// like OpenAI's; Ollama tool calls, which have no ids, are numbered `call_0`, `call_1`...
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::transcript::Transcript;
+///
+/// let mut transcript = Transcript::from_anthropic(&request_body)?;
+/// transcript.push_anthropic_response(&response_body)?;
+///
+/// // Replay the same conversation against OpenAI
+/// let messages = transcript.to_openai();
+/// println!("{:?}", transcript.usage());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Transcript {
+    /// The provider the transcript was read from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<Framework>,
+    pub messages: Vec<TranscriptMessage>,
+}
+
+impl Transcript {
+    /// Reads an OpenAI `messages` array, or any object holding one (a chat completion request,
+    /// a fine-tuning line).
+    pub fn from_openai(value: &Value) -> Result<Self> {
+        let mut transcript = Self {
+            framework: Some(Framework::OpenAI),
+            messages: vec![],
+        };
+        for message in messages_of(value, "OpenAI")? {
+            transcript.messages.push(openai_message(message)?);
+        }
+        Ok(transcript)
+    }
+
+    /// Appends the assistant message of an OpenAI chat completion, with its usage.
+    pub fn push_openai_response(&mut self, response: &Value) -> Result<()> {
+        let mut message = openai_message(&response["choices"][0]["message"])?;
+        message.timestamp = response["created"].as_u64();
+        message.model = response["model"].as_str().map(String::from);
+        message.usage = usage(&response["usage"], "prompt_tokens", "completion_tokens");
+        self.messages.push(message);
+        Ok(())
+    }
+
+    /// Reads an Anthropic Messages request: its `system` prompt and `messages`.
+    pub fn from_anthropic(value: &Value) -> Result<Self> {
+        let mut transcript = Self {
+            framework: Some(Framework::Anthropic),
+            messages: vec![],
+        };
+        match &value["system"] {
+            Value::String(text) => transcript
+                .messages
+                .push(TranscriptMessage::text(Role::System, text)),
+            system @ Value::Array(_) => transcript
+                .messages
+                .push(TranscriptMessage::text(Role::System, &block_text(system))),
+            _ => {}
+        }
+        for message in messages_of(value, "Anthropic")? {
+            transcript.messages.extend(anthropic_messages(message)?);
+        }
+        Ok(transcript)
+    }
+
+    /// Appends the reply of an Anthropic Messages response, with its usage.
+    pub fn push_anthropic_response(&mut self, response: &Value) -> Result<()> {
+        let mut messages = anthropic_messages(&json!({
+            "role": "assistant",
+            "content": response["content"],
+        }))?;
+        if let Some(message) = messages.first_mut() {
+            message.model = response["model"].as_str().map(String::from);
+            message.usage = usage(&response["usage"], "input_tokens", "output_tokens");
+        }
+        self.messages.extend(messages);
+        Ok(())
+    }
+
+    /// Reads an Ollama chat `messages` array, or a chat request holding one.
+    pub fn from_ollama(value: &Value) -> Result<Self> {
+        let mut transcript = Self {
+            framework: Some(Framework::Ollama),
+            messages: vec![],
+        };
+        for message in messages_of(value, "Ollama")? {
+            let message = transcript.ollama_message(message)?;
+            transcript.messages.push(message);
+        }
+        Ok(transcript)
+    }
+
+    /// Appends the message of an Ollama chat response, with its usage.
+    pub fn push_ollama_response(&mut self, response: &Value) -> Result<()> {
+        let mut message = self.ollama_message(&response["message"])?;
+        message.model = response["model"].as_str().map(String::from);
+        message.usage = usage(response, "prompt_eval_count", "eval_count");
+        self.messages.push(message);
+        Ok(())
+    }
+
+    /// Writes the transcript as an OpenAI `messages` array.
+    ///
+    /// Base64 images become `data:` URLs.
+    pub fn to_openai(&self) -> Value {
+        let mut messages = vec![];
+        for message in &self.messages {
+            if message.role == Role::Tool {
+                for part in &message.content {
+                    if let ContentPart::ToolResult {
+                        call_id, content, ..
+                    } = part
+                    {
+                        messages.push(json!({
+                            "role": "tool",
+                            "tool_call_id": call_id,
+                            "content": content,
+                        }));
+                    }
+                }
+                continue;
+            }
+
+            let mut parts = vec![];
+            let mut tool_calls = vec![];
+            for part in &message.content {
+                match part {
+                    ContentPart::Text { text } => {
+                        parts.push(json!({ "type": "text", "text": text }))
+                    }
+                    ContentPart::Image { source } => parts.push(source.openai_part()),
+                    ContentPart::ToolCall {
+                        id,
+                        name,
+                        arguments,
+                    } => tool_calls.push(json!({
+                        "id": id,
+                        "type": "function",
+                        "function": { "name": name, "arguments": arguments.to_string() },
+                    })),
+                    ContentPart::ToolResult { .. } => {}
+                }
+            }
+
+            let mut out = Map::new();
+            out.insert("role".to_string(), json!(role_name(message.role)));
+            // Plain text stays a string, as most tools expect
+            let content = match parts.as_slice() {
+                [] if !tool_calls.is_empty() => Value::Null,
+                [] => json!(""),
+                [part] if part["type"] == "text" => part["text"].clone(),
+                _ => Value::Array(parts),
+            };
+            out.insert("content".to_string(), content);
+            if !tool_calls.is_empty() {
+                out.insert("tool_calls".to_string(), Value::Array(tool_calls));
+            }
+            messages.push(Value::Object(out));
+        }
+        Value::Array(messages)
+    }
+
+    /// Writes the transcript as the `system` and `messages` of an Anthropic request.
+    ///
+    /// Consecutive tool results are sent back in one user message, as Anthropic requires.
+    pub fn to_anthropic(&self) -> Value {
+        let mut system = vec![];
+        let mut messages: Vec<Value> = vec![];
+        for message in &self.messages {
+            if message.role == Role::System {
+                system.push(message.joined_text());
+                continue;
+            }
+
+            let blocks: Vec<Value> = message
+                .content
+                .iter()
+                .map(|part| match part {
+                    ContentPart::Text { text } => json!({ "type": "text", "text": text }),
+                    ContentPart::Image { source } => source.anthropic_block(),
+                    ContentPart::ToolCall {
+                        id,
+                        name,
+                        arguments,
+                    } => json!({ "type": "tool_use", "id": id, "name": name, "input": arguments }),
+                    ContentPart::ToolResult {
+                        call_id,
+                        content,
+                        is_error,
+                    } => json!({
+                        "type": "tool_result",
+                        "tool_use_id": call_id,
+                        "content": content,
+                        "is_error": is_error,
+                    }),
+                })
+                .collect();
+
+            let role = match message.role {
+                Role::Assistant => "assistant",
+                _ => "user",
+            };
+            match messages.last_mut() {
+                Some(last) if message.role == Role::Tool && last["role"] == role => {
+                    if let Some(content) = last["content"].as_array_mut() {
+                        content.extend(blocks);
+                    }
+                }
+                _ => messages.push(json!({ "role": role, "content": blocks })),
+            }
+        }
+
+        let mut out = Map::new();
+        if !system.is_empty() {
+            out.insert("system".to_string(), json!(system.join("\n\n")));
+        }
+        out.insert("messages".to_string(), Value::Array(messages));
+        Value::Object(out)
+    }
+
+    /// Writes the transcript as an Ollama chat `messages` array.
+    ///
+    /// Ollama only takes base64 images, so images given by URL are left out.
+    pub fn to_ollama(&self) -> Value {
+        let mut messages = vec![];
+        for message in &self.messages {
+            if message.role == Role::Tool {
+                for part in &message.content {
+                    if let ContentPart::ToolResult { content, .. } = part {
+                        messages.push(json!({ "role": "tool", "content": content }));
+                    }
+                }
+                continue;
+            }
+
+            let mut out = Map::new();
+            out.insert("role".to_string(), json!(role_name(message.role)));
+            out.insert("content".to_string(), json!(message.joined_text()));
+            let images: Vec<&str> = message
+                .content
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Image {
+                        source: ImageSource::Base64 { data, .. },
+                    } => Some(data.as_str()),
+                    _ => None,
+                })
+                .collect();
+            if !images.is_empty() {
+                out.insert("images".to_string(), json!(images));
+            }
+            let tool_calls: Vec<Value> = message
+                .content
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::ToolCall {
+                        name, arguments, ..
+                    } => Some(json!({ "function": { "name": name, "arguments": arguments } })),
+                    _ => None,
+                })
+                .collect();
+            if !tool_calls.is_empty() {
+                out.insert("tool_calls".to_string(), Value::Array(tool_calls));
+            }
+            messages.push(Value::Object(out));
+        }
+        Value::Array(messages)
+    }
+
+    /// The text of the transcript as a `Conversation`; images and tool traffic are dropped.
+    pub fn conversation(&self) -> Conversation {
+        let system = self
+            .messages
+            .iter()
+            .filter(|message| message.role == Role::System)
+            .map(TranscriptMessage::joined_text)
+            .collect::<Vec<_>>();
+        let mut conversation = Conversation::new((!system.is_empty()).then(|| system.join("\n\n")));
+
+        let mut pending: Option<(String, String)> = None;
+        for message in &self.messages {
+            let text = message.joined_text();
+            match message.role {
+                Role::User if !text.is_empty() => {
+                    if let Some((content, output)) = pending.take() {
+                        conversation.push(&content, &output);
+                    }
+                    pending = Some((text, String::new()));
+                }
+                Role::Assistant if !text.is_empty() => {
+                    let (_, output) = pending.get_or_insert_with(Default::default);
+                    if !output.is_empty() {
+                        output.push_str("\n\n");
+                    }
+                    output.push_str(&text);
+                }
+                _ => {}
+            }
+        }
+        if let Some((content, output)) = pending {
+            conversation.push(&content, &output);
+        }
+        conversation
+    }
+
+    /// Token usage summed over the messages that report it.
+    pub fn usage(&self) -> TranscriptUsage {
+        self.messages
+            .iter()
+            .filter_map(|message| message.usage)
+            .fold(TranscriptUsage::default(), |total, usage| TranscriptUsage {
+                prompt_tokens: total.prompt_tokens + usage.prompt_tokens,
+                answer_tokens: total.answer_tokens + usage.answer_tokens,
+            })
+    }
+
+    fn ollama_message(&self, message: &Value) -> Result<TranscriptMessage> {
+        let role = match message["role"].as_str() {
+            Some("system") => Role::System,
+            Some("user") => Role::User,
+            Some("assistant") => Role::Assistant,
+            Some("tool") => Role::Tool,
+            other => return Err(invalid_role("Ollama", other)),
+        };
+        let text = message["content"].as_str().unwrap_or_default();
+
+        let mut content = vec![];
+        if role == Role::Tool {
+            // Results answer the calls in order
+            let answered = self
+                .messages
+                .iter()
+                .filter(|message| message.role == Role::Tool)
+                .count();
+            content.push(ContentPart::ToolResult {
+                call_id: format!("call_{}", answered),
+                content: text.to_string(),
+                is_error: false,
+            });
+        } else if !text.is_empty() {
+            content.push(ContentPart::Text {
+                text: text.to_string(),
+            });
+        }
+        for data in message["images"].as_array().into_iter().flatten() {
+            if let Some(data) = data.as_str() {
+                content.push(ContentPart::Image {
+                    source: ImageSource::Base64 {
+                        media_type: None,
+                        data: data.to_string(),
+                    },
+                });
+            }
+        }
+        let calls = self
+            .messages
+            .iter()
+            .flat_map(|message| &message.content)
+            .filter(|part| matches!(part, ContentPart::ToolCall { .. }))
+            .count();
+        for (index, call) in message["tool_calls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            content.push(ContentPart::ToolCall {
+                id: format!("call_{}", calls + index),
+                name: call["function"]["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                arguments: call["function"]["arguments"].clone(),
+            });
+        }
+        Ok(bare_message(role, content))
+    }
+}
+
+impl From<&Conversation> for Transcript {
+    fn from(conversation: &Conversation) -> Self {
+        let mut messages = vec![];
+        if let Some(system_prompt) = &conversation.system_prompt {
+            messages.push(TranscriptMessage::text(Role::System, system_prompt));
+        }
+        for exchange in &conversation.messages {
+            if !exchange.content.is_empty() {
+                messages.push(TranscriptMessage::text(Role::User, &exchange.content));
+            }
+            if !exchange.output.is_empty() {
+                messages.push(TranscriptMessage::text(Role::Assistant, &exchange.output));
+            }
+        }
+        Self {
+            framework: None,
+            messages,
+        }
+    }
+}
+
+fn openai_message(message: &Value) -> Result<TranscriptMessage> {
+    let role = match message["role"].as_str() {
+        Some("system") | Some("developer") => Role::System,
+        Some("user") => Role::User,
+        Some("assistant") => Role::Assistant,
+        Some("tool") => Role::Tool,
+        other => return Err(invalid_role("OpenAI", other)),
+    };
+
+    let mut content = vec![];
+    if role == Role::Tool {
+        content.push(ContentPart::ToolResult {
+            call_id: message["tool_call_id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            content: match &message["content"] {
+                Value::String(text) => text.clone(),
+                parts => block_text(parts),
+            },
+            is_error: false,
+        });
+        return Ok(bare_message(role, content));
+    }
+
+    match &message["content"] {
+        Value::String(text) if !text.is_empty() => {
+            content.push(ContentPart::Text { text: text.clone() })
+        }
+        Value::Array(parts) => {
+            for part in parts {
+                match part["type"].as_str() {
+                    Some("text") => content.push(ContentPart::Text {
+                        text: part["text"].as_str().unwrap_or_default().to_string(),
+                    }),
+                    Some("image_url") => {
+                        let url = part["image_url"]["url"].as_str().unwrap_or_default();
+                        content.push(ContentPart::Image {
+                            source: image_from_url(url),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+    for call in message["tool_calls"].as_array().into_iter().flatten() {
+        let arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+        content.push(ContentPart::ToolCall {
+            id: call["id"].as_str().unwrap_or_default().to_string(),
+            name: call["function"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            // Models sometimes send arguments that are not valid JSON; keep them as text
+            arguments: serde_json::from_str(arguments)
+                .unwrap_or_else(|_| Value::String(arguments.to_string())),
+        });
+    }
+    Ok(bare_message(role, content))
+}
+
+/// An Anthropic message, with each tool result split out into a `Role::Tool` message.
+fn anthropic_messages(message: &Value) -> Result<Vec<TranscriptMessage>> {
+    let role = match message["role"].as_str() {
+        Some("user") => Role::User,
+        Some("assistant") => Role::Assistant,
+        other => return Err(invalid_role("Anthropic", other)),
+    };
+
+    let blocks = match &message["content"] {
+        Value::String(text) => {
+            return Ok(vec![TranscriptMessage::text(role, text)]);
+        }
+        Value::Array(blocks) => blocks,
+        _ => return Ok(vec![bare_message(role, vec![])]),
+    };
+
+    let mut content = vec![];
+    let mut results = vec![];
+    for block in blocks {
+        match block["type"].as_str() {
+            Some("text") => content.push(ContentPart::Text {
+                text: block["text"].as_str().unwrap_or_default().to_string(),
+            }),
+            Some("image") => {
+                let source = &block["source"];
+                let source = match source["type"].as_str() {
+                    Some("url") => ImageSource::Url {
+                        url: source["url"].as_str().unwrap_or_default().to_string(),
+                    },
+                    _ => ImageSource::Base64 {
+                        media_type: source["media_type"].as_str().map(String::from),
+                        data: source["data"].as_str().unwrap_or_default().to_string(),
+                    },
+                };
+                content.push(ContentPart::Image { source });
+            }
+            Some("tool_use") => content.push(ContentPart::ToolCall {
+                id: block["id"].as_str().unwrap_or_default().to_string(),
+                name: block["name"].as_str().unwrap_or_default().to_string(),
+                arguments: block["input"].clone(),
+            }),
+            Some("tool_result") => results.push(bare_message(
+                Role::Tool,
+                vec![ContentPart::ToolResult {
+                    call_id: block["tool_use_id"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    content: match &block["content"] {
+                        Value::String(text) => text.clone(),
+                        blocks => block_text(blocks),
+                    },
+                    is_error: block["is_error"].as_bool().unwrap_or(false),
+                }],
+            )),
+            _ => {}
+        }
+    }
+
+    let mut messages = results;
+    if !content.is_empty() || messages.is_empty() {
+        messages.push(bare_message(role, content));
+    }
+    Ok(messages)
+}
+
+fn bare_message(role: Role, content: Vec<ContentPart>) -> TranscriptMessage {
+    TranscriptMessage {
+        role,
+        content,
+        timestamp: None,
+        model: None,
+        usage: None,
+    }
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+/// `data:` URLs become base64 images, anything else stays a URL.
+fn image_from_url(url: &str) -> ImageSource {
+    url.strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(media_type, data)| ImageSource::Base64 {
+            media_type: (!media_type.is_empty()).then(|| media_type.to_string()),
+            data: data.to_string(),
+        })
+        .unwrap_or_else(|| ImageSource::Url {
+            url: url.to_string(),
+        })
+}
+
+/// Concatenates the text blocks of a content array.
+fn block_text(content: &Value) -> String {
+    content
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|block| block["type"].as_str() == Some("text"))
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+fn usage(value: &Value, prompt_key: &str, answer_key: &str) -> Option<TranscriptUsage> {
+    let prompt_tokens = value[prompt_key].as_u64();
+    let answer_tokens = value[answer_key].as_u64();
+    (prompt_tokens.is_some() || answer_tokens.is_some()).then(|| TranscriptUsage {
+        prompt_tokens: prompt_tokens.unwrap_or(0),
+        answer_tokens: answer_tokens.unwrap_or(0),
+    })
+}
+
+fn messages_of<'a>(value: &'a Value, source: &str) -> Result<&'a Vec<Value>> {
+    match value {
+        Value::Array(messages) => Ok(messages),
+        Value::Object(object) => {
+            object
+                .get("messages")
+                .and_then(Value::as_array)
+                .ok_or_else(|| {
+                    AppError::UnexpectedError(format!(
+                        "Invalid {} transcript: object without `messages`",
+                        source
+                    ))
+                })
+        }
+        _ => Err(AppError::UnexpectedError(format!(
+            "Invalid {} transcript: expected a list of messages",
+            source
+        ))),
+    }
+}
+
+fn invalid_role(source: &str, role: Option<&str>) -> AppError {
+    AppError::UnexpectedError(format!(
+        "Invalid {} transcript: unknown role {:?}",
+        source,
+        role.unwrap_or_default()
+    ))
+}
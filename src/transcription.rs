@@ -0,0 +1,152 @@
+use crate::ask_ai::{openai_api_request, send_request};
+use crate::config::{AiConfig, Framework};
+use crate::error::{AppError, Result};
+use reqwest::multipart::{Form, Part};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// The audio to transcribe: a file to read, or its bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioFile {
+    Path(PathBuf),
+    /// The bytes of an audio file; the file name's extension (e.g. `.mp3`, `.wav`) tells the
+    /// provider its format.
+    Bytes {
+        file_name: String,
+        data: Vec<u8>,
+    },
+}
+
+/// The form of a transcription.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptFormat {
+    /// The transcribed text.
+    #[default]
+    Json,
+    /// The transcribed text, as plain text from the provider.
+    Text,
+    /// SubRip subtitles.
+    Srt,
+    /// WebVTT subtitles.
+    Vtt,
+    /// JSON with the text, language, duration and timed segments.
+    VerboseJson,
+}
+
+impl TranscriptFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            TranscriptFormat::Json => "json",
+            TranscriptFormat::Text => "text",
+            TranscriptFormat::Srt => "srt",
+            TranscriptFormat::Vtt => "vtt",
+            TranscriptFormat::VerboseJson => "verbose_json",
+        }
+    }
+}
+
+/// Options of a transcription request. Unset options take the provider's default.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TranscriptionOptions {
+    /// The spoken language as an ISO-639-1 code (e.g. `fr`), which improves accuracy and
+    /// latency over detection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Text to guide the model's style or spelling of names, e.g. a previous segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub response_format: TranscriptFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+/// Transcribes `audio` with the speech-to-text model named by `ai_config.model`, e.g.
+/// `whisper-1` or `gpt-4o-transcribe` on OpenAI, `whisper-large-v3` on Groq.
+///
+/// Uses the `audio/transcriptions` endpoint next to chat completions, so
+/// `AiConfig::base_url` and the `*_API_URL` overrides apply. With
+/// `TranscriptFormat::Json` the transcribed text is returned, with the other formats the
+/// response body as is. Other providers fail with `AppError::ModelError`.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::transcription::{transcribe_audio, AudioFile, TranscriptionOptions};
+///
+/// let ai_config = AiConfig {
+///     llm: Framework::Groq,
+///     model: "whisper-large-v3".to_string(),
+///     ..Default::default()
+/// };
+/// let options = TranscriptionOptions {
+///     language: Some("en".to_string()),
+///     ..Default::default()
+/// };
+/// let text = transcribe_audio(&ai_config, AudioFile::Path("standup.mp3".into()), &options).await?;
+/// ```
+pub async fn transcribe_audio(
+    ai_config: &AiConfig,
+    audio: AudioFile,
+    options: &TranscriptionOptions,
+) -> Result<String> {
+    if !matches!(ai_config.llm, Framework::OpenAI | Framework::Groq) {
+        return Err(AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("{} has no transcription API", ai_config.llm),
+        });
+    }
+
+    let (file_name, data) = match audio {
+        AudioFile::Path(path) => {
+            let data = fs::read(&path).map_err(|e| {
+                AppError::UnexpectedError(format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "audio".to_string());
+            (file_name, data)
+        }
+        AudioFile::Bytes { file_name, data } => (file_name, data),
+    };
+
+    let mut form = Form::new()
+        .text("model", ai_config.model.to_string())
+        .text("response_format", options.response_format.as_str())
+        .part("file", Part::bytes(data).file_name(file_name));
+    if let Some(language) = &options.language {
+        form = form.text("language", language.to_string());
+    }
+    if let Some(prompt) = &options.prompt {
+        form = form.text("prompt", prompt.to_string());
+    }
+    if let Some(temperature) = options.temperature {
+        form = form.text("temperature", temperature.to_string());
+    }
+
+    let builder = openai_api_request(ai_config, Method::POST, "audio/transcriptions")?;
+    let body = send_request(builder.multipart(form), ai_config)
+        .await?
+        .text()
+        .await
+        .map_err(|e| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: format!("Failed to read response: {}", e),
+        })?;
+
+    if options.response_format != TranscriptFormat::Json {
+        return Ok(body);
+    }
+    serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|response| response["text"].as_str().map(str::to_string))
+        .ok_or_else(|| AppError::ModelError {
+            model_name: ai_config.model.to_string(),
+            failure_str: "Failed to extract text from response".to_string(),
+        })
+}
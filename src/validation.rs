@@ -0,0 +1,190 @@
+use crate::capabilities::capabilities;
+use crate::config::{AiConfig, EmptyPromptPolicy, Framework, Question};
+use crate::error::AppError;
+use crate::limits::{LimitPolicy, PayloadMeasure};
+use crate::tenant::prompt_tokens;
+use std::fmt;
+
+/// A problem found by `Question::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The new prompt is empty and no `EmptyPromptPolicy::Substitute` is configured, so it
+    /// would be rejected or silently replaced with `.`.
+    EmptyPrompt,
+    /// The question is over `AiConfig::limits`, which fail instead of truncating.
+    PayloadTooLarge {
+        measure: PayloadMeasure,
+        size: u64,
+        limit: u64,
+    },
+    /// The estimated prompt plus the answer limit do not fit the model's context window.
+    ContextTooLong {
+        prompt_tokens: u64,
+        max_token: u32,
+        context: u32,
+    },
+    /// An option has a value no request can use.
+    InvalidOption {
+        option: &'static str,
+        reason: &'static str,
+    },
+    /// Two options cannot both take effect as configured.
+    ConflictingOptions {
+        first: &'static str,
+        second: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EmptyPrompt => write!(f, "The new prompt is empty"),
+            ValidationError::PayloadTooLarge {
+                measure,
+                size,
+                limit,
+            } => write!(f, "{} {} exceeds the limit of {}", size, measure, limit),
+            ValidationError::ContextTooLong {
+                prompt_tokens,
+                max_token,
+                context,
+            } => write!(
+                f,
+                "About {} prompt tokens plus {} answer tokens exceed the context of {}",
+                prompt_tokens, max_token, context
+            ),
+            ValidationError::InvalidOption { option, reason } => {
+                write!(f, "Invalid `{}`: {}", option, reason)
+            }
+            ValidationError::ConflictingOptions {
+                first,
+                second,
+                reason,
+            } => write!(f, "`{}` conflicts with `{}`: {}", first, second, reason),
+        }
+    }
+}
+
+impl Question {
+    /// Checks the question against `ai_config` without any network call, returning every
+    /// problem found.
+    ///
+    /// Token counts are estimates, and the context window comes from
+    /// `capabilities::capabilities`, so unknown models skip that check. Questions carry text
+    /// only, so there are no content types to check yet.
+    ///
+    /// ### Example Usage:
+    ///
+    /// ```rust,ignore
+    /// if let Err(errors) = question.validate(&ai_config) {
+    ///     for error in &errors {
+    ///         eprintln!("{}", error);
+    ///     }
+    ///     return;
+    /// }
+    /// let answer = ask_question(&ai_config, question).await?;
+    /// ```
+    pub fn validate(&self, ai_config: &AiConfig) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        let substitutes_empty = matches!(
+            ai_config
+                .prompts
+                .as_ref()
+                .map(|prompts| &prompts.empty_prompt),
+            Some(EmptyPromptPolicy::Substitute(_))
+        );
+        if self.new_prompt.is_empty() && !substitutes_empty {
+            errors.push(ValidationError::EmptyPrompt);
+        }
+
+        // Compression runs first and may bring the question under the limits
+        if let (Some(limits), None) = (&ai_config.limits, &ai_config.compression) {
+            if limits.on_exceed == LimitPolicy::Fail {
+                if let Some(AppError::PayloadTooLarge {
+                    measure,
+                    size,
+                    limit,
+                }) = limits.check(ai_config, self)
+                {
+                    errors.push(ValidationError::PayloadTooLarge {
+                        measure,
+                        size,
+                        limit,
+                    });
+                }
+            }
+        }
+
+        let max_token = ai_config.max_token.unwrap_or(0);
+        if let Some(context) = capabilities(ai_config.llm.clone(), &ai_config.model).max_context {
+            let prompt_tokens = prompt_tokens(self);
+            if prompt_tokens + u64::from(max_token) > u64::from(context) {
+                errors.push(ValidationError::ContextTooLong {
+                    prompt_tokens,
+                    max_token,
+                    context,
+                });
+            }
+        }
+
+        if ai_config.max_token == Some(0) {
+            errors.push(ValidationError::InvalidOption {
+                option: "max_token",
+                reason: "an answer of 0 tokens is always empty",
+            });
+        }
+        if ai_config.seed.is_some() && ai_config.llm == Framework::Anthropic {
+            errors.push(ValidationError::ConflictingOptions {
+                first: "seed",
+                second: "llm",
+                reason: "Anthropic does not support seeds, so answers are not reproducible",
+            });
+        }
+        if let Some(vllm) = &ai_config.vllm {
+            if ai_config.llm != Framework::OpenAI {
+                errors.push(ValidationError::ConflictingOptions {
+                    first: "vllm",
+                    second: "llm",
+                    reason: "vLLM options are only sent to OpenAI-compatible servers",
+                });
+            }
+            if let [first, second, ..] = vllm.guides()[..] {
+                errors.push(ValidationError::ConflictingOptions {
+                    first,
+                    second,
+                    reason: "vLLM applies one kind of guided decoding at a time",
+                });
+            }
+        }
+        if ai_config.response_format.is_some() && ai_config.constraint.is_some() {
+            errors.push(ValidationError::ConflictingOptions {
+                first: "response_format",
+                second: "constraint",
+                reason: "both set the shape of the answer",
+            });
+        }
+        let target = ai_config.compression.as_ref().map(|c| c.target_tokens);
+        let limit = ai_config
+            .limits
+            .as_ref()
+            .filter(|limits| limits.on_exceed == LimitPolicy::Fail)
+            .and_then(|limits| limits.max_prompt_tokens);
+        if let (Some(target), Some(limit)) = (target, limit) {
+            if target > limit {
+                errors.push(ValidationError::ConflictingOptions {
+                    first: "compression.target_tokens",
+                    second: "limits.max_prompt_tokens",
+                    reason: "questions compressed to the target still fail the limit",
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::config::AiConfig;
+use crate::embeddings::{get_embedding, get_embeddings};
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+
+/// A text stored with its embedding.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StoredText {
+    pub id: String,
+    pub text: String,
+    pub embedding: Vec<f32>,
+}
+
+/// A stored text matching a search, with its cosine similarity to the query.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SearchHit {
+    pub id: String,
+    pub text: String,
+    /// Cosine similarity to the query, from -1 to 1.
+    pub score: f32,
+}
+
+/// An in-memory vector store: texts with their embeddings, searched by cosine similarity.
+///
+/// Meant for prototyping retrieval without an external database. Search compares the query
+/// with every stored text, which stays fast up to tens of thousands of texts. The store
+/// serializes with serde, so it can be saved and loaded as JSON.
+///
+/// ### Example Usage:
+///
+/// ```rust,ignore
+/// use ask_ai::vectorstore::VectorStore;
+///
+/// let ai_config = AiConfig {
+///     llm: Framework::Ollama,
+///     model: "nomic-embed-text".to_string(),
+///     ..Default::default()
+/// };
+/// let mut store = VectorStore::new();
+/// store
+///     .insert_texts(&ai_config, &[
+///         ("ownership", "Each value in Rust has a single owner."),
+///         ("borrowing", "References borrow a value without taking ownership."),
+///     ])
+///     .await?;
+///
+/// for hit in store.search_text(&ai_config, "Who owns a value?", 1).await? {
+///     println!("{} ({:.2}): {}", hit.id, hit.score, hit.text);
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct VectorStore {
+    texts: Vec<StoredText>,
+}
+
+impl VectorStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `text` under `id` with its `embedding`, replacing any text stored under `id`.
+    ///
+    /// Fails when the embedding's dimensions differ from those already stored, as vectors
+    /// from different models cannot be compared.
+    pub fn insert(&mut self, id: &str, text: &str, embedding: Vec<f32>) -> Result<()> {
+        if let Some(stored) = self.texts.iter().find(|stored| stored.id != id) {
+            if stored.embedding.len() != embedding.len() {
+                return Err(AppError::UnexpectedError(format!(
+                    "Embedding of `{}` has {} dimensions, the store holds {}",
+                    id,
+                    embedding.len(),
+                    stored.embedding.len()
+                )));
+            }
+        }
+        self.remove(id);
+        self.texts.push(StoredText {
+            id: id.to_string(),
+            text: text.to_string(),
+            embedding,
+        });
+        Ok(())
+    }
+
+    /// Embeds `texts`, given as `(id, text)` pairs, in one request and stores them.
+    pub async fn insert_texts(
+        &mut self,
+        ai_config: &AiConfig,
+        texts: &[(&str, &str)],
+    ) -> Result<()> {
+        let contents: Vec<&str> = texts.iter().map(|(_, text)| *text).collect();
+        let embeddings = get_embeddings(ai_config, &contents).await?;
+        for ((id, text), embedding) in texts.iter().zip(embeddings) {
+            self.insert(id, text, embedding)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the text stored under `id`. Returns whether there was one.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let len = self.texts.len();
+        self.texts.retain(|stored| stored.id != id);
+        self.texts.len() != len
+    }
+
+    /// The text stored under `id`, if any.
+    pub fn get(&self, id: &str) -> Option<&StoredText> {
+        self.texts.iter().find(|stored| stored.id == id)
+    }
+
+    /// Number of stored texts.
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    /// Whether the store holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    /// The `top_k` stored texts most similar to `query`, most similar first.
+    pub fn search(&self, query: &[f32], top_k: usize) -> Vec<SearchHit> {
+        let mut hits: Vec<SearchHit> = self
+            .texts
+            .iter()
+            .map(|stored| SearchHit {
+                id: stored.id.clone(),
+                text: stored.text.clone(),
+                score: cosine_similarity(query, &stored.embedding),
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(top_k);
+        hits
+    }
+
+    /// Embeds `query` and returns the `top_k` stored texts most similar to it.
+    ///
+    /// `ai_config` must name the model the stored texts were embedded with.
+    pub async fn search_text(
+        &self,
+        ai_config: &AiConfig,
+        query: &str,
+        top_k: usize,
+    ) -> Result<Vec<SearchHit>> {
+        let query = get_embedding(ai_config, query).await?;
+        Ok(self.search(&query, top_k))
+    }
+}
+
+/// Cosine similarity of two vectors, from -1 to 1. Vectors of different lengths, or with no
+/// magnitude, have a similarity of 0.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
@@ -0,0 +1,269 @@
+use ask_ai::{
+    anthropic::{
+        ask_with_blocks, ask_with_citations, count_tokens, tool_results, ContentBlock, Document,
+    },
+    ask_ai::ask_question,
+    config::{AiConfig, AnthropicOptions, Framework, Question, WebSearch},
+    error::AppError,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn question() -> Question {
+    Question {
+        system_prompt: Some("You are a Rust tutor.".to_string()),
+        messages: None,
+        new_prompt: "What is a lifetime?".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn tokens_are_counted_without_generation_options() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages/count_tokens")
+            .header("x-api-key", "anthropic_testkey")
+            .header("anthropic-version", "2023-06-01")
+            .body_contains(r#""system":"You are a Rust tutor.""#)
+            .body_contains("What is a lifetime?")
+            .matches(|req| {
+                !String::from_utf8_lossy(req.body.as_deref().unwrap_or_default())
+                    .contains("max_tokens")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "input_tokens": 21 }"#);
+    });
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var("ANTHROPIC_API_URL", server.url("/v1/messages"));
+
+    let ai_config = AiConfig::default_for(Framework::Anthropic);
+    let tokens = count_tokens(&ai_config, &question()).await;
+    env::remove_var("ANTHROPIC_API_URL");
+
+    mock.assert();
+    assert_eq!(tokens.expect("Should succeed"), 21);
+}
+
+#[tokio::test]
+async fn other_providers_cannot_count_tokens() {
+    let result = count_tokens(&AiConfig::default_for(Framework::OpenAI), &question()).await;
+
+    assert!(matches!(result, Err(AppError::ModelError { .. })));
+}
+
+#[tokio::test]
+#[serial]
+async fn cited_answers_keep_their_passages() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains(r#"{"citations":{"enabled":true},"source":{"data":"Staff get 25 days of leave.","media_type":"text/plain","type":"text"},"title":"Handbook","type":"document"}"#)
+            .body_contains(r#"{"text":"What is a lifetime?","type":"text"}"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "content": [
+                    { "type": "text", "text": "According to the handbook, ", "citations": null },
+                    { "type": "text", "text": "staff get 25 days of leave", "citations": [ {
+                        "type": "char_location", "cited_text": "Staff get 25 days of leave.",
+                        "document_index": 0, "document_title": "Handbook",
+                        "start_char_index": 0, "end_char_index": 27
+                    } ] },
+                    { "type": "text", "text": "." }
+                ], "stop_reason": "end_turn" }"#,
+            );
+    });
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var("ANTHROPIC_API_URL", server.url("/v1/messages"));
+
+    let ai_config = AiConfig::default_for(Framework::Anthropic);
+    let documents = vec![Document {
+        title: Some("Handbook".to_string()),
+        text: "Staff get 25 days of leave.".to_string(),
+    }];
+    let answer = ask_with_citations(&ai_config, question(), &documents).await;
+    env::remove_var("ANTHROPIC_API_URL");
+
+    mock.assert();
+    let answer = answer.expect("Should succeed");
+    assert_eq!(
+        answer.text(),
+        "According to the handbook, staff get 25 days of leave."
+    );
+    let citations: Vec<_> = answer.citations().collect();
+    assert_eq!(citations.len(), 1);
+    assert_eq!(citations[0].document_index, Some(0));
+    assert_eq!(citations[0].end_char_index, Some(27));
+}
+
+const WEB_SEARCH_RESPONSE: &str = r#"{ "content": [
+    { "type": "text", "text": "I'll look that up." },
+    { "type": "server_tool_use", "id": "srvtoolu_1", "name": "web_search",
+      "input": { "query": "rust 2024 edition release" } },
+    { "type": "web_search_tool_result", "tool_use_id": "srvtoolu_1", "content": [
+        { "type": "web_search_result", "url": "https://blog.rust-lang.org/2025/02/20/Rust-1.85.0.html",
+          "title": "Announcing Rust 1.85.0 and Rust 2024", "encrypted_content": "abc",
+          "page_age": "February 20, 2025" }
+    ] },
+    { "type": "text", "text": "The 2024 edition shipped with Rust 1.85.", "citations": [ {
+        "type": "web_search_result_location",
+        "url": "https://blog.rust-lang.org/2025/02/20/Rust-1.85.0.html",
+        "title": "Announcing Rust 1.85.0 and Rust 2024", "encrypted_index": "def",
+        "cited_text": "Rust 1.85.0 also stabilizes the 2024 edition."
+    } ] }
+], "stop_reason": "end_turn" }"#;
+
+fn web_search_config() -> AiConfig {
+    AiConfig {
+        anthropic: Some(AnthropicOptions {
+            web_search: Some(WebSearch {
+                max_uses: Some(2),
+                allowed_domains: vec!["rust-lang.org".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..AiConfig::default_for(Framework::Anthropic)
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn web_search_answers_join_their_text_blocks() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains(r#""tools":[{"allowed_domains":["rust-lang.org"],"max_uses":2,"name":"web_search","type":"web_search_20250305"}]"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(WEB_SEARCH_RESPONSE);
+    });
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var("ANTHROPIC_API_URL", server.url("/v1/messages"));
+
+    let answer = ask_question(&web_search_config(), question()).await;
+    env::remove_var("ANTHROPIC_API_URL");
+
+    mock.assert();
+    assert_eq!(
+        answer.expect("Should succeed"),
+        "I'll look that up.The 2024 edition shipped with Rust 1.85."
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn web_search_results_are_cited() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains("web_search_20250305");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(WEB_SEARCH_RESPONSE);
+    });
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var("ANTHROPIC_API_URL", server.url("/v1/messages"));
+
+    let answer = ask_with_citations(&web_search_config(), question(), &[]).await;
+    env::remove_var("ANTHROPIC_API_URL");
+
+    mock.assert();
+    let answer = answer.expect("Should succeed");
+    assert_eq!(answer.search_results.len(), 1);
+    assert_eq!(
+        answer.search_results[0].page_age.as_deref(),
+        Some("February 20, 2025")
+    );
+    let citations: Vec<_> = answer.citations().collect();
+    assert_eq!(citations.len(), 1);
+    assert_eq!(
+        citations[0].url.as_deref(),
+        Some("https://blog.rust-lang.org/2025/02/20/Rust-1.85.0.html")
+    );
+    assert_eq!(citations[0].document_index, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn beta_tool_calls_are_returned_as_blocks() {
+    let server = MockServer::start();
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .header("anthropic-beta", "computer-use-2025-01-24")
+            .body_contains(r#""tools":[{"display_height_px":800,"display_width_px":1280,"name":"computer","type":"computer_20250124"}]"#)
+            .matches(|req| !String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains("tool_result"));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [
+                { "type": "thinking", "thinking": "I need to see the screen.", "signature": "sig" },
+                { "type": "text", "text": "Taking a screenshot." },
+                { "type": "tool_use", "id": "toolu_1", "name": "computer", "input": { "action": "screenshot" } },
+                { "type": "future_block", "payload": 1 }
+            ], "stop_reason": "tool_use" }"#);
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains(r#""tool_use_id":"toolu_1""#)
+            .body_contains(r#""signature":"sig""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [{ "type": "text", "text": "The screen is empty." }], "stop_reason": "end_turn" }"#);
+    });
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var("ANTHROPIC_API_URL", server.url("/v1/messages"));
+
+    let ai_config = AiConfig {
+        anthropic: Some(AnthropicOptions {
+            betas: vec!["computer-use-2025-01-24".to_string()],
+            tools: vec![serde_json::json!({
+                "type": "computer_20250124",
+                "name": "computer",
+                "display_width_px": 1280,
+                "display_height_px": 800
+            })],
+            ..Default::default()
+        }),
+        ..AiConfig::default_for(Framework::Anthropic)
+    };
+    let reply = ask_with_blocks(&ai_config, question(), &[]).await;
+    let reply = reply.expect("Should succeed");
+    assert_eq!(reply.stop_reason.as_deref(), Some("tool_use"));
+    assert_eq!(reply.text(), "Taking a screenshot.");
+    assert!(matches!(
+        &reply.content[0],
+        ContentBlock::Thinking { thinking, .. } if thinking == "I need to see the screen."
+    ));
+    assert_eq!(
+        reply.content[3],
+        ContentBlock::Other(serde_json::json!({ "type": "future_block", "payload": 1 }))
+    );
+    let calls: Vec<_> = reply.tool_uses().collect();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].2["action"], "screenshot");
+
+    let continuation = vec![
+        reply.message(),
+        tool_results([(calls[0].0, serde_json::json!("no windows open"))]),
+    ];
+    let reply = ask_with_blocks(&ai_config, question(), &continuation).await;
+    env::remove_var("ANTHROPIC_API_URL");
+
+    first.assert();
+    second.assert();
+    assert_eq!(
+        reply.expect("Should succeed").text(),
+        "The screen is empty."
+    );
+}
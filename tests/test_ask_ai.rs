@@ -1,9 +1,19 @@
 use ask_ai::{
-    ask_ai::ask_question,
-    config::{AiConfig, Framework, Question},
+    ask_ai::{
+        ask_question, build_anthropic_payload, build_groq_payload, build_mistral_payload,
+        build_ollama_payload, build_openai_payload,
+    },
+    config::{
+        AiConfig, AiPrompt, AnthropicOptions, AudioInput, ClientMetadata, Framework, OllamaOptions,
+        Question, VllmOptions,
+    },
     error::AppError,
+    grammar::ResponseFormat,
+    params::GenerationParams,
+    transcript::ImageSource,
 };
 use httpmock::prelude::*;
+use serde_json::json;
 use serial_test::serial;
 use std::env;
 
@@ -32,18 +42,21 @@ async fn openai_reqwest_httpmock_success() {
     env::set_var("OPENAI_API_KEY", "open_api_testkey");
     env::set_var(
         "OPENAI_API_URL",
-        &format!("{}/v1/chat/completions", server.base_url()),
+        format!("{}/v1/chat/completions", server.base_url()),
     );
 
     let ai_config = AiConfig {
         llm: Framework::OpenAI,
         model: "gpt-3.5-turbo".to_string(),
         max_token: Some(1000),
+        ..Default::default()
     };
     let question = Question {
         system_prompt: None,
         messages: None,
         new_prompt: "Say something, please.".to_string(),
+        images: vec![],
+        audio: vec![],
     };
 
     let answer = ask_question(&ai_config, question)
@@ -82,18 +95,21 @@ async fn anthropic_reqwest_httpmock_success() {
     env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
     env::set_var(
         "ANTHROPIC_API_URL",
-        &format!("{}/v1/messages", server.base_url()),
+        format!("{}/v1/messages", server.base_url()),
     );
 
     let ai_config = AiConfig {
         llm: Framework::Anthropic,
         model: "claude-2".to_string(),
         max_token: Some(80),
+        ..Default::default()
     };
     let question = Question {
         system_prompt: Some("You are friendly.".to_string()),
         messages: None,
         new_prompt: "Anthropic question!".to_string(),
+        images: vec![],
+        audio: vec![],
     };
 
     let answer = ask_question(&ai_config, question)
@@ -106,6 +122,61 @@ async fn anthropic_reqwest_httpmock_success() {
     env::remove_var("ANTHROPIC_API_URL");
 }
 
+#[tokio::test]
+#[serial]
+async fn anthropic_version_and_betas_from_config() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .header("anthropic-version", "2025-01-01")
+            .header(
+                "anthropic-beta",
+                "context-1m-2025-08-07,token-efficient-tools-2025-02-19",
+            );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "Beta answer" } ] }"#);
+    });
+
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-sonnet-4".to_string(),
+        anthropic: Some(AnthropicOptions {
+            version: Some("2025-01-01".to_string()),
+            betas: vec![
+                "context-1m-2025-08-07".to_string(),
+                "token-efficient-tools-2025-02-19".to_string(),
+            ],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Long context question".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Beta answer");
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+}
+
 #[tokio::test]
 #[serial]
 async fn openai_reqwest_httpmock_error() {
@@ -121,18 +192,21 @@ async fn openai_reqwest_httpmock_error() {
     env::set_var("OPENAI_API_KEY", "bad_api_key");
     env::set_var(
         "OPENAI_API_URL",
-        &format!("{}/v1/chat/completions", server.base_url()),
+        format!("{}/v1/chat/completions", server.base_url()),
     );
 
     let ai_config = AiConfig {
         llm: Framework::OpenAI,
         model: "gpt-3.5-turbo".to_string(),
         max_token: Some(1000),
+        ..Default::default()
     };
     let question = Question {
         system_prompt: None,
         messages: None,
         new_prompt: "bad".to_string(),
+        images: vec![],
+        audio: vec![],
     };
 
     match ask_question(&ai_config, question).await {
@@ -170,18 +244,21 @@ async fn anthropic_reqwest_httpmock_error_model_parse() {
     env::set_var("ANTHROPIC_API_KEY", "badkey");
     env::set_var(
         "ANTHROPIC_API_URL",
-        &format!("{}/v1/messages", server.base_url()),
+        format!("{}/v1/messages", server.base_url()),
     );
 
     let ai_config = AiConfig {
         llm: Framework::Anthropic,
         model: "claude-2".to_string(),
         max_token: Some(80),
+        ..Default::default()
     };
     let question = Question {
         system_prompt: None,
         messages: None,
         new_prompt: "blah".to_string(),
+        images: vec![],
+        audio: vec![],
     };
 
     match ask_question(&ai_config, question).await {
@@ -199,3 +276,585 @@ async fn anthropic_reqwest_httpmock_error_model_parse() {
     env::remove_var("ANTHROPIC_API_KEY");
     env::remove_var("ANTHROPIC_API_URL");
 }
+
+fn history_question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: Some(vec![AiPrompt {
+            content: "Hi".to_string(),
+            output: "Hello!".to_string(),
+        }]),
+        new_prompt: "".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[test]
+fn openai_payload_builder() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        seed: Some(7),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        build_openai_payload(&history_question(), &ai_config),
+        json!({
+            "model": "gpt-4o-mini",
+            "seed": 7,
+            "messages": [
+                { "role": "system", "content": "" },
+                { "role": "user", "content": "Hi" },
+                { "role": "assistant", "content": "Hello!" },
+                { "role": "user", "content": "." }
+            ]
+        })
+    );
+}
+
+#[test]
+fn mistral_payload_builder() {
+    let ai_config = AiConfig {
+        llm: Framework::Mistral,
+        model: "mistral-small-latest".to_string(),
+        max_token: Some(256),
+        seed: Some(7),
+        client: Some(ClientMetadata {
+            user_id: Some("user-1".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        build_mistral_payload(&history_question(), &ai_config),
+        json!({
+            "model": "mistral-small-latest",
+            "max_tokens": 256,
+            "random_seed": 7,
+            "messages": [
+                { "role": "system", "content": "" },
+                { "role": "user", "content": "Hi" },
+                { "role": "assistant", "content": "Hello!" },
+                { "role": "user", "content": "." }
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn mistral_reqwest_httpmock_success() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .header("Authorization", "Bearer mistral_testkey")
+            .body_contains(r#""model":"mistral-large-latest""#)
+            .body_contains(r#""max_tokens":1000"#);
+        then.status(200)
+            .header("Content-Type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Bonjour!" }, "finish_reason": "stop" } ] }"#);
+    });
+
+    env::set_var("MISTRAL_API_KEY", "mistral_testkey");
+    env::set_var(
+        "MISTRAL_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: "mistral".parse().unwrap(),
+        model: "mistral-large-latest".to_string(),
+        max_token: Some(1000),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Say hello in French.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Bonjour!");
+
+    env::remove_var("MISTRAL_API_KEY");
+    env::remove_var("MISTRAL_API_URL");
+}
+
+#[test]
+fn anthropic_payload_builder() {
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-3-5-haiku-latest".to_string(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        build_anthropic_payload(&history_question(), &ai_config),
+        json!({
+            "model": "claude-3-5-haiku-latest",
+            "max_tokens": 1024,
+            "system": "You are a helpful assistant. Answer the question concisely.",
+            "messages": [
+                { "role": "user", "content": [{ "type": "text", "text": "Hi" }] },
+                { "role": "assistant", "content": [{ "type": "text", "text": "Hello!" }] },
+                { "role": "user", "content": [{ "type": "text", "text": "." }] }
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn anthropic_prefill_starts_the_answer() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/messages").json_body_partial(
+            r#"{ "messages": [
+                { "role": "user", "content": [{ "type": "text", "text": "List two colors as JSON" }] },
+                { "role": "assistant", "content": [{ "type": "text", "text": "{" }] }
+            ] }"#,
+        );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "\"colors\": [\"red\", \"blue\"]}" } ] }"#);
+    });
+
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-sonnet-4".to_string(),
+        anthropic: Some(AnthropicOptions {
+            // Trailing whitespace is not sent
+            prefill: Some("{ \n".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "List two colors as JSON".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, r#"{"colors": ["red", "blue"]}"#);
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+}
+
+#[test]
+fn ollama_payload_builder() {
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        ..Default::default()
+    };
+
+    let payload = build_ollama_payload(&history_question(), &ai_config);
+    assert_eq!(payload["model"], "llama3");
+    assert_eq!(payload["stream"], false);
+    let roles: Vec<&str> = payload["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, ["system", "user", "assistant", "user"]);
+}
+
+#[test]
+fn ollama_options_are_passed_through() {
+    let mut other = serde_json::Map::new();
+    other.insert("num_batch".to_string(), json!(256));
+    let ai_config = AiConfig {
+        seed: Some(42),
+        params: Some(GenerationParams {
+            temperature: Some(0.9),
+            top_k: Some(20),
+            ..Default::default()
+        }),
+        ollama: Some(OllamaOptions {
+            num_ctx: Some(16_384),
+            temperature: Some(0.5),
+            mirostat: Some(2),
+            repeat_penalty: Some(1.25),
+            num_gpu: Some(0),
+            keep_alive: Some("0".to_string()),
+            other,
+            ..Default::default()
+        }),
+        ..AiConfig::default_for(Framework::Ollama)
+    };
+
+    let payload = build_ollama_payload(&history_question(), &ai_config);
+    assert_eq!(payload["keep_alive"], "0");
+    let options = &payload["options"];
+    assert_eq!(options["seed"], 42);
+    assert_eq!(options["top_k"], 20);
+    // Ollama options win over `params`
+    assert_eq!(options["temperature"], 0.5);
+    assert_eq!(options["num_ctx"], 16_384);
+    assert_eq!(options["mirostat"], 2);
+    assert_eq!(options["repeat_penalty"], 1.25);
+    assert_eq!(options["num_gpu"], 0);
+    assert_eq!(options["num_batch"], 256);
+    assert!(options.get("keep_alive").is_none());
+}
+
+#[test]
+fn groq_payload_builder() {
+    let ai_config = AiConfig {
+        llm: Framework::Groq,
+        model: "llama-3.1-8b-instant".to_string(),
+        max_token: Some(256),
+        seed: Some(7),
+        client: Some(ClientMetadata {
+            user_id: Some("user-1".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        build_groq_payload(&history_question(), &ai_config),
+        json!({
+            "model": "llama-3.1-8b-instant",
+            "max_tokens": 256,
+            "seed": 7,
+            "user": "user-1",
+            "messages": [
+                { "role": "system", "content": "" },
+                { "role": "user", "content": "Hi" },
+                { "role": "assistant", "content": "Hello!" },
+                { "role": "user", "content": "." }
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn groq_reqwest_httpmock_success() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/openai/v1/chat/completions")
+            .header("Authorization", "Bearer groq_testkey")
+            .body_contains(r#""model":"llama-3.3-70b-versatile""#);
+        then.status(200)
+            .header("Content-Type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hola!" }, "finish_reason": "stop" } ] }"#);
+    });
+
+    env::set_var("GROQ_API_KEY", "groq_testkey");
+    env::set_var(
+        "GROQ_API_URL",
+        format!("{}/openai/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: "groq".parse().unwrap(),
+        model: "llama-3.3-70b-versatile".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Say hello in Spanish.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Hola!");
+
+    env::remove_var("GROQ_API_KEY");
+    env::remove_var("GROQ_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn huggingface_inference_endpoint_httpmock_success() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .header("Authorization", "Bearer hf_testtoken")
+            .body_contains(r#""model":"Qwen/Qwen2.5-7B-Instruct""#)
+            .body_contains(r#""max_tokens":200"#);
+        then.status(200)
+            .header("Content-Type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hallo!" }, "finish_reason": "length" } ] }"#);
+    });
+
+    env::set_var("HF_TOKEN", "hf_testtoken");
+    env::set_var(
+        "HUGGINGFACE_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::HuggingFace,
+        model: "Qwen/Qwen2.5-7B-Instruct".to_string(),
+        max_token: Some(200),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Say hello in German.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Hallo!");
+
+    env::remove_var("HF_TOKEN");
+    env::remove_var("HUGGINGFACE_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn openai_compatible_base_url_httpmock_success() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""model":"qwen2.5-7b-instruct""#)
+            // The OpenAI key stays with OpenAI
+            .matches(|req| {
+                !req.headers
+                    .iter()
+                    .flatten()
+                    .any(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+            });
+        then.status(200)
+            .header("Content-Type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Local hello" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", "http://127.0.0.1:9/v1/chat/completions");
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "qwen2.5-7b-instruct".to_string(),
+        base_url: Some(format!("{}/v1/", server.base_url())),
+        local_only: true,
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Say hello.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Local hello");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[test]
+fn vllm_options_extend_the_openai_payload() {
+    let ai_config = AiConfig {
+        model: "Qwen/Qwen2.5-7B-Instruct".to_string(),
+        base_url: Some("http://localhost:8000/v1".to_string()),
+        vllm: Some(VllmOptions {
+            guided_json: Some(json!({ "type": "object" })),
+            best_of: Some(3),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let payload = build_openai_payload(&history_question(), &ai_config);
+
+    assert_eq!(payload["guided_json"], json!({ "type": "object" }));
+    assert_eq!(payload["best_of"], 3);
+    assert!(payload.get("guided_regex").is_none());
+    assert!(payload.get("guided_choice").is_none());
+}
+
+#[test]
+fn response_format_uses_each_providers_json_mode() {
+    let schema = json!({ "type": "object", "properties": { "city": { "type": "string" } } });
+    let ai_config = AiConfig {
+        response_format: Some(ResponseFormat::JsonSchema {
+            name: "city".to_string(),
+            schema: schema.clone(),
+        }),
+        ..Default::default()
+    };
+    let payload = build_openai_payload(&history_question(), &ai_config);
+    assert_eq!(payload["response_format"]["type"], "json_schema");
+    assert_eq!(payload["response_format"]["json_schema"]["name"], "city");
+    assert_eq!(payload["response_format"]["json_schema"]["schema"], schema);
+
+    let ai_config = AiConfig {
+        response_format: Some(ResponseFormat::JsonObject),
+        ..AiConfig::default_for(Framework::Groq)
+    };
+    let payload = build_groq_payload(&history_question(), &ai_config);
+    assert_eq!(payload["response_format"], json!({ "type": "json_object" }));
+
+    let ai_config = AiConfig {
+        response_format: Some(ResponseFormat::JsonObject),
+        ..AiConfig::default_for(Framework::Ollama)
+    };
+    let payload = build_ollama_payload(&history_question(), &ai_config);
+    assert_eq!(payload["format"], "json");
+    assert!(!payload["messages"][0]["content"]
+        .as_str()
+        .unwrap()
+        .contains("JSON"));
+}
+
+#[test]
+fn response_format_falls_back_to_the_anthropic_system_prompt() {
+    let ai_config = AiConfig {
+        response_format: Some(ResponseFormat::JsonSchema {
+            name: "city".to_string(),
+            schema: json!({ "type": "object", "required": ["city"] }),
+        }),
+        ..AiConfig::default_for(Framework::Anthropic)
+    };
+    let payload = build_anthropic_payload(&history_question(), &ai_config);
+
+    let system = payload["system"].as_str().unwrap();
+    assert!(system.contains("Respond only with a valid JSON object matching this JSON Schema"));
+    assert!(system.contains(r#"{"required":["city"],"type":"object"}"#));
+    assert!(payload.get("response_format").is_none());
+}
+
+fn image_question() -> Question {
+    Question {
+        new_prompt: "What is in these pictures?".to_string(),
+        images: vec![
+            ImageSource::Url {
+                url: "https://example.com/cat.jpg".to_string(),
+            },
+            ImageSource::Base64 {
+                media_type: Some("image/jpeg".to_string()),
+                data: "/9j/4AAQ".to_string(),
+            },
+        ],
+        ..history_question()
+    }
+}
+
+#[test]
+fn images_are_sent_in_each_providers_format() {
+    let payload = build_openai_payload(&image_question(), &AiConfig::default());
+    assert_eq!(
+        payload["messages"][3]["content"],
+        json!([
+            { "type": "text", "text": "What is in these pictures?" },
+            { "type": "image_url", "image_url": { "url": "https://example.com/cat.jpg" } },
+            { "type": "image_url", "image_url": { "url": "data:image/jpeg;base64,/9j/4AAQ" } }
+        ])
+    );
+    // Earlier turns and image-free prompts keep plain string content
+    assert_eq!(payload["messages"][1]["content"], "Hi");
+
+    let payload = build_anthropic_payload(
+        &image_question(),
+        &AiConfig::default_for(Framework::Anthropic),
+    );
+    assert_eq!(
+        payload["messages"][2]["content"],
+        json!([
+            { "type": "image", "source": { "type": "url", "url": "https://example.com/cat.jpg" } },
+            { "type": "image", "source": {
+                "type": "base64", "media_type": "image/jpeg", "data": "/9j/4AAQ"
+            } },
+            { "type": "text", "text": "What is in these pictures?" }
+        ])
+    );
+
+    let question = Question {
+        images: image_question().images.split_off(1),
+        ..image_question()
+    };
+    let payload = build_ollama_payload(&question, &AiConfig::default_for(Framework::Ollama));
+    assert_eq!(payload["messages"][3]["images"], json!(["/9j/4AAQ"]));
+    assert!(payload["messages"][1]["images"].is_null());
+}
+
+#[tokio::test]
+async fn image_urls_are_refused_for_ollama() {
+    let result = ask_question(&AiConfig::default_for(Framework::Ollama), image_question()).await;
+
+    match result {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert_eq!(failure_str, "Ollama takes images as base64 data, not URLs")
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn audio_is_sent_as_input_audio_parts() {
+    let question = Question {
+        new_prompt: "What is said in this clip?".to_string(),
+        audio: vec![AudioInput::from_bytes("wav", b"RIFF")],
+        ..history_question()
+    };
+    let ai_config = AiConfig {
+        model: "gpt-4o-audio-preview".to_string(),
+        ..Default::default()
+    };
+    let payload = build_openai_payload(&question, &ai_config);
+    assert_eq!(
+        payload["messages"][3]["content"],
+        json!([
+            { "type": "text", "text": "What is said in this clip?" },
+            { "type": "input_audio", "input_audio": { "data": "UklGRg==", "format": "wav" } }
+        ])
+    );
+
+    let result = ask_question(&AiConfig::default_for(Framework::Anthropic), question).await;
+    match result {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert_eq!(failure_str, "anthropic does not take audio")
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
@@ -0,0 +1,170 @@
+use ask_ai::{
+    assistants::{ask_thread, create_assistant, create_thread, list_messages, RunStatus},
+    config::{AiConfig, Framework},
+    error::AppError,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn setup_openai(server: &MockServer) -> AiConfig {
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn assistants_and_threads_are_created() {
+    let server = MockServer::start();
+    let assistant = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/assistants")
+            .header("authorization", "Bearer open_api_testkey")
+            .header("openai-beta", "assistants=v2")
+            .body_contains(r#""instructions":"You teach Rust.""#)
+            .body_contains(r#""name":"Tutor""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "id": "asst_1", "object": "assistant", "name": "Tutor", "model": "gpt-4o",
+                     "instructions": "You teach Rust.", "tools": [] }"#,
+            );
+    });
+    let thread = server.mock(|when, then| {
+        when.method(POST).path("/v1/threads");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "thread_1", "object": "thread", "created_at": 1700000000 }"#);
+    });
+    let messages = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/threads/thread_1/messages")
+            .query_param("order", "asc");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "object": "list", "data": [
+                    { "id": "msg_1", "role": "user", "run_id": null,
+                      "content": [ { "type": "text", "text": { "value": "Hi", "annotations": [] } } ] }
+                ] }"#,
+            );
+    });
+
+    let ai_config = setup_openai(&server);
+    let created = create_assistant(&ai_config, Some("Tutor"), "You teach Rust.").await;
+    let created_thread = create_thread(&ai_config).await;
+    let listed = list_messages(&ai_config, "thread_1").await;
+    env::remove_var("OPENAI_API_URL");
+
+    assistant.assert();
+    thread.assert();
+    messages.assert();
+    assert_eq!(created.expect("Should succeed").id, "asst_1");
+    assert_eq!(created_thread.expect("Should succeed").id, "thread_1");
+    let listed = listed.expect("Should succeed");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].text, "Hi");
+    assert!(RunStatus::RequiresAction.is_settled());
+    assert!(!RunStatus::InProgress.is_settled());
+}
+
+#[tokio::test]
+#[serial]
+async fn ask_thread_runs_the_assistant() {
+    let server = MockServer::start();
+    let message = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/threads/thread_1/messages")
+            .body_contains(r#""content":"What is a lifetime?""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "id": "msg_1", "role": "user",
+                     "content": [ { "type": "text", "text": { "value": "What is a lifetime?" } } ] }"#,
+            );
+    });
+    let run = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/threads/thread_1/runs")
+            .body_contains(r#""assistant_id":"asst_1""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "run_1", "thread_id": "thread_1", "assistant_id": "asst_1", "status": "queued" }"#);
+    });
+    let poll = server.mock(|when, then| {
+        when.method(GET).path("/v1/threads/thread_1/runs/run_1");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "run_1", "thread_id": "thread_1", "assistant_id": "asst_1", "status": "completed" }"#);
+    });
+    let answer = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/threads/thread_1/messages")
+            .query_param("run_id", "run_1");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "object": "list", "data": [
+                    { "id": "msg_2", "role": "assistant", "run_id": "run_1",
+                      "content": [ { "type": "text", "text": { "value": "How long a reference is valid." } } ] }
+                ] }"#,
+            );
+    });
+
+    let ai_config = setup_openai(&server);
+    let result = ask_thread(&ai_config, "thread_1", "asst_1", "What is a lifetime?").await;
+    env::remove_var("OPENAI_API_URL");
+
+    message.assert();
+    run.assert();
+    poll.assert();
+    answer.assert();
+    assert_eq!(
+        result.expect("Should succeed"),
+        "How long a reference is valid."
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_runs_are_a_model_error() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/threads/thread_1/messages");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "msg_1", "role": "user", "content": [] }"#);
+    });
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/threads/thread_1/runs");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "run_1", "thread_id": "thread_1", "assistant_id": "asst_1", "status": "queued" }"#);
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/threads/thread_1/runs/run_1");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "id": "run_1", "thread_id": "thread_1", "assistant_id": "asst_1", "status": "failed",
+                     "last_error": { "code": "rate_limit_exceeded", "message": "Slow down" } }"#,
+            );
+    });
+
+    let ai_config = setup_openai(&server);
+    let result = ask_thread(&ai_config, "thread_1", "asst_1", "Hi").await;
+    env::remove_var("OPENAI_API_URL");
+
+    match result {
+        Err(AppError::ModelError { failure_str, .. }) => assert_eq!(
+            failure_str,
+            "Run run_1 did not complete: rate_limit_exceeded: Slow down"
+        ),
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
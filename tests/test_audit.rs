@@ -0,0 +1,281 @@
+use ask_ai::{
+    audit::{AuditDecision, AuditEvent, AuditLog, CallbackAuditSink, FileAuditSink},
+    capabilities::UnsupportedPolicy,
+    config::{AiConfig, EmptyPromptPolicy, Framework, PromptDefaults, Question},
+    error::AppError,
+    limits::PayloadLimits,
+    tenant::{TenantConfig, TenantLimits, TenantQuota, TenantRegistry},
+    transcript::ImageSource,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What is the dosage?".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn file_audit_sink_appends_answers_and_failures() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Two tablets" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let audit = AuditLog::new(FileAuditSink::open(&path).unwrap());
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+
+    let answer = audit
+        .ask_question("alice", &ai_config, question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Two tablets");
+    mock.assert();
+
+    env::set_var("OPENAI_API_URL", "http://127.0.0.1:9/v1/chat/completions");
+    assert!(audit
+        .ask_question("bob", &ai_config, question())
+        .await
+        .is_err());
+
+    // Reopening appends instead of truncating
+    drop(audit);
+    let _ = FileAuditSink::open(&path).unwrap();
+
+    let events: Vec<AuditEvent> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].actor, "alice");
+    assert_eq!(events[0].model, "gpt-4o-mini");
+    assert_eq!(events[0].prompt, "What is the dosage?");
+    assert!(matches!(
+        events[0].decision,
+        AuditDecision::Answered {
+            answer_tokens: 3,
+            ..
+        }
+    ));
+    assert_eq!(events[1].actor, "bob");
+    assert!(matches!(events[1].decision, AuditDecision::Failed { .. }));
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+async fn tenant_quota_blocks_are_audited() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let audit = AuditLog::new(CallbackAuditSink::new(move |event: &AuditEvent| {
+        recorded.lock().unwrap().push(event.clone());
+    }));
+
+    let registry = TenantRegistry::new().with_audit(audit);
+    registry.insert(
+        "acme",
+        TenantConfig {
+            ai_config: AiConfig {
+                model: "gpt-4o-mini".to_string(),
+                ..Default::default()
+            },
+            limits: TenantLimits {
+                quota: Some(TenantQuota {
+                    window_secs: 60,
+                    max_requests: Some(0),
+                    max_tokens: None,
+                }),
+                ..Default::default()
+            },
+        },
+    );
+
+    match registry.ask_question_for("acme", question()).await {
+        Err(AppError::QuotaExceeded { .. }) => {}
+        other => panic!("Expected AppError::QuotaExceeded, got {:?}", other),
+    }
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].actor, "acme");
+    match &events[0].decision {
+        AuditDecision::Blocked { reason } => assert!(reason.contains("Quota exceeded")),
+        other => panic!("Expected AuditDecision::Blocked, got {:?}", other),
+    }
+}
+
+/// Asks `question` through an audit log and returns the error and the recorded decision.
+async fn refused(ai_config: &AiConfig, question: Question) -> (AppError, AuditDecision) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let audit = AuditLog::new(CallbackAuditSink::new(move |event: &AuditEvent| {
+        recorded.lock().unwrap().push(event.clone());
+    }));
+
+    let error = audit
+        .ask_question("carol", ai_config, question)
+        .await
+        .expect_err("Should be refused");
+    let mut events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    (error, events.remove(0).decision)
+}
+
+fn openai_config() -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        api_key: Some("open_api_testkey".into()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn local_only_blocks_are_audited() {
+    env::remove_var("OPENAI_API_URL");
+    let ai_config = AiConfig {
+        local_only: true,
+        ..openai_config()
+    };
+
+    let (error, decision) = refused(&ai_config, question()).await;
+    assert!(matches!(error, AppError::RemoteEndpointBlocked { .. }));
+    assert!(matches!(decision, AuditDecision::Blocked { .. }));
+}
+
+#[tokio::test]
+async fn payload_limit_blocks_are_audited() {
+    let ai_config = AiConfig {
+        limits: Some(PayloadLimits {
+            max_prompt_tokens: Some(1),
+            ..Default::default()
+        }),
+        ..openai_config()
+    };
+
+    let (error, decision) = refused(&ai_config, question()).await;
+    assert!(matches!(error, AppError::PayloadTooLarge { .. }));
+    assert!(matches!(decision, AuditDecision::Blocked { .. }));
+}
+
+#[tokio::test]
+#[serial]
+async fn moderation_blocks_are_audited() {
+    let server = MockServer::start();
+    let moderation = server.mock(|when, then| {
+        when.method(POST).path("/v1/moderations");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "results": [ { "flagged": true, "categories": { "violence": true } } ] }"#);
+    });
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let ai_config = AiConfig {
+        moderate_prompts: true,
+        ..openai_config()
+    };
+    let (error, decision) = refused(&ai_config, question()).await;
+    env::remove_var("OPENAI_API_URL");
+
+    moderation.assert();
+    assert!(matches!(error, AppError::ContentFlagged { .. }));
+    match decision {
+        AuditDecision::Blocked { reason } => assert!(reason.contains("violence")),
+        other => panic!("Expected AuditDecision::Blocked, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn unsupported_capability_blocks_are_audited() {
+    let ai_config = AiConfig {
+        model: "gpt-3.5-turbo".to_string(),
+        on_unsupported: Some(UnsupportedPolicy::Error),
+        ..openai_config()
+    };
+    let question = Question {
+        images: vec![ImageSource::Url {
+            url: "https://example.com/label.png".to_string(),
+        }],
+        ..question()
+    };
+
+    let (error, decision) = refused(&ai_config, question).await;
+    assert!(matches!(error, AppError::UnsupportedCapability { .. }));
+    assert!(matches!(decision, AuditDecision::Blocked { .. }));
+}
+
+#[tokio::test]
+async fn empty_prompt_blocks_are_audited() {
+    let ai_config = AiConfig {
+        prompts: Some(PromptDefaults {
+            empty_prompt: EmptyPromptPolicy::Reject,
+            ..Default::default()
+        }),
+        ..openai_config()
+    };
+    let question = Question {
+        new_prompt: String::new(),
+        ..question()
+    };
+
+    let (error, decision) = refused(&ai_config, question).await;
+    assert!(matches!(error, AppError::EmptyPrompt { .. }));
+    assert!(matches!(decision, AuditDecision::Blocked { .. }));
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_audit_sink_appends() {
+    use ask_ai::audit::{AuditSink, SqliteAuditSink};
+
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("audit.db");
+    let sink = SqliteAuditSink::open(&db).unwrap();
+    let event = AuditEvent {
+        timestamp: 1,
+        actor: "alice".to_string(),
+        framework: Framework::Anthropic,
+        model: "claude-3-5-haiku-latest".to_string(),
+        prompt: "Hi".to_string(),
+        redactions: Vec::new(),
+        decision: AuditDecision::Blocked {
+            reason: "test".to_string(),
+        },
+    };
+
+    sink.record(&event).unwrap();
+    sink.record(&event).unwrap();
+
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM audit_log", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 2);
+}
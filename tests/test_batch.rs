@@ -0,0 +1,261 @@
+use ask_ai::{
+    batch::{
+        batch_file, batch_results, list_batches, submit_batch, unanswered, wait_for_batch,
+        watch_batch, BatchStatus, RequestCounts,
+    },
+    config::{AiConfig, Framework, Question},
+};
+use httpmock::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::env;
+
+fn question(prompt: &str) -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: prompt.to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn setup_openai(server: &MockServer) -> AiConfig {
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        max_token: None,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn batch_files_hold_one_request_per_line() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+    let file = batch_file(
+        &ai_config,
+        &[("q1", question("First")), ("q2", question("Second"))],
+    );
+
+    let lines: Vec<Value> = file
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["custom_id"], "q2");
+    assert_eq!(lines[1]["url"], "/v1/chat/completions");
+    assert_eq!(lines[1]["body"]["model"], "gpt-4o-mini");
+    assert_eq!(lines[1]["body"]["messages"][1]["content"], "Second");
+}
+
+#[tokio::test]
+#[serial]
+async fn batches_are_submitted_and_their_results_parsed() {
+    let server = MockServer::start();
+    let upload = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/files")
+            .header("authorization", "Bearer open_api_testkey")
+            .body_contains("name=\"purpose\"\r\n\r\nbatch\r\n")
+            .body_contains(r#""custom_id":"q1""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "file-in", "object": "file", "purpose": "batch" }"#);
+    });
+    let create = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/batches")
+            .body_contains(r#""input_file_id":"file-in""#)
+            .body_contains(r#""completion_window":"24h""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "batch_1", "status": "validating", "input_file_id": "file-in" }"#);
+    });
+    let poll = server.mock(|when, then| {
+        when.method(GET).path("/v1/batches/batch_1");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "id": "batch_1", "status": "completed", "input_file_id": "file-in",
+                     "output_file_id": "file-out", "error_file_id": "file-err",
+                     "request_counts": { "total": 2, "completed": 1, "failed": 1 } }"#,
+            );
+    });
+    let output = server.mock(|when, then| {
+        when.method(GET).path("/v1/files/file-out/content");
+        then.status(200).body(
+            r#"{"id":"r1","custom_id":"q1","response":{"status_code":200,"body":{"choices":[{"message":{"content":"One"}}]}},"error":null}
+"#,
+        );
+    });
+    let errors = server.mock(|when, then| {
+        when.method(GET).path("/v1/files/file-err/content");
+        then.status(200).body(
+            r#"{"id":"r2","custom_id":"q2","response":{"status_code":400,"body":{"error":{"message":"Bad request"}}},"error":null}
+"#,
+        );
+    });
+
+    let ai_config = setup_openai(&server);
+    let submitted = submit_batch(
+        &ai_config,
+        &[("q1", question("First")), ("q2", question("Second"))],
+    )
+    .await;
+    let batch = wait_for_batch(&ai_config, "batch_1").await;
+    let results = match &batch {
+        Ok(batch) => Some(batch_results(&ai_config, batch).await),
+        Err(_) => None,
+    };
+    env::remove_var("OPENAI_API_URL");
+
+    upload.assert();
+    create.assert();
+    poll.assert();
+    output.assert();
+    errors.assert();
+    assert_eq!(
+        submitted.expect("Should succeed").status,
+        BatchStatus::Validating
+    );
+    let batch = batch.expect("Should succeed");
+    assert_eq!(batch.request_counts.failed, 1);
+    let results = results.unwrap().expect("Should succeed");
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].custom_id, "q1");
+    assert_eq!(results[0].answer, Ok("One".to_string()));
+    assert_eq!(results[1].custom_id, "q2");
+    assert_eq!(results[1].answer, Err("Bad request".to_string()));
+}
+
+#[tokio::test]
+#[serial]
+async fn batches_are_listed_across_pages() {
+    let server = MockServer::start();
+    let first = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/batches")
+            .query_param("limit", "100")
+            .matches(|req| {
+                req.query_params
+                    .iter()
+                    .flatten()
+                    .all(|(name, _)| name != "after")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "object": "list", "has_more": true, "last_id": "batch_2", "data": [
+                     { "id": "batch_3", "status": "in_progress", "input_file_id": "file-3" },
+                     { "id": "batch_2", "status": "completed", "input_file_id": "file-2" }
+                   ] }"#,
+            );
+    });
+    let second = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/batches")
+            .query_param("limit", "100")
+            .query_param("after", "batch_2");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "object": "list", "has_more": false, "last_id": "batch_1", "data": [
+                     { "id": "batch_1", "status": "expired", "input_file_id": "file-1" }
+                   ] }"#,
+            );
+    });
+
+    let ai_config = setup_openai(&server);
+    let batches = list_batches(&ai_config).await;
+    env::remove_var("OPENAI_API_URL");
+
+    first.assert();
+    second.assert();
+    let ids: Vec<_> = batches
+        .expect("Should succeed")
+        .into_iter()
+        .map(|batch| batch.id)
+        .collect();
+    assert_eq!(ids, vec!["batch_3", "batch_2", "batch_1"]);
+}
+
+#[tokio::test]
+#[serial]
+async fn cancelled_batches_are_reattached_and_partially_answered() {
+    let server = MockServer::start();
+    let list = server.mock(|when, then| {
+        when.method(GET).path("/v1/batches");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "object": "list", "data": [
+                     { "id": "batch_2", "status": "cancelling", "input_file_id": "file-in" }
+                   ] }"#,
+            );
+    });
+    let poll = server.mock(|when, then| {
+        when.method(GET).path("/v1/batches/batch_2");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "id": "batch_2", "status": "cancelled", "input_file_id": "file-in",
+                     "output_file_id": "file-out",
+                     "request_counts": { "total": 3, "completed": 1, "failed": 0 } }"#,
+            );
+    });
+    let output = server.mock(|when, then| {
+        when.method(GET).path("/v1/files/file-out/content");
+        then.status(200).body(
+            r#"{"id":"r1","custom_id":"q1","response":{"status_code":200,"body":{"choices":[{"message":{"content":"One"}}]}},"error":null}
+"#,
+        );
+    });
+
+    let ai_config = setup_openai(&server);
+    let batches = list_batches(&ai_config).await;
+    let mut progress = vec![];
+    let batch = watch_batch(&ai_config, "batch_2", |batch| {
+        progress.push(batch.request_counts)
+    })
+    .await;
+    let results = match &batch {
+        Ok(batch) => Some(batch_results(&ai_config, batch).await),
+        Err(_) => None,
+    };
+    env::remove_var("OPENAI_API_URL");
+
+    list.assert();
+    poll.assert();
+    output.assert();
+    assert_eq!(batches.expect("Should succeed")[0].id, "batch_2");
+    assert_eq!(
+        progress,
+        vec![RequestCounts {
+            total: 3,
+            completed: 1,
+            failed: 0
+        }]
+    );
+    assert_eq!(
+        batch.expect("Should succeed").status,
+        BatchStatus::Cancelled
+    );
+
+    let results = results.unwrap().expect("Should succeed");
+    let questions = [
+        ("q1", question("First")),
+        ("q2", question("Second")),
+        ("q3", question("Third")),
+    ];
+    let left: Vec<&str> = unanswered(&questions, &results)
+        .into_iter()
+        .map(|(custom_id, _)| custom_id)
+        .collect();
+    assert_eq!(left, vec!["q2", "q3"]);
+}
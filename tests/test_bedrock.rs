@@ -0,0 +1,252 @@
+use ask_ai::{
+    ask_ai::{ask_question, ask_question_raw},
+    bedrock::{build_bedrock_payload, AwsCredentials},
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    params::GenerationParams,
+    safety::{Guardrail, SafetySettings},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const MODEL: &str = "anthropic.claude-3-5-sonnet-20240620-v1:0";
+const CONVERSE_PATH: &str = "/model/anthropic.claude-3-5-sonnet-20240620-v1%3A0/converse";
+
+fn ai_config() -> AiConfig {
+    AiConfig {
+        llm: Framework::Bedrock,
+        model: MODEL.to_string(),
+        max_token: Some(100),
+        ..Default::default()
+    }
+}
+
+fn question() -> Question {
+    Question {
+        system_prompt: Some("Be brief.".to_string()),
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn remove_env() {
+    for var in [
+        "BEDROCK_API_URL",
+        "AWS_REGION",
+        "AWS_ACCESS_KEY_ID",
+        "AWS_SECRET_ACCESS_KEY",
+        "AWS_SESSION_TOKEN",
+        "AWS_BEARER_TOKEN_BEDROCK",
+    ] {
+        env::remove_var(var);
+    }
+}
+
+#[test]
+fn sigv4_signature_matches_the_reference_algorithm() {
+    let credentials = AwsCredentials {
+        access_key_id: "AKIDEXAMPLE".to_string(),
+        secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+        session_token: None,
+    };
+    let authorization = credentials
+        .authorization(
+            "us-west-2",
+            &format!(
+                "https://bedrock-runtime.us-west-2.amazonaws.com{}",
+                CONVERSE_PATH
+            ),
+            br#"{"messages":[]}"#,
+            1_700_000_000,
+        )
+        .unwrap();
+
+    assert_eq!(
+        authorization,
+        "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20231114/us-west-2/bedrock/aws4_request, \
+         SignedHeaders=content-type;host;x-amz-date, \
+         Signature=61484417c131b3e027e4f83bb6b0bba449470afd0a8b6cd2c9ee1ca275f8ac5c"
+    );
+}
+
+#[test]
+fn payload_follows_the_converse_api() {
+    let ai_config = AiConfig {
+        params: Some(GenerationParams {
+            temperature: Some(1.5),
+            top_p: Some(0.9),
+            top_k: Some(40),
+            stop: vec!["END".to_string()],
+            ..Default::default()
+        }),
+        ..ai_config()
+    };
+    let payload = build_bedrock_payload(&question(), &ai_config);
+
+    assert_eq!(payload["system"][0]["text"], "Be brief.");
+    assert_eq!(payload["messages"][0]["role"], "user");
+    assert_eq!(payload["messages"][0]["content"][0]["text"], "Hi");
+    assert!(payload.get("model").is_none());
+
+    let inference = &payload["inferenceConfig"];
+    assert_eq!(inference["maxTokens"], 100);
+    assert_eq!(inference["temperature"], 1.0);
+    assert_eq!(inference["topP"], 0.9);
+    assert_eq!(inference["stopSequences"][0], "END");
+    assert!(inference.get("top_k").is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn questions_are_signed_and_answered() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path(CONVERSE_PATH)
+            .header_exists("x-amz-date")
+            .header("x-amz-security-token", "session")
+            .matches(|req| {
+                req.headers.iter().flatten().any(|(name, value)| {
+                    name.eq_ignore_ascii_case("authorization")
+                        && value.starts_with("AWS4-HMAC-SHA256 Credential=AKID/")
+                        && value.contains("/eu-west-1/bedrock/aws4_request")
+                        && value.contains("SignedHeaders=content-type;host;x-amz-date;x-amz-security-token")
+                })
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                    "output": { "message": { "role": "assistant", "content": [ { "text": "Hello!" } ] } },
+                    "stopReason": "end_turn",
+                    "usage": { "inputTokens": 10, "outputTokens": 2, "totalTokens": 12 }
+                }"#,
+            );
+    });
+    env::set_var("BEDROCK_API_URL", server.base_url());
+    env::set_var("AWS_REGION", "eu-west-1");
+    env::set_var("AWS_ACCESS_KEY_ID", "AKID");
+    env::set_var("AWS_SECRET_ACCESS_KEY", "secret");
+    env::set_var("AWS_SESSION_TOKEN", "session");
+
+    let answer = ask_question(&ai_config(), question()).await;
+    remove_env();
+
+    mock.assert();
+    assert_eq!(answer.expect("Should succeed"), "Hello!");
+}
+
+#[tokio::test]
+#[serial]
+async fn bedrock_api_keys_are_sent_as_bearer_tokens() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path(CONVERSE_PATH)
+            .header("authorization", "Bearer bedrock-key");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "output": { "message": { "content": [ { "text": "Hi" } ] } } }"#);
+    });
+    env::set_var("BEDROCK_API_URL", server.base_url());
+    env::set_var("AWS_BEARER_TOKEN_BEDROCK", "bedrock-key");
+
+    let answer = ask_question(&ai_config(), question()).await;
+    remove_env();
+
+    mock.assert();
+    assert_eq!(answer.expect("Should succeed"), "Hi");
+}
+
+#[tokio::test]
+#[serial]
+async fn missing_credentials_fail_before_sending() {
+    remove_env();
+    env::set_var("BEDROCK_API_URL", "http://127.0.0.1:9");
+
+    let result = ask_question(&ai_config(), question()).await;
+    remove_env();
+
+    match result {
+        Err(AppError::ApiError { failure_str, .. }) => {
+            assert!(failure_str.contains("AWS_ACCESS_KEY_ID"))
+        }
+        other => panic!("Expected AppError::ApiError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn guardrails_are_applied_and_their_verdict_returned() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST).path(CONVERSE_PATH).json_body_partial(
+            r#"{
+                "guardrailConfig": {
+                    "guardrailIdentifier": "gr-support",
+                    "guardrailVersion": "3",
+                    "trace": "enabled"
+                }
+            }"#,
+        );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                    "output": { "message": { "role": "assistant", "content": [ { "text": "Sorry, I can't help with that." } ] } },
+                    "stopReason": "guardrail_intervened",
+                    "usage": { "inputTokens": 10, "outputTokens": 0, "totalTokens": 10 },
+                    "trace": { "guardrail": { "inputAssessment": { "gr-support": { "topicPolicy": { "topics": [ { "name": "Investing", "action": "BLOCKED" } ] } } } } }
+                }"#,
+            );
+    });
+    env::set_var("BEDROCK_API_URL", server.base_url());
+    env::set_var("AWS_BEARER_TOKEN_BEDROCK", "bedrock-key");
+
+    let ai_config = AiConfig {
+        safety: Some(SafetySettings {
+            guardrail: Some(Guardrail {
+                id: "gr-support".to_string(),
+                version: "3".to_string(),
+                trace: true,
+            }),
+        }),
+        ..ai_config()
+    };
+    let response = ask_question_raw(&ai_config, question()).await;
+    remove_env();
+
+    mock.assert();
+    let verdict = &response.expect("Should succeed")["ask_ai"]["safety"];
+    assert_eq!(verdict["intervened"], true);
+    assert_eq!(
+        verdict["trace"]["inputAssessment"]["gr-support"]["topicPolicy"]["topics"][0]["action"],
+        "BLOCKED"
+    );
+}
+
+#[tokio::test]
+async fn guardrails_are_rejected_for_other_providers() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        safety: Some(SafetySettings {
+            guardrail: Some(Guardrail {
+                id: "gr-support".to_string(),
+                version: "DRAFT".to_string(),
+                trace: false,
+            }),
+        }),
+        ..Default::default()
+    };
+
+    match ask_question(&ai_config, question()).await {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert_eq!(failure_str, "openai cannot apply a guardrail")
+        }
+        other => panic!("Expected a ModelError, got {:?}", other),
+    }
+}
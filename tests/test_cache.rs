@@ -0,0 +1,336 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    cache::{
+        cache_key, Cache, CacheEntry, CacheKeyPolicy, CachePolicy, DiskCache, MemoryCache,
+        ResponseCache,
+    },
+    config::{AiConfig, AnthropicOptions, AudioInput, Framework, Question, VllmOptions},
+    grammar::ResponseFormat,
+    params::GenerationParams,
+    transcript::ImageSource,
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+use std::time::Duration;
+
+fn question(prompt: &str) -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: prompt.to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn cached_answers_skip_the_provider() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Paris" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let cache = Cache::new(MemoryCache::new());
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        cache: Some(cache.clone()),
+        ..Default::default()
+    };
+
+    for _ in 0..2 {
+        let answer = ask_question(&ai_config, question("Capital of France?"))
+            .await
+            .expect("Should succeed");
+        assert_eq!(answer, "Paris");
+    }
+    mock.assert_hits(1);
+
+    // Another question, or the same one after invalidation, goes to the provider again
+    ask_question(&ai_config, question("Capital of Italy?"))
+        .await
+        .expect("Should succeed");
+    let key = cache.key(&ai_config, &question("Capital of France?"));
+    cache.invalidate(&key).await.unwrap();
+    ask_question(&ai_config, question("Capital of France?"))
+        .await
+        .expect("Should succeed");
+    mock.assert_hits(3);
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[test]
+fn cache_key_depends_on_model_and_question() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+    let other_model = AiConfig {
+        model: "gpt-4o".to_string(),
+        ..ai_config.clone()
+    };
+    let policy = CacheKeyPolicy::default();
+
+    let key = cache_key(&ai_config, &question("Hi"), &policy);
+    assert_eq!(key, cache_key(&ai_config, &question("Hi"), &policy));
+    assert_eq!(key.len(), 64);
+    assert_ne!(key, cache_key(&ai_config, &question("Hello"), &policy));
+    assert_ne!(key, cache_key(&other_model, &question("Hi"), &policy));
+}
+
+#[test]
+fn cache_key_covers_everything_sent() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+    let policy = CacheKeyPolicy::default();
+    let key = cache_key(&ai_config, &question("Describe it"), &policy);
+
+    let with_image = Question {
+        images: vec![ImageSource::Url {
+            url: "https://example.com/cat.png".to_string(),
+        }],
+        ..question("Describe it")
+    };
+    let with_audio = Question {
+        audio: vec![AudioInput::from_bytes("wav", b"RIFF")],
+        ..question("Describe it")
+    };
+    let with_schema = AiConfig {
+        response_format: Some(ResponseFormat::JsonSchema {
+            name: "animal".to_string(),
+            schema: json!({"type": "object", "properties": {"species": {"type": "string"}}}),
+        }),
+        ..ai_config.clone()
+    };
+    let with_other_schema = AiConfig {
+        response_format: Some(ResponseFormat::JsonSchema {
+            name: "animal".to_string(),
+            schema: json!({"type": "object", "properties": {"legs": {"type": "integer"}}}),
+        }),
+        ..ai_config.clone()
+    };
+    let prefilled = AiConfig {
+        anthropic: Some(AnthropicOptions {
+            prefill: Some("{".to_string()),
+            ..Default::default()
+        }),
+        ..ai_config.clone()
+    };
+    let elsewhere = AiConfig {
+        base_url: Some("https://eu.example.com/v1".to_string()),
+        ..ai_config.clone()
+    };
+    let with_vllm = AiConfig {
+        vllm: Some(VllmOptions {
+            guided_choice: vec!["cat".to_string(), "dog".to_string()],
+            ..Default::default()
+        }),
+        ..ai_config.clone()
+    };
+
+    let keys = [
+        cache_key(&ai_config, &with_image, &policy),
+        cache_key(&ai_config, &with_audio, &policy),
+        cache_key(&with_schema, &question("Describe it"), &policy),
+        cache_key(&with_other_schema, &question("Describe it"), &policy),
+        cache_key(&prefilled, &question("Describe it"), &policy),
+        cache_key(&elsewhere, &question("Describe it"), &policy),
+        cache_key(&with_vllm, &question("Describe it"), &policy),
+    ];
+    for (i, other) in keys.iter().enumerate() {
+        assert_ne!(&key, other);
+        assert!(keys[i + 1..].iter().all(|next| next != other));
+    }
+}
+
+#[test]
+fn cache_key_policy_selects_fingerprinted_parts() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+    let hot = AiConfig {
+        params: Some(GenerationParams {
+            temperature: Some(1.2),
+            ..Default::default()
+        }),
+        ..ai_config.clone()
+    };
+    let dated = Question {
+        system_prompt: Some("Today is 2024-05-01.".to_string()),
+        ..question("Hi")
+    };
+
+    let default = CacheKeyPolicy::default();
+    assert_ne!(
+        cache_key(&ai_config, &question("Hi"), &default),
+        cache_key(&hot, &question("Hi"), &default)
+    );
+    assert_ne!(
+        cache_key(&ai_config, &question("Hi"), &default),
+        cache_key(&ai_config, &dated, &default)
+    );
+
+    let loose = CacheKeyPolicy {
+        system_prompt: false,
+        params: false,
+        model_revision: None,
+    };
+    assert_eq!(
+        cache_key(&ai_config, &question("Hi"), &loose),
+        cache_key(&hot, &dated, &loose)
+    );
+
+    let revised = CacheKeyPolicy {
+        model_revision: Some("2024-07-18".to_string()),
+        ..Default::default()
+    };
+    assert_ne!(
+        cache_key(&ai_config, &question("Hi"), &default),
+        cache_key(&ai_config, &question("Hi"), &revised)
+    );
+}
+
+#[tokio::test]
+async fn entries_expire_after_their_ttl() {
+    let cache = Cache::new(MemoryCache::new()).with_policy(CachePolicy {
+        ttl: Some(Duration::from_millis(50)),
+        ..Default::default()
+    });
+
+    cache.put("key", "Short-lived").await.unwrap();
+    cache
+        .put_entry("pinned", &CacheEntry::new("Long-lived", None, None))
+        .await
+        .unwrap();
+    assert_eq!(
+        cache.get("key").await.unwrap().as_deref(),
+        Some("Short-lived")
+    );
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(cache.get("key").await.unwrap(), None);
+    assert_eq!(
+        cache.get("pinned").await.unwrap().as_deref(),
+        Some("Long-lived")
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn stale_answers_are_served_while_revalidating() {
+    let server = MockServer::start();
+
+    let mut old = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Old answer" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let cache = Cache::new(MemoryCache::new()).with_policy(CachePolicy {
+        ttl: Some(Duration::from_millis(50)),
+        stale_while_revalidate: Some(Duration::from_secs(60)),
+        ..Default::default()
+    });
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        cache: Some(cache.clone()),
+        ..Default::default()
+    };
+
+    let ask = || ask_question(&ai_config, question("News?"));
+    assert_eq!(ask().await.unwrap(), "Old answer");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    old.delete();
+    let new = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "New answer" } } ] }"#);
+    });
+
+    // The stale answer comes back at once, and the refresh replaces it in the background
+    assert_eq!(ask().await.unwrap(), "Old answer");
+    let key = cache.key(&ai_config, &question("News?"));
+    for _ in 0..50 {
+        if cache.get(&key).await.unwrap().as_deref() == Some("New answer") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(ask().await.unwrap(), "New answer");
+    new.assert_hits(1);
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[tokio::test]
+async fn disk_cache_persists_answers() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let entry = CacheEntry::new("Cached answer", Some(Duration::from_secs(60)), None);
+    DiskCache::new(dir.path())
+        .unwrap()
+        .put("some key", &entry)
+        .await
+        .unwrap();
+
+    let cache = DiskCache::new(dir.path()).unwrap();
+    assert_eq!(cache.get("some key").await.unwrap(), Some(entry));
+    assert_eq!(cache.get("other key").await.unwrap(), None);
+
+    cache.invalidate("some key").await.unwrap();
+    cache.invalidate("some key").await.unwrap();
+    assert_eq!(cache.get("some key").await.unwrap(), None);
+}
+
+/// Runs against a real server when `ASK_AI_TEST_REDIS_URL` is set, e.g. `redis://127.0.0.1/`.
+#[cfg(feature = "redis")]
+#[tokio::test]
+async fn redis_cache_round_trips() {
+    use ask_ai::cache::RedisCache;
+
+    let Ok(url) = env::var("ASK_AI_TEST_REDIS_URL") else {
+        return;
+    };
+    let prefix = format!("ask_ai_test:{}", std::process::id());
+    let cache = RedisCache::open(&url, &prefix)
+        .await
+        .expect("Should connect");
+
+    let entry = CacheEntry::new("Cached answer", Some(Duration::from_secs(60)), None);
+    cache.put("key", &entry).await.unwrap();
+    assert_eq!(cache.get("key").await.unwrap(), Some(entry));
+    cache.invalidate("key").await.unwrap();
+    assert_eq!(cache.get("key").await.unwrap(), None);
+}
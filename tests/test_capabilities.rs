@@ -0,0 +1,215 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    capabilities::{capabilities, Capabilities, Capability, UnsupportedPolicy},
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    tools::{run_with_tools, ToolRegistry},
+    transcript::ImageSource,
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+
+fn tool_question() -> (Question, ToolRegistry) {
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What's the weather in Paris?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    let registry = ToolRegistry::new().register(
+        "get_weather",
+        "Current weather for a city",
+        json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        |_| async { Ok("Sunny".to_string()) },
+    );
+    (question, registry)
+}
+
+fn text_only(policy: UnsupportedPolicy) -> AiConfig {
+    AiConfig {
+        llm: Framework::Ollama,
+        model: "tinyllama".to_string(),
+        on_unsupported: Some(policy),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn known_model_families() {
+    let gpt = capabilities(Framework::OpenAI, "gpt-4o-mini-2024-07-18");
+    assert!(gpt.supports_tools && gpt.supports_vision && gpt.supports_json_schema);
+    assert_eq!(gpt.max_context, Some(128_000));
+
+    let legacy = capabilities(Framework::OpenAI, "gpt-4-0613");
+    assert!(!legacy.supports_vision);
+    assert_eq!(legacy.max_context, Some(8_192));
+    assert!(!gpt.reasoning && !legacy.reasoning);
+    assert!(capabilities(Framework::OpenAI, "o4-mini").reasoning);
+    assert!(capabilities(Framework::OpenAI, "gpt-5-mini").reasoning);
+    assert!(!capabilities(Framework::OpenAI, "gpt-5-chat-latest").reasoning);
+
+    let claude = capabilities(Framework::Anthropic, "Claude-Sonnet-4-20250514");
+    assert!(claude.supports_tools && claude.supports_vision);
+    assert_eq!(claude.max_context, Some(200_000));
+
+    let llava = capabilities(Framework::Ollama, "llava:13b");
+    assert!(llava.supports_vision && !llava.supports_tools);
+    assert!(capabilities(Framework::Ollama, "llama3.1:8b").supports_tools);
+
+    // OpenRouter models are looked up as their vendor's
+    assert_eq!(
+        capabilities(Framework::OpenRouter, "openai/gpt-4o-mini"),
+        gpt
+    );
+}
+
+#[test]
+fn unknown_models_only_stream_text() {
+    assert_eq!(
+        capabilities(Framework::Anthropic, "my-finetune"),
+        Capabilities {
+            supports_streaming: true,
+            ..Default::default()
+        }
+    );
+    // Ollama's `format` works with any model
+    assert!(capabilities(Framework::Ollama, "my-finetune").supports_json_schema);
+    // The family is matched per framework
+    assert!(!capabilities(Framework::Ollama, "gpt-4o").supports_vision);
+}
+
+#[tokio::test]
+async fn unsupported_tools_fail_early() {
+    let (question, registry) = tool_question();
+
+    let result = run_with_tools(&text_only(UnsupportedPolicy::Error), question, &registry).await;
+    assert!(matches!(
+        result,
+        Err(AppError::UnsupportedCapability {
+            capability: Capability::Tools,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn unsupported_tools_are_stripped_or_rerouted() {
+    let server = MockServer::start();
+
+    let plain = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .matches(|req| !String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains("get_weather"));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"tinyllama","message":{"role":"assistant","content":"I cannot check"},"done":true}"#);
+    });
+    let fallback = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("get_weather");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "choices": [ { "message": { "role": "assistant", "content": "Sunny" } } ] }"#,
+            );
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let (question, registry) = tool_question();
+    let answer = run_with_tools(&text_only(UnsupportedPolicy::Strip), question, &registry)
+        .await
+        .expect("Should succeed");
+    plain.assert();
+    assert_eq!(answer, "I cannot check");
+
+    let capable = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+    let (question, registry) = tool_question();
+    let answer = run_with_tools(
+        &text_only(UnsupportedPolicy::Fallback(Box::new(capable))),
+        question,
+        &registry,
+    )
+    .await
+    .expect("Should succeed");
+    fallback.assert();
+    assert_eq!(answer, "Sunny");
+
+    env::remove_var("OLLAMA_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+fn has_images(req: &HttpMockRequest) -> bool {
+    String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains(r#""images":["#)
+}
+
+#[tokio::test]
+#[serial]
+async fn images_reach_ollama_vision_models() {
+    let server = MockServer::start();
+    let vision = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains(r#""model":"llava""#)
+            .body_contains(r#""images":["iVBORw=="]"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llava","message":{"role":"assistant","content":"A red square"},"done":true}"#);
+    });
+    let stripped = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains(r#""model":"tinyllama""#)
+            .matches(|req| !has_images(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"tinyllama","message":{"role":"assistant","content":"I cannot see it"},"done":true}"#);
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What is in this picture?".to_string(),
+        images: vec![ImageSource::from_bytes("image/png", b"\x89PNG")],
+        audio: vec![],
+    };
+    let llava = AiConfig {
+        llm: Framework::Ollama,
+        model: "llava".to_string(),
+        on_unsupported: Some(UnsupportedPolicy::Error),
+        ..Default::default()
+    };
+    let answer = ask_question(&llava, question.clone()).await;
+    let text_only_answer =
+        ask_question(&text_only(UnsupportedPolicy::Strip), question.clone()).await;
+    let refused = ask_question(&text_only(UnsupportedPolicy::Error), question).await;
+    env::remove_var("OLLAMA_API_URL");
+
+    vision.assert();
+    stripped.assert();
+    assert_eq!(answer.expect("Should succeed"), "A red square");
+    assert_eq!(text_only_answer.expect("Should succeed"), "I cannot see it");
+    assert!(matches!(
+        refused,
+        Err(AppError::UnsupportedCapability {
+            capability: Capability::Vision,
+            ..
+        })
+    ));
+}
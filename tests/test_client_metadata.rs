@@ -0,0 +1,172 @@
+use ask_ai::{
+    ask_ai::build_openai_payload,
+    ask_question,
+    config::{AiConfig, ClientMetadata, Framework, Question},
+    params::GenerationParams,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::collections::BTreeMap;
+use std::env;
+
+fn client_metadata() -> ClientMetadata {
+    ClientMetadata {
+        user_agent: Some("acme-support/2.1".to_string()),
+        app_url: Some("https://support.acme.com".to_string()),
+        app_title: Some("Acme Support".to_string()),
+        user_id: Some("user-1234".to_string()),
+        headers: BTreeMap::from([("X-Team".to_string(), "billing".to_string())]),
+    }
+}
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Who am I?".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn client_metadata_is_sent_with_requests() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .header("user-agent", "acme-support/2.1")
+            .header("http-referer", "https://support.acme.com")
+            .header("x-title", "Acme Support")
+            .header("x-team", "billing")
+            .body_contains(r#""metadata":{"user_id":"user-1234"}"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "Support agent" } ] }"#);
+    });
+
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-3-5-haiku-latest".to_string(),
+        client: Some(client_metadata()),
+        ..Default::default()
+    };
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Support agent");
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn default_user_agent_names_the_crate() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions").header(
+            "user-agent",
+            format!("ask_ai/{}", env!("CARGO_PKG_VERSION")),
+        );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hi" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let mut ai_config = AiConfig {
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+    ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    mock.assert();
+
+    ai_config.client = Some(client_metadata());
+    assert_eq!(
+        build_openai_payload(&question(), &ai_config)["user"],
+        "user-1234"
+    );
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn openrouter_requests_name_the_application() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/v1/chat/completions")
+            .header("authorization", "Bearer openrouter_testkey")
+            .header("http-referer", env!("CARGO_PKG_REPOSITORY"))
+            .header("x-title", "ask_ai")
+            .body_contains(r#""model":"anthropic/claude-3.5-haiku""#)
+            .body_contains(r#""top_k":40"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hi" } } ] }"#);
+    });
+
+    env::set_var("OPENROUTER_API_KEY", "openrouter_testkey");
+    env::set_var(
+        "OPENROUTER_API_URL",
+        format!("{}/api/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenRouter,
+        model: "anthropic/claude-3.5-haiku".to_string(),
+        params: Some(GenerationParams {
+            top_k: Some(40),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Hi");
+
+    // Configured metadata takes precedence
+    let ai_config = AiConfig {
+        client: Some(client_metadata()),
+        ..ai_config
+    };
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/v1/chat/completions")
+            .header("http-referer", "https://support.acme.com")
+            .header("x-title", "Acme Support");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hi" } } ] }"#);
+    });
+    ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    mock.assert();
+
+    env::remove_var("OPENROUTER_API_KEY");
+    env::remove_var("OPENROUTER_API_URL");
+}
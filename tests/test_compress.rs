@@ -0,0 +1,213 @@
+use ask_ai::{
+    ask_question,
+    compress::Compression,
+    config::{AiConfig, AiPrompt, Framework, Question},
+    error::AppError,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const PASSAGE: &str =
+    "The deployment runs nightly at two in the morning and rebuilds every service image.";
+
+fn body_text(req: &HttpMockRequest) -> String {
+    String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).into_owned()
+}
+
+#[tokio::test]
+#[serial]
+async fn repeated_paragraphs_and_filler_are_dropped() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("what does it rebuild?")
+            .matches(|req| {
+                let body = body_text(req);
+                body.matches(PASSAGE).count() == 1 && !body.contains("o be honest")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llama3","message":{"role":"assistant","content":"Every image"},"done":true}"#);
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        compression: Some(Compression::new(10)),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: Some(vec![AiPrompt {
+            content: format!("{}\n\n\n{}", PASSAGE, PASSAGE),
+            output: "Noted.".to_string(),
+        }]),
+        new_prompt: format!("{}\n\nTo be honest, what does it rebuild?", PASSAGE),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Every image");
+
+    env::remove_var("OLLAMA_API_URL");
+}
+
+#[tokio::test]
+async fn code_blocks_and_intensifiers_are_kept() {
+    const CODE: &str = "```\nlet total: u64 = prices.iter().sum(); // needless to say\n\nlet total: u64 = prices.iter().sum(); // needless to say\n```";
+
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("Is it really required? It is not very good.")
+            .body_contains("let total: u64 = prices.iter().sum(); // needless to say\\n\\nlet total: u64 = prices.iter().sum(); // needless to say")
+            .matches(|req| !body_text(req).contains("Needless to say"));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{"model":"llama3","message":{"role":"assistant","content":"Yes"},"done":true}"#,
+            );
+    });
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        base_url: Some(server.base_url()),
+        compression: Some(Compression::new(10)),
+        ..Default::default()
+    };
+    let question = Question {
+        new_prompt: format!(
+            "Needless to say, {}\n{}\nIs it really required? It is not very good.",
+            PASSAGE, CODE
+        ),
+        ..Default::default()
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Yes");
+}
+
+#[tokio::test]
+#[serial]
+async fn long_passages_are_rewritten_by_the_compression_model() {
+    let server = MockServer::start();
+
+    let compressor = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("Compress the user's text");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"tiny","message":{"role":"assistant","content":"Nightly 2am rebuild of all images."},"done":true}"#);
+    });
+    let main = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Nightly 2am rebuild of all images.")
+            .matches(|req| !body_text(req).contains(PASSAGE));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "At 2am" } } ] }"#);
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        compression: Some(Compression {
+            model: Some(Box::new(AiConfig {
+                llm: Framework::Ollama,
+                model: "tiny".to_string(),
+                ..Default::default()
+            })),
+            ..Compression::new(50)
+        }),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: Some(vec![AiPrompt {
+            content: (0..4)
+                .map(|i| format!("{} (run {})", PASSAGE, i))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            output: "Understood.".to_string(),
+        }]),
+        new_prompt: "When does it run?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    compressor.assert();
+    main.assert();
+    assert_eq!(answer, "At 2am");
+
+    env::remove_var("OLLAMA_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+async fn compression_models_are_held_to_local_only() {
+    let server = MockServer::start();
+    let main = server.mock(|when, then| {
+        when.method(POST).path("/api/chat");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llama3","message":{"role":"assistant","content":"At 2am"},"done":true}"#);
+    });
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        base_url: Some(server.base_url()),
+        local_only: true,
+        compression: Some(Compression {
+            model: Some(Box::new(AiConfig {
+                llm: Framework::OpenAI,
+                model: "gpt-4o-mini".to_string(),
+                api_key: Some("open_api_testkey".into()),
+                base_url: Some("https://api.openai.com/v1".to_string()),
+                ..Default::default()
+            })),
+            ..Compression::new(50)
+        }),
+        ..Default::default()
+    };
+    let question = Question {
+        new_prompt: (0..4)
+            .map(|i| format!("{} (run {})", PASSAGE, i))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        ..Default::default()
+    };
+
+    match ask_question(&ai_config, question).await {
+        Err(AppError::RemoteEndpointBlocked { .. }) => {}
+        other => panic!("Expected AppError::RemoteEndpointBlocked, got {:?}", other),
+    }
+    main.assert_hits(0);
+}
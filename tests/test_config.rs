@@ -0,0 +1,81 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    capabilities::capabilities,
+    config::{AiConfig, Framework, Question, DEFAULT_MAX_TOKEN},
+    error::AppError,
+};
+
+#[test]
+fn default_for_picks_a_known_model_per_provider() {
+    for framework in [Framework::OpenAI, Framework::Anthropic, Framework::Ollama] {
+        let ai_config = AiConfig::default_for(framework.clone());
+        assert_eq!(ai_config.llm, framework);
+        assert_eq!(ai_config.model, framework.default_model());
+        assert_eq!(ai_config.max_token, Some(DEFAULT_MAX_TOKEN));
+        assert!(ai_config.api_key.is_none());
+
+        // Default models are in the capabilities table, with tool support
+        assert!(capabilities(framework, &ai_config.model).supports_tools);
+    }
+}
+
+#[test]
+fn framework_parses_known_and_custom_names() {
+    assert_eq!("openai".parse::<Framework>().unwrap(), Framework::OpenAI);
+    assert_eq!(
+        " Anthropic ".parse::<Framework>().unwrap(),
+        Framework::Anthropic
+    );
+    assert_eq!("OLLAMA".parse::<Framework>().unwrap(), Framework::Ollama);
+    assert_eq!("Mistral".parse::<Framework>().unwrap(), Framework::Mistral);
+    assert_eq!("groq".parse::<Framework>().unwrap(), Framework::Groq);
+    assert_eq!(
+        "HuggingFace".parse::<Framework>().unwrap(),
+        Framework::HuggingFace
+    );
+    assert_eq!(
+        "OpenRouter".parse::<Framework>().unwrap(),
+        Framework::OpenRouter
+    );
+    assert_eq!(
+        "Together".parse::<Framework>().unwrap(),
+        Framework::Custom("Together".to_string())
+    );
+    assert!(matches!(
+        "  ".parse::<Framework>(),
+        Err(AppError::UnexpectedError(_))
+    ));
+}
+
+#[test]
+fn framework_serializes_as_its_name() {
+    let custom = Framework::Custom("together".to_string());
+    assert_eq!(serde_json::to_value(&custom).unwrap(), "together");
+    assert_eq!(serde_json::to_value(Framework::OpenAI).unwrap(), "openai");
+
+    let ai_config: AiConfig =
+        serde_json::from_str(r#"{ "llm": "together", "model": "llama-3.1-8b" }"#).unwrap();
+    assert_eq!(ai_config.llm, custom);
+    assert!(serde_json::from_str::<Framework>(r#""""#).is_err());
+}
+
+#[tokio::test]
+async fn custom_frameworks_cannot_be_asked() {
+    let ai_config = AiConfig {
+        llm: Framework::Custom("together".to_string()),
+        model: "llama-3.1-8b".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    match ask_question(&ai_config, question).await {
+        Err(AppError::ModelError { failure_str, .. }) => assert!(failure_str.contains("together")),
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
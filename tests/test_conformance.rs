@@ -0,0 +1,344 @@
+#![cfg(feature = "conformance")]
+
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    conformance::{check_provider, Harness, Reply},
+    error::{AppError, Result},
+    provider::{register_provider, BoxFuture, Completion, Provider},
+    transcript::Role,
+};
+use httpmock::{prelude::*, Mock};
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct Backend {
+    reply: Option<Reply>,
+    received: Vec<(Role, String)>,
+}
+
+/// An in-process provider answering from a scripted backend, optionally dropping history.
+struct Scripted {
+    backend: Arc<Mutex<Backend>>,
+    drops_history: bool,
+}
+
+impl Provider for Scripted {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let mut turns = Vec::new();
+            if let Some(system_prompt) = question.system_prompt {
+                turns.push((Role::System, system_prompt));
+            }
+            if !self.drops_history {
+                for message in question.messages.into_iter().flatten() {
+                    turns.push((Role::User, message.content));
+                    turns.push((Role::Assistant, message.output));
+                }
+            }
+            turns.push((Role::User, question.new_prompt));
+
+            let mut backend = self.backend.lock().unwrap();
+            backend.received = turns;
+            match backend.reply.clone() {
+                Some(Reply::Answer { text, truncated }) => Ok(Completion {
+                    answer: text,
+                    truncated,
+                }),
+                Some(Reply::Failure { status }) => Err(AppError::ApiError {
+                    model_name: ai_config.llm.to_string(),
+                    failure_str: format!("Status {}", status),
+                }),
+                None => panic!("No reply scripted"),
+            }
+        })
+    }
+}
+
+struct ScriptedHarness {
+    name: &'static str,
+    backend: Arc<Mutex<Backend>>,
+}
+
+impl ScriptedHarness {
+    fn register(name: &'static str, drops_history: bool) -> Self {
+        let backend = Arc::new(Mutex::new(Backend::default()));
+        register_provider(
+            name,
+            Scripted {
+                backend: backend.clone(),
+                drops_history,
+            },
+        );
+        Self { name, backend }
+    }
+}
+
+impl Harness for ScriptedHarness {
+    fn ai_config(&self) -> AiConfig {
+        AiConfig {
+            llm: self.name.parse().unwrap(),
+            model: "scripted-1".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn reply(&self, reply: Reply) {
+        self.backend.lock().unwrap().reply = Some(reply);
+    }
+
+    fn received(&self) -> Vec<(Role, String)> {
+        self.backend.lock().unwrap().received.clone()
+    }
+}
+
+#[tokio::test]
+async fn conforming_providers_pass() {
+    let harness = ScriptedHarness::register("conforming", false);
+    let violations = check_provider(&harness).await;
+    assert!(violations.is_empty(), "{:#?}", violations);
+}
+
+#[tokio::test]
+async fn dropped_history_is_a_violation() {
+    let harness = ScriptedHarness::register("forgetful", true);
+    let checks: Vec<_> = check_provider(&harness)
+        .await
+        .into_iter()
+        .map(|violation| violation.check)
+        .collect();
+    assert_eq!(checks, vec!["history", "system_prompt"]);
+}
+
+/// The last request body each mock server received, one slot per built-in API. httpmock
+/// matchers are plain function pointers, so they can only record into statics.
+static BODIES: [Mutex<Option<Value>>; 3] = [const { Mutex::new(None) }; 3];
+
+/// Records the request body in `SLOT`, and matches streaming requests when `STREAM` is set,
+/// the others otherwise.
+fn record<const SLOT: usize, const STREAM: bool>(req: &HttpMockRequest) -> bool {
+    let body: Option<Value> = req
+        .body
+        .as_deref()
+        .and_then(|body| serde_json::from_slice(body).ok());
+    let stream = body.as_ref().is_some_and(|body| body["stream"] == true);
+    *BODIES[SLOT].lock().unwrap() = body;
+    stream == STREAM
+}
+
+/// A built-in provider's API, as a mock server speaks it.
+#[derive(Debug, Clone, Copy)]
+enum Api {
+    OpenAI,
+    Anthropic,
+    Ollama,
+}
+
+impl Api {
+    fn slot(self) -> usize {
+        self as usize
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            Api::OpenAI => "/v1/chat/completions",
+            Api::Anthropic => "/v1/messages",
+            Api::Ollama => "/api/chat",
+        }
+    }
+
+    fn matcher(self, stream: bool) -> fn(&HttpMockRequest) -> bool {
+        match (self, stream) {
+            (Api::OpenAI, false) => record::<0, false>,
+            (Api::OpenAI, true) => record::<0, true>,
+            (Api::Anthropic, false) => record::<1, false>,
+            (Api::Anthropic, true) => record::<1, true>,
+            (Api::Ollama, false) => record::<2, false>,
+            (Api::Ollama, true) => record::<2, true>,
+        }
+    }
+
+    fn answer(self, text: &str, truncated: bool) -> Value {
+        match self {
+            Api::OpenAI => json!({
+                "choices": [{
+                    "message": { "role": "assistant", "content": text },
+                    "finish_reason": if truncated { "length" } else { "stop" }
+                }]
+            }),
+            Api::Anthropic => json!({
+                "content": [{ "type": "text", "text": text }],
+                "stop_reason": if truncated { "max_tokens" } else { "end_turn" }
+            }),
+            Api::Ollama => json!({
+                "message": { "role": "assistant", "content": text },
+                "done": true,
+                "done_reason": if truncated { "length" } else { "stop" }
+            }),
+        }
+    }
+
+    /// `text` streamed a word at a time.
+    fn stream(self, text: &str) -> String {
+        let deltas = text.split_inclusive(' ');
+        match self {
+            Api::OpenAI => deltas
+                .map(|delta| json!({ "choices": [{ "delta": { "content": delta } }] }))
+                .map(|chunk| format!("data: {}\n\n", chunk))
+                .chain(["data: [DONE]\n\n".to_string()])
+                .collect(),
+            Api::Anthropic => deltas
+                .map(|delta| {
+                    json!({
+                        "type": "content_block_delta",
+                        "index": 0,
+                        "delta": { "type": "text_delta", "text": delta }
+                    })
+                })
+                .chain([json!({ "type": "message_stop" })])
+                .map(|event| format!("event: {}\ndata: {}\n\n", event["type"], event))
+                .collect(),
+            Api::Ollama => deltas
+                .map(|delta| json!({ "message": { "role": "assistant", "content": delta }, "done": false }))
+                .chain([json!({ "message": { "role": "assistant", "content": "" }, "done": true })])
+                .map(|chunk| format!("{}\n", chunk))
+                .collect(),
+        }
+    }
+
+    /// The turns of a request body.
+    fn turns(self, body: &Value) -> Vec<(Role, String)> {
+        let mut turns = Vec::new();
+        if let Some(system) = body["system"].as_str() {
+            turns.push((Role::System, system.to_string()));
+        }
+        for message in body["messages"].as_array().into_iter().flatten() {
+            let role = match message["role"].as_str() {
+                Some("system") => Role::System,
+                Some("assistant") => Role::Assistant,
+                _ => Role::User,
+            };
+            let text = match &message["content"] {
+                Value::Array(blocks) => blocks
+                    .iter()
+                    .filter_map(|block| block["text"].as_str())
+                    .collect(),
+                content => content.as_str().unwrap_or_default().to_string(),
+            };
+            turns.push((role, text));
+        }
+        turns
+    }
+}
+
+/// A built-in provider talking to an httpmock server.
+struct MockHarness {
+    api: Api,
+    server: MockServer,
+    mocks: Mutex<Vec<usize>>,
+}
+
+impl MockHarness {
+    fn start(api: Api) -> Self {
+        Self {
+            api,
+            server: MockServer::start(),
+            mocks: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl Harness for MockHarness {
+    fn ai_config(&self) -> AiConfig {
+        match self.api {
+            Api::OpenAI => AiConfig {
+                llm: Framework::OpenAI,
+                model: "gpt-4o-mini".to_string(),
+                base_url: Some(self.server.url("/v1")),
+                api_key: Some("open_api_testkey".into()),
+                ..Default::default()
+            },
+            Api::Anthropic => AiConfig {
+                llm: Framework::Anthropic,
+                model: "claude-3-5-haiku-latest".to_string(),
+                api_key: Some("anthropic_testkey".into()),
+                ..Default::default()
+            },
+            Api::Ollama => AiConfig {
+                llm: Framework::Ollama,
+                model: "llama3.2".to_string(),
+                base_url: Some(self.server.base_url()),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn reply(&self, reply: Reply) {
+        let mut mocks = self.mocks.lock().unwrap();
+        for id in mocks.drain(..) {
+            Mock::new(id, &self.server).delete();
+        }
+        for stream in [false, true] {
+            let mock = self.server.mock(|when, then| {
+                when.method(POST)
+                    .path(self.api.path())
+                    .matches(self.api.matcher(stream));
+                match &reply {
+                    Reply::Answer { text, .. } if stream => {
+                        then.status(200)
+                            .header("content-type", "text/event-stream")
+                            .body(self.api.stream(text));
+                    }
+                    Reply::Answer { text, truncated } => {
+                        then.status(200)
+                            .header("content-type", "application/json")
+                            .json_body(self.api.answer(text, *truncated));
+                    }
+                    Reply::Failure { status } => {
+                        then.status(*status)
+                            .header("content-type", "application/json")
+                            .json_body(json!({ "error": { "message": "Scripted failure" } }));
+                    }
+                }
+            });
+            mocks.push(mock.id);
+        }
+    }
+
+    fn received(&self) -> Vec<(Role, String)> {
+        match &*BODIES[self.api.slot()].lock().unwrap() {
+            Some(body) => self.api.turns(body),
+            None => vec![],
+        }
+    }
+}
+
+#[tokio::test]
+async fn openai_conforms() {
+    let harness = MockHarness::start(Api::OpenAI);
+    let violations = check_provider(&harness).await;
+    assert!(violations.is_empty(), "{:#?}", violations);
+}
+
+#[tokio::test]
+#[serial]
+async fn anthropic_conforms() {
+    let harness = MockHarness::start(Api::Anthropic);
+    env::set_var("ANTHROPIC_API_URL", harness.server.url("/v1/messages"));
+    let violations = check_provider(&harness).await;
+    env::remove_var("ANTHROPIC_API_URL");
+    assert!(violations.is_empty(), "{:#?}", violations);
+}
+
+#[tokio::test]
+async fn ollama_conforms() {
+    let harness = MockHarness::start(Api::Ollama);
+    let violations = check_provider(&harness).await;
+    assert!(violations.is_empty(), "{:#?}", violations);
+}
@@ -0,0 +1,172 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    config::{AiConfig, Framework, Question},
+    continuation::{AutoContinue, CONTINUE_PROMPT},
+    transcript::ImageSource,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Write a long story.".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn set_env(server: &MockServer) {
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+    env::set_var("ANTHROPIC_API_KEY", "test_key");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+}
+
+fn remove_env() {
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+    env::remove_var("ANTHROPIC_API_KEY");
+}
+
+fn continuing(llm: Framework, model: &str, max_continuations: u32) -> AiConfig {
+    AiConfig {
+        llm,
+        model: model.to_string(),
+        auto_continue: Some(AutoContinue {
+            max_continuations,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn truncated_answers_are_continued() {
+    let server = MockServer::start();
+
+    let rest = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(CONTINUE_PROMPT)
+            .body_contains(r#""content":"Once upon a time, ""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "the end." }, "finish_reason": "stop" } ] }"#);
+    });
+    let start = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Once upon a time, " }, "finish_reason": "length" } ] }"#);
+    });
+    set_env(&server);
+
+    let answer = ask_question(&continuing(Framework::OpenAI, "gpt-4o-mini", 3), question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Once upon a time, the end.");
+    start.assert_hits(1);
+    rest.assert_hits(1);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn continuations_stop_at_the_cap() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/messages");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "more " } ], "stop_reason": "max_tokens" }"#);
+    });
+    set_env(&server);
+
+    let ai_config = continuing(Framework::Anthropic, "claude-sonnet-4-5", 2);
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "more more more ");
+    mock.assert_hits(3);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn truncated_answers_are_returned_as_is_by_default() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/messages");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "Once upon" } ], "stop_reason": "max_tokens" }"#);
+    });
+    set_env(&server);
+
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-sonnet-4-5".to_string(),
+        ..Default::default()
+    };
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Once upon");
+    mock.assert_hits(1);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn continuations_keep_the_images() {
+    let server = MockServer::start();
+
+    let rest = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(CONTINUE_PROMPT)
+            .body_contains("https://example.com/chart.png");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "then falls." }, "finish_reason": "stop" } ] }"#);
+    });
+    let start = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "The curve rises, " }, "finish_reason": "length" } ] }"#);
+    });
+    set_env(&server);
+
+    let question = Question {
+        new_prompt: "Describe this chart.".to_string(),
+        images: vec![ImageSource::Url {
+            url: "https://example.com/chart.png".to_string(),
+        }],
+        ..Default::default()
+    };
+    let answer = ask_question(&continuing(Framework::OpenAI, "gpt-4o-mini", 3), question)
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "The curve rises, then falls.");
+    start.assert_hits(1);
+    rest.assert_hits(1);
+
+    remove_env();
+}
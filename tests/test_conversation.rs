@@ -0,0 +1,133 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    conversation::Conversation,
+    error::AppError,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn openai_config(server: &MockServer) -> AiConfig {
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        max_token: None,
+        ..Default::default()
+    }
+}
+
+fn three_turns() -> Conversation {
+    let mut conversation = Conversation::new(None);
+    conversation.push("What is Rust?", "A language.");
+    conversation.push("Who made it?", "Mozilla.");
+    conversation.push("Is it fast?", "Yes.");
+    conversation
+}
+
+#[tokio::test]
+#[serial]
+async fn conversation_edit_truncates_later_turns() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("What is Rust?")
+            .body_contains("Who made Go?")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                !String::from_utf8_lossy(&body).contains("Is it fast?")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Google." } } ] }"#);
+    });
+
+    let ai_config = openai_config(&server);
+    let mut conversation = three_turns();
+
+    let answer = conversation
+        .edit(&ai_config, 1, "Who made Go?")
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Google.");
+    assert_eq!(conversation.messages.len(), 2);
+    assert_eq!(conversation.messages[1].content, "Who made Go?");
+    assert_eq!(conversation.messages[1].output, "Google.");
+
+    match conversation.edit(&ai_config, 5, "Out of range").await {
+        Err(AppError::UnexpectedError(msg)) => assert!(msg.contains("Cannot edit exchange 5")),
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    };
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn conversation_regenerate_replaces_last_answer() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Is it fast?");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Very fast." } } ] }"#);
+    });
+
+    let ai_config = openai_config(&server);
+    let mut conversation = three_turns();
+
+    let answer = conversation
+        .regenerate(&ai_config)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Very fast.");
+    assert_eq!(conversation.messages.len(), 3);
+    assert_eq!(conversation.messages[2].output, "Very fast.");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn conversation_generate_title_stores_title() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("short title")
+            .body_contains("User: What is Rust?")
+            .body_contains("Assistant: Mozilla.");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "\"Rust Basics.\"\n" } } ] }"#);
+    });
+
+    let ai_config = openai_config(&server);
+    let mut conversation = three_turns();
+
+    let title = conversation
+        .generate_title(&ai_config)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(title, "Rust Basics");
+    assert_eq!(conversation.title.as_deref(), Some("Rust Basics"));
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
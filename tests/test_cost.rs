@@ -0,0 +1,107 @@
+use ask_ai::{
+    audit::{AuditDecision, AuditEvent},
+    config::{AiConfig, Framework, Question},
+    cost::{CostGroup, Pricing, UsageLedger},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn answered(timestamp: u64, actor: &str, model: &str, tokens: u64) -> AuditEvent {
+    AuditEvent {
+        timestamp,
+        actor: actor.to_string(),
+        framework: Framework::OpenAI,
+        model: model.to_string(),
+        prompt: "...".to_string(),
+        redactions: Vec::new(),
+        decision: AuditDecision::Answered {
+            prompt_tokens: tokens,
+            answer_tokens: tokens,
+        },
+    }
+}
+
+#[test]
+fn reports_group_by_model_day_and_tag() {
+    let ledger = UsageLedger::new(Pricing::new().price("gpt-4o", 2.5, 10.0).price(
+        "gpt-4o-mini",
+        0.15,
+        0.6,
+    ));
+    // 2024-02-29 and 2024-03-01
+    ledger.import_audit(&[
+        answered(1_709_164_800, "acme", "gpt-4o-2024-08-06", 1_000_000),
+        answered(1_709_200_000, "globex", "gpt-4o-mini", 1_000_000),
+        answered(1_709_251_200, "acme", "gpt-4o-mini", 1_000_000),
+    ]);
+
+    let by_day_and_model = ledger.report(&[CostGroup::Day, CostGroup::Model]);
+    assert_eq!(
+        by_day_and_model.to_csv(),
+        "model,day,tag,requests,prompt_tokens,answer_tokens,cost_usd\n\
+         gpt-4o-2024-08-06,2024-02-29,,1,1000000,1000000,12.500000\n\
+         gpt-4o-mini,2024-02-29,,1,1000000,1000000,0.750000\n\
+         gpt-4o-mini,2024-03-01,,1,1000000,1000000,0.750000\n"
+    );
+
+    let by_tag = ledger.report(&[CostGroup::Tag]);
+    assert_eq!(by_tag.lines.len(), 2);
+    assert_eq!(by_tag.lines[0].tag.as_deref(), Some("acme"));
+    assert_eq!(by_tag.lines[0].requests, 2);
+    assert_eq!(by_tag.lines[0].cost, 13.25);
+
+    let json: serde_json::Value = serde_json::from_str(&ledger.report(&[]).to_json()).unwrap();
+    assert_eq!(json[0]["requests"], 3);
+    assert!(json[0].get("model").is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn answered_questions_are_recorded_with_tags() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Twelve chars" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Eight ch".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let ledger = UsageLedger::new(Pricing::new().price("gpt-4o", 1_000_000.0, 1_000_000.0));
+    ledger
+        .ask_question(&ai_config, question, &["billing", "team, \"core\""])
+        .await
+        .expect("Should succeed");
+    mock.assert();
+
+    let records = ledger.records();
+    assert_eq!((records[0].prompt_tokens, records[0].answer_tokens), (2, 3));
+    assert_eq!(records[0].cost, 5.0);
+    assert!(ledger
+        .report(&[CostGroup::Tag])
+        .to_csv()
+        .contains("\"team, \"\"core\"\"\",1,2,3,5.000000"));
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
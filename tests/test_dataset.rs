@@ -0,0 +1,128 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    dataset::{Dataset, DatasetRunner, RowResult},
+    error::AppError,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::{env, fs};
+
+fn ai_config() -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    }
+}
+
+fn runner(template: &str) -> DatasetRunner {
+    DatasetRunner {
+        template: template.to_string(),
+        concurrency: 2,
+        max_attempts: 2,
+        retry_delay_ms: 0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn csv_and_jsonl_rows_are_read_by_column() {
+    let csv = "id,review\r\n1,\"Great, \"\"really\"\"\nfast\"\r\n\n2,Slow\n";
+    let dataset = Dataset::from_csv(csv).unwrap();
+    assert_eq!(dataset.columns, vec!["id", "review"]);
+    assert_eq!(dataset.rows.len(), 2);
+    assert_eq!(dataset.rows[0]["review"], "Great, \"really\"\nfast");
+    assert_eq!(dataset.rows[1]["id"], "2");
+
+    assert!(Dataset::from_csv("id,review\n1\n").is_err());
+
+    let dataset =
+        Dataset::from_jsonl("{\"id\": 1, \"review\": \"Slow\"}\n\n{\"id\": 2}\n").unwrap();
+    assert_eq!(dataset.columns, vec!["id", "review"]);
+    assert_eq!(dataset.rows[0]["id"], "1");
+    assert_eq!(dataset.rows[0]["review"], "Slow");
+    assert!(!dataset.rows[1].contains_key("review"));
+}
+
+#[test]
+fn missing_columns_fail_before_anything_is_sent() {
+    let dataset = Dataset::from_csv("id\n1\n").unwrap();
+    match runner("Review: {{review}}").question(&dataset.rows[0]) {
+        Err(AppError::UnexpectedError(msg)) => assert!(msg.contains("`review`")),
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn rows_are_answered_retried_and_written_with_usage() {
+    let server = MockServer::start();
+
+    let failing = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Review: Broken");
+        then.status(500).body("Internal Server Error");
+    });
+    let answering = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Review: ");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "positive" } } ] }"#);
+    });
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("reviews.csv");
+    let output = dir.path().join("labels.csv");
+    fs::write(&input, "id,review\n1,Great\n2,Broken\n3,Fine\n").unwrap();
+
+    let results = runner("Review: {{review}}")
+        .run(&ai_config(), &input, &output)
+        .await
+        .expect("Should succeed");
+
+    let rows: Vec<usize> = results.iter().map(|result| result.row).collect();
+    assert_eq!(rows, vec![0, 1, 2]);
+    assert_eq!(results[0].answer.as_deref(), Some("positive"));
+    assert_eq!(results[0].attempts, 1);
+    assert!(results[0].prompt_tokens > 0 && results[0].answer_tokens > 0);
+    assert_eq!(results[1].answer, None);
+    assert_eq!(results[1].attempts, 2);
+    assert!(results[1].error.as_deref().unwrap().contains("500"));
+    failing.assert_hits(2);
+    answering.assert_hits(2);
+
+    let written = fs::read_to_string(&output).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+    assert_eq!(
+        lines[0],
+        "id,review,answer,error,attempts,prompt_tokens,answer_tokens"
+    );
+    assert!(lines[1].starts_with("1,Great,positive,,1,"));
+    assert!(lines[2].starts_with("2,Broken,,"));
+
+    // JSONL output holds the same results
+    let output = dir.path().join("labels.jsonl");
+    runner("Review: {{review}}")
+        .run(&ai_config(), &input, &output)
+        .await
+        .expect("Should succeed");
+    let written: Vec<RowResult> = fs::read_to_string(&output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(written.len(), 3);
+    assert_eq!(written[2].input["review"], "Fine");
+    assert_eq!(written[2].answer.as_deref(), Some("positive"));
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
@@ -0,0 +1,154 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    hedge::Hedge,
+    stream::ask_question_stream,
+};
+use futures_util::StreamExt;
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::time::{Duration, Instant};
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn set_env(server: &MockServer) {
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+}
+
+fn remove_env() {
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+fn with_deadline(deadline_ms: u64) -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        deadline_ms: Some(deadline_ms),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn answers_within_the_deadline_succeed() {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "In time" } } ] }"#);
+    });
+    set_env(&server);
+
+    let answer = ask_question(&with_deadline(2000), question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "In time");
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn slow_answers_exceed_the_deadline() {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .delay(Duration::from_secs(2))
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Late" } } ] }"#);
+    });
+    set_env(&server);
+
+    let started = Instant::now();
+    match ask_question(&with_deadline(200), question()).await {
+        Err(AppError::DeadlineExceeded {
+            model_name,
+            deadline_ms,
+        }) => {
+            assert_eq!(model_name, "gpt-4o-mini");
+            assert_eq!(deadline_ms, 200);
+        }
+        other => panic!("Expected AppError::DeadlineExceeded, got {:?}", other),
+    }
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn the_deadline_covers_hedged_requests() {
+    let server = MockServer::start();
+
+    let slow = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .delay(Duration::from_millis(600))
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Late" } } ] }"#);
+    });
+    set_env(&server);
+
+    // The hedge is sent after 300ms, but neither request answers within the deadline
+    let ai_config = AiConfig {
+        hedge: Some(Hedge {
+            after_ms: 300,
+            to: None,
+        }),
+        ..with_deadline(500)
+    };
+    let started = Instant::now();
+    let result = ask_question(&ai_config, question()).await;
+    assert!(matches!(result, Err(AppError::DeadlineExceeded { .. })));
+    assert!(started.elapsed() < Duration::from_millis(600));
+    slow.assert_hits(2);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn streams_are_bounded_by_the_deadline() {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .delay(Duration::from_secs(2))
+            .header("content-type", "text/event-stream")
+            .body(concat!(
+                "data: {\"choices\":[{\"delta\":{\"content\":\"Late\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ));
+    });
+    set_env(&server);
+
+    let started = Instant::now();
+    let result = match ask_question_stream(&with_deadline(200), question()).await {
+        Ok(mut stream) => stream.next().await.expect("Should yield an item"),
+        Err(e) => Err(e),
+    };
+    assert!(matches!(result, Err(AppError::DeadlineExceeded { .. })));
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    remove_env();
+}
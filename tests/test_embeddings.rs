@@ -0,0 +1,116 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    embeddings::{get_embedding, get_embeddings},
+    error::AppError,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn openai_embeddings_keep_the_input_order() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/embeddings")
+            .header("authorization", "Bearer open_api_testkey")
+            .body_contains(r#""input":["first","second"]"#)
+            .body_contains(r#""model":"text-embedding-3-small""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "object": "list", "data": [
+                    { "object": "embedding", "index": 1, "embedding": [0.5, -0.5] },
+                    { "object": "embedding", "index": 0, "embedding": [0.25, 0.75] }
+                ] }"#,
+            );
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "text-embedding-3-small".to_string(),
+        ..Default::default()
+    };
+    let vectors = get_embeddings(&ai_config, &["first", "second"]).await;
+    env::remove_var("OPENAI_API_URL");
+
+    mock.assert();
+    assert_eq!(
+        vectors.expect("Should succeed"),
+        vec![vec![0.25, 0.75], vec![0.5, -0.5]]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_embeddings() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/embed")
+            .body_contains(r#""model":"nomic-embed-text""#)
+            .body_contains(r#""input":["closures"]"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "model": "nomic-embed-text", "embeddings": [[0.1, 0.2, 0.3]] }"#);
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "nomic-embed-text".to_string(),
+        ..Default::default()
+    };
+    let vector = get_embedding(&ai_config, "closures").await;
+    env::remove_var("OLLAMA_API_URL");
+
+    mock.assert();
+    assert_eq!(vector.expect("Should succeed"), vec![0.1, 0.2, 0.3]);
+}
+
+#[tokio::test]
+#[serial]
+async fn cohere_embeddings() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v2/embed")
+            .header("authorization", "Bearer cohere_testkey")
+            .body_contains(r#""texts":["closures"]"#)
+            .body_contains(r#""input_type":"search_document""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "id": "e1", "embeddings": { "float": [[1.0, 0.0]] }, "texts": ["closures"] }"#,
+            );
+    });
+    env::set_var("COHERE_API_KEY", "cohere_testkey");
+    env::set_var("COHERE_API_URL", server.url("/v2/embed"));
+
+    let ai_config = AiConfig {
+        llm: Framework::Custom("cohere".to_string()),
+        model: "embed-v4.0".to_string(),
+        ..Default::default()
+    };
+    let vector = get_embedding(&ai_config, "closures").await;
+    env::remove_var("COHERE_API_KEY");
+    env::remove_var("COHERE_API_URL");
+
+    mock.assert();
+    assert_eq!(vector.expect("Should succeed"), vec![1.0, 0.0]);
+}
+
+#[tokio::test]
+async fn providers_without_embeddings_are_a_model_error() {
+    let result = get_embedding(&AiConfig::default_for(Framework::Anthropic), "closures").await;
+
+    match result {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert_eq!(failure_str, "anthropic has no embeddings API")
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
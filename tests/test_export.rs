@@ -0,0 +1,90 @@
+use ask_ai::{
+    config::Question,
+    conversation::Conversation,
+    export::{to_anthropic_finetune_jsonl, to_openai_finetune_jsonl, to_openai_messages},
+};
+use serde_json::{json, Value};
+
+fn sample_conversations() -> Vec<Conversation> {
+    let mut with_system = Conversation::new(Some("You are concise.".to_string()));
+    with_system.push("What is Rust?", "A systems language.");
+    with_system.push("Is it fast?", "Yes.");
+
+    let mut without_system = Conversation::new(None);
+    without_system.push("Hi", "Hello!");
+
+    // Nothing answered yet: not a usable training example
+    let mut unanswered = Conversation::new(None);
+    unanswered.push("Pending?", "");
+
+    vec![with_system, without_system, unanswered]
+}
+
+fn parse_lines(jsonl: &str) -> Vec<Value> {
+    jsonl
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Each line should be JSON"))
+        .collect()
+}
+
+#[test]
+fn openai_finetune_jsonl_export() {
+    let jsonl = to_openai_finetune_jsonl(&sample_conversations());
+    let lines = parse_lines(&jsonl);
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0],
+        json!({"messages": [
+            {"role": "system", "content": "You are concise."},
+            {"role": "user", "content": "What is Rust?"},
+            {"role": "assistant", "content": "A systems language."},
+            {"role": "user", "content": "Is it fast?"},
+            {"role": "assistant", "content": "Yes."}
+        ]})
+    );
+    assert_eq!(
+        lines[1],
+        json!({"messages": [
+            {"role": "user", "content": "Hi"},
+            {"role": "assistant", "content": "Hello!"}
+        ]})
+    );
+}
+
+#[test]
+fn anthropic_finetune_jsonl_export() {
+    let jsonl = to_anthropic_finetune_jsonl(&sample_conversations());
+    let lines = parse_lines(&jsonl);
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["system"], "You are concise.");
+    assert_eq!(lines[0]["messages"].as_array().unwrap().len(), 4);
+    assert_eq!(lines[0]["messages"][0]["role"], "user");
+    assert!(lines[1].get("system").is_none());
+}
+
+#[test]
+fn question_exports_as_openai_messages() {
+    let mut conversation = Conversation::new(None);
+    conversation.push("What is Rust?", "A systems language.");
+    let question = conversation.question("Is it fast?");
+
+    assert_eq!(
+        to_openai_messages(&question),
+        json!([
+            { "role": "user", "content": "What is Rust?" },
+            { "role": "assistant", "content": "A systems language." },
+            { "role": "user", "content": "Is it fast?" }
+        ])
+    );
+
+    let question = Question {
+        system_prompt: Some("You are concise.".to_string()),
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    assert_eq!(to_openai_messages(&question)[0]["role"], "system");
+}
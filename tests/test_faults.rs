@@ -0,0 +1,150 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    faults::{FaultInjection, Latency},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::time::{Duration, Instant};
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn openai(server: &MockServer, faults: FaultInjection) -> AiConfig {
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        faults: Some(faults),
+        ..Default::default()
+    }
+}
+
+fn ok_mock(server: &MockServer) -> httpmock::Mock<'_> {
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hello" } } ] }"#);
+    })
+}
+
+#[tokio::test]
+#[serial]
+async fn injected_faults_fail_before_sending() {
+    let server = MockServer::start();
+    let mock = ok_mock(&server);
+
+    let rate_limited = openai(
+        &server,
+        FaultInjection {
+            rate_limit_rate: 1.0,
+            ..Default::default()
+        },
+    );
+    match ask_question(&rate_limited, question()).await {
+        Err(AppError::ApiError { failure_str, .. }) => {
+            assert!(failure_str.starts_with("Status 429 Too Many Requests"))
+        }
+        other => panic!("Expected AppError::ApiError, got {:?}", other),
+    }
+
+    let timing_out = openai(
+        &server,
+        FaultInjection {
+            timeout_rate: 1.0,
+            timeout_after_ms: 50,
+            ..Default::default()
+        },
+    );
+    let started = Instant::now();
+    match ask_question(&timing_out, question()).await {
+        Err(AppError::ApiError { failure_str, .. }) => assert!(failure_str.contains("timed out")),
+        other => panic!("Expected AppError::ApiError, got {:?}", other),
+    }
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    mock.assert_hits(0);
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[tokio::test]
+#[serial]
+async fn seeded_faults_are_reproducible() {
+    let server = MockServer::start();
+    let _mock = ok_mock(&server);
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let faults = FaultInjection {
+            server_error_rate: 0.5,
+            seed: Some(42),
+            ..Default::default()
+        };
+        let ai_config = openai(&server, faults);
+        let mut outcomes = Vec::new();
+        for _ in 0..20 {
+            outcomes.push(ask_question(&ai_config, question()).await.is_ok());
+        }
+        runs.push(outcomes);
+    }
+
+    assert_eq!(runs[0], runs[1]);
+    assert!(runs[0].contains(&true) && runs[0].contains(&false));
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[tokio::test]
+#[serial]
+async fn latency_is_added_to_requests() {
+    let server = MockServer::start();
+    let mock = ok_mock(&server);
+
+    let ai_config = openai(
+        &server,
+        FaultInjection {
+            latency: Some(Latency::Fixed { ms: 100 }),
+            ..Default::default()
+        },
+    );
+    let started = Instant::now();
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Hello");
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    mock.assert();
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[test]
+fn latency_serializes_with_its_distribution() {
+    let latency = Latency::Normal {
+        mean_ms: 300.0,
+        std_dev_ms: 50.0,
+    };
+    let json = serde_json::to_value(latency).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "distribution": "normal", "mean_ms": 300.0, "std_dev_ms": 50.0 })
+    );
+    assert_eq!(serde_json::from_value::<Latency>(json).unwrap(), latency);
+}
@@ -0,0 +1,114 @@
+use ask_ai::{
+    ask_ai::{ask_question, build_ollama_payload, build_openai_payload},
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    grammar::OutputConstraint,
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Is Rust memory safe?".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn constrained(llm: Framework, model: &str, constraint: OutputConstraint) -> AiConfig {
+    AiConfig {
+        llm,
+        model: model.to_string(),
+        constraint: Some(constraint),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn openai_compatible_servers_get_llama_cpp_and_vllm_fields() {
+    let grammar = r#"root ::= "yes" | "no""#;
+    let ai_config = constrained(
+        Framework::OpenAI,
+        "qwen2.5-7b",
+        OutputConstraint::Gbnf(grammar.to_string()),
+    );
+    let payload = build_openai_payload(&question(), &ai_config);
+    assert_eq!(payload["grammar"], grammar);
+    assert_eq!(payload["guided_grammar"], grammar);
+
+    let ai_config = constrained(
+        Framework::OpenAI,
+        "qwen2.5-7b",
+        OutputConstraint::Regex("(yes|no)".to_string()),
+    );
+    let payload = build_openai_payload(&question(), &ai_config);
+    assert_eq!(payload["guided_regex"], "(yes|no)");
+    assert!(payload.get("grammar").is_none());
+}
+
+#[test]
+fn ollama_gets_json_schemas_as_format() {
+    let schema = json!({
+        "type": "object",
+        "properties": { "safe": { "type": "boolean" } },
+        "required": ["safe"]
+    });
+    let ai_config = constrained(
+        Framework::Ollama,
+        "llama3.2",
+        OutputConstraint::JsonSchema(schema.clone()),
+    );
+    assert_eq!(
+        build_ollama_payload(&question(), &ai_config)["format"],
+        schema
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn unenforceable_constraints_are_rejected_before_sending() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "Maybe" } ] }"#);
+    });
+    env::set_var("ANTHROPIC_API_KEY", "test_key");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let configs = [
+        constrained(
+            Framework::Anthropic,
+            "claude-sonnet-4-5",
+            OutputConstraint::Regex("(yes|no)".to_string()),
+        ),
+        constrained(
+            Framework::Ollama,
+            "llama3.2",
+            OutputConstraint::Gbnf(r#"root ::= "yes" | "no""#.to_string()),
+        ),
+    ];
+    for ai_config in configs {
+        match ask_question(&ai_config, question()).await {
+            Err(AppError::ModelError { failure_str, .. }) => {
+                assert!(failure_str.contains("cannot enforce"));
+            }
+            other => panic!("Expected AppError::ModelError, got {:?}", other),
+        }
+    }
+    mock.assert_hits(0);
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+    env::remove_var("OLLAMA_API_URL");
+}
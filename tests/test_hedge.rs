@@ -0,0 +1,207 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    config::{AiConfig, Framework, Question},
+    error::Result,
+    hedge::Hedge,
+    provider::{register_provider, BoxFuture, Completion, Provider},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn set_env(server: &MockServer) {
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+    env::set_var("ANTHROPIC_API_KEY", "test_key");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+}
+
+fn remove_env() {
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+    env::remove_var("ANTHROPIC_API_KEY");
+}
+
+fn hedged(after_ms: u64) -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        hedge: Some(Hedge {
+            after_ms,
+            to: Some(Box::new(AiConfig {
+                llm: Framework::Anthropic,
+                model: "claude-sonnet-4-5".to_string(),
+                ..Default::default()
+            })),
+        }),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn slow_answers_are_hedged() {
+    let server = MockServer::start();
+
+    let slow = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .delay(Duration::from_secs(2))
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Slow" } } ] }"#);
+    });
+    let fast = server.mock(|when, then| {
+        when.method(POST).path("/v1/messages");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "Fast" } ] }"#);
+    });
+    set_env(&server);
+
+    let started = Instant::now();
+    let answer = ask_question(&hedged(100), question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Fast");
+    assert!(started.elapsed() < Duration::from_secs(2));
+    slow.assert_hits(1);
+    fast.assert_hits(1);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn fast_answers_are_not_hedged() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "First" } } ] }"#);
+    });
+    let hedge = server.mock(|when, then| {
+        when.method(POST).path("/v1/messages");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "Hedge" } ] }"#);
+    });
+    set_env(&server);
+
+    let answer = ask_question(&hedged(1000), question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "First");
+    first.assert_hits(1);
+    hedge.assert_hits(0);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn a_failed_hedge_waits_for_the_first_request() {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .delay(Duration::from_millis(300))
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "First" } } ] }"#);
+    });
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/messages");
+        then.status(500).body("Internal Server Error");
+    });
+    set_env(&server);
+
+    let answer = ask_question(&hedged(50), question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "First");
+
+    remove_env();
+}
+
+/// Answers at once, recording the local-only mode it was asked under.
+struct LocalOnlyRecorder {
+    local_only: Arc<Mutex<Option<bool>>>,
+}
+
+impl Provider for LocalOnlyRecorder {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        _question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            *self.local_only.lock().unwrap() = Some(ai_config.local_only);
+            Ok(Completion {
+                answer: "From the hedge".to_string(),
+                truncated: false,
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn hedges_inherit_local_only() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST).path("/api/chat");
+        then.status(200)
+            .delay(Duration::from_millis(500))
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llama3","message":{"role":"assistant","content":"Local"},"done":true}"#);
+    });
+    let local_only = Arc::new(Mutex::new(None));
+    register_provider(
+        "hedge-recorder",
+        LocalOnlyRecorder {
+            local_only: local_only.clone(),
+        },
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        base_url: Some(server.base_url()),
+        local_only: true,
+        hedge: Some(Hedge {
+            after_ms: 20,
+            to: Some(Box::new(AiConfig {
+                llm: "hedge-recorder".parse().unwrap(),
+                model: "recorder".to_string(),
+                ..Default::default()
+            })),
+        }),
+        ..Default::default()
+    };
+
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "From the hedge");
+    assert_eq!(*local_only.lock().unwrap(), Some(true));
+}
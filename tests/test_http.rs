@@ -0,0 +1,137 @@
+use ask_ai::{
+    ask_question,
+    config::{AiConfig, Framework, Question},
+    http::{HttpOptions, HttpVersion},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn tuned_client_reuses_its_pool() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Pooled" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        http: Some(HttpOptions {
+            pool_max_idle_per_host: Some(4),
+            pool_idle_timeout_secs: Some(30),
+            http_version: HttpVersion::Http1,
+            tcp_keepalive_secs: Some(15),
+            unix_socket: None,
+        }),
+        ..Default::default()
+    };
+
+    for _ in 0..3 {
+        let question = Question {
+            system_prompt: None,
+            messages: None,
+            new_prompt: "Hi".to_string(),
+            images: vec![],
+            audio: vec![],
+        };
+        let answer = ask_question(&ai_config, question)
+            .await
+            .expect("Should succeed");
+        assert_eq!(answer, "Pooled");
+    }
+    mock.assert_hits(3);
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[test]
+fn http_options_from_config_file() {
+    let options: HttpOptions =
+        serde_json::from_str(r#"{ "http_version": "http2", "tcp_keepalive_secs": 30 }"#).unwrap();
+
+    assert_eq!(
+        options,
+        HttpOptions {
+            http_version: HttpVersion::Http2,
+            tcp_keepalive_secs: Some(30),
+            ..Default::default()
+        }
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+#[serial]
+async fn requests_go_through_a_unix_socket() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixListener;
+
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("ollama.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        // Headers plus the small JSON body arrive well within a few reads
+        while !String::from_utf8_lossy(&request).contains("\"messages\"") {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+
+        let body = r#"{"model":"llama3","message":{"role":"assistant","content":"Over the socket"},"done":true}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).into_owned()
+    });
+
+    // Nothing listens on this port, only the socket can answer
+    env::set_var("OLLAMA_API_URL", "http://localhost:1");
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        local_only: true,
+        http: Some(HttpOptions {
+            unix_socket: Some(socket),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hello socket".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Over the socket");
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("POST /api/chat HTTP/1.1"));
+    assert!(request.contains("Hello socket"));
+
+    env::remove_var("OLLAMA_API_URL");
+}
@@ -0,0 +1,98 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    error::AppError,
+    images::{generate_image, GeneratedImage, ImageOptions},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn generated_images_are_decoded() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/images/generations")
+            .header("authorization", "Bearer open_api_testkey")
+            .body_contains(r#""model":"gpt-image-1""#)
+            .body_contains(r#""size":"1024x1024""#)
+            .matches(|req| {
+                !String::from_utf8_lossy(req.body.as_deref().unwrap_or_default())
+                    .contains("response_format")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "created": 1, "data": [ { "b64_json": "iVBORw==" } ] }"#);
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-image-1".to_string(),
+        ..Default::default()
+    };
+    let options = ImageOptions {
+        size: Some("1024x1024".to_string()),
+        ..Default::default()
+    };
+    let images = generate_image(&ai_config, "A crab made of rust", &options).await;
+    env::remove_var("OPENAI_API_URL");
+
+    mock.assert();
+    assert_eq!(
+        images.expect("Should succeed"),
+        vec![GeneratedImage::Bytes(vec![0x89, b'P', b'N', b'G'])]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn dall_e_can_return_urls() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/images/generations")
+            .body_contains(r#""response_format":"url""#)
+            .body_contains(r#""n":2"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "created": 1, "data": [
+                    { "url": "https://images.example.com/1.png" },
+                    { "url": "https://images.example.com/2.png" }
+                ] }"#,
+            );
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "dall-e-2".to_string(),
+        ..Default::default()
+    };
+    let options = ImageOptions {
+        n: Some(2),
+        urls: true,
+        ..Default::default()
+    };
+    let images = generate_image(&ai_config, "A crab made of rust", &options).await;
+    env::remove_var("OPENAI_API_URL");
+
+    mock.assert();
+    assert_eq!(
+        images.expect("Should succeed"),
+        vec![
+            GeneratedImage::Url("https://images.example.com/1.png".to_string()),
+            GeneratedImage::Url("https://images.example.com/2.png".to_string())
+        ]
+    );
+
+    let anthropic = AiConfig::default_for(Framework::Anthropic);
+    assert!(matches!(
+        generate_image(&anthropic, "A crab", &ImageOptions::default()).await,
+        Err(AppError::ModelError { .. })
+    ));
+}
@@ -0,0 +1,124 @@
+use ask_ai::{
+    config::AiPrompt,
+    error::AppError,
+    export::to_openai_messages,
+    import::{from_chatgpt_export, from_claude_export, from_openai_messages},
+};
+
+#[test]
+fn chatgpt_export_follows_current_branch() {
+    // "b2" is an abandoned regeneration; "a2" is the branch shown in the UI
+    let export = r#"[{
+        "title": "Rust chat",
+        "current_node": "u2",
+        "mapping": {
+            "root": { "id": "root", "message": null, "parent": null, "children": ["sys"] },
+            "sys": { "id": "sys", "parent": "root", "children": ["u1"], "message": {
+                "author": { "role": "system" },
+                "content": { "content_type": "text", "parts": [""] } } },
+            "u1": { "id": "u1", "parent": "sys", "children": ["a1", "b1"], "message": {
+                "author": { "role": "user" },
+                "content": { "content_type": "text", "parts": ["What is Rust?"] } } },
+            "b1": { "id": "b1", "parent": "u1", "children": [], "message": {
+                "author": { "role": "assistant" },
+                "content": { "content_type": "text", "parts": ["Discarded answer"] } } },
+            "a1": { "id": "a1", "parent": "u1", "children": ["u2"], "message": {
+                "author": { "role": "assistant" },
+                "content": { "content_type": "text", "parts": ["A systems language."] } } },
+            "u2": { "id": "u2", "parent": "a1", "children": [], "message": {
+                "author": { "role": "user" },
+                "content": { "content_type": "text", "parts": ["Is it fast?"] } } }
+        }
+    }]"#;
+
+    let conversations = from_chatgpt_export(export).expect("Should parse");
+    assert_eq!(conversations.len(), 1);
+    assert_eq!(conversations[0].system_prompt, None);
+    assert_eq!(
+        conversations[0].messages,
+        vec![
+            AiPrompt {
+                content: "What is Rust?".to_string(),
+                output: "A systems language.".to_string(),
+            },
+            AiPrompt {
+                content: "Is it fast?".to_string(),
+                output: String::new(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn claude_export_pairs_messages() {
+    let export = r#"[{
+        "uuid": "c1",
+        "name": "Greeting",
+        "chat_messages": [
+            { "sender": "human", "text": "Hi Claude" },
+            { "sender": "assistant", "text": "",
+              "content": [ { "type": "text", "text": "Hello!" } ] },
+            { "sender": "human", "text": "Bye" },
+            { "sender": "assistant", "text": "Goodbye." }
+        ]
+    }]"#;
+
+    let conversations = from_claude_export(export).expect("Should parse");
+    assert_eq!(conversations.len(), 1);
+    assert_eq!(conversations[0].messages.len(), 2);
+    assert_eq!(conversations[0].messages[0].output, "Hello!");
+    assert_eq!(conversations[0].messages[1].content, "Bye");
+
+    let question = conversations[0].question("One more thing");
+    assert_eq!(question.messages.map(|m| m.len()), Some(2));
+
+    match from_claude_export(r#"{"uuid": "no-messages"}"#) {
+        Err(AppError::UnexpectedError(msg)) => assert!(msg.contains("chat_messages")),
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    }
+}
+
+#[test]
+fn openai_messages_become_a_question() {
+    let messages = r#"{ "model": "gpt-4o", "messages": [
+        { "role": "developer", "content": "You are concise." },
+        { "role": "user", "content": "What is Rust?" },
+        { "role": "assistant", "content": null, "tool_calls": [] },
+        { "role": "tool", "tool_call_id": "1", "content": "search results" },
+        { "role": "assistant", "content": [{ "type": "text", "text": "A systems language." }] },
+        { "role": "user", "content": [{ "type": "text", "text": "Is it fast?" },
+                                      { "type": "image_url", "image_url": { "url": "x" } }] }
+    ] }"#;
+
+    let question = from_openai_messages(messages).unwrap();
+    assert_eq!(question.system_prompt.as_deref(), Some("You are concise."));
+    assert_eq!(
+        question.messages,
+        Some(vec![AiPrompt {
+            content: "What is Rust?".to_string(),
+            output: "A systems language.".to_string(),
+        }])
+    );
+    assert_eq!(question.new_prompt, "Is it fast?");
+
+    // Writing it back and reading it again gives the same question
+    let exported = to_openai_messages(&question).to_string();
+    let reimported = from_openai_messages(&exported).unwrap();
+    assert_eq!(reimported.system_prompt, question.system_prompt);
+    assert_eq!(reimported.messages, question.messages);
+    assert_eq!(reimported.new_prompt, question.new_prompt);
+}
+
+#[test]
+fn openai_messages_must_end_with_the_user() {
+    let answered = r#"[
+        { "role": "user", "content": "Hi" },
+        { "role": "assistant", "content": "Hello!" }
+    ]"#;
+    for json in [answered, "[]", r#"{ "model": "gpt-4o" }"#, "not json"] {
+        assert!(matches!(
+            from_openai_messages(json),
+            Err(AppError::UnexpectedError(_))
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+#![cfg(any(feature = "async-openai", feature = "genai"))]
+
+use ask_ai::config::{AiPrompt, Question};
+
+fn question() -> Question {
+    Question {
+        system_prompt: Some("You are concise.".to_string()),
+        messages: Some(vec![AiPrompt {
+            content: "What is Rust?".to_string(),
+            output: "A systems language.".to_string(),
+        }]),
+        new_prompt: "Is it fast?".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[cfg(feature = "async-openai")]
+#[test]
+fn async_openai_requests_round_trip() {
+    use ask_ai::{
+        config::AiConfig,
+        interop::{from_async_openai_request, to_async_openai_request},
+        params::GenerationParams,
+    };
+
+    let ai_config = AiConfig {
+        model: "gpt-4o-mini".to_string(),
+        max_token: Some(256),
+        seed: Some(7),
+        params: Some(GenerationParams {
+            temperature: Some(0.5),
+            stop: vec!["END".to_string()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let request = to_async_openai_request(&ai_config, &question());
+    assert_eq!(request.messages.len(), 4);
+    assert_eq!(
+        serde_json::to_value(&request.messages).unwrap()[3],
+        serde_json::json!({ "role": "user", "content": "Is it fast?" })
+    );
+
+    let (config, question) = from_async_openai_request(&request).unwrap();
+    assert_eq!(config.model, "gpt-4o-mini");
+    assert_eq!(config.max_token, Some(256));
+    assert_eq!(config.seed, Some(7));
+    assert_eq!(config.params, ai_config.params);
+    assert_eq!(question.system_prompt.as_deref(), Some("You are concise."));
+    assert_eq!(question.messages, self::question().messages);
+    assert_eq!(question.new_prompt, "Is it fast?");
+}
+
+#[cfg(feature = "genai")]
+#[test]
+fn genai_requests_round_trip() {
+    use ask_ai::{config::AiConfig, error::AppError, params::GenerationParams};
+    use genai::chat::{ChatMessage, ChatOptions, ChatRequest};
+
+    let request = ChatRequest::from(&question());
+    assert_eq!(request.system.as_deref(), Some("You are concise."));
+    assert_eq!(request.messages.len(), 3);
+
+    let question = Question::try_from(&request).unwrap();
+    assert_eq!(question.system_prompt.as_deref(), Some("You are concise."));
+    assert_eq!(question.messages, self::question().messages);
+    assert_eq!(question.new_prompt, "Is it fast?");
+
+    let answered = request.append_message(ChatMessage::assistant("Very."));
+    assert!(matches!(
+        Question::try_from(&answered),
+        Err(AppError::UnexpectedError(_))
+    ));
+
+    let options = ChatOptions::from(&AiConfig {
+        max_token: Some(256),
+        params: Some(GenerationParams {
+            temperature: Some(0.5),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    assert_eq!(options.max_tokens, Some(256));
+    assert_eq!(options.temperature, Some(0.5));
+}
@@ -0,0 +1,120 @@
+use ask_ai::{
+    ask_question,
+    config::{AiConfig, AiPrompt, Framework, Question},
+    error::AppError,
+    limits::{LimitPolicy, PayloadLimits, PayloadMeasure},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn long_history() -> Question {
+    Question {
+        system_prompt: None,
+        messages: Some(vec![
+            AiPrompt {
+                content: "oldest ".repeat(200),
+                output: "reply".to_string(),
+            },
+            AiPrompt {
+                content: "recent question".to_string(),
+                output: "recent answer".to_string(),
+            },
+        ]),
+        new_prompt: "And now?".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn oversized_questions_fail_before_sending() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/api/chat");
+        then.status(400);
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        limits: Some(PayloadLimits {
+            max_prompt_tokens: Some(100),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let result = ask_question(&ai_config, long_history()).await;
+    match result {
+        Err(AppError::PayloadTooLarge {
+            measure: PayloadMeasure::Tokens,
+            size,
+            limit: 100,
+        }) => assert!(size > 100),
+        other => panic!("Expected PayloadTooLarge, got {:?}", other),
+    }
+    mock.assert_hits(0);
+
+    env::remove_var("OLLAMA_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn truncation_drops_the_oldest_history() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("recent question")
+            .matches(|req| {
+                !String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains("oldest")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llama3","message":{"role":"assistant","content":"Trimmed"},"done":true}"#);
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        limits: Some(PayloadLimits {
+            max_payload_bytes: Some(1024),
+            on_exceed: LimitPolicy::Truncate,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let answer = ask_question(&ai_config, long_history())
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Trimmed");
+
+    // Without history left to drop, truncation still fails
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "x".repeat(2048),
+        images: vec![],
+        audio: vec![],
+    };
+    let result = ask_question(&ai_config, question).await;
+    assert!(matches!(
+        result,
+        Err(AppError::PayloadTooLarge {
+            measure: PayloadMeasure::Bytes,
+            ..
+        })
+    ));
+
+    env::remove_var("OLLAMA_API_URL");
+}
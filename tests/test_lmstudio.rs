@@ -0,0 +1,92 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    lmstudio::loaded_models,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn questions_go_to_the_first_loaded_model() {
+    let server = MockServer::start();
+    let models = server.mock(|when, then| {
+        when.method(GET).path("/v1/models");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "object": "list", "data": [
+                    { "id": "qwen2.5-7b-instruct", "object": "model" },
+                    { "id": "text-embedding-nomic-embed-text-v1.5", "object": "model" }
+                ] }"#,
+            );
+    });
+    let chat = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""model":"qwen2.5-7b-instruct""#)
+            .matches(|req| {
+                !req.headers
+                    .iter()
+                    .flatten()
+                    .any(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hello from LM Studio" } } ] }"#);
+    });
+    env::set_var("LMSTUDIO_API_URL", server.url("/v1"));
+
+    let ai_config = AiConfig {
+        local_only: true,
+        ..AiConfig::default_for(Framework::LmStudio)
+    };
+    assert_eq!(
+        loaded_models(&ai_config).await.unwrap(),
+        vec![
+            "qwen2.5-7b-instruct",
+            "text-embedding-nomic-embed-text-v1.5"
+        ]
+    );
+    let answer = ask_question(&ai_config, question()).await;
+    env::remove_var("LMSTUDIO_API_URL");
+
+    models.assert_hits(2);
+    chat.assert();
+    assert_eq!(answer.expect("Should succeed"), "Hello from LM Studio");
+}
+
+#[tokio::test]
+#[serial]
+async fn an_idle_server_is_a_model_error() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/models");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "object": "list", "data": [] }"#);
+    });
+    env::set_var("LMSTUDIO_API_URL", server.url("/v1"));
+
+    let result = ask_question(&AiConfig::default_for(Framework::LmStudio), question()).await;
+    env::remove_var("LMSTUDIO_API_URL");
+
+    match result {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert!(failure_str.contains("no model loaded"))
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
@@ -0,0 +1,98 @@
+use ask_ai::{
+    ask_question,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Summarize the incident report.".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn local_only_blocks_remote_providers() {
+    env::remove_var("ANTHROPIC_API_URL");
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-3-5-haiku-latest".to_string(),
+        api_key: Some("anthropic_testkey".into()),
+        local_only: true,
+        ..Default::default()
+    };
+
+    match ask_question(&ai_config, question()).await {
+        Err(AppError::RemoteEndpointBlocked {
+            model_name,
+            endpoint,
+        }) => {
+            assert_eq!(model_name, "anthropic");
+            assert_eq!(endpoint, "https://api.anthropic.com/v1/messages");
+        }
+        other => panic!("Expected AppError::RemoteEndpointBlocked, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn local_only_allows_local_endpoints() {
+    let server = MockServer::start();
+
+    let ollama = server.mock(|when, then| {
+        when.method(POST).path("/api/chat");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llama3","created_at":"2024-01-01T00:00:00Z","message":{"role":"assistant","content":"All local"},"done":true}"#);
+    });
+    let openai = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Local proxy" } } ] }"#);
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let mut ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        local_only: true,
+        ..Default::default()
+    };
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "All local");
+    ollama.assert();
+
+    // An OpenAI-compatible server on this machine is local too
+    ai_config.llm = Framework::OpenAI;
+    ai_config.api_key = Some("open_api_testkey".into());
+    let answer = ask_question(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Local proxy");
+    openai.assert();
+
+    env::set_var("OLLAMA_API_URL", "http://gpu-box.example.com:11434");
+    ai_config.llm = Framework::Ollama;
+    assert!(matches!(
+        ask_question(&ai_config, question()).await,
+        Err(AppError::RemoteEndpointBlocked { .. })
+    ));
+
+    env::remove_var("OLLAMA_API_URL");
+    env::remove_var("OPENAI_API_URL");
+}
@@ -0,0 +1,60 @@
+use ask_ai::{
+    ask_question,
+    config::{AiConfig, Framework, Question},
+    locale::{detect_language, LocaleConfig},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const SPANISH: &str =
+    "¿Cuál es la capital de Francia y cuántos habitantes tiene la ciudad hoy en día?";
+
+#[test]
+fn detects_reliable_languages_only() {
+    let language = detect_language(SPANISH).expect("Should detect Spanish");
+    assert_eq!((language.code, language.name), ("spa", "Spanish"));
+    assert_eq!(detect_language("ok"), None);
+}
+
+#[tokio::test]
+#[serial]
+async fn locale_defaults_are_added_to_the_system_prompt() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("Eres un asistente conciso.\\n\\nRespond in Spanish.");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llama3","message":{"role":"assistant","content":"París"},"done":true}"#);
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        locale: Some(LocaleConfig {
+            match_language: true,
+            system_prompts: [("spa".to_string(), "Eres un asistente conciso.".to_string())].into(),
+        }),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: SPANISH.to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "París");
+
+    env::remove_var("OLLAMA_API_URL");
+}
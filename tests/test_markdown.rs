@@ -0,0 +1,91 @@
+use ask_ai::markdown::{
+    extract_code_blocks, extract_lists, extract_tables, CodeBlock, List, Table,
+};
+
+const ANSWER: &str = r#"Here is the plan:
+
+1. Install the crate
+2. Write the config
+   with your API key
+   - nested detail
+
+Notes:
+- fast
+* small
+
+| Provider | Local |
+|:---------|:-----:|
+| Ollama   | yes   |
+| OpenAI   |
+
+```rust title="main.rs"
+fn main() {
+    // - not a list
+}
+```
+
+~~~
+| not | a table |
+|-----|---------|
+~~~
+
+````markdown
+```inner```
+````
+"#;
+
+#[test]
+fn code_blocks_keep_their_language() {
+    assert_eq!(
+        extract_code_blocks(ANSWER),
+        vec![
+            CodeBlock {
+                language: Some("rust".to_string()),
+                code: "fn main() {\n    // - not a list\n}".to_string(),
+            },
+            CodeBlock {
+                language: None,
+                code: "| not | a table |\n|-----|---------|".to_string(),
+            },
+            CodeBlock {
+                language: Some("markdown".to_string()),
+                code: "```inner```".to_string(),
+            },
+        ]
+    );
+
+    let truncated = extract_code_blocks("```python\nprint('hi')");
+    assert_eq!(truncated[0].code, "print('hi')");
+}
+
+#[test]
+fn lists_and_tables_outside_code() {
+    assert_eq!(
+        extract_lists(ANSWER),
+        vec![
+            List {
+                ordered: true,
+                items: vec![
+                    "Install the crate".to_string(),
+                    "Write the config with your API key".to_string(),
+                    "nested detail".to_string(),
+                ],
+            },
+            List {
+                ordered: false,
+                items: vec!["fast".to_string(), "small".to_string()],
+            },
+        ]
+    );
+
+    assert_eq!(
+        extract_tables(ANSWER),
+        vec![Table {
+            headers: vec!["Provider".to_string(), "Local".to_string()],
+            rows: vec![
+                vec!["Ollama".to_string(), "yes".to_string()],
+                vec!["OpenAI".to_string(), String::new()],
+            ],
+        }]
+    );
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "mcp")]
+
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    mcp::McpClient,
+    tools::{run_with_tools, ToolRegistry},
+};
+use httpmock::prelude::*;
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::env;
+use tokio::io::{duplex, AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+
+/// A filesystem-like MCP server answering on `stream` until the client hangs up. Its tools
+/// are listed over two pages, and it pings the client before answering a tool call.
+async fn serve(stream: DuplexStream) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let request: Value = serde_json::from_str(&line).unwrap();
+        let result = match request["method"].as_str().unwrap() {
+            "initialize" => json!({
+                "protocolVersion": request["params"]["protocolVersion"],
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "files", "version": "1.0.0" }
+            }),
+            "tools/list" if request["params"]["cursor"].is_null() => json!({
+                "tools": [{
+                    "name": "read_file",
+                    "description": "Read a file",
+                    "inputSchema": { "type": "object", "properties": { "path": { "type": "string" } } }
+                }],
+                "nextCursor": "page-2"
+            }),
+            "tools/list" => json!({
+                "tools": [{
+                    "name": "delete_file",
+                    "description": "Delete a file",
+                    "inputSchema": { "type": "object" }
+                }]
+            }),
+            "tools/call" => {
+                let ping = json!({ "jsonrpc": "2.0", "id": "ping-1", "method": "ping" });
+                writer
+                    .write_all(format!("{}\n", ping).as_bytes())
+                    .await
+                    .unwrap();
+                let pong: Value =
+                    serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+                assert_eq!(pong["id"], "ping-1");
+
+                match request["params"]["name"].as_str().unwrap() {
+                    "read_file" => json!({
+                        "content": [{ "type": "text", "text": format!(
+                            "contents of {}", request["params"]["arguments"]["path"].as_str().unwrap()
+                        ) }]
+                    }),
+                    _ => json!({
+                        "content": [{ "type": "text", "text": "permission denied" }],
+                        "isError": true
+                    }),
+                }
+            }
+            _ => continue,
+        };
+        let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+        writer
+            .write_all(format!("{}\n", response).as_bytes())
+            .await
+            .unwrap();
+    }
+}
+
+async fn connect() -> McpClient {
+    let (client_end, server_end) = duplex(64 * 1024);
+    tokio::spawn(serve(server_end));
+    let (reader, writer) = tokio::io::split(client_end);
+    McpClient::connect(reader, writer)
+        .await
+        .expect("Should initialize")
+}
+
+fn has_tool_result(req: &HttpMockRequest) -> bool {
+    let body = req.body.clone().unwrap_or_default();
+    String::from_utf8_lossy(&body).contains(r#""tool_call_id":"call_1""#)
+}
+
+#[tokio::test]
+async fn tools_are_listed_and_called() {
+    let client = connect().await;
+    assert_eq!(client.server_name(), "files");
+
+    let tools = client.list_tools().await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(names, vec!["read_file", "delete_file"]);
+    assert_eq!(
+        tools[0].input_schema["properties"]["path"]["type"],
+        "string"
+    );
+
+    let output = client
+        .call_tool("read_file", json!({ "path": "notes.txt" }))
+        .await
+        .unwrap();
+    assert_eq!(output, "contents of notes.txt");
+
+    match client.call_tool("delete_file", json!({})).await {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert_eq!(failure_str, "permission denied")
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn mcp_tools_join_the_tool_loop() {
+    let server = MockServer::start();
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""name":"read_file""#)
+            .body_contains(r#""name":"delete_file""#)
+            .matches(|req| !has_tool_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "choices": [ { "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [
+                        { "id": "call_1", "type": "function",
+                          "function": { "name": "read_file", "arguments": "{\"path\":\"todo.md\"}" } }
+                    ]
+                } } ] }"#,
+            );
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("contents of todo.md")
+            .matches(has_tool_result);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Your list is short." } } ] }"#);
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let registry = ToolRegistry::new()
+        .with_mcp(&connect().await)
+        .await
+        .unwrap();
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        max_token: None,
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What's on my todo list?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    let answer = run_with_tools(&ai_config, question, &registry).await;
+    env::remove_var("OPENAI_API_URL");
+
+    first.assert();
+    second.assert();
+    assert_eq!(answer.expect("Should succeed"), "Your list is short.");
+}
@@ -0,0 +1,138 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    moderation::{moderate, MODERATION_MODEL},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const FLAGGED: &str = r#"{ "id": "modr-1", "model": "omni-moderation-latest", "results": [ {
+    "flagged": true,
+    "categories": { "harassment": true, "violence": true, "self-harm": false },
+    "category_scores": { "harassment": 0.91, "violence": 0.62, "self-harm": 0.01 }
+} ] }"#;
+
+fn question(prompt: &str) -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: prompt.to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn moderation_results_are_parsed() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/moderations")
+            .header("authorization", "Bearer open_api_testkey")
+            .body_contains(r#""model":"omni-moderation-latest""#)
+            .body_contains(r#""input":"You are worthless""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(FLAGGED);
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: MODERATION_MODEL.to_string(),
+        ..Default::default()
+    };
+    let result = moderate(&ai_config, "You are worthless").await;
+    env::remove_var("OPENAI_API_URL");
+
+    mock.assert();
+    let result = result.expect("Should succeed");
+    assert!(result.flagged);
+    assert_eq!(result.flagged_categories(), vec!["harassment", "violence"]);
+    assert_eq!(result.category_scores["harassment"], 0.91);
+}
+
+#[tokio::test]
+#[serial]
+async fn flagged_prompts_are_not_sent() {
+    let server = MockServer::start();
+    let moderation = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/moderations")
+            .header("authorization", "Bearer open_api_testkey");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(FLAGGED);
+    });
+    let anthropic = server.mock(|when, then| {
+        when.method(POST).path("/v1/messages");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "type": "text", "text": "Sent" } ] }"#);
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var("ANTHROPIC_API_URL", server.url("/v1/messages"));
+
+    let ai_config = AiConfig {
+        moderate_prompts: true,
+        ..AiConfig::default_for(Framework::Anthropic)
+    };
+    let result = ask_question(&ai_config, question("You are worthless")).await;
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("ANTHROPIC_API_URL");
+
+    moderation.assert();
+    anthropic.assert_hits(0);
+    match result {
+        Err(AppError::ContentFlagged { categories, .. }) => {
+            assert_eq!(categories, vec!["harassment", "violence"])
+        }
+        other => panic!("Expected AppError::ContentFlagged, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn clean_prompts_are_sent() {
+    let server = MockServer::start();
+    let moderation = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/moderations")
+            .body_contains(r#""input":"Hello there""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "results": [ { "flagged": false, "categories": { "harassment": false } } ] }"#,
+            );
+    });
+    let chat = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""model":"gpt-4o""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hi!" } } ] }"#);
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        max_token: None,
+        moderate_prompts: true,
+        ..Default::default()
+    };
+    let answer = ask_question(&ai_config, question("Hello there")).await;
+    env::remove_var("OPENAI_API_URL");
+
+    moderation.assert();
+    chat.assert();
+    assert_eq!(answer.expect("Should succeed"), "Hi!");
+}
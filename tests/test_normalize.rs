@@ -0,0 +1,80 @@
+use ask_ai::{
+    ask_ai::ask_question_raw,
+    config::{AiConfig, Framework, Question},
+    normalize::Normalizer,
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn raw_responses_normalize_to_a_stable_snapshot() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                    "usage": { "total_tokens": 12, "prompt_tokens": 9 },
+                    "id": "chatcmpl-123",
+                    "created": 1700000000,
+                    "system_fingerprint": "fp_abc",
+                    "choices": [ { "message": { "role": "assistant", "content": "Hello\r\n\r\n\r\nWorld   \n\n" } } ]
+                }"#,
+            );
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Say hello.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let response = ask_question_raw(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(response["id"], "chatcmpl-123");
+
+    let normalized = Normalizer::default()
+        .canonicalize_whitespace(true)
+        .normalize(&response);
+    assert_eq!(
+        normalized,
+        json!({
+            "choices": [ { "message": { "content": "Hello\n\nWorld", "role": "assistant" } } ],
+            "usage": { "prompt_tokens": 9, "total_tokens": 12 }
+        })
+    );
+    assert_eq!(
+        serde_json::to_string(&normalized).unwrap(),
+        r#"{"choices":[{"message":{"content":"Hello\n\nWorld","role":"assistant"}}],"usage":{"prompt_tokens":9,"total_tokens":12}}"#
+    );
+
+    let without_usage = Normalizer::default().strip_key("usage").keep_key("id");
+    assert_eq!(
+        without_usage.normalize(&response)["id"],
+        json!("chatcmpl-123")
+    );
+    assert!(without_usage.normalize(&response).get("usage").is_none());
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
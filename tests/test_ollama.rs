@@ -0,0 +1,353 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    error::AppError,
+    ollama::{
+        create_model, delete_model, embed, ensure_model, fill_in_middle, generate, list_models,
+        pull_model, show_model, PullProgress,
+    },
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+
+fn ollama_config(server: &MockServer) -> AiConfig {
+    AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn ollama_create_model_httpmock_success() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/api/create").json_body(json!({
+            "model": "rust-expert",
+            "stream": false,
+            "from": "llama3",
+            "system": "You are a terse Rust expert.\nAnswer in code.",
+            "parameters": { "temperature": 0.2, "num_ctx": 8192, "stop": ["<|end|>", "###"] },
+            "messages": [{ "role": "user", "content": "Hi" }]
+        }));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "status": "success" }"#);
+    });
+
+    let modelfile = r#"# A terse assistant
+FROM llama3
+SYSTEM """You are a terse Rust expert.
+Answer in code."""
+PARAMETER temperature 0.2
+PARAMETER num_ctx 8192
+PARAMETER stop "<|end|>"
+PARAMETER stop ###
+MESSAGE user Hi
+"#;
+    let status = create_model(&ollama_config(&server), "rust-expert", modelfile)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(status, "success");
+}
+
+#[tokio::test]
+async fn ollama_create_model_httpmock_error() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/api/create");
+        then.status(400)
+            .header("content-type", "application/json")
+            .body(r#"{ "error": "no FROM line" }"#);
+    });
+
+    match create_model(&ollama_config(&server), "broken", "SYSTEM missing base").await {
+        Err(AppError::ModelError {
+            model_name,
+            failure_str,
+        }) => {
+            assert_eq!(model_name, "broken");
+            assert!(failure_str.contains("no FROM line"));
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    };
+    mock.assert();
+}
+
+#[tokio::test]
+async fn ollama_create_model_respects_local_only() {
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        base_url: Some("https://ollama.example.com".to_string()),
+        local_only: true,
+        ..Default::default()
+    };
+    match create_model(&ai_config, "rust-expert", "FROM llama3").await {
+        Err(AppError::RemoteEndpointBlocked { .. }) => {}
+        other => panic!("Expected AppError::RemoteEndpointBlocked, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_embed_runs_offline() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/embed")
+            .body_contains(r#""model":"mxbai-embed-large""#)
+            .body_contains(r#""input":["ownership","borrowing"]"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "model": "mxbai-embed-large", "embeddings": [[0.1, 0.9], [0.8, 0.2]] }"#);
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    let vectors = embed("mxbai-embed-large", &["ownership", "borrowing"]).await;
+
+    env::set_var("OLLAMA_API_URL", "https://ollama.example.com");
+    let remote = embed("mxbai-embed-large", &["ownership"]).await;
+    env::remove_var("OLLAMA_API_URL");
+
+    mock.assert();
+    assert_eq!(
+        vectors.expect("Should succeed"),
+        vec![vec![0.1, 0.9], vec![0.8, 0.2]]
+    );
+    assert!(matches!(
+        remote,
+        Err(AppError::RemoteEndpointBlocked { .. })
+    ));
+}
+
+fn coder() -> AiConfig {
+    AiConfig {
+        llm: Framework::Ollama,
+        model: "qwen2.5-coder:1.5b-base".to_string(),
+        seed: Some(3),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_generate_completes_raw_prompts() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/generate")
+            .json_body(serde_json::json!({
+                "model": "qwen2.5-coder:1.5b-base",
+                "prompt": "fn add(a: i32, b: i32) -> i32 {",
+                "raw": true,
+                "stream": false,
+                "options": { "seed": 3 }
+            }));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "model": "qwen2.5-coder:1.5b-base", "response": " a + b }", "done": true }"#,
+            );
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    let completion = generate(&coder(), "fn add(a: i32, b: i32) -> i32 {").await;
+    env::remove_var("OLLAMA_API_URL");
+
+    mock.assert();
+    assert_eq!(completion.expect("Should succeed"), " a + b }");
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_fill_in_middle_sends_the_suffix() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/generate")
+            .json_body_partial(
+                r#"{ "prompt": "let total = ", "suffix": ";\nprintln!(\"{}\", total);" }"#,
+            )
+            .matches(|req| {
+                !String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains("raw")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "response": "prices.iter().sum::<u32>()", "done": true }"#);
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    let completion = fill_in_middle(&coder(), "let total = ", ";\nprintln!(\"{}\", total);").await;
+    let other = fill_in_middle(
+        &AiConfig {
+            llm: Framework::OpenAI,
+            ..coder()
+        },
+        "let total = ",
+        ";",
+    )
+    .await;
+    env::remove_var("OLLAMA_API_URL");
+
+    mock.assert();
+    assert_eq!(
+        completion.expect("Should succeed"),
+        "prices.iter().sum::<u32>()"
+    );
+    assert!(matches!(other, Err(AppError::ModelError { .. })));
+}
+
+const TAGS: &str = r#"{ "models": [ {
+    "name": "llama3.1:8b", "model": "llama3.1:8b", "size": 4920753328,
+    "digest": "46e0c10c039e", "modified_at": "2025-05-04T17:37:44.706015396-07:00",
+    "details": { "format": "gguf", "family": "llama", "parameter_size": "8.0B", "quantization_level": "Q4_K_M" }
+} ] }"#;
+
+#[tokio::test]
+#[serial]
+async fn ollama_models_are_listed_shown_and_deleted() {
+    let server = MockServer::start();
+    let tags = server.mock(|when, then| {
+        when.method(GET).path("/api/tags");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TAGS);
+    });
+    let show = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/show")
+            .json_body(serde_json::json!({ "model": "llama3.1:8b" }));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "template": "{{ .Prompt }}", "capabilities": ["completion", "tools"],
+                       "details": { "family": "llama" }, "model_info": { "llama.context_length": 131072 } }"#);
+    });
+    let delete = server.mock(|when, then| {
+        when.method(DELETE)
+            .path("/api/delete")
+            .json_body(serde_json::json!({ "model": "llama3.1:8b" }));
+        then.status(200);
+    });
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    };
+    let models = list_models(&ai_config).await;
+    let info = show_model(&ai_config, "llama3.1:8b").await;
+    let deleted = delete_model(&ai_config, "llama3.1:8b").await;
+
+    tags.assert();
+    show.assert();
+    delete.assert();
+    let models = models.expect("Should succeed");
+    assert_eq!(models[0].name, "llama3.1:8b");
+    assert_eq!(models[0].details.quantization_level, "Q4_K_M");
+    let info = info.expect("Should succeed");
+    assert_eq!(info.capabilities, vec!["completion", "tools"]);
+    assert_eq!(info.model_info["llama.context_length"], 131072);
+    assert!(deleted.is_ok());
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_pull_reports_progress() {
+    let server = MockServer::start();
+    let pull = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/pull")
+            .json_body(serde_json::json!({ "model": "qwen3:0.6b", "stream": true }));
+        then.status(200)
+            .header("content-type", "application/x-ndjson")
+            .body(concat!(
+                "{\"status\":\"pulling manifest\"}\n",
+                "{\"status\":\"pulling 7f4030143c1c\",\"digest\":\"sha256:7f4030143c1c\",\"total\":400,\"completed\":100}\n",
+                "{\"status\":\"success\"}\n"
+            ));
+    });
+    let missing = server.mock(|when, then| {
+        when.method(POST).path("/api/pull").body_contains("no-such-model");
+        then.status(200)
+            .header("content-type", "application/x-ndjson")
+            .body("{\"status\":\"pulling manifest\"}\n{\"error\":\"pull model manifest: file does not exist\"}\n");
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        ..Default::default()
+    };
+    let mut reports: Vec<PullProgress> = vec![];
+    let pulled = pull_model(&ai_config, "qwen3:0.6b", |progress| {
+        reports.push(progress.clone())
+    })
+    .await;
+    let failed = pull_model(&ai_config, "no-such-model", |_| {}).await;
+    env::remove_var("OLLAMA_API_URL");
+
+    pull.assert();
+    missing.assert();
+    assert!(pulled.is_ok());
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[1].fraction(), Some(0.25));
+    assert_eq!(reports[2].status, "success");
+    assert!(
+        matches!(failed, Err(AppError::ModelError { failure_str, .. }) if failure_str.contains("file does not exist"))
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_ensure_model_only_pulls_missing_models() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/api/tags");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TAGS);
+    });
+    let pull = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/pull")
+            .json_body_partial(r#"{ "model": "mistral:latest" }"#);
+        then.status(200).body("{\"status\":\"success\"}\n");
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    let config = |model: &str| AiConfig {
+        llm: Framework::Ollama,
+        model: model.to_string(),
+        ..Default::default()
+    };
+    let present = ensure_model(&config("llama3.1:8b"), |_| {}).await;
+    let missing = ensure_model(&config("mistral"), |_| {}).await;
+    env::remove_var("OLLAMA_API_URL");
+
+    pull.assert_hits(1);
+    assert!(!present.expect("Should succeed"));
+    assert!(missing.expect("Should succeed"));
+}
+
+#[tokio::test]
+async fn ollama_management_respects_local_only() {
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3.1:8b".to_string(),
+        base_url: Some("http://gpu-box.example.com:11434".to_string()),
+        local_only: true,
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        list_models(&ai_config).await,
+        Err(AppError::RemoteEndpointBlocked { .. })
+    ));
+    assert!(matches!(
+        pull_model(&ai_config, "llama3.1:8b", |_| {}).await,
+        Err(AppError::RemoteEndpointBlocked { .. })
+    ));
+    assert!(matches!(
+        ensure_model(&ai_config, |_| {}).await,
+        Err(AppError::RemoteEndpointBlocked { .. })
+    ));
+}
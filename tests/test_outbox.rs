@@ -0,0 +1,125 @@
+#![cfg(feature = "sqlite")]
+
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    outbox::{JobStatus, Outbox},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn question(prompt: &str) -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: prompt.to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn ai_config() -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        ..Default::default()
+    }
+}
+
+fn set_env(server: &MockServer) {
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+}
+
+fn remove_env() {
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[tokio::test]
+#[serial]
+async fn pending_jobs_survive_a_restart() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Processed" } } ] }"#);
+    });
+    set_env(&server);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("outbox.db");
+
+    // Submitted, then the process goes away before anything is sent
+    let outbox = Outbox::open(&path, ai_config()).unwrap();
+    let first = outbox.submit(&question("First")).unwrap();
+    let second = outbox.submit(&question("Second")).unwrap();
+    drop(outbox);
+    mock.assert_hits(0);
+
+    let outbox = Outbox::open(&path, ai_config()).unwrap();
+    let processed = outbox.run_pending().await.expect("Should succeed");
+    let ids: Vec<i64> = processed.iter().map(|job| job.id).collect();
+    assert_eq!(ids, vec![first, second]);
+    assert_eq!(processed[1].question.new_prompt, "Second");
+    assert!(processed
+        .iter()
+        .all(|job| job.status == JobStatus::Done && job.attempts == 1));
+    mock.assert_hits(2);
+
+    // Answers are stored, and nothing is left to send
+    let outbox = Outbox::open(&path, ai_config()).unwrap();
+    assert_eq!(
+        outbox.job(first).unwrap().answer.as_deref(),
+        Some("Processed")
+    );
+    assert!(outbox.run_pending().await.unwrap().is_empty());
+    assert_eq!(outbox.purge_done().unwrap(), 2);
+    assert!(outbox.jobs(None).unwrap().is_empty());
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_jobs_are_kept_until_retried() {
+    let server = MockServer::start();
+
+    let mut failing = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(500).body("Internal Server Error");
+    });
+    set_env(&server);
+
+    let dir = tempfile::tempdir().unwrap();
+    let outbox = Outbox::open(dir.path().join("outbox.db"), ai_config()).unwrap();
+    assert!(outbox.ask(question("Doomed")).await.is_err());
+
+    let failed = outbox.jobs(Some(JobStatus::Failed)).unwrap();
+    assert_eq!(failed.len(), 1);
+    assert!(failed[0].error.as_deref().unwrap().contains("500"));
+    assert!(outbox.run_pending().await.unwrap().is_empty());
+
+    failing.delete();
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Recovered" } } ] }"#);
+    });
+
+    assert_eq!(outbox.retry_failed().unwrap(), 1);
+    let processed = outbox.run_pending().await.expect("Should succeed");
+    assert_eq!(processed.len(), 1);
+    assert_eq!(processed[0].status, JobStatus::Done);
+    assert_eq!(processed[0].answer.as_deref(), Some("Recovered"));
+    assert_eq!(processed[0].error, None);
+    assert_eq!(processed[0].attempts, 2);
+
+    remove_env();
+}
@@ -0,0 +1,256 @@
+use ask_ai::{
+    ask_ai::{ask_question, ask_question_raw},
+    config::{AiConfig, AiPrompt, Framework, Question},
+    error::{AppError, Result},
+    overflow::{is_context_length_error, ContextOverflow},
+    provider::{register_provider, BoxFuture, Completion, Provider},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+const OVERFLOW_BODY: &str = r#"{ "error": { "message": "This model's maximum context length is 128000 tokens.", "type": "invalid_request_error", "code": "context_length_exceeded" } }"#;
+
+fn set_env(server: &MockServer) {
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+}
+
+fn remove_env() {
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+fn openai(model: &str) -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: model.to_string(),
+        ..Default::default()
+    }
+}
+
+fn long_question() -> Question {
+    let exchange = |content: &str, output: &str| AiPrompt {
+        content: content.to_string(),
+        output: output.to_string(),
+    };
+    Question {
+        system_prompt: None,
+        messages: Some(vec![
+            exchange("Old question", "Old answer"),
+            exchange("Older context", "Long ago"),
+            exchange("Recent question", "Recent answer"),
+        ]),
+        new_prompt: "And now?".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[test]
+fn context_length_errors_are_recognized() {
+    let error = |failure_str: &str| AppError::ApiError {
+        model_name: "openai".to_string(),
+        failure_str: failure_str.to_string(),
+    };
+    assert!(is_context_length_error(&error(&format!(
+        "Status 400 Bad Request: {}",
+        OVERFLOW_BODY
+    ))));
+    assert!(is_context_length_error(&error(
+        "Status 400 Bad Request: prompt is too long: 210000 tokens > 200000 maximum"
+    )));
+    assert!(!is_context_length_error(&error(
+        "Status 400 Bad Request: invalid model"
+    )));
+    assert!(!is_context_length_error(&AppError::UnexpectedError(
+        "maximum context length".to_string()
+    )));
+}
+
+#[tokio::test]
+#[serial]
+async fn overflowing_questions_go_to_the_fallback() {
+    let server = MockServer::start();
+
+    let small = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""model":"gpt-4o-mini""#);
+        then.status(400)
+            .header("content-type", "application/json")
+            .body(OVERFLOW_BODY);
+    });
+    let large = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""model":"gpt-4.1""#)
+            .body_contains("Old question");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "From the large model" } } ] }"#);
+    });
+    set_env(&server);
+
+    let ai_config = AiConfig {
+        on_context_overflow: Some(ContextOverflow {
+            fallback: Some(Box::new(openai("gpt-4.1"))),
+            truncate: false,
+        }),
+        ..openai("gpt-4o-mini")
+    };
+    let answer = ask_question(&ai_config, long_question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "From the large model");
+
+    let response = ask_question_raw(&ai_config, long_question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(
+        response["ask_ai"]["context_overflow"],
+        serde_json::json!({ "action": "fallback", "model": "gpt-4.1" })
+    );
+    small.assert_hits(2);
+    large.assert_hits(2);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn overflowing_questions_drop_their_oldest_exchanges() {
+    let server = MockServer::start();
+
+    let overflow = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Old");
+        then.status(400)
+            .header("content-type", "application/json")
+            .body(OVERFLOW_BODY);
+    });
+    let fits = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Recent question");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Shorter" } } ] }"#);
+    });
+    set_env(&server);
+
+    let ai_config = AiConfig {
+        on_context_overflow: Some(ContextOverflow {
+            fallback: None,
+            truncate: true,
+        }),
+        ..openai("gpt-4o-mini")
+    };
+    let response = ask_question_raw(&ai_config, long_question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(
+        response["choices"][0]["message"]["content"],
+        serde_json::json!("Shorter")
+    );
+    assert_eq!(
+        response["ask_ai"]["context_overflow"],
+        serde_json::json!({ "action": "truncated", "model": "gpt-4o-mini", "dropped": 2 })
+    );
+    overflow.assert_hits(1);
+    fits.assert_hits(1);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn overflow_errors_are_returned_without_a_policy() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(400)
+            .header("content-type", "application/json")
+            .body(OVERFLOW_BODY);
+    });
+    set_env(&server);
+
+    match ask_question(&openai("gpt-4o-mini"), long_question()).await {
+        Err(e) => assert!(is_context_length_error(&e)),
+        Ok(answer) => panic!("Expected a context-length error, got {}", answer),
+    }
+    mock.assert_hits(1);
+
+    remove_env();
+}
+
+/// Answers at once, recording the local-only mode it was asked under.
+struct LocalOnlyRecorder {
+    local_only: Arc<Mutex<Option<bool>>>,
+}
+
+impl Provider for LocalOnlyRecorder {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        _question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            *self.local_only.lock().unwrap() = Some(ai_config.local_only);
+            Ok(Completion {
+                answer: "From the fallback".to_string(),
+                truncated: false,
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn fallbacks_inherit_local_only() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(400)
+            .header("content-type", "application/json")
+            .body(OVERFLOW_BODY);
+    });
+    let local_only = Arc::new(Mutex::new(None));
+    register_provider(
+        "overflow-recorder",
+        LocalOnlyRecorder {
+            local_only: local_only.clone(),
+        },
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "local-model".to_string(),
+        base_url: Some(server.url("/v1")),
+        local_only: true,
+        on_context_overflow: Some(ContextOverflow {
+            fallback: Some(Box::new(AiConfig {
+                llm: "overflow-recorder".parse().unwrap(),
+                model: "recorder".to_string(),
+                ..Default::default()
+            })),
+            truncate: false,
+        }),
+        ..Default::default()
+    };
+    let question = Question {
+        new_prompt: "Summarize everything.".to_string(),
+        ..Default::default()
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "From the fallback");
+    assert_eq!(*local_only.lock().unwrap(), Some(true));
+}
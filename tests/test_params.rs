@@ -0,0 +1,154 @@
+use ask_ai::{
+    ask_ai::{
+        ask_question_raw, build_anthropic_payload, build_ollama_payload, build_openai_payload,
+    },
+    config::{AiConfig, Framework, Question, ReasoningEffort},
+    params::{GenerationParams, ParamWarning},
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+
+fn params() -> GenerationParams {
+    GenerationParams {
+        temperature: Some(1.5),
+        top_k: Some(40),
+        stop: (1..=5).map(|i| format!("STOP{}", i)).collect(),
+        presence_penalty: Some(0.5),
+        ..Default::default()
+    }
+}
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hello".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[test]
+fn params_map_onto_each_provider() {
+    let anthropic = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-3-5-haiku".to_string(),
+        params: Some(params()),
+        ..Default::default()
+    };
+    let payload = build_anthropic_payload(&question(), &anthropic);
+    assert_eq!(payload["temperature"], json!(1.0));
+    assert_eq!(payload["top_k"], json!(40));
+    assert_eq!(payload["stop_sequences"].as_array().unwrap().len(), 5);
+    assert!(payload.get("presence_penalty").is_none());
+    assert_eq!(
+        params().translate(Framework::Anthropic).warnings,
+        vec![
+            ParamWarning::Adjusted {
+                param: "temperature".to_string(),
+                from: json!(1.5),
+                to: json!(1.0),
+            },
+            ParamWarning::Dropped {
+                param: "presence_penalty".to_string(),
+            },
+        ]
+    );
+
+    let ollama = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        seed: Some(7),
+        params: Some(params()),
+        ..Default::default()
+    };
+    let options = &build_ollama_payload(&question(), &ollama)["options"];
+    assert_eq!(options["seed"], json!(7));
+    assert_eq!(options["temperature"], json!(1.5));
+    assert_eq!(options["presence_penalty"], json!(0.5));
+    assert!(params().translate(Framework::Ollama).warnings.is_empty());
+}
+
+#[test]
+fn reasoning_models_get_reasoning_params() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "o3-mini".to_string(),
+        max_token: Some(4000),
+        reasoning_effort: Some(ReasoningEffort::Low),
+        params: Some(params()),
+        ..Default::default()
+    };
+    let payload = build_openai_payload(&question(), &ai_config);
+    assert_eq!(payload["max_completion_tokens"], json!(4000));
+    assert_eq!(payload["reasoning_effort"], json!("low"));
+    assert!(payload.get("max_tokens").is_none());
+    assert!(payload.get("temperature").is_none());
+    assert!(payload.get("presence_penalty").is_none());
+    assert_eq!(payload["stop"].as_array().unwrap().len(), 4);
+
+    let warnings = params()
+        .translate_for_model(Framework::OpenAI, "o3-mini")
+        .warnings;
+    assert!(warnings.contains(&ParamWarning::Dropped {
+        param: "temperature".to_string(),
+    }));
+    assert!(warnings.contains(&ParamWarning::Dropped {
+        param: "presence_penalty".to_string(),
+    }));
+
+    // Chat models keep their sampling parameters
+    let payload = build_openai_payload(
+        &question(),
+        &AiConfig {
+            model: "gpt-4o".to_string(),
+            ..ai_config
+        },
+    );
+    assert_eq!(payload["temperature"], json!(1.5));
+    assert!(payload.get("max_completion_tokens").is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn raw_responses_report_param_warnings() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .json_body_partial(
+                r#"{ "temperature": 1.5, "stop": ["STOP1", "STOP2", "STOP3", "STOP4"] }"#,
+            );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hi" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        params: Some(params()),
+        ..Default::default()
+    };
+
+    let response = ask_question_raw(&ai_config, question())
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    let warnings = &response["ask_ai"]["param_warnings"];
+    assert_eq!(warnings[0], json!({ "kind": "dropped", "param": "top_k" }));
+    assert_eq!(warnings[1]["kind"], "adjusted");
+    assert_eq!(warnings[1]["param"], "stop");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
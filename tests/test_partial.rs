@@ -0,0 +1,107 @@
+use ask_ai::{
+    error::AppError,
+    partial::{json_items, parse_partial_json, partial_json},
+    stream::AnswerStream,
+};
+use futures_util::{stream, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+
+fn answer(fragments: &[&str]) -> AnswerStream {
+    let fragments: Vec<_> = fragments.iter().map(|f| Ok(f.to_string())).collect();
+    Box::pin(stream::iter(fragments))
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Step {
+    title: String,
+    minutes: u32,
+}
+
+#[test]
+fn prefixes_are_closed_at_the_last_complete_member() {
+    assert_eq!(parse_partial_json("Sure! ```json\n"), None);
+    assert_eq!(parse_partial_json("```json\n{"), Some(json!({})));
+    assert_eq!(
+        parse_partial_json(r#"{"title": "Rust", "tags": ["fast", "sa"#),
+        Some(json!({ "title": "Rust", "tags": ["fast", "sa"] }))
+    );
+    // Keys, numbers and literals still being written are left out
+    assert_eq!(
+        parse_partial_json(r#"{"title": "Rust", "ti"#),
+        Some(json!({ "title": "Rust" }))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"a": 1, "b": tr"#),
+        Some(json!({ "a": 1 }))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"quote": "say \"hi\" \"#),
+        Some(json!({ "quote": "say \"hi\" " }))
+    );
+    assert_eq!(
+        parse_partial_json("[1, 2] and some trailing text"),
+        Some(json!([1, 2]))
+    );
+}
+
+#[tokio::test]
+async fn partial_values_grow_until_the_answer_is_complete() {
+    let updates: Vec<_> = partial_json(answer(&["{\"name\": \"Ad", "a\", \"ag", "e\": 36", "}"]))
+        .collect()
+        .await;
+    let updates: Vec<_> = updates.into_iter().map(Result::unwrap).collect();
+
+    assert_eq!(
+        updates,
+        vec![
+            json!({ "name": "Ad" }),
+            json!({ "name": "Ada" }),
+            json!({ "name": "Ada", "age": 36 }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn list_items_are_emitted_as_they_complete() {
+    let mut steps = json_items::<Step>(
+        answer(&[
+            "{\"steps\": [{\"title\": \"Boil\", \"minutes\": 10}",
+            ", {\"title\": \"Dr",
+            "ain\", \"minutes\": 1}]}",
+        ]),
+        "/steps",
+    );
+
+    // The first step is complete once the second one starts
+    assert_eq!(
+        steps.next().await.unwrap().unwrap(),
+        Step {
+            title: "Boil".to_string(),
+            minutes: 10
+        }
+    );
+    assert_eq!(
+        steps.next().await.unwrap().unwrap(),
+        Step {
+            title: "Drain".to_string(),
+            minutes: 1
+        }
+    );
+    assert!(steps.next().await.is_none());
+}
+
+#[tokio::test]
+async fn answers_that_are_not_json_fail_the_stream() {
+    let items: Vec<_> = json_items::<u32>(answer(&["[1, 2", ", 3"]), "")
+        .collect()
+        .await;
+
+    assert_eq!(items.len(), 3);
+    assert_eq!(*items[0].as_ref().unwrap(), 1);
+    assert_eq!(*items[1].as_ref().unwrap(), 2);
+    match &items[2] {
+        Err(AppError::UnexpectedError(msg)) => assert!(msg.contains("not valid JSON")),
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    }
+}
@@ -0,0 +1,93 @@
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    patch::{ask_for_patch, HunkLine, Patch},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const SOURCE: &str = "fn main() {\n    run();\n}\n\nfn run() {\n    println!(\"hi\");\n}\n";
+
+#[test]
+fn parses_validates_and_applies_diffs() {
+    let diff = "diff --git a/src/main.rs b/src/main.rs\n\
+                --- a/src/main.rs\n\
+                +++ b/src/main.rs\n\
+                @@ -1,3 +1,3 @@\n \
+                fn main() {\n\
+                -    run();\n\
+                +    start();\n \
+                }\n\
+                @@ -4,3 +4,3 @@\n\
+                \n\
+                -fn run() {\n\
+                +fn start() {\n     \
+                println!(\"hi\");\n";
+
+    let patch = Patch::parse(diff).expect("Should parse");
+    let file = &patch.files[0];
+    assert_eq!(file.old_path.as_deref(), Some("src/main.rs"));
+    assert_eq!(file.hunks.len(), 2);
+    assert_eq!(
+        file.hunks[0].lines[1],
+        HunkLine::Removed("    run();".to_string())
+    );
+    assert_eq!(
+        file.apply(SOURCE).expect("Should apply"),
+        SOURCE.replace("run()", "start()")
+    );
+
+    let miscounted = "--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n-a\n+b\n";
+    assert!(matches!(
+        Patch::parse(miscounted),
+        Err(AppError::UnexpectedError(msg)) if msg.contains("1 original and 1 changed")
+    ));
+    assert!(Patch::parse("I could not produce a diff.").is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn fenced_diffs_in_answers_are_parsed() {
+    let server = MockServer::start();
+
+    let answer =
+        "Here you go:\n\n```diff\n--- /dev/null\n+++ b/NOTES.md\n@@ -0,0 +1 @@\n+# Notes\n```";
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("Answer only with a unified diff");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(serde_json::json!({
+                "model": "llama3",
+                "message": { "role": "assistant", "content": answer },
+                "done": true
+            }));
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Add a NOTES.md with a heading".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let patch = ask_for_patch(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(patch.files[0].old_path, None);
+    assert_eq!(patch.files[0].display_path(), "NOTES.md");
+    assert_eq!(patch.files[0].apply("").unwrap(), "# Notes");
+
+    env::remove_var("OLLAMA_API_URL");
+}
@@ -0,0 +1,194 @@
+use ask_ai::{
+    ask_question,
+    config::{AiConfig, AudioInput, Framework, Question},
+    privacy::{PrivacyConfig, PrivacyMode, PrivacyRule},
+    stream::{ask_question_stream, stream_to_writer},
+    transcript::ImageSource,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn private_config(mode: PrivacyMode) -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        privacy: Some(PrivacyConfig {
+            mode,
+            rules: vec![
+                PrivacyRule::email(),
+                PrivacyRule::literal("client", "Acme Corp"),
+            ],
+        }),
+        ..Default::default()
+    }
+}
+
+fn question() -> Question {
+    Question {
+        system_prompt: Some("You work for Acme Corp.".to_string()),
+        messages: None,
+        new_prompt: "Draft a reply to jane@acme.com about the Acme Corp renewal.".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn pseudonymized_values_never_leave_and_are_restored() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Draft a reply to [EMAIL_1] about the [CLIENT_1] renewal.")
+            .body_contains("You work for [CLIENT_1].")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                let body = String::from_utf8_lossy(&body);
+                !body.contains("jane@acme.com") && !body.contains("Acme Corp")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Dear [EMAIL_1], [CLIENT_1] thanks you." } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let answer = ask_question(&private_config(PrivacyMode::Pseudonymize), question())
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Dear jane@acme.com, Acme Corp thanks you.");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn streamed_placeholders_are_restored_across_deltas() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("[EMAIL_1]");
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(concat!(
+                "data: {\"choices\":[{\"delta\":{\"content\":\"Write to [EM\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"AIL_1] [sic] now\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ));
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let stream = ask_question_stream(&private_config(PrivacyMode::Pseudonymize), question())
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    let answer = stream_to_writer(stream, &mut written, false)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Write to jane@acme.com [sic] now");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[test]
+fn strip_mode_keeps_only_labels() {
+    let privacy = PrivacyConfig {
+        mode: PrivacyMode::Strip,
+        rules: vec![PrivacyRule::email(), PrivacyRule::phone()],
+    };
+    let (redacted, redactions) = privacy
+        .redact(Question {
+            system_prompt: None,
+            messages: None,
+            new_prompt: "Call +41 22 123 45 67 or mail a@b.io, then b@c.io".to_string(),
+            images: vec![],
+            audio: vec![],
+        })
+        .unwrap();
+
+    assert_eq!(
+        redacted.new_prompt,
+        "Call [PHONE] or mail [EMAIL], then [EMAIL]"
+    );
+    assert_eq!(redactions.labels(), ["EMAIL", "EMAIL", "PHONE"]);
+    assert_eq!(redactions.restore("[EMAIL]"), "[EMAIL]");
+}
+
+#[test]
+fn redaction_keeps_images() {
+    let privacy = PrivacyConfig {
+        mode: PrivacyMode::Pseudonymize,
+        rules: vec![PrivacyRule::email()],
+    };
+    let image = ImageSource::Url {
+        url: "https://example.com/invoice.png".to_string(),
+    };
+    let (redacted, _) = privacy
+        .redact(Question {
+            new_prompt: "Is this invoice for a@b.io?".to_string(),
+            images: vec![image.clone()],
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(redacted.new_prompt, "Is this invoice for [EMAIL_1]?");
+    assert_eq!(redacted.images, [image]);
+}
+
+#[tokio::test]
+#[serial]
+async fn redacted_questions_keep_their_attachments() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Is [EMAIL_1] speaking in this recording?")
+            .body_contains(r#""image_url":{"url":"https://example.com/badge.png"}"#)
+            .body_contains(r#""input_audio":{"data":"UklGRg==","format":"wav"}"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Yes, [EMAIL_1] is." } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let question = Question {
+        new_prompt: "Is jane@acme.com speaking in this recording?".to_string(),
+        images: vec![ImageSource::Url {
+            url: "https://example.com/badge.png".to_string(),
+        }],
+        audio: vec![AudioInput::from_bytes("wav", b"RIFF")],
+        ..Default::default()
+    };
+    let answer = ask_question(&private_config(PrivacyMode::Pseudonymize), question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Yes, jane@acme.com is.");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
@@ -0,0 +1,120 @@
+use ask_ai::{
+    ask_ai::{ask_question, build_anthropic_payload, build_ollama_payload, build_openai_payload},
+    config::{AiConfig, EmptyPromptPolicy, Framework, PromptDefaults, Question},
+    error::AppError,
+};
+
+fn question(system_prompt: Option<&str>, new_prompt: &str) -> Question {
+    Question {
+        system_prompt: system_prompt.map(str::to_string),
+        messages: None,
+        new_prompt: new_prompt.to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn config(llm: Framework, prompts: Option<PromptDefaults>) -> AiConfig {
+    AiConfig {
+        llm,
+        model: "test-model".to_string(),
+        prompts,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn builtin_defaults_are_kept_without_prompt_defaults() {
+    let question = question(None, "");
+
+    let openai = build_openai_payload(&question, &config(Framework::OpenAI, None));
+    assert_eq!(openai["messages"][0]["content"], "");
+    assert_eq!(openai["messages"][1]["content"], ".");
+
+    let anthropic = build_anthropic_payload(&question, &config(Framework::Anthropic, None));
+    assert_eq!(
+        anthropic["system"],
+        "You are a helpful assistant. Answer the question concisely."
+    );
+    assert_eq!(anthropic["messages"][0]["content"][0]["text"], ".");
+}
+
+#[test]
+fn configured_defaults_apply_to_every_provider() {
+    let prompts = PromptDefaults {
+        system_prompt: Some("You are Acme's support assistant.".to_string()),
+        empty_prompt: EmptyPromptPolicy::Substitute("Continue.".to_string()),
+    };
+    let question = question(None, "");
+
+    let openai = build_openai_payload(&question, &config(Framework::OpenAI, Some(prompts.clone())));
+    assert_eq!(
+        openai["messages"][0]["content"],
+        "You are Acme's support assistant."
+    );
+    assert_eq!(openai["messages"][1]["content"], "Continue.");
+
+    let anthropic = build_anthropic_payload(
+        &question,
+        &config(Framework::Anthropic, Some(prompts.clone())),
+    );
+    assert_eq!(anthropic["system"], "You are Acme's support assistant.");
+    assert_eq!(anthropic["messages"][0]["content"][0]["text"], "Continue.");
+
+    let ollama = build_ollama_payload(&question, &config(Framework::Ollama, Some(prompts)));
+    assert_eq!(
+        ollama["messages"][0]["content"],
+        "You are Acme's support assistant."
+    );
+    assert_eq!(ollama["messages"][1]["content"], "Continue.");
+}
+
+#[test]
+fn question_system_prompt_wins_over_default() {
+    let prompts = PromptDefaults {
+        system_prompt: Some("Default prompt.".to_string()),
+        ..Default::default()
+    };
+    let payload = build_openai_payload(
+        &question(Some("Own prompt."), "Hi"),
+        &config(Framework::OpenAI, Some(prompts)),
+    );
+    assert_eq!(payload["messages"][0]["content"], "Own prompt.");
+    assert_eq!(payload["messages"][1]["content"], "Hi");
+}
+
+#[tokio::test]
+async fn empty_prompts_can_be_rejected() {
+    let prompts = PromptDefaults {
+        empty_prompt: EmptyPromptPolicy::Reject,
+        ..Default::default()
+    };
+    // Fails before anything is sent, so no server is needed
+    let ai_config = AiConfig {
+        api_key: Some("test_key".into()),
+        ..config(Framework::OpenAI, Some(prompts))
+    };
+
+    match ask_question(&ai_config, question(None, "")).await {
+        Err(AppError::EmptyPrompt { model_name }) => assert_eq!(model_name, "test-model"),
+        other => panic!("Expected AppError::EmptyPrompt, got {:?}", other),
+    }
+}
+
+#[test]
+fn empty_prompt_policy_serializes_in_snake_case() {
+    let prompts: PromptDefaults =
+        serde_json::from_str(r#"{ "empty_prompt": { "substitute": "..." } }"#).unwrap();
+    assert_eq!(
+        prompts.empty_prompt,
+        EmptyPromptPolicy::Substitute("...".to_string())
+    );
+    assert_eq!(
+        serde_json::to_value(EmptyPromptPolicy::Reject).unwrap(),
+        "reject"
+    );
+    assert_eq!(
+        PromptDefaults::default().empty_prompt,
+        EmptyPromptPolicy::Substitute(".".to_string())
+    );
+}
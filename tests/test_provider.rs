@@ -0,0 +1,161 @@
+use ask_ai::{
+    ask_ai::{ask_question, ask_question_raw},
+    cache::{Cache, MemoryCache},
+    config::{AiConfig, Framework, Question},
+    error::{AppError, Result},
+    provider::{
+        register_provider, unregister_provider, BoxFuture, Completion, OpenAICompatibleProvider,
+        Provider,
+    },
+    stream::ask_question_stream,
+};
+use futures_util::StreamExt;
+use httpmock::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Answers with the model name and the prompt, counting calls.
+struct Echo {
+    calls: Arc<AtomicUsize>,
+}
+
+impl Provider for Echo {
+    fn ask<'a>(
+        &'a self,
+        ai_config: &'a AiConfig,
+        question: Question,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Completion {
+                answer: format!("{}: {}", ai_config.model, question.new_prompt),
+                truncated: false,
+            })
+        })
+    }
+}
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+async fn registered_providers_answer_for_their_custom_name() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    register_provider(
+        "Echo",
+        Echo {
+            calls: calls.clone(),
+        },
+    );
+
+    let ai_config = AiConfig {
+        llm: "echo".parse().unwrap(),
+        model: "echo-1".to_string(),
+        cache: Some(Cache::new(MemoryCache::default())),
+        ..Default::default()
+    };
+    let answer = ask_question(&ai_config, question()).await.unwrap();
+    assert_eq!(answer, "echo-1: Hi");
+
+    // The rest of the pipeline applies as for built-in providers
+    ask_question(&ai_config, question()).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let chunks: Vec<String> = ask_question_stream(&ai_config, question())
+        .await
+        .unwrap()
+        .map(|chunk| chunk.unwrap())
+        .collect()
+        .await;
+    assert_eq!(chunks, vec!["echo-1: Hi"]);
+
+    assert!(unregister_provider("echo"));
+    let ai_config = AiConfig {
+        cache: None,
+        ..ai_config
+    };
+    match ask_question(&ai_config, question()).await {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert!(failure_str.contains("No client registered for provider `echo`"))
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+    assert!(!unregister_provider("echo"));
+}
+
+#[tokio::test]
+async fn built_in_names_are_not_custom_providers() {
+    register_provider(
+        "openai",
+        Echo {
+            calls: Arc::default(),
+        },
+    );
+    let ai_config = AiConfig {
+        llm: "openai".parse().unwrap(),
+        ..Default::default()
+    };
+    assert_eq!(ai_config.llm, Framework::OpenAI);
+    unregister_provider("openai");
+}
+
+#[tokio::test]
+async fn custom_providers_answer_raw_questions() {
+    register_provider(
+        "raw-echo",
+        Echo {
+            calls: Arc::default(),
+        },
+    );
+    let ai_config = AiConfig {
+        llm: "raw-echo".parse().unwrap(),
+        model: "echo-1".to_string(),
+        ..Default::default()
+    };
+
+    let response = ask_question_raw(&ai_config, question()).await.unwrap();
+    assert_eq!(response["choices"][0]["message"]["content"], "echo-1: Hi");
+    assert_eq!(response["choices"][0]["finish_reason"], "stop");
+    unregister_provider("raw-echo");
+}
+
+#[tokio::test]
+async fn openai_compatible_providers_serve_custom_names() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .header("authorization", "Bearer gateway_testkey");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "choices": [{ "message": { "content": "Through the gateway" }, "finish_reason": "stop" }] }"#,
+            );
+    });
+
+    register_provider("gateway", OpenAICompatibleProvider::new(Framework::OpenAI));
+    let ai_config = AiConfig {
+        llm: "gateway".parse().unwrap(),
+        model: "gpt-4o-mini".to_string(),
+        api_key: Some("gateway_testkey".into()),
+        base_url: Some(server.url("/v1")),
+        ..Default::default()
+    };
+
+    let answer = ask_question(&ai_config, question()).await.unwrap();
+    assert_eq!(answer, "Through the gateway");
+    let response = ask_question_raw(&ai_config, question()).await.unwrap();
+    assert_eq!(
+        response["choices"][0]["message"]["content"],
+        "Through the gateway"
+    );
+    mock.assert_hits(2);
+    unregister_provider("gateway");
+}
@@ -0,0 +1,143 @@
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    quota::{MemoryQuotaStore, QuotaStore},
+    tenant::{TenantConfig, TenantLimits, TenantQuota, TenantRegistry},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::sync::Arc;
+
+const QUOTA: TenantQuota = TenantQuota {
+    window_secs: 60,
+    max_requests: Some(1),
+    max_tokens: None,
+};
+
+fn replica(store: Arc<MemoryQuotaStore>) -> TenantRegistry {
+    let registry = TenantRegistry::new().with_quota_store(store);
+    registry.insert(
+        "globex",
+        TenantConfig {
+            ai_config: AiConfig {
+                llm: Framework::OpenAI,
+                model: "gpt-4o-mini".to_string(),
+                api_key: Some("globex_key".into()),
+                ..Default::default()
+            },
+            limits: TenantLimits {
+                quota: Some(QUOTA),
+                ..Default::default()
+            },
+        },
+    );
+    registry
+}
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn registries_sharing_a_store_share_the_quota() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hello Globex" } } ] }"#);
+    });
+
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let store = Arc::new(MemoryQuotaStore::new());
+    let first = replica(store.clone());
+    let second = replica(store.clone());
+
+    let answer = first
+        .ask_question_for("globex", question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Hello Globex");
+    assert_eq!(second.usage_for("globex").await.unwrap().requests, 1);
+
+    match second.ask_question_for("globex", question()).await {
+        Err(AppError::QuotaExceeded { tenant, .. }) => assert_eq!(tenant, "globex"),
+        other => panic!("Expected AppError::QuotaExceeded, got {:?}", other),
+    }
+    mock.assert_hits(1);
+
+    second.remove("globex").await;
+    assert!(store.usage("globex", &QUOTA).await.unwrap().is_none());
+
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+async fn memory_store_counts_prompt_and_answer_tokens() {
+    let store = MemoryQuotaStore::new();
+    let quota = TenantQuota {
+        max_requests: None,
+        max_tokens: Some(10),
+        ..QUOTA
+    };
+
+    store
+        .reserve("acme", &quota, 4)
+        .await
+        .expect("Should reserve");
+    store.add_tokens("acme", &quota, 6).await.unwrap();
+    let usage = store.usage("acme", &quota).await.unwrap().unwrap();
+    assert_eq!((usage.requests, usage.tokens), (1, 10));
+
+    assert!(matches!(
+        store.reserve("acme", &quota, 1).await,
+        Err(AppError::QuotaExceeded { .. })
+    ));
+}
+
+/// Runs against a real server when `ASK_AI_TEST_REDIS_URL` is set, e.g. `redis://127.0.0.1/`.
+#[cfg(feature = "redis")]
+#[tokio::test]
+async fn redis_store_is_shared_between_connections() {
+    use ask_ai::quota::RedisQuotaStore;
+
+    let Ok(url) = env::var("ASK_AI_TEST_REDIS_URL") else {
+        return;
+    };
+    let prefix = format!("ask_ai_test:{}", std::process::id());
+    let first = RedisQuotaStore::open(&url, &prefix)
+        .await
+        .expect("Should connect");
+    let second = RedisQuotaStore::open(&url, &prefix)
+        .await
+        .expect("Should connect");
+
+    first
+        .reserve("acme", &QUOTA, 3)
+        .await
+        .expect("Should reserve");
+    second.add_tokens("acme", &QUOTA, 2).await.unwrap();
+    let usage = second.usage("acme", &QUOTA).await.unwrap().unwrap();
+    assert_eq!((usage.requests, usage.tokens), (1, 5));
+
+    assert!(matches!(
+        second.reserve("acme", &QUOTA, 1).await,
+        Err(AppError::QuotaExceeded { .. })
+    ));
+
+    first.clear("acme", &QUOTA).await.unwrap();
+    assert!(second.usage("acme", &QUOTA).await.unwrap().is_none());
+}
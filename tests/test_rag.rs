@@ -0,0 +1,93 @@
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    rag::{chunk_text, Rag},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[test]
+fn chunks_overlap_and_end_at_whitespace() {
+    let chunks = chunk_text("one two three four five six", 10, 4);
+    assert_eq!(
+        chunks,
+        vec!["one two", "two three", "four five", "five six"]
+    );
+    assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 10));
+
+    assert_eq!(chunk_text("short", 100, 20), vec!["short"]);
+    assert!(chunk_text("  \n ", 10, 2).is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn retrieved_chunks_are_added_to_the_system_prompt() {
+    let server = MockServer::start();
+    let documents = server.mock(|when, then| {
+        when.method(POST).path("/api/embed").body_contains(
+            r#""input":["Each value has one owner.","Traits define shared behavior."]"#,
+        );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "embeddings": [[0.9, 0.1], [0.1, 0.9]] }"#);
+    });
+    let query = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/embed")
+            .body_contains(r#""input":["Who owns a value?"]"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "embeddings": [[1.0, 0.0]] }"#);
+    });
+    let chat = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#"You are a Rust tutor.\n\nAnswer using the context below."#)
+            .body_contains(r#"Context:\n\n[1] Each value has one owner.""#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Its single owner." } } ] }"#);
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let mut rag = Rag {
+        chunk_size: 32,
+        chunk_overlap: 0,
+        top_k: 1,
+        ..Rag::new(AiConfig {
+            llm: Framework::Ollama,
+            model: "nomic-embed-text".to_string(),
+            ..Default::default()
+        })
+    };
+    let stored = rag
+        .add_document(
+            "book",
+            "Each value has one owner.\nTraits define shared behavior.",
+        )
+        .await;
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        max_token: None,
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: Some("You are a Rust tutor.".to_string()),
+        messages: None,
+        new_prompt: "Who owns a value?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    let answer = rag.ask(&ai_config, question).await;
+    env::remove_var("OLLAMA_API_URL");
+    env::remove_var("OPENAI_API_URL");
+
+    assert_eq!(stored.expect("Should succeed"), 2);
+    documents.assert();
+    query.assert();
+    chat.assert();
+    assert_eq!(answer.expect("Should succeed"), "Its single owner.");
+}
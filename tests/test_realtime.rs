@@ -0,0 +1,134 @@
+#![cfg(feature = "realtime")]
+
+use ask_ai::{
+    config::{AiConfig, Framework},
+    error::AppError,
+    realtime::{connect, ClientEvent, ServerEvent},
+};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::env;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+
+fn ai_config() -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-realtime".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Serves one Realtime session: checks the handshake, then answers a text message with a
+/// text delta, an audio delta and `response.done`. Returns the events it received.
+// The handshake callback's error type is tungstenite's
+#[allow(clippy::result_large_err)]
+async fn serve_session(listener: TcpListener) -> Vec<Value> {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut socket = tokio_tungstenite::accept_hdr_async(stream, |req: &Request, resp| {
+        assert_eq!(req.uri().path(), "/v1/realtime");
+        assert_eq!(req.uri().query(), Some("model=gpt-realtime"));
+        assert_eq!(
+            req.headers()["authorization"].to_str().unwrap(),
+            "Bearer open_api_testkey"
+        );
+        Ok::<Response, _>(resp)
+    })
+    .await
+    .unwrap();
+
+    let send = |event: Value| Message::text(event.to_string());
+    socket
+        .send(send(
+            json!({ "type": "session.created", "session": { "id": "sess_1" } }),
+        ))
+        .await
+        .unwrap();
+    let mut received = vec![];
+    while received.len() < 2 {
+        if let Some(Ok(Message::Text(text))) = socket.next().await {
+            received.push(serde_json::from_str(&text).unwrap());
+        }
+    }
+    for event in [
+        // Beta event name
+        json!({ "type": "response.text.delta", "response_id": "resp_1", "delta": "Hi" }),
+        json!({ "type": "response.output_audio.delta", "response_id": "resp_1", "delta": "AAEC" }),
+        json!({ "type": "rate_limits.updated", "rate_limits": [] }),
+        json!({ "type": "response.done", "response": { "status": "completed" } }),
+    ] {
+        socket.send(send(event)).await.unwrap();
+    }
+    socket.close(None).await.unwrap();
+    received
+}
+
+#[tokio::test]
+#[serial]
+async fn realtime_session_streams_events() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!(
+            "http://{}/v1/chat/completions",
+            listener.local_addr().unwrap()
+        ),
+    );
+    let server = tokio::spawn(serve_session(listener));
+
+    let mut session = connect(&ai_config()).await.expect("Should connect");
+    env::remove_var("OPENAI_API_URL");
+    assert_eq!(
+        session.next_event().await.unwrap(),
+        Some(ServerEvent::SessionCreated {
+            session: json!({ "id": "sess_1" })
+        })
+    );
+    session.send_text("Hello").await.unwrap();
+
+    let mut events = vec![];
+    while let Some(event) = session.next_event().await.unwrap() {
+        events.push(event);
+    }
+    assert_eq!(
+        events[0],
+        ServerEvent::TextDelta {
+            response_id: "resp_1".to_string(),
+            delta: "Hi".to_string()
+        }
+    );
+    assert_eq!(
+        events[1],
+        ServerEvent::AudioDelta {
+            response_id: "resp_1".to_string(),
+            delta: vec![0, 1, 2]
+        }
+    );
+    assert!(
+        matches!(&events[2], ServerEvent::Other(event) if event["type"] == "rate_limits.updated")
+    );
+    assert!(matches!(events[3], ServerEvent::ResponseDone { .. }));
+
+    let received = server.await.unwrap();
+    assert_eq!(received[0]["type"], "conversation.item.create");
+    assert_eq!(received[0]["item"]["content"][0]["text"], "Hello");
+    assert_eq!(
+        serde_json::from_value::<ClientEvent>(received[1].clone()).unwrap(),
+        ClientEvent::ResponseCreate { response: None }
+    );
+}
+
+#[tokio::test]
+async fn other_providers_have_no_realtime_api() {
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        ..ai_config()
+    };
+    assert!(matches!(
+        connect(&ai_config).await,
+        Err(AppError::ModelError { .. })
+    ));
+}
@@ -0,0 +1,86 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    conversation::Conversation,
+    error::AppError,
+    replay::Replay,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn conversation_is_recorded_then_replayed_offline() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""seed":42"#)
+            .body_contains("Name a colour.")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                !String::from_utf8_lossy(&body).contains("Another one.")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Teal" } } ] }"#);
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Another one.");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Ochre  \n" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let cassette = dir.path().join("cassettes").join("colours.json");
+    let mut ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        seed: Some(42),
+        replay: Some(Replay::record(&cassette)),
+        ..Default::default()
+    };
+
+    let mut recorded = Conversation::new(None);
+    recorded.ask(&ai_config, "Name a colour.").await.unwrap();
+    recorded.ask(&ai_config, "Another one.").await.unwrap();
+    first.assert();
+    second.assert();
+
+    // Replay needs no provider at all
+    env::remove_var("OPENAI_API_KEY");
+    env::set_var("OPENAI_API_URL", "http://127.0.0.1:9/v1/chat/completions");
+    let replay = Replay::open(&cassette).unwrap();
+    ai_config.replay = Some(replay.clone());
+
+    let mut replayed = Conversation::new(None);
+    replayed.ask(&ai_config, "Name a colour.").await.unwrap();
+    assert!(replay.finish().is_err());
+    replayed.ask(&ai_config, "Another one.").await.unwrap();
+    replay.finish().unwrap();
+    assert_eq!(replayed, recorded);
+    assert_eq!(replayed.messages[1].output, "Ochre  \n");
+
+    // A drifting prompt fails instead of silently getting another answer
+    let replay = Replay::open(&cassette).unwrap();
+    ai_config.replay = Some(replay);
+    match Conversation::new(None)
+        .ask(&ai_config, "Name a color.")
+        .await
+    {
+        Err(AppError::UnexpectedError(msg)) => assert!(msg.contains("Replay mismatch")),
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    }
+
+    env::remove_var("OPENAI_API_URL");
+}
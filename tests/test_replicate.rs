@@ -0,0 +1,193 @@
+use ask_ai::{
+    ask_ai::ask_question,
+    config::{AiConfig, AiPrompt, Framework, Question},
+    error::AppError,
+    params::GenerationParams,
+    replicate::build_replicate_payload,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const MODEL: &str = "meta/meta-llama-3-8b-instruct";
+
+fn ai_config() -> AiConfig {
+    AiConfig {
+        llm: Framework::Replicate,
+        model: MODEL.to_string(),
+        max_token: Some(5),
+        ..Default::default()
+    }
+}
+
+fn question() -> Question {
+    Question {
+        system_prompt: Some("Be brief.".to_string()),
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[test]
+fn payload_follows_the_chat_model_input_schema() {
+    let ai_config = AiConfig {
+        model: format!("{}:5a6809ca", MODEL),
+        params: Some(GenerationParams {
+            temperature: Some(0.7),
+            top_k: Some(40),
+            stop: vec!["END".to_string(), "STOP".to_string()],
+            ..Default::default()
+        }),
+        ..ai_config()
+    };
+    let question = Question {
+        messages: Some(vec![AiPrompt {
+            content: "What is 2 + 2?".to_string(),
+            output: "4".to_string(),
+        }]),
+        new_prompt: "And times 3?".to_string(),
+        ..question()
+    };
+    let payload = build_replicate_payload(&question, &ai_config);
+
+    assert_eq!(payload["version"], "5a6809ca");
+    let input = &payload["input"];
+    assert_eq!(input["system_prompt"], "Be brief.");
+    assert_eq!(
+        input["prompt"],
+        "User: What is 2 + 2?\n\nAssistant: 4\n\nUser: And times 3?\n\nAssistant:"
+    );
+    assert_eq!(input["max_tokens"], 5);
+    assert_eq!(input["temperature"], 0.7);
+    assert_eq!(input["top_k"], 40);
+    assert_eq!(input["stop_sequences"], "END,STOP");
+}
+
+#[tokio::test]
+#[serial]
+async fn running_predictions_are_polled_until_they_finish() {
+    let server = MockServer::start();
+    let create = server.mock(|when, then| {
+        when.method(POST)
+            .path(format!("/v1/models/{}/predictions", MODEL))
+            .header("authorization", "Bearer r8_testtoken")
+            .header("prefer", "wait")
+            .body_contains(r#""prompt":"Hi""#);
+        then.status(201)
+            .header("content-type", "application/json")
+            .body(
+                serde_json::json!({
+                    "id": "p1",
+                    "status": "processing",
+                    "output": null,
+                    "urls": { "get": server.url("/v1/predictions/p1") }
+                })
+                .to_string(),
+            );
+    });
+    let poll = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/predictions/p1")
+            .header("authorization", "Bearer r8_testtoken");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                    "id": "p1",
+                    "status": "succeeded",
+                    "output": ["Hel", "lo", "!"],
+                    "metrics": { "output_token_count": 3 }
+                }"#,
+            );
+    });
+    env::set_var("REPLICATE_API_URL", server.url("/v1"));
+    env::set_var("REPLICATE_API_TOKEN", "r8_testtoken");
+
+    let answer = ask_question(&ai_config(), question()).await;
+    env::remove_var("REPLICATE_API_URL");
+    env::remove_var("REPLICATE_API_TOKEN");
+
+    create.assert();
+    poll.assert();
+    assert_eq!(answer.expect("Should succeed"), "Hello!");
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_predictions_are_model_errors() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST)
+            .path(format!("/v1/models/{}/predictions", MODEL));
+        then.status(201)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "p2", "status": "failed", "error": "CUDA out of memory" }"#);
+    });
+    env::set_var("REPLICATE_API_URL", server.url("/v1"));
+    env::set_var("REPLICATE_API_TOKEN", "r8_testtoken");
+
+    let result = ask_question(&ai_config(), question()).await;
+    env::remove_var("REPLICATE_API_URL");
+    env::remove_var("REPLICATE_API_TOKEN");
+
+    match result {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert!(failure_str.contains("CUDA out of memory"))
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[serial]
+async fn stuck_predictions_are_canceled() {
+    let server = MockServer::start();
+    let prediction = serde_json::json!({
+        "id": "p3",
+        "status": "starting",
+        "output": null,
+        "urls": {
+            "get": server.url("/v1/predictions/p3"),
+            "cancel": server.url("/v1/predictions/p3/cancel")
+        }
+    })
+    .to_string();
+    server.mock(|when, then| {
+        when.method(POST)
+            .path(format!("/v1/models/{}/predictions", MODEL));
+        then.status(201)
+            .header("content-type", "application/json")
+            .body(&prediction);
+    });
+    let poll = server.mock(|when, then| {
+        when.method(GET).path("/v1/predictions/p3");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(&prediction);
+    });
+    let cancel = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/predictions/p3/cancel")
+            .header("authorization", "Bearer r8_testtoken");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "id": "p3", "status": "canceled" }"#);
+    });
+    env::set_var("REPLICATE_API_URL", server.url("/v1"));
+    env::set_var("REPLICATE_API_TOKEN", "r8_testtoken");
+
+    let result = ask_question(&ai_config(), question()).await;
+    env::remove_var("REPLICATE_API_URL");
+    env::remove_var("REPLICATE_API_TOKEN");
+
+    match result {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert!(failure_str.contains("was canceled"))
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    }
+    assert!(poll.hits() > 1);
+    cancel.assert();
+}
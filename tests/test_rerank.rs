@@ -0,0 +1,119 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    rerank::{rerank, RerankResult},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn cohere_rerank_orders_by_relevance() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v2/rerank")
+            .header("authorization", "Bearer cohere_testkey")
+            .body_contains(r#""query":"Who owns a value?""#)
+            .body_contains(
+                r#""documents":["Traits define shared behavior.","Each value has one owner."]"#,
+            );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "id": "r1", "results": [
+                    { "index": 1, "relevance_score": 0.9 },
+                    { "index": 0, "relevance_score": 0.1 }
+                ] }"#,
+            );
+    });
+    env::set_var("COHERE_API_KEY", "cohere_testkey");
+    env::set_var("COHERE_API_URL", server.url("/v2/embed"));
+
+    let ai_config = AiConfig {
+        llm: Framework::Custom("cohere".to_string()),
+        model: "rerank-v3.5".to_string(),
+        ..Default::default()
+    };
+    let ranked = rerank(
+        &ai_config,
+        "Who owns a value?",
+        &[
+            "Traits define shared behavior.",
+            "Each value has one owner.",
+        ],
+    )
+    .await;
+    env::remove_var("COHERE_API_KEY");
+    env::remove_var("COHERE_API_URL");
+
+    mock.assert();
+    assert_eq!(
+        ranked.expect("Should succeed"),
+        vec![
+            RerankResult {
+                index: 1,
+                score: 0.9
+            },
+            RerankResult {
+                index: 0,
+                score: 0.1
+            }
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn chat_models_score_each_document() {
+    let server = MockServer::start();
+    let relevant = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("Document: Each value has one owner.");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"qwen2.5:0.5b","message":{"role":"assistant","content":"9"},"done":true}"#);
+    });
+    let unrelated = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains("Document: Traits define shared behavior.");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"qwen2.5:0.5b","message":{"role":"assistant","content":"Score: 2"},"done":true}"#);
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "qwen2.5:0.5b".to_string(),
+        ..Default::default()
+    };
+    let ranked = rerank(
+        &ai_config,
+        "Who owns a value?",
+        &[
+            "Traits define shared behavior.",
+            "Each value has one owner.",
+        ],
+    )
+    .await;
+    env::remove_var("OLLAMA_API_URL");
+
+    relevant.assert();
+    unrelated.assert();
+    assert_eq!(
+        ranked.expect("Should succeed"),
+        vec![
+            RerankResult {
+                index: 1,
+                score: 0.9
+            },
+            RerankResult {
+                index: 0,
+                score: 0.2
+            }
+        ]
+    );
+}
@@ -0,0 +1,151 @@
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    schema::{ask_validated, SchemaValidation},
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+
+fn is_retry(req: &HttpMockRequest) -> bool {
+    let body = req.body.clone().unwrap_or_default();
+    String::from_utf8_lossy(&body).contains("does not match the required JSON Schema")
+}
+
+fn validation() -> SchemaValidation {
+    SchemaValidation {
+        schema: json!({
+            "type": "object",
+            "properties": { "city": { "type": "string" }, "population": { "type": "integer" } },
+            "required": ["city", "population"]
+        }),
+        max_attempts: 2,
+    }
+}
+
+fn setup_openai(server: &MockServer) -> (AiConfig, Question) {
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        max_token: None,
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What is the largest city in France?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    (ai_config, question)
+}
+
+#[tokio::test]
+#[serial]
+async fn invalid_answers_are_sent_back_with_the_errors() {
+    let server = MockServer::start();
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""response_format":{"json_schema""#)
+            .matches(|req| !is_retry(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "{\"city\": \"Paris\"}" } } ] }"#);
+    });
+    let retry = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("population")
+            .body_contains("is a required property")
+            .matches(is_retry);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "choices": [ { "message": {
+                    "content": "```json\n{\"city\": \"Paris\", \"population\": 2102650}\n```"
+                } } ] }"#,
+            );
+    });
+
+    let (ai_config, question) = setup_openai(&server);
+    let answer = ask_validated(&ai_config, question, &validation()).await;
+    env::remove_var("OPENAI_API_URL");
+
+    first.assert();
+    retry.assert();
+    assert_eq!(
+        answer.expect("Should succeed"),
+        json!({ "city": "Paris", "population": 2102650 })
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn exhausted_attempts_are_a_schema_mismatch() {
+    let server = MockServer::start();
+    let chat = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "{\"city\": 75}" } } ] }"#);
+    });
+
+    let (ai_config, question) = setup_openai(&server);
+    let result = ask_validated(&ai_config, question, &validation()).await;
+    env::remove_var("OPENAI_API_URL");
+
+    chat.assert_hits(2);
+    match result {
+        Err(AppError::SchemaMismatch {
+            attempts, errors, ..
+        }) => {
+            assert_eq!(attempts, 2);
+            assert_eq!(errors.len(), 2);
+            assert!(errors.iter().any(|e| e.starts_with("/city: ")));
+        }
+        other => panic!("Expected AppError::SchemaMismatch, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_decodes_against_the_schema() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .json_body_partial(json!({ "format": validation().schema }).to_string());
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{ "model": "llama3.2", "message": { "role": "assistant",
+                    "content": "{\"city\": \"Paris\", \"population\": 2102650}" }, "done": true }"#,
+            );
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3.2".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What is the largest city in France?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    let answer = ask_validated(&ai_config, question, &validation()).await;
+    env::remove_var("OLLAMA_API_URL");
+
+    mock.assert_hits(1);
+    assert_eq!(
+        answer.expect("Should succeed"),
+        json!({ "city": "Paris", "population": 2102650 })
+    );
+}
@@ -0,0 +1,78 @@
+use ask_ai::{
+    ask_question,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    secret::SecretString,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const KEY: &str = "sk-proj-abcdefghijklmnopqrstuvwxyz0123";
+
+#[test]
+fn api_key_is_redacted_in_debug_output() {
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        api_key: Some(SecretString::from(KEY)),
+        ..Default::default()
+    };
+
+    let debug = format!("{:?}", ai_config);
+    assert!(!debug.contains(KEY));
+    assert!(debug.contains("[REDACTED]"));
+
+    // Serialization keeps the key, so configuration files round-trip
+    let json = serde_json::to_string(&ai_config).unwrap();
+    let parsed: AiConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.api_key.unwrap().expose_secret(), KEY);
+}
+
+#[tokio::test]
+#[serial]
+async fn echoed_credentials_are_scrubbed_from_errors() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .header("authorization", format!("Bearer {}", KEY));
+        then.status(401).body(format!(
+            r#"{{"error": "Incorrect API key {} (header: Bearer {}). Other key: sk-ant-REDACTED"}}"#,
+            KEY, KEY
+        ));
+    });
+
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        api_key: Some(KEY.into()),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    match ask_question(&ai_config, question).await {
+        Err(AppError::ApiError { failure_str, .. }) => {
+            assert!(failure_str.contains("401"));
+            assert!(!failure_str.contains(KEY));
+            assert!(!failure_str.contains("zyxwvutsrqponmlkjihg"));
+            assert!(failure_str.contains("Bearer [REDACTED]"));
+        }
+        other => panic!("Expected AppError::ApiError, got {:?}", other),
+    }
+    mock.assert();
+
+    env::remove_var("OPENAI_API_URL");
+}
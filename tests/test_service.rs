@@ -0,0 +1,90 @@
+#![cfg(feature = "tower")]
+
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    service::AskService,
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::time::Duration;
+use tower::{ServiceBuilder, ServiceExt};
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn questions_pass_through_tower_middleware() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hello" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let service = ServiceBuilder::new()
+        .concurrency_limit(1)
+        .service(AskService::new(AiConfig {
+            llm: Framework::OpenAI,
+            model: "gpt-4o-mini".to_string(),
+            ..Default::default()
+        }));
+    let answer = service.oneshot(question()).await.expect("Should succeed");
+    assert_eq!(answer, "Hello");
+    mock.assert();
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+#[tokio::test]
+#[serial]
+async fn timeout_layer_cuts_slow_answers() {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .delay(Duration::from_millis(500))
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Late" } } ] }"#);
+    });
+
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_millis(50))
+        .service(AskService::new(AiConfig {
+            llm: Framework::OpenAI,
+            model: "gpt-4o-mini".to_string(),
+            ..Default::default()
+        }));
+    let error = service
+        .oneshot(question())
+        .await
+        .expect_err("Should time out");
+    assert!(error.is::<tower::timeout::error::Elapsed>());
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
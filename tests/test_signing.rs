@@ -0,0 +1,116 @@
+use ask_ai::{
+    ask_question,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    signing::RequestSigning,
+    transcription::{transcribe_audio, AudioFile, TranscriptionOptions},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const GATEWAY_KEY: &str = "gateway_secret";
+
+fn header<'a>(req: &'a HttpMockRequest, name: &str) -> Option<&'a str> {
+    req.headers
+        .as_ref()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn correctly_signed(req: &HttpMockRequest) -> bool {
+    let (Some(timestamp), Some(signature)) = (
+        header(req, "x-timestamp"),
+        header(req, "x-gateway-signature"),
+    ) else {
+        return false;
+    };
+    let Ok(timestamp) = timestamp.parse() else {
+        return false;
+    };
+    let body = req.body.clone().unwrap_or_default();
+
+    RequestSigning::new(GATEWAY_KEY).signature(timestamp, &body) == signature
+}
+
+#[test]
+fn signature_matches_reference_hmac() {
+    assert_eq!(
+        RequestSigning::new(GATEWAY_KEY).signature(1700000000, br#"{"a":1}"#),
+        "03dd33a6622de03d917e936e85eacbcef898543226bfc19c2b37247339bd312e"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn requests_carry_a_valid_signature() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .matches(correctly_signed);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"llama3","message":{"role":"assistant","content":"Signed and delivered"},"done":true}"#);
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        signing: Some(RequestSigning::new(GATEWAY_KEY).signature_header("X-Gateway-Signature")),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hello gateway".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = ask_question(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Signed and delivered");
+
+    env::remove_var("OLLAMA_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn streamed_bodies_are_not_signed_empty() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/audio/transcriptions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "text": "Unsigned" }"#);
+    });
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "whisper-1".to_string(),
+        api_key: Some("open_api_testkey".into()),
+        base_url: Some(server.url("/v1")),
+        signing: Some(RequestSigning::new(GATEWAY_KEY)),
+        ..Default::default()
+    };
+    let audio = AudioFile::Bytes {
+        file_name: "memo.wav".to_string(),
+        data: b"RIFF".to_vec(),
+    };
+
+    match transcribe_audio(&ai_config, audio, &TranscriptionOptions::default()).await {
+        Err(AppError::UnexpectedError(message)) => {
+            assert!(message.contains("Cannot sign the streamed body"))
+        }
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    }
+    mock.assert_hits(0);
+}
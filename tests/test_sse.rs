@@ -0,0 +1,46 @@
+#![cfg(feature = "axum")]
+
+use ask_ai::{error::AppError, sse::sse_response};
+use axum::response::IntoResponse;
+use futures_util::stream;
+
+async fn body(answer: Vec<Result<String, AppError>>) -> (String, String) {
+    let response = sse_response(stream::iter(answer)).into_response();
+    let content_type = response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (content_type, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn fragments_are_framed_as_events() {
+    let (content_type, body) = body(vec![
+        Ok("Hello".to_string()),
+        Ok(" world\nand more".to_string()),
+    ])
+    .await;
+
+    assert_eq!(content_type, "text/event-stream");
+    assert_eq!(
+        body,
+        "data: Hello\n\ndata:  world\ndata: and more\n\nevent: done\ndata: [DONE]\n\n"
+    );
+}
+
+#[tokio::test]
+async fn errors_end_the_stream() {
+    let (_, body) = body(vec![
+        Ok("Partial".to_string()),
+        Err(AppError::UnexpectedError("Connection reset".to_string())),
+        Ok("Never sent".to_string()),
+    ])
+    .await;
+
+    assert!(body.starts_with("data: Partial\n\nevent: error\ndata: "));
+    assert!(body.contains("Connection reset"));
+    assert!(!body.contains("Never sent") && !body.contains("event: done"));
+}
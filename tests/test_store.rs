@@ -0,0 +1,71 @@
+use ask_ai::{
+    conversation::Conversation,
+    error::AppError,
+    store::{ConversationStore, JsonStore},
+};
+
+fn sample_conversation() -> Conversation {
+    let mut conversation = Conversation::new(Some("You are a nurse.".to_string()));
+    conversation.push("My patient id is 12345", "Noted, patient 12345.");
+    conversation
+}
+
+#[test]
+fn json_store_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = JsonStore::new(dir.path()).unwrap();
+    let conversation = sample_conversation();
+
+    store.save("chat-1", &conversation).unwrap();
+    store.save("chat-2", &Conversation::default()).unwrap();
+    assert_eq!(store.list().unwrap(), vec!["chat-1", "chat-2"]);
+    assert_eq!(store.load("chat-1").unwrap(), conversation);
+
+    store.delete("chat-2").unwrap();
+    assert_eq!(store.list().unwrap(), vec!["chat-1"]);
+    assert!(store.save("../escape", &conversation).is_err());
+}
+
+#[test]
+fn json_store_encryption_at_rest() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = [7u8; 32];
+    let store = JsonStore::new(dir.path()).unwrap().with_encryption(&key);
+    let conversation = sample_conversation();
+
+    store.save("chat-1", &conversation).unwrap();
+    let raw = std::fs::read(dir.path().join("chat-1.enc")).unwrap();
+    assert!(!String::from_utf8_lossy(&raw).contains("12345"));
+    assert_eq!(store.load("chat-1").unwrap(), conversation);
+
+    let wrong_key = JsonStore::new(dir.path())
+        .unwrap()
+        .with_encryption(&[8u8; 32]);
+    match wrong_key.load("chat-1") {
+        Err(AppError::UnexpectedError(msg)) => assert!(msg.contains("Failed to decrypt")),
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    }
+
+    // A record moved under another id fails authentication
+    std::fs::copy(dir.path().join("chat-1.enc"), dir.path().join("chat-2.enc")).unwrap();
+    assert!(store.load("chat-2").is_err());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_store_encryption_at_rest() {
+    use ask_ai::store::SqliteStore;
+
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("conversations.db");
+    let store = SqliteStore::open(&db).unwrap().with_encryption(&[7u8; 32]);
+    let conversation = sample_conversation();
+
+    store.save("chat-1", &conversation).unwrap();
+    assert_eq!(store.list().unwrap(), vec!["chat-1"]);
+    assert_eq!(store.load("chat-1").unwrap(), conversation);
+    assert!(!String::from_utf8_lossy(&std::fs::read(&db).unwrap()).contains("12345"));
+
+    store.delete("chat-1").unwrap();
+    assert!(store.list().unwrap().is_empty());
+}
@@ -0,0 +1,221 @@
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    stream::{ask_question_stream, stream_to_writer},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn openai_stream_to_writer_httpmock_success() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""stream":true"#);
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(concat!(
+                "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"Hello \"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"from a stream!\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ));
+    });
+
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-3.5-turbo".to_string(),
+        max_token: Some(1000),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Stream something.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let stream = ask_question_stream(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    let answer = stream_to_writer(stream, &mut written, true)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Hello from a stream!");
+    assert_eq!(String::from_utf8(written).unwrap(), "Hello from a stream!");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn mistral_streams_like_openai() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .header("Authorization", "Bearer mistral_testkey")
+            .body_contains(r#""stream":true"#);
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(concat!(
+                "data: {\"choices\":[{\"delta\":{\"content\":\"Bon\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"jour!\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ));
+    });
+
+    env::set_var("MISTRAL_API_KEY", "mistral_testkey");
+    env::set_var(
+        "MISTRAL_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::Mistral,
+        model: "mistral-small-latest".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Stream something.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let stream = ask_question_stream(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    let answer = stream_to_writer(stream, &mut written, false)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Bonjour!");
+
+    env::remove_var("MISTRAL_API_KEY");
+    env::remove_var("MISTRAL_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn anthropic_stream_httpmock_error_event() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains(r#""stream":true"#);
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(concat!(
+                "event: content_block_delta\n",
+                "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Partial\"}}\n\n",
+                "event: error\n",
+                "data: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n",
+            ));
+    });
+
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-2".to_string(),
+        max_token: Some(80),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Stream something.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let stream = ask_question_stream(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    match stream_to_writer(stream, &mut written, false).await {
+        Err(AppError::ModelError {
+            model_name,
+            failure_str,
+        }) => {
+            assert_eq!(model_name, "claude-2");
+            assert!(failure_str.contains("Overloaded"));
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    };
+    mock.assert();
+    assert_eq!(String::from_utf8(written).unwrap(), "Partial");
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn ollama_stream_httpmock_success() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/chat")
+            .body_contains(r#""stream":true"#);
+        then.status(200)
+            .header("content-type", "application/x-ndjson")
+            .body(concat!(
+                "{\"model\":\"llama3\",\"message\":{\"role\":\"assistant\",\"content\":\"Local \"},\"done\":false}\n",
+                "{\"model\":\"llama3\",\"message\":{\"role\":\"assistant\",\"content\":\"answer\"},\"done\":false}\n",
+                "{\"model\":\"llama3\",\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true}\n",
+            ));
+    });
+
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3".to_string(),
+        max_token: None,
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Stream something.".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let stream = ask_question_stream(&ai_config, question)
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    let answer = stream_to_writer(stream, &mut written, false)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Local answer");
+
+    env::remove_var("OLLAMA_API_URL");
+}
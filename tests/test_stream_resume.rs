@@ -0,0 +1,141 @@
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    continuation::CONTINUE_PROMPT,
+    error::AppError,
+    stream::{ask_question_stream, stream_to_writer, StreamResume},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+const CUT_OFF: &str = "data: {\"choices\":[{\"delta\":{\"content\":\"Hello \"}}]}\n\n";
+
+fn question() -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Greet the world.".to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn set_env(server: &MockServer) {
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+}
+
+fn remove_env() {
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
+
+fn openai(stream_resume: Option<StreamResume>) -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o-mini".to_string(),
+        stream_resume,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn interrupted_streams_are_resumed() {
+    let server = MockServer::start();
+
+    let rest = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(CONTINUE_PROMPT)
+            .body_contains(r#""content":"Hello ""#);
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(concat!(
+                "data: {\"choices\":[{\"delta\":{\"content\":\"world!\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ));
+    });
+    let start = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(CUT_OFF);
+    });
+    set_env(&server);
+
+    let stream = ask_question_stream(&openai(Some(StreamResume::default())), question())
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    let answer = stream_to_writer(stream, &mut written, false)
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Hello world!");
+    start.assert_hits(1);
+    rest.assert_hits(1);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn resumptions_stop_at_the_cap() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(CUT_OFF);
+    });
+    set_env(&server);
+
+    let resume = StreamResume {
+        max_resumes: 1,
+        ..Default::default()
+    };
+    let stream = ask_question_stream(&openai(Some(resume)), question())
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    match stream_to_writer(stream, &mut written, false).await {
+        Err(AppError::ApiError { failure_str, .. }) => {
+            assert!(failure_str.starts_with("Stream interrupted"));
+        }
+        other => panic!("Expected AppError::ApiError, got {:?}", other),
+    }
+    assert_eq!(String::from_utf8(written).unwrap(), "Hello Hello ");
+    mock.assert_hits(2);
+
+    remove_env();
+}
+
+#[tokio::test]
+#[serial]
+async fn interrupted_streams_end_quietly_by_default() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/v1/chat/completions");
+        then.status(200)
+            .header("content-type", "text/event-stream")
+            .body(CUT_OFF);
+    });
+    set_env(&server);
+
+    let stream = ask_question_stream(&openai(None), question())
+        .await
+        .expect("Should succeed");
+    let mut written: Vec<u8> = Vec::new();
+    let answer = stream_to_writer(stream, &mut written, false)
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Hello ");
+    mock.assert_hits(1);
+
+    remove_env();
+}
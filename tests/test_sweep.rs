@@ -0,0 +1,132 @@
+use ask_ai::{
+    config::{AiConfig, Framework, Question},
+    sweep::{Sweep, SweepPoint},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+fn question(prompt: &str) -> Question {
+    Question {
+        system_prompt: Some("Be brief.".to_string()),
+        messages: None,
+        new_prompt: prompt.to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+#[test]
+fn points_cover_the_grid_and_empty_axes_keep_the_base() {
+    let sweep = Sweep {
+        models: vec!["a".to_string(), "b".to_string()],
+        temperatures: vec![0.0, 1.0],
+        ..Default::default()
+    };
+    let points = sweep.points();
+    assert_eq!(points.len(), 4);
+    assert_eq!(
+        points[1],
+        SweepPoint {
+            model: Some("a".to_string()),
+            temperature: Some(1.0),
+            system_prompt: None,
+        }
+    );
+
+    let base = AiConfig {
+        llm: Framework::OpenAI,
+        model: "base".to_string(),
+        ..Default::default()
+    };
+    let ai_config = points[1].config(&base);
+    assert_eq!(ai_config.model, "a");
+    assert_eq!(ai_config.params.unwrap().temperature, Some(1.0));
+    assert_eq!(
+        points[1].question(&question("Hi")).system_prompt.as_deref(),
+        Some("Be brief.")
+    );
+
+    assert_eq!(Sweep::default().points(), vec![SweepPoint::default()]);
+}
+
+#[tokio::test]
+#[serial]
+async fn answers_are_scored_and_averaged_per_combination() {
+    let server = MockServer::start();
+
+    let failing = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("\"model\":\"weak\"")
+            .body_contains("Capital of Peru?");
+        then.status(500).body("Internal Server Error");
+    });
+    let weak = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("\"model\":\"weak\"");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Paris" } } ] }"#);
+    });
+    let strong = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("\"model\":\"strong\"")
+            .body_contains("Capital of Peru?");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Lima" } } ] }"#);
+    });
+    let strong_france = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("\"model\":\"strong\"");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Paris" } } ] }"#);
+    });
+    env::set_var("OPENAI_API_KEY", "test_key");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let base = AiConfig {
+        llm: Framework::OpenAI,
+        model: "base".to_string(),
+        ..Default::default()
+    };
+    let questions = vec![question("Capital of France?"), question("Capital of Peru?")];
+    let expected = ["Paris", "Lima"];
+    let sweep = Sweep {
+        models: vec!["weak".to_string(), "strong".to_string()],
+        concurrency: 2,
+        ..Default::default()
+    };
+
+    let report = sweep
+        .run(&base, &questions, |index, answer| {
+            (answer == expected[index]) as u8 as f64
+        })
+        .await;
+
+    assert_eq!(report.results.len(), 2);
+    assert_eq!(report.results[0].scores, vec![Some(1.0), None]);
+    assert_eq!(report.results[0].mean_score, 0.5);
+    assert_eq!(report.results[0].errors, 1);
+    assert_eq!(report.results[1].scores, vec![Some(1.0), Some(1.0)]);
+
+    let best = report.best().unwrap();
+    assert_eq!(best.point.model.as_deref(), Some("strong"));
+    assert_eq!(report.ranked()[1].point.model.as_deref(), Some("weak"));
+
+    failing.assert();
+    weak.assert();
+    strong.assert();
+    strong_france.assert();
+
+    env::remove_var("OPENAI_API_URL");
+    env::remove_var("OPENAI_API_KEY");
+}
@@ -0,0 +1,173 @@
+use ask_ai::{
+    config::{AiConfig, AiPrompt, Framework, Question},
+    error::AppError,
+    tenant::{TenantConfig, TenantLimits, TenantQuota, TenantRegistry},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+use std::time::{Duration, SystemTime};
+
+fn registry() -> TenantRegistry {
+    let registry = TenantRegistry::new();
+    registry.insert(
+        "acme",
+        TenantConfig {
+            ai_config: AiConfig {
+                llm: Framework::Anthropic,
+                model: "claude-3-5-haiku-latest".to_string(),
+                max_token: Some(4000),
+                api_key: Some("acme_key".into()),
+                ..Default::default()
+            },
+            limits: TenantLimits {
+                max_token: Some(500),
+                max_history: Some(1),
+                ..Default::default()
+            },
+        },
+    );
+    registry.insert(
+        "globex",
+        TenantConfig {
+            ai_config: AiConfig {
+                llm: Framework::OpenAI,
+                model: "gpt-4o-mini".to_string(),
+                api_key: Some("globex_key".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    registry
+}
+
+#[tokio::test]
+#[serial]
+async fn tenant_question_uses_tenant_key_and_limits() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .header("x-api-key", "acme_key")
+            .body_contains(r#""model":"claude-3-5-haiku-latest""#)
+            .body_contains(r#""max_tokens":500"#)
+            .body_contains("Second question")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                !String::from_utf8_lossy(&body).contains("First question")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "text": "Hello Acme" } ] }"#);
+    });
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+
+    let question = Question {
+        system_prompt: None,
+        messages: Some(vec![
+            AiPrompt {
+                content: "First question".to_string(),
+                output: "First answer".to_string(),
+            },
+            AiPrompt {
+                content: "Second question".to_string(),
+                output: "Second answer".to_string(),
+            },
+        ]),
+        new_prompt: "Third question".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let registry = registry();
+    let answer = registry
+        .ask_question_for("acme", question)
+        .await
+        .expect("Should succeed");
+    mock.assert();
+    assert_eq!(answer, "Hello Acme");
+    assert_eq!(registry.tenant_ids(), vec!["acme", "globex"]);
+
+    env::remove_var("ANTHROPIC_API_URL");
+}
+
+#[tokio::test]
+async fn unknown_tenant_is_rejected() {
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    match registry().ask_question_for("initech", question).await {
+        Err(AppError::UnexpectedError(msg)) => assert!(msg.contains("Unknown tenant `initech`")),
+        other => panic!("Expected AppError::UnexpectedError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn tenant_quota_is_enforced_before_dispatch() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .header("authorization", "Bearer globex_key");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Hello Globex" } } ] }"#);
+    });
+
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let registry = registry();
+    let mut globex = registry.get("globex").unwrap();
+    globex.limits.quota = Some(TenantQuota {
+        window_secs: 60,
+        max_requests: Some(1),
+        max_tokens: None,
+    });
+    registry.insert("globex", globex);
+
+    let question = || Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "Hi".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+
+    let answer = registry
+        .ask_question_for("globex", question())
+        .await
+        .expect("Should succeed");
+    assert_eq!(answer, "Hello Globex");
+    let usage = registry.usage_for("globex").await.unwrap();
+    assert_eq!(usage.requests, 1);
+    assert!(usage.tokens > 0);
+
+    match registry.ask_question_for("globex", question()).await {
+        Err(AppError::QuotaExceeded { tenant, reset_at }) => {
+            assert_eq!(tenant, "globex");
+            assert!(reset_at > SystemTime::now());
+            assert!(reset_at <= SystemTime::now() + Duration::from_secs(60));
+        }
+        other => panic!("Expected AppError::QuotaExceeded, got {:?}", other),
+    }
+    mock.assert_hits(1);
+
+    env::remove_var("OPENAI_API_URL");
+}
@@ -0,0 +1,516 @@
+use ask_ai::{
+    capabilities::UnsupportedPolicy,
+    config::{AiConfig, Framework, Question},
+    error::AppError,
+    privacy::{PrivacyConfig, PrivacyMode, PrivacyRule},
+    tools::{run_with_tools, ToolApproval, ToolPolicy, ToolRegistry},
+};
+use httpmock::prelude::*;
+use serde_json::json;
+use serial_test::serial;
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const TOOL_CALL_RESPONSE: &str = r#"{
+    "choices": [
+        { "message": {
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [
+                { "id": "call_1", "type": "function",
+                  "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" } }
+            ]
+        } }
+    ]
+}"#;
+
+fn has_tool_result(req: &HttpMockRequest) -> bool {
+    let body = req.body.clone().unwrap_or_default();
+    String::from_utf8_lossy(&body).contains(r#""tool_call_id":"call_1""#)
+}
+
+fn has_tool_use_result(req: &HttpMockRequest) -> bool {
+    let body = req.body.clone().unwrap_or_default();
+    String::from_utf8_lossy(&body).contains(r#""tool_use_id":"toolu_1""#)
+}
+
+fn weather_registry(calls: Arc<AtomicUsize>) -> ToolRegistry {
+    ToolRegistry::new().register(
+        "get_weather",
+        "Current weather for a city",
+        json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        move |args| {
+            let calls = calls.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(format!("Sunny in {}", args["city"].as_str().unwrap_or("?")))
+            }
+        },
+    )
+}
+
+fn setup_openai(server: &MockServer) -> (AiConfig, Question) {
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var(
+        "OPENAI_API_URL",
+        format!("{}/v1/chat/completions", server.base_url()),
+    );
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4o".to_string(),
+        max_token: None,
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What's the weather in Paris?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    (ai_config, question)
+}
+
+#[tokio::test]
+#[serial]
+async fn openai_tools_approved_call_runs() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""name":"get_weather""#)
+            .matches(|req| !has_tool_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Sunny in Paris")
+            .matches(has_tool_result);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "It is sunny." } } ] }"#);
+    });
+
+    let (ai_config, question) = setup_openai(&server);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let registry = weather_registry(calls.clone()).with_approval(|call| async move {
+        assert_eq!(call.arguments["city"], "Paris");
+        ToolApproval::Approve
+    });
+
+    let answer = run_with_tools(&ai_config, question, &registry)
+        .await
+        .expect("Should succeed");
+    first.assert();
+    second.assert();
+    assert_eq!(answer, "It is sunny.");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn openai_tools_denied_call_reports_error() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .matches(|req| !has_tool_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("tool call denied: weather is private")
+            .matches(has_tool_result);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "I can't check that." } } ] }"#);
+    });
+
+    let (ai_config, question) = setup_openai(&server);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let registry = weather_registry(calls.clone())
+        .with_approval(|_| async move { ToolApproval::Deny("weather is private".to_string()) });
+
+    let answer = run_with_tools(&ai_config, question, &registry)
+        .await
+        .expect("Should succeed");
+    first.assert();
+    second.assert();
+    assert_eq!(answer, "I can't check that.");
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn openai_tools_policy_rejects_invalid_arguments() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .matches(|req| !has_tool_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("invalid arguments for `get_weather`: only Berlin")
+            .matches(has_tool_result);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "Only Berlin." } } ] }"#);
+    });
+
+    let (ai_config, question) = setup_openai(&server);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let policy = ToolPolicy::new()
+        .allow("get_weather")
+        .validate("get_weather", |args| match args["city"].as_str() {
+            Some("Berlin") => Ok(()),
+            _ => Err("only Berlin".to_string()),
+        });
+    let registry = weather_registry(calls.clone()).with_policy(policy);
+
+    let answer = run_with_tools(&ai_config, question, &registry)
+        .await
+        .expect("Should succeed");
+    first.assert();
+    second.assert();
+    assert_eq!(answer, "Only Berlin.");
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn openai_tools_policy_enforces_timeout_and_max_calls() {
+    let server = MockServer::start();
+
+    // The model keeps asking for the tool, which never finishes in time
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .matches(|req| !has_tool_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+    let retries = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("tool `get_weather` timed out after 20ms");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+
+    let (ai_config, question) = setup_openai(&server);
+    let registry = ToolRegistry::new()
+        .register("get_weather", "Slow weather", json!({}), |_| async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok("too late".to_string())
+        })
+        .with_policy(
+            ToolPolicy::new()
+                .timeout("get_weather", Duration::from_millis(20))
+                .max_calls(2),
+        );
+
+    match run_with_tools(&ai_config, question, &registry).await {
+        Err(AppError::ModelError { failure_str, .. }) => {
+            assert!(failure_str.contains("limit of 2 tool calls"));
+        }
+        other => panic!("Expected AppError::ModelError, got {:?}", other),
+    };
+    first.assert();
+    retries.assert_hits(2);
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn anthropic_tools_round_trip() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains(r#""input_schema""#)
+            .matches(|req| !has_tool_use_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                    "content": [
+                        { "type": "text", "text": "Let me check." },
+                        { "type": "tool_use", "id": "toolu_1", "name": "get_weather",
+                          "input": { "city": "Paris" } }
+                    ],
+                    "stop_reason": "tool_use"
+                }"#,
+            );
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains("Sunny in Paris")
+            .body_contains(r#""type":"tool_use""#)
+            .matches(has_tool_use_result);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "type": "text", "text": "It is sunny." } ], "stop_reason": "end_turn" }"#);
+    });
+
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-sonnet-4-5".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: "What's the weather in Paris?".to_string(),
+        images: vec![],
+        audio: vec![],
+    };
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let answer = run_with_tools(&ai_config, question, &weather_registry(calls.clone()))
+        .await
+        .expect("Should succeed");
+    first.assert();
+    second.assert();
+    assert_eq!(answer, "It is sunny.");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn openai_tools_apply_privacy_mode() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Tell [EMAIL_1] the weather in Paris.")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                !has_tool_result(req) && !String::from_utf8_lossy(&body).contains("jane@acme.com")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                    "choices": [
+                        { "message": {
+                            "role": "assistant",
+                            "content": null,
+                            "tool_calls": [
+                                { "id": "call_1", "type": "function",
+                                  "function": { "name": "notify", "arguments": "{\"to\":\"[EMAIL_1]\"}" } }
+                            ]
+                        } }
+                    ]
+                }"#,
+            );
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Sent to [EMAIL_1], cc [EMAIL_2]")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                has_tool_result(req) && !String::from_utf8_lossy(&body).contains("acme.com")
+            });
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "[EMAIL_2] was copied." } } ] }"#);
+    });
+
+    let (ai_config, question) = setup_openai(&server);
+    let ai_config = AiConfig {
+        privacy: Some(PrivacyConfig {
+            mode: PrivacyMode::Pseudonymize,
+            rules: vec![PrivacyRule::email()],
+        }),
+        ..ai_config
+    };
+    let question = Question {
+        new_prompt: "Tell jane@acme.com the weather in Paris.".to_string(),
+        ..question
+    };
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let registry = {
+        let received = received.clone();
+        ToolRegistry::new().register(
+            "notify",
+            "Sends the weather to someone",
+            json!({"type": "object", "properties": {"to": {"type": "string"}}}),
+            move |args| {
+                let received = received.clone();
+                async move {
+                    received.lock().unwrap().push(args.clone());
+                    Ok(format!(
+                        "Sent to {}, cc bob@acme.com",
+                        args["to"].as_str().unwrap_or("?")
+                    ))
+                }
+            },
+        )
+    };
+
+    let answer = run_with_tools(&ai_config, question, &registry)
+        .await
+        .expect("Should succeed");
+    first.assert();
+    second.assert();
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![json!({"to": "jane@acme.com"})]
+    );
+    assert_eq!(answer, "bob@acme.com was copied.");
+
+    env::remove_var("OPENAI_API_KEY");
+    env::remove_var("OPENAI_API_URL");
+}
+
+#[tokio::test]
+#[serial]
+async fn anthropic_tool_errors_are_flagged() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .matches(|req| !has_tool_use_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(
+                r#"{
+                    "content": [
+                        { "type": "tool_use", "id": "toolu_1", "name": "get_weather",
+                          "input": { "city": "Paris" } }
+                    ],
+                    "stop_reason": "tool_use"
+                }"#,
+            );
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/messages")
+            .body_contains(r#""content":"station offline""#)
+            .body_contains(r#""is_error":true"#)
+            .matches(has_tool_use_result);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "content": [ { "type": "text", "text": "The station is down." } ], "stop_reason": "end_turn" }"#);
+    });
+
+    env::set_var("ANTHROPIC_API_KEY", "anthropic_testkey");
+    env::set_var(
+        "ANTHROPIC_API_URL",
+        format!("{}/v1/messages", server.base_url()),
+    );
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-sonnet-4-5".to_string(),
+        ..Default::default()
+    };
+    let question = Question {
+        new_prompt: "What's the weather in Paris?".to_string(),
+        ..Default::default()
+    };
+    let registry = ToolRegistry::new().register(
+        "get_weather",
+        "Current weather for a city",
+        json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        |_| async move { Err("station offline".to_string()) },
+    );
+
+    let answer = run_with_tools(&ai_config, question, &registry)
+        .await
+        .expect("Should succeed");
+    first.assert();
+    second.assert();
+    assert_eq!(answer, "The station is down.");
+
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_API_URL");
+}
+
+#[tokio::test]
+async fn ollama_tools_use_the_openai_compatible_endpoint() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains(r#""model":"llama3.1""#)
+            .body_contains(r#""name":"get_weather""#)
+            .matches(|req| !has_tool_result(req));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOOL_CALL_RESPONSE);
+    });
+    let second = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/chat/completions")
+            .body_contains("Sunny in Paris")
+            .matches(has_tool_result);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "choices": [ { "message": { "content": "It is sunny." } } ] }"#);
+    });
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3.1".to_string(),
+        base_url: Some(server.base_url()),
+        on_unsupported: Some(UnsupportedPolicy::Error),
+        ..Default::default()
+    };
+    let question = Question {
+        new_prompt: "What's the weather in Paris?".to_string(),
+        ..Default::default()
+    };
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let answer = run_with_tools(&ai_config, question, &weather_registry(calls.clone()))
+        .await
+        .expect("Should succeed");
+    first.assert();
+    second.assert();
+    assert_eq!(answer, "It is sunny.");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
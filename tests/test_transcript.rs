@@ -0,0 +1,191 @@
+use ask_ai::{
+    config::Framework,
+    conversation::Conversation,
+    transcript::{ContentPart, ImageSource, Role, Transcript, TranscriptUsage},
+};
+use serde_json::json;
+
+#[test]
+fn openai_tool_traffic_and_usage_are_read_and_written() {
+    let request = json!({
+        "model": "gpt-4o",
+        "messages": [
+            { "role": "system", "content": "Be brief." },
+            { "role": "user", "content": [
+                { "type": "text", "text": "Weather here?" },
+                { "type": "image_url", "image_url": { "url": "data:image/jpeg;base64,AAAA" } }
+            ] },
+            { "role": "assistant", "content": null, "tool_calls": [
+                { "id": "call_1", "type": "function",
+                  "function": { "name": "weather", "arguments": "{\"city\":\"Paris\"}" } }
+            ] },
+            { "role": "tool", "tool_call_id": "call_1", "content": "Sunny" }
+        ]
+    });
+    let mut transcript = Transcript::from_openai(&request).unwrap();
+    transcript
+        .push_openai_response(&json!({
+            "created": 1700000000,
+            "model": "gpt-4o-2024-08-06",
+            "choices": [ { "message": { "role": "assistant", "content": "It is sunny." } } ],
+            "usage": { "prompt_tokens": 40, "completion_tokens": 5 }
+        }))
+        .unwrap();
+
+    assert_eq!(transcript.framework, Some(Framework::OpenAI));
+    let roles: Vec<Role> = transcript.messages.iter().map(|m| m.role).collect();
+    assert_eq!(
+        roles,
+        vec![
+            Role::System,
+            Role::User,
+            Role::Assistant,
+            Role::Tool,
+            Role::Assistant
+        ]
+    );
+    assert_eq!(
+        transcript.messages[1].content[1],
+        ContentPart::Image {
+            source: ImageSource::Base64 {
+                media_type: Some("image/jpeg".to_string()),
+                data: "AAAA".to_string(),
+            }
+        }
+    );
+    assert_eq!(
+        transcript.messages[2].content[0],
+        ContentPart::ToolCall {
+            id: "call_1".to_string(),
+            name: "weather".to_string(),
+            arguments: json!({ "city": "Paris" }),
+        }
+    );
+    let last = transcript.messages.last().unwrap();
+    assert_eq!(last.timestamp, Some(1700000000));
+    assert_eq!(last.model.as_deref(), Some("gpt-4o-2024-08-06"));
+    assert_eq!(
+        transcript.usage(),
+        TranscriptUsage {
+            prompt_tokens: 40,
+            answer_tokens: 5
+        }
+    );
+
+    // Written back out, the request messages round-trip
+    let messages = transcript.to_openai();
+    assert_eq!(messages[0], request["messages"][0]);
+    assert_eq!(messages[1], request["messages"][1]);
+    assert_eq!(messages[2], request["messages"][2]);
+    assert_eq!(messages[3], request["messages"][3]);
+    assert_eq!(messages[4]["content"], "It is sunny.");
+
+    assert!(Transcript::from_openai(&json!([{ "role": "robot", "content": "" }])).is_err());
+}
+
+#[test]
+fn anthropic_tool_results_become_tool_messages_and_back() {
+    let request = json!({
+        "system": "Be brief.",
+        "messages": [
+            { "role": "user", "content": "Weather in Paris?" },
+            { "role": "assistant", "content": [
+                { "type": "tool_use", "id": "toolu_1", "name": "weather", "input": { "city": "Paris" } }
+            ] },
+            { "role": "user", "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_1", "content": "Sunny", "is_error": false }
+            ] }
+        ]
+    });
+    let mut transcript = Transcript::from_anthropic(&request).unwrap();
+    transcript
+        .push_anthropic_response(&json!({
+            "model": "claude-3-5-sonnet",
+            "content": [ { "type": "text", "text": "It is sunny." } ],
+            "usage": { "input_tokens": 30, "output_tokens": 4 }
+        }))
+        .unwrap();
+
+    assert_eq!(transcript.messages[0].role, Role::System);
+    assert_eq!(transcript.messages[3].role, Role::Tool);
+    assert_eq!(
+        transcript.messages[3].content[0],
+        ContentPart::ToolResult {
+            call_id: "toolu_1".to_string(),
+            content: "Sunny".to_string(),
+            is_error: false,
+        }
+    );
+    assert_eq!(transcript.usage().prompt_tokens, 30);
+
+    let written = transcript.to_anthropic();
+    assert_eq!(written["system"], "Be brief.");
+    assert_eq!(written["messages"][1], request["messages"][1]);
+    assert_eq!(written["messages"][2], request["messages"][2]);
+    assert_eq!(written["messages"][3]["role"], "assistant");
+
+    // The same conversation, for OpenAI
+    let openai = transcript.to_openai();
+    assert_eq!(openai[2]["tool_calls"][0]["id"], "toolu_1");
+    assert_eq!(openai[3]["role"], "tool");
+    assert_eq!(openai[3]["tool_call_id"], "toolu_1");
+}
+
+#[test]
+fn ollama_tool_calls_are_numbered_and_usage_read() {
+    let mut transcript = Transcript::from_ollama(&json!({
+        "model": "llama3.1",
+        "messages": [
+            { "role": "user", "content": "What is in this picture?", "images": ["AAAA"] },
+            { "role": "assistant", "content": "", "tool_calls": [
+                { "function": { "name": "describe", "arguments": { "detail": "high" } } }
+            ] },
+            { "role": "tool", "content": "A cat" }
+        ]
+    }))
+    .unwrap();
+    transcript
+        .push_ollama_response(&json!({
+            "model": "llama3.1",
+            "message": { "role": "assistant", "content": "A cat." },
+            "prompt_eval_count": 20,
+            "eval_count": 3
+        }))
+        .unwrap();
+
+    assert!(matches!(
+        &transcript.messages[1].content[0],
+        ContentPart::ToolCall { id, .. } if id == "call_0"
+    ));
+    assert!(matches!(
+        &transcript.messages[2].content[0],
+        ContentPart::ToolResult { call_id, .. } if call_id == "call_0"
+    ));
+    assert_eq!(transcript.usage().answer_tokens, 3);
+
+    let messages = transcript.to_ollama();
+    assert_eq!(messages[0]["images"], json!(["AAAA"]));
+    assert_eq!(
+        messages[1]["tool_calls"][0]["function"]["arguments"],
+        json!({ "detail": "high" })
+    );
+    assert_eq!(messages[2], json!({ "role": "tool", "content": "A cat" }));
+}
+
+#[test]
+fn transcripts_convert_to_and_from_conversations() {
+    let mut conversation = Conversation::new(Some("Be brief.".to_string()));
+    conversation.push("Hi", "Hello!");
+    conversation.push("Bye", "Goodbye!");
+
+    let transcript = Transcript::from(&conversation);
+    assert_eq!(transcript.messages.len(), 5);
+    assert_eq!(transcript.conversation(), conversation);
+
+    // Serialized transcripts read back unchanged
+    let json = serde_json::to_string(&transcript).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Transcript>(&json).unwrap(),
+        transcript
+    );
+}
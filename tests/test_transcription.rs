@@ -0,0 +1,92 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    error::AppError,
+    transcription::{transcribe_audio, AudioFile, TranscriptFormat, TranscriptionOptions},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[tokio::test]
+#[serial]
+async fn openai_transcribes_bytes() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/audio/transcriptions")
+            .header("authorization", "Bearer open_api_testkey")
+            .header_exists("content-type")
+            .body_contains(r#"filename="standup.mp3""#)
+            .body_contains("whisper-1")
+            .body_contains("name=\"language\"\r\n\r\nfr\r\n");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "text": "Bonjour à tous." }"#);
+    });
+    env::set_var("OPENAI_API_KEY", "open_api_testkey");
+    env::set_var("OPENAI_API_URL", server.url("/v1/chat/completions"));
+
+    let ai_config = AiConfig {
+        llm: Framework::OpenAI,
+        model: "whisper-1".to_string(),
+        ..Default::default()
+    };
+    let audio = AudioFile::Bytes {
+        file_name: "standup.mp3".to_string(),
+        data: b"ID3 audio".to_vec(),
+    };
+    let options = TranscriptionOptions {
+        language: Some("fr".to_string()),
+        ..Default::default()
+    };
+    let text = transcribe_audio(&ai_config, audio, &options).await;
+    env::remove_var("OPENAI_API_URL");
+
+    mock.assert();
+    assert_eq!(text.expect("Should succeed"), "Bonjour à tous.");
+}
+
+#[tokio::test]
+#[serial]
+async fn groq_transcribes_files_as_subtitles() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/openai/v1/audio/transcriptions")
+            .header("authorization", "Bearer groq_testkey")
+            .body_contains(r#"filename="meeting.wav""#)
+            .body_contains("name=\"response_format\"\r\n\r\nsrt\r\n");
+        then.status(200)
+            .header("content-type", "text/plain")
+            .body("1\n00:00:00,000 --> 00:00:01,500\nHello everyone.\n");
+    });
+    env::set_var("GROQ_API_KEY", "groq_testkey");
+    env::set_var("GROQ_API_URL", server.url("/openai/v1/chat/completions"));
+    let path = env::temp_dir().join("meeting.wav");
+    std::fs::write(&path, b"RIFF audio").unwrap();
+
+    let ai_config = AiConfig {
+        llm: Framework::Groq,
+        model: "whisper-large-v3".to_string(),
+        ..Default::default()
+    };
+    let options = TranscriptionOptions {
+        response_format: TranscriptFormat::Srt,
+        ..Default::default()
+    };
+    let subtitles = transcribe_audio(&ai_config, AudioFile::Path(path.clone()), &options).await;
+    env::remove_var("GROQ_API_URL");
+    std::fs::remove_file(&path).unwrap();
+
+    mock.assert();
+    assert_eq!(
+        subtitles.expect("Should succeed"),
+        "1\n00:00:00,000 --> 00:00:01,500\nHello everyone.\n"
+    );
+
+    let missing = AudioFile::Path(env::temp_dir().join("missing.wav"));
+    assert!(matches!(
+        transcribe_audio(&ai_config, missing, &options).await,
+        Err(AppError::UnexpectedError(_))
+    ));
+}
@@ -0,0 +1,177 @@
+use ask_ai::{
+    compress::Compression,
+    config::{
+        AiConfig, AiPrompt, EmptyPromptPolicy, Framework, PromptDefaults, Question, VllmOptions,
+    },
+    grammar::{OutputConstraint, ResponseFormat},
+    limits::{LimitPolicy, PayloadLimits, PayloadMeasure},
+    validation::ValidationError,
+};
+
+fn question(new_prompt: &str) -> Question {
+    Question {
+        system_prompt: None,
+        messages: None,
+        new_prompt: new_prompt.to_string(),
+        images: vec![],
+        audio: vec![],
+    }
+}
+
+fn gpt4() -> AiConfig {
+    AiConfig {
+        llm: Framework::OpenAI,
+        model: "gpt-4".to_string(),
+        max_token: Some(1000),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn valid_question_passes() {
+    assert_eq!(question("What is Rust?").validate(&gpt4()), Ok(()));
+}
+
+#[test]
+fn empty_prompt_is_reported_unless_substituted() {
+    assert_eq!(
+        question("").validate(&gpt4()),
+        Err(vec![ValidationError::EmptyPrompt])
+    );
+
+    let substituting = AiConfig {
+        prompts: Some(PromptDefaults {
+            empty_prompt: EmptyPromptPolicy::Substitute("Go on.".to_string()),
+            ..Default::default()
+        }),
+        ..gpt4()
+    };
+    assert_eq!(question("").validate(&substituting), Ok(()));
+}
+
+#[test]
+fn oversized_history_is_reported() {
+    // About 10,000 estimated tokens, over gpt-4's 8,192 token context
+    let question = Question {
+        messages: Some(vec![AiPrompt {
+            content: "word ".repeat(8_000),
+            output: "word ".repeat(8_000),
+        }]),
+        ..question("And now?")
+    };
+    let ai_config = AiConfig {
+        limits: Some(PayloadLimits {
+            max_prompt_tokens: Some(4_000),
+            ..Default::default()
+        }),
+        ..gpt4()
+    };
+
+    let errors = question.validate(&ai_config).unwrap_err();
+    assert!(matches!(
+        errors[0],
+        ValidationError::PayloadTooLarge {
+            measure: PayloadMeasure::Tokens,
+            limit: 4_000,
+            ..
+        }
+    ));
+    assert!(matches!(
+        errors[1],
+        ValidationError::ContextTooLong {
+            max_token: 1000,
+            context: 8_192,
+            ..
+        }
+    ));
+
+    // Truncation fixes the limit, but not the context window
+    let truncating = AiConfig {
+        limits: Some(PayloadLimits {
+            max_prompt_tokens: Some(4_000),
+            on_exceed: LimitPolicy::Truncate,
+            ..Default::default()
+        }),
+        ..gpt4()
+    };
+    assert_eq!(question.validate(&truncating).unwrap_err().len(), 1);
+}
+
+#[test]
+fn conflicting_options_are_reported() {
+    let ai_config = AiConfig {
+        llm: Framework::Anthropic,
+        model: "claude-3-5-haiku-latest".to_string(),
+        max_token: Some(0),
+        seed: Some(42),
+        compression: Some(Compression::new(8_000)),
+        limits: Some(PayloadLimits {
+            max_prompt_tokens: Some(4_000),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let errors = question("Hi").validate(&ai_config).unwrap_err();
+    let descriptions: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            "Invalid `max_token`: an answer of 0 tokens is always empty",
+            "`seed` conflicts with `llm`: Anthropic does not support seeds, so answers are not \
+             reproducible",
+            "`compression.target_tokens` conflicts with `limits.max_prompt_tokens`: questions \
+             compressed to the target still fail the limit",
+        ]
+    );
+}
+
+#[test]
+fn vllm_options_are_checked() {
+    let vllm = VllmOptions {
+        guided_regex: Some("[0-9]+".to_string()),
+        guided_choice: vec!["yes".to_string(), "no".to_string()],
+        ..Default::default()
+    };
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "llama3.1".to_string(),
+        vllm: Some(vllm.clone()),
+        ..Default::default()
+    };
+
+    let errors = question("Hi").validate(&ai_config).unwrap_err();
+    let descriptions: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            "`vllm` conflicts with `llm`: vLLM options are only sent to OpenAI-compatible servers",
+            "`vllm.guided_regex` conflicts with `vllm.guided_choice`: vLLM applies one kind of \
+             guided decoding at a time",
+        ]
+    );
+
+    let ai_config = AiConfig {
+        vllm: Some(VllmOptions {
+            guided_regex: None,
+            ..vllm
+        }),
+        ..gpt4()
+    };
+    assert!(question("Hi").validate(&ai_config).is_ok());
+}
+
+#[test]
+fn response_format_and_constraint_conflict() {
+    let ai_config = AiConfig {
+        response_format: Some(ResponseFormat::JsonObject),
+        constraint: Some(OutputConstraint::Regex("[0-9]+".to_string())),
+        ..gpt4()
+    };
+
+    let errors = question("Hi").validate(&ai_config).unwrap_err();
+    assert_eq!(
+        errors[0].to_string(),
+        "`response_format` conflicts with `constraint`: both set the shape of the answer"
+    );
+}
@@ -0,0 +1,88 @@
+use ask_ai::{
+    config::{AiConfig, Framework},
+    error::AppError,
+    vectorstore::{cosine_similarity, VectorStore},
+};
+use httpmock::prelude::*;
+use serial_test::serial;
+use std::env;
+
+#[test]
+fn search_ranks_by_cosine_similarity() {
+    let mut store = VectorStore::new();
+    store.insert("east", "Points east", vec![1.0, 0.0]).unwrap();
+    store
+        .insert("north", "Points north", vec![0.0, 2.0])
+        .unwrap();
+    store
+        .insert("northeast", "Points northeast", vec![1.0, 1.0])
+        .unwrap();
+
+    let hits = store.search(&[0.9, 0.1], 2);
+    let ids: Vec<&str> = hits.iter().map(|hit| hit.id.as_str()).collect();
+    assert_eq!(ids, vec!["east", "northeast"]);
+    assert!(hits[0].score > 0.99);
+
+    // Re-inserting an id replaces it
+    store
+        .insert("east", "Points west", vec![-1.0, 0.0])
+        .unwrap();
+    assert_eq!(store.len(), 3);
+    assert_eq!(store.search(&[-1.0, 0.0], 1)[0].text, "Points west");
+
+    assert!(matches!(
+        store.insert("up", "Points up", vec![0.0, 0.0, 1.0]),
+        Err(AppError::UnexpectedError(_))
+    ));
+    assert!(store.remove("north"));
+    assert!(!store.remove("north"));
+    assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+}
+
+#[tokio::test]
+#[serial]
+async fn texts_are_embedded_and_searched() {
+    let server = MockServer::start();
+    let documents = server.mock(|when, then| {
+        when.method(POST).path("/api/embed").body_contains(
+            r#""input":["Each value has one owner.","Traits define shared behavior."]"#,
+        );
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "embeddings": [[0.9, 0.1], [0.1, 0.9]] }"#);
+    });
+    let query = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/embed")
+            .body_contains(r#""input":["Who owns a value?"]"#);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{ "embeddings": [[1.0, 0.0]] }"#);
+    });
+    env::set_var("OLLAMA_API_URL", server.base_url());
+
+    let ai_config = AiConfig {
+        llm: Framework::Ollama,
+        model: "nomic-embed-text".to_string(),
+        ..Default::default()
+    };
+    let mut store = VectorStore::new();
+    let inserted = store
+        .insert_texts(
+            &ai_config,
+            &[
+                ("ownership", "Each value has one owner."),
+                ("traits", "Traits define shared behavior."),
+            ],
+        )
+        .await;
+    let hits = store.search_text(&ai_config, "Who owns a value?", 1).await;
+    env::remove_var("OLLAMA_API_URL");
+
+    inserted.expect("Should succeed");
+    documents.assert();
+    query.assert();
+    let hits = hits.expect("Should succeed");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].id, "ownership");
+}